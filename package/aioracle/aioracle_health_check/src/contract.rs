@@ -1,13 +1,15 @@
-use std::ops::{Add, Div, Mul, Sub};
+use std::ops::{Add, Div, Mul};
 
 use crate::error::ContractError;
 use crate::migrations::migrate_v01_to_v02;
 use crate::msg::{
-    HandleMsg, InitMsg, MigrateMsg, QueryExecutor, QueryExecutorMsg, QueryMsg,
-    QueryPingInfoResponse, QueryPingInfosResponse,
+    ExecutorStatsResponse, HandleMsg, Heartbeat, InitMsg, MigrateMsg, ParticipationResponse,
+    QueryExecutor, QueryExecutorMsg, QueryExecutorStats, QueryMsg, QueryPingInfoResponse,
+    QueryPingInfosResponse,
 };
 use crate::state::{
-    config, config_read, PingInfo, ReadPingInfo, State, MAPPED_COUNT, READ_ONLY_MAPPED_COUNT,
+    config, config_read, ParticipationCheckpoint, PingInfo, ReadPingInfo, State, MAPPED_COUNT,
+    PARTICIPATION_CHECKPOINTS, READ_ONLY_MAPPED_COUNT,
 };
 use aioracle_base::Executor;
 use cosmwasm_std::{
@@ -23,6 +25,7 @@ use sha2::Digest;
 const DEFAULT_LIMIT: u8 = 10;
 const MAX_LIMIT: u8 = 30;
 pub const PING_JUMP_INTERVAL: u64 = 438291; // 1 month in blocks, assuming 6 secs/block,
+pub const PARTICIPATION_FLOOR: u64 = 100; // permille of the reward paid for pure liveness
 
 // Note, you can use StdResult in some functions where you do not
 // make use of the custom errors
@@ -34,6 +37,7 @@ pub fn init(deps: DepsMut, env: Env, info: MessageInfo, init: InitMsg) -> StdRes
         ping_jump: init.ping_jump,
         ping_jump_interval: PING_JUMP_INTERVAL,
         max_reward_claim: Uint128::from(1u64),
+        participation_floor: init.participation_floor,
     };
 
     // save owner
@@ -57,6 +61,7 @@ pub fn handle(
             ping_jump,
             ping_jump_interval,
             max_reward_claim,
+            participation_floor,
         } => change_state(
             deps,
             info,
@@ -66,8 +71,12 @@ pub fn handle(
             ping_jump,
             ping_jump_interval,
             max_reward_claim,
+            participation_floor,
         ),
         HandleMsg::Ping { pubkey } => add_ping(deps, info, env, pubkey),
+        HandleMsg::SubmitHeartbeats { heartbeats } => {
+            submit_heartbeats(deps, info, env, heartbeats)
+        }
         HandleMsg::ClaimReward { pubkey } => claim_reward(deps, info, env, pubkey),
     }
 }
@@ -101,6 +110,7 @@ pub fn change_state(
     ping_jump: Option<u64>,
     ping_jump_interval: Option<u64>,
     max_reward_claim: Option<Uint128>,
+    participation_floor: Option<u64>,
 ) -> Result<HandleResponse, ContractError> {
     let mut state = query_state(deps.as_ref())?;
     if info.sender != state.owner {
@@ -130,6 +140,10 @@ pub fn change_state(
         state.max_reward_claim = max_reward_claim;
     }
 
+    if let Some(participation_floor) = participation_floor {
+        state.participation_floor = Some(participation_floor);
+    }
+
     config(deps.storage).save(&state)?;
     let info_sender = info.sender.clone();
 
@@ -140,7 +154,7 @@ pub fn change_state(
 }
 
 pub fn add_ping(
-    deps: DepsMut,
+    mut deps: DepsMut,
     info: MessageInfo,
     env: Env,
     pubkey: Binary,
@@ -158,6 +172,96 @@ pub fn add_ping(
     } = config_read(deps.storage).load()?;
 
     // find if executor exists or active on aioracle list
+    if !is_active_executor(deps.as_ref(), &aioracle_addr, &pubkey) {
+        return Err(ContractError::UnauthorizedExecutor {});
+    }
+
+    let height = env.block.height;
+    record_ping(
+        &mut deps,
+        &env,
+        &pubkey,
+        height,
+        ping_jump,
+        ping_jump_interval,
+    )?;
+    Ok(HandleResponse {
+        attributes: vec![attr("action", "add_ping"), attr("executor", info.sender)],
+        ..HandleResponse::default()
+    })
+}
+
+/// records the pings of many executors at once, from heartbeats they signed off-chain.
+/// Invalid heartbeats are skipped so that a single one cannot block the whole batch
+pub fn submit_heartbeats(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    heartbeats: Vec<Heartbeat>,
+) -> Result<HandleResponse, ContractError> {
+    let State {
+        aioracle_addr,
+        ping_jump,
+        ping_jump_interval,
+        ..
+    } = config_read(deps.storage).load()?;
+
+    let mut accepted: Vec<String> = vec![];
+    let mut rejected: Vec<String> = vec![];
+    for Heartbeat {
+        pubkey,
+        height,
+        signature,
+    } in heartbeats
+    {
+        // heartbeats cannot be signed in advance nor back-dated
+        let is_valid = height <= env.block.height
+            && env.block.height - height < ping_jump
+            && verify_heartbeat_signature(&env.contract.address, height, &pubkey, &signature)
+            && is_active_executor(deps.as_ref(), &aioracle_addr, &pubkey)
+            && record_ping(
+                &mut deps,
+                &env,
+                &pubkey,
+                height,
+                ping_jump,
+                ping_jump_interval,
+            )
+            .is_ok();
+        if is_valid {
+            accepted.push(pubkey.to_base64());
+        } else {
+            rejected.push(pubkey.to_base64());
+        }
+    }
+    if accepted.is_empty() {
+        return Err(ContractError::NoValidHeartbeat {});
+    }
+
+    Ok(HandleResponse {
+        attributes: vec![
+            attr("action", "submit_heartbeats"),
+            attr("relayer", info.sender),
+            attr("accepted", accepted.join(",")),
+            attr("rejected", rejected.join(",")),
+        ],
+        ..HandleResponse::default()
+    })
+}
+
+/// executors sign the sha256 hash of the contract address followed by the height
+pub fn verify_heartbeat_signature(
+    contract_addr: &HumanAddr,
+    height: u64,
+    pubkey: &Binary,
+    signature: &Binary,
+) -> bool {
+    let msg = format!("{}{}", contract_addr, height);
+    let msg_hash = sha2::Sha256::digest(msg.as_bytes());
+    cosmwasm_crypto::secp256k1_verify(msg_hash.as_slice(), signature, pubkey).unwrap_or(false)
+}
+
+fn is_active_executor(deps: Deps, aioracle_addr: &HumanAddr, pubkey: &Binary) -> bool {
     let executor_result: StdResult<Executor> = deps.querier.query_wasm_smart(
         aioracle_addr,
         &QueryExecutor {
@@ -166,16 +270,23 @@ pub fn add_ping(
             },
         },
     );
+    executor_result
+        .map(|executor| executor.is_active)
+        .unwrap_or(false)
+}
 
-    if executor_result.is_err() || !executor_result.unwrap().is_active {
-        return Err(ContractError::UnauthorizedExecutor {});
-    }
-
-    let QueryPingInfoResponse { mut ping_info, .. } =
-        query_ping_info(deps.as_ref(), &env, &pubkey)?;
+fn record_ping(
+    deps: &mut DepsMut,
+    env: &Env,
+    pubkey: &Binary,
+    height: u64,
+    ping_jump: u64,
+    ping_jump_interval: u64,
+) -> Result<(), ContractError> {
+    let QueryPingInfoResponse { mut ping_info, .. } = query_ping_info(deps.as_ref(), env, pubkey)?;
 
     // if add ping too soon & it's not the initial case (case where no one has the first round info) => error
-    if env.block.height.sub(ping_info.latest_ping_height) < ping_jump
+    if height < ping_info.latest_ping_height.add(ping_jump)
         && ping_info.latest_ping_height.ne(&0u64)
     {
         return Err(ContractError::PingTooEarly {});
@@ -183,22 +294,19 @@ pub fn add_ping(
 
     // if time updating ping is valid => update round of round & block
     ping_info.total_ping = ping_info.total_ping + 1;
-    ping_info.latest_ping_height = env.block.height;
+    ping_info.latest_ping_height = height;
 
-    let mut read_ping_info = query_read_ping_info(deps.as_ref(), &env, &pubkey)?;
-    if read_ping_info.checkpoint_height + ping_jump_interval < env.block.height {
-        read_ping_info.checkpoint_height = env.block.height;
+    let mut read_ping_info = query_read_ping_info(deps.as_ref(), env, pubkey)?;
+    if read_ping_info.checkpoint_height + ping_jump_interval < height {
+        read_ping_info.checkpoint_height = height;
         read_ping_info.prev_total_ping = read_ping_info.total_ping;
     };
     read_ping_info.total_ping = read_ping_info.total_ping + 1;
-    read_ping_info.latest_ping_height = env.block.height;
+    read_ping_info.latest_ping_height = height;
 
     MAPPED_COUNT.save(deps.storage, pubkey.as_slice(), &ping_info)?;
     READ_ONLY_MAPPED_COUNT.save(deps.storage, pubkey.as_slice(), &read_ping_info)?;
-    Ok(HandleResponse {
-        attributes: vec![attr("action", "add_ping"), attr("executor", info.sender)],
-        ..HandleResponse::default()
-    })
+    Ok(())
 }
 
 pub fn claim_reward(
@@ -244,9 +352,16 @@ pub fn claim_reward(
         return Err(ContractError::ZeroPing {});
     }
 
+    // pings are only fully rewarded if the executor also served the stages it was needed for
+    let (participation, checkpoint) = load_participation(deps.as_ref(), &pubkey)?;
     let mut total_reward: Coin = Coin {
         denom: base_reward.denom.clone(),
-        amount: Uint128::from(ping_info.total_ping.mul(base_reward.amount.u128() as u64)),
+        amount: Uint128::from(
+            (ping_info.total_ping as u128)
+                .mul(base_reward.amount.u128())
+                .mul(participation.weight as u128)
+                .div(1000),
+        ),
     };
 
     let contract_balance = deps
@@ -266,6 +381,9 @@ pub fn claim_reward(
     ping_info.total_ping = 0;
     ping_info.latest_ping_height = env.block.height;
     MAPPED_COUNT.save(deps.storage, pubkey.as_slice(), &ping_info)?;
+    if let Some(checkpoint) = checkpoint {
+        PARTICIPATION_CHECKPOINTS.save(deps.storage, pubkey.as_slice(), &checkpoint)?;
+    }
 
     let mut cosmos_msgs: Vec<CosmosMsg> = vec![];
     if !total_reward.amount.is_zero() {
@@ -286,6 +404,7 @@ pub fn claim_reward(
         attributes: vec![
             attr("action", "claim_reward"),
             attr("executor", info.sender),
+            attr("participation", participation.weight),
             attr("denom", total_reward.denom),
             attr("amount", total_reward.amount),
         ],
@@ -301,6 +420,7 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             to_binary(&query_read_ping_info(deps, &env, &executor)?)
         }
         QueryMsg::GetState {} => to_binary(&query_state(deps)?),
+        QueryMsg::GetParticipation(executor) => to_binary(&load_participation(deps, &executor)?.0),
         QueryMsg::GetPingInfos {
             offset,
             limit,
@@ -345,6 +465,77 @@ fn query_read_ping_info(deps: Deps, env: &Env, executor: &Binary) -> StdResult<R
     })
 }
 
+/// compares the stage counters of the executor in the aioracle contract with the ones saved at its
+/// last claim. Also returns the current counters, to be saved as the next checkpoint. Without stages
+/// to serve, or without executor stats to weight the reward, only the liveness floor is paid
+fn load_participation(
+    deps: Deps,
+    executor: &Binary,
+) -> StdResult<(ParticipationResponse, Option<ParticipationCheckpoint>)> {
+    let State {
+        aioracle_addr,
+        participation_floor,
+        ..
+    } = config_read(deps.storage).load()?;
+    let floor = participation_floor.unwrap_or(PARTICIPATION_FLOOR).min(1000);
+    let stats: StdResult<ExecutorStatsResponse> = deps.querier.query_wasm_smart(
+        aioracle_addr,
+        &QueryExecutorStats {
+            get_executor_stats: QueryExecutorMsg {
+                pubkey: executor.clone(),
+            },
+        },
+    );
+    let (stages_included, stages_expected) = match stats {
+        // aioracle contracts not tracking the expected stages needed the executor for every stage it was counted in
+        Ok(ExecutorStatsResponse {
+            stages_included,
+            stages_missed,
+            stages_expected,
+            ..
+        }) => (
+            stages_included,
+            stages_expected.unwrap_or((stages_included + stages_missed) * 1000),
+        ),
+        Err(_) => {
+            return Ok((
+                ParticipationResponse {
+                    stages_served: 0,
+                    stages_expected: 0,
+                    weight: floor,
+                },
+                None,
+            ))
+        }
+    };
+    let checkpoint = PARTICIPATION_CHECKPOINTS
+        .may_load(deps.storage, executor.as_slice())?
+        .unwrap_or_default();
+
+    let stages_served = stages_included.saturating_sub(checkpoint.stages_included);
+    let expected = stages_expected.saturating_sub(checkpoint.stages_expected.unwrap_or_default());
+    // no stage needed the executor means it did no more than staying alive
+    let weight = if expected.eq(&0) {
+        floor
+    } else {
+        (stages_served * 1000 * 1000 / expected)
+            .min(1000)
+            .max(floor)
+    };
+
+    Ok((
+        ParticipationResponse {
+            stages_served,
+            stages_expected: expected,
+            weight,
+        },
+        Some(ParticipationCheckpoint {
+            stages_included,
+            stages_expected: Some(stages_expected),
+        }),
+    ))
+}
+
 fn query_state(deps: Deps) -> StdResult<State> {
     config_read(deps.storage).load()
 }
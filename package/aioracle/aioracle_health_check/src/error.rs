@@ -25,6 +25,9 @@ pub enum ContractError {
 
     #[error("Ping for the next round is too early")]
     PingTooEarly {},
+
+    #[error("None of the heartbeats is valid")]
+    NoValidHeartbeat {},
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
@@ -46,6 +46,7 @@ pub fn migrate_v01_to_v02(storage: &mut dyn Storage, migrate_msg: MigrateMsg) ->
         base_reward,
         ping_jump_interval,
         max_reward_claim: Uint128::from(0u64),
+        participation_floor: None,
     })?;
     Ok(())
 }
@@ -9,6 +9,7 @@ pub struct InitMsg {
     pub aioracle_addr: HumanAddr,
     pub base_reward: Coin,
     pub ping_jump: u64,
+    pub participation_floor: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -21,15 +22,29 @@ pub enum HandleMsg {
         ping_jump: Option<u64>,
         ping_jump_interval: Option<u64>,
         max_reward_claim: Option<Uint128>,
+        participation_floor: Option<u64>,
     },
     Ping {
         pubkey: Binary,
     },
+    /// can be relayed by anyone, see `Heartbeat`
+    SubmitHeartbeats {
+        heartbeats: Vec<Heartbeat>,
+    },
     ClaimReward {
         pubkey: Binary,
     },
 }
 
+/// a ping signed off-chain by the executor: secp256k1 signature of sha256(contract address + height)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct Heartbeat {
+    pub pubkey: Binary,
+    pub height: u64,
+    pub signature: Binary,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
@@ -41,6 +56,7 @@ pub enum QueryMsg {
         order: Option<u8>,
     },
     GetState {},
+    GetParticipation(Binary),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -49,6 +65,12 @@ pub struct QueryExecutor {
     pub get_executor: QueryExecutorMsg,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct QueryExecutorStats {
+    pub get_executor_stats: QueryExecutorMsg,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct QueryExecutorMsg {
@@ -81,3 +103,26 @@ pub struct PercentageResponse {
     pub executor: HumanAddr,
     pub percent: u8,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ExecutorStatsResponse {
+    pub pubkey: Binary,
+    pub stages_included: u64,
+    pub stages_missed: u64,
+    pub evidences: u64,
+    pub reputation: u64,
+    /// stages the executor was needed for, in permille of a stage. None if the aioracle does not track it
+    pub stages_expected: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ParticipationResponse {
+    /// stages the executor took part in since its last claim
+    pub stages_served: u64,
+    /// share of the eligible stages the executor was needed for since its last claim, in permille of a stage
+    pub stages_expected: u64,
+    /// permille of the ping reward the executor will receive
+    pub weight: u64,
+}
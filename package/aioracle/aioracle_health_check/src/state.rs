@@ -14,6 +14,8 @@ pub struct State {
     pub base_reward: Coin,
     pub ping_jump_interval: u64,
     pub max_reward_claim: Uint128,
+    /// permille of the reward still paid to an executor that served none of the stages it was needed for
+    pub participation_floor: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -32,6 +34,15 @@ pub struct ReadPingInfo {
     pub latest_ping_height: u64,
 }
 
+/// stage counters of the executor in the aioracle contract when it last claimed its reward
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ParticipationCheckpoint {
+    pub stages_included: u64,
+    /// in permille of a stage
+    pub stages_expected: Option<u64>,
+}
+
 pub fn config(storage: &mut dyn Storage) -> Singleton<State> {
     singleton(storage, CONFIG_KEY)
 }
@@ -42,3 +53,5 @@ pub fn config_read(storage: &dyn Storage) -> ReadonlySingleton<State> {
 
 pub const MAPPED_COUNT: Map<&[u8], PingInfo> = Map::new("ping_count");
 pub const READ_ONLY_MAPPED_COUNT: Map<&[u8], ReadPingInfo> = Map::new("read_only_ping_count");
+pub const PARTICIPATION_CHECKPOINTS: Map<&[u8], ParticipationCheckpoint> =
+    Map::new("participation_checkpoint");
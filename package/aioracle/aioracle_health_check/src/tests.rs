@@ -1,19 +1,22 @@
 use crate::error::ContractError;
-use crate::msg::{HandleMsg, InitMsg, QueryMsg, QueryPingInfoResponse};
-use crate::state::{PingInfo, ReadPingInfo};
-
-use bech32::{self, FromBase32, ToBase32, Variant};
-use cosmwasm_std::testing::{
-    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+use crate::msg::{
+    ExecutorStatsResponse, HandleMsg, Heartbeat, InitMsg, ParticipationResponse, QueryMsg,
+    QueryPingInfoResponse,
 };
+use crate::state::ReadPingInfo;
+
+use aioracle_base::{Executor, ServiceMsg};
+use aioracle_v2::msg::{BoundExecutorFeeMsg, LatestStageResponse, QueryMsg as AioracleQueryMsg};
+use cosmwasm_std::testing::{mock_env, MockApi, MockStorage};
 use cosmwasm_std::{
-    coin, coins, from_binary, from_slice, Binary, BlockInfo, Coin, ContractInfo, Env, HumanAddr,
-    OwnedDeps, StdError, Uint128,
+    coin, coins, to_binary, Binary, BlockInfo, Coin, Deps, DepsMut, Empty, Env, HandleResponse,
+    HumanAddr, InitResponse, MessageInfo, StdError, StdResult, Uint128,
 };
 use cw_multi_test::{next_block, App, Contract, ContractWrapper, SimpleBank};
-use ripemd::{Digest as RipeDigest, Ripemd160};
-use serde::Deserialize;
-use sha2::Digest;
+use cw_storage_plus::Map;
+use k256::ecdsa::{signature::Signer, Signature, SigningKey};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
 const PING_OWNER: &str = "owner";
 const AIORACLE_OWNER: &str = "admin0002";
@@ -75,6 +78,7 @@ fn init_ping(
         aioracle_addr,
         base_reward,
         ping_jump,
+        participation_floor: None,
     };
 
     app.instantiate_contract(group_id, PING_OWNER, &msg, &[], "ping_contract")
@@ -299,6 +303,7 @@ fn test_claim() {
     let mut app = mock_app();
     let (ping_contract, aioracle_addr) = setup_test_case(&mut app);
 
+    // the executor serves no stage, pure liveness is paid in full
     app.execute_contract(
         &HumanAddr::from(PING_OWNER),
         &ping_contract,
@@ -309,6 +314,7 @@ fn test_claim() {
             ping_jump: None,
             ping_jump_interval: None,
             max_reward_claim: Some(Uint128::from(1000u64)),
+            participation_floor: Some(1000),
         },
         &[],
     )
@@ -355,6 +361,7 @@ fn test_claim() {
             ping_jump: None,
             ping_jump_interval: None,
             max_reward_claim: Some(Uint128::from(1u64)),
+            participation_floor: None,
         },
         &[],
     )
@@ -389,3 +396,467 @@ fn test_claim() {
         1u64.to_string() // should be 10 because base reward is 10, mul with 1 ping => 10
     );
 }
+
+// aioracle contract whose executor stats can be set freely
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum MockAioracleHandleMsg {
+    SetStats {
+        pubkey: Binary,
+        stages_included: u64,
+        stages_expected: u64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum MockAioracleQueryMsg {
+    GetExecutor { pubkey: Binary },
+    GetExecutorStats { pubkey: Binary },
+}
+
+const MOCK_STATS: Map<&[u8], ExecutorStatsResponse> = Map::new("stats");
+
+fn mock_aioracle_init(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<InitResponse> {
+    Ok(InitResponse::default())
+}
+
+fn mock_aioracle_handle(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: MockAioracleHandleMsg,
+) -> StdResult<HandleResponse> {
+    let MockAioracleHandleMsg::SetStats {
+        pubkey,
+        stages_included,
+        stages_expected,
+    } = msg;
+    MOCK_STATS.save(
+        deps.storage,
+        pubkey.as_slice(),
+        &ExecutorStatsResponse {
+            pubkey: pubkey.clone(),
+            stages_included,
+            stages_missed: 0,
+            evidences: 0,
+            reputation: 0,
+            stages_expected: Some(stages_expected),
+        },
+    )?;
+    Ok(HandleResponse::default())
+}
+
+fn mock_aioracle_query(deps: Deps, _env: Env, msg: MockAioracleQueryMsg) -> StdResult<Binary> {
+    match msg {
+        MockAioracleQueryMsg::GetExecutor { pubkey } => to_binary(&Executor {
+            pubkey,
+            is_active: true,
+            executing_power: 0,
+            index: 0,
+            left_block: None,
+            jailed_block: None,
+        }),
+        MockAioracleQueryMsg::GetExecutorStats { pubkey } => {
+            to_binary(&MOCK_STATS.load(deps.storage, pubkey.as_slice())?)
+        }
+    }
+}
+
+#[test]
+fn test_claim_weighted_by_participation() {
+    let mut app = mock_app();
+    let executor = HumanAddr::from("orai14n3tx8s5ftzhlxvq0w5962v60vd82h30rha573");
+    let pubkey = Binary::from_base64("AipQCudhlHpWnHjSgVKZ+SoSicvjH7Mp5gCFyDdlnQtn").unwrap();
+
+    let aioracle_id = app.store_code(Box::new(ContractWrapper::new(
+        mock_aioracle_handle,
+        mock_aioracle_init,
+        mock_aioracle_query,
+    )));
+    let aioracle_addr = app
+        .instantiate_contract(aioracle_id, AIORACLE_OWNER, &Empty {}, &[], "aioracle")
+        .unwrap();
+    let ping_id = app.store_code(contract_ping());
+    let ping_contract = app
+        .instantiate_contract(
+            ping_id,
+            PING_OWNER,
+            &InitMsg {
+                aioracle_addr: aioracle_addr.clone(),
+                base_reward: coin(100u128, "orai"),
+                ping_jump: 300,
+                participation_floor: Some(200),
+            },
+            &[],
+            "ping_contract",
+        )
+        .unwrap();
+    app.set_bank_balance(ping_contract.clone(), coins(10000, "orai"))
+        .unwrap();
+    app.execute_contract(
+        &HumanAddr::from(PING_OWNER),
+        &ping_contract,
+        &HandleMsg::ChangeState {
+            owner: None,
+            aioracle_addr: None,
+            base_reward: None,
+            ping_jump: None,
+            ping_jump_interval: None,
+            max_reward_claim: Some(Uint128::from(1000u64)),
+            participation_floor: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let set_stats = |app: &mut App, stages_included: u64, stages_expected: u64| {
+        app.execute_contract(
+            &HumanAddr::from(AIORACLE_OWNER),
+            &aioracle_addr,
+            &MockAioracleHandleMsg::SetStats {
+                pubkey: pubkey.clone(),
+                stages_included,
+                stages_expected,
+            },
+            &[],
+        )
+        .unwrap();
+    };
+    let ping_and_claim = |app: &mut App| -> String {
+        app.execute_contract(
+            &executor,
+            &ping_contract,
+            &HandleMsg::Ping {
+                pubkey: pubkey.clone(),
+            },
+            &[],
+        )
+        .unwrap();
+        let result = app
+            .execute_contract(
+                &executor,
+                &ping_contract,
+                &HandleMsg::ClaimReward {
+                    pubkey: pubkey.clone(),
+                },
+                &[],
+            )
+            .unwrap();
+        result.attributes.iter().last().unwrap().value.clone()
+    };
+
+    // without executor stats the reward cannot be weighted, only the liveness floor is paid
+    assert_eq!(ping_and_claim(&mut app), "20");
+    app.update_block(skip_ping_interval);
+
+    // eligible for 10 stages that needed 1 of 4 executors, it served 2 of the 2.5 stages expected
+    set_stats(&mut app, 2, 2500);
+    let participation: ParticipationResponse = app
+        .wrap()
+        .query_wasm_smart(&ping_contract, &QueryMsg::GetParticipation(pubkey.clone()))
+        .unwrap();
+    assert_eq!(
+        participation,
+        ParticipationResponse {
+            stages_served: 2,
+            stages_expected: 2500,
+            weight: 800,
+        }
+    );
+    assert_eq!(ping_and_claim(&mut app), "80");
+
+    // the claim is a new checkpoint, no stage has needed the executor since
+    let participation: ParticipationResponse = app
+        .wrap()
+        .query_wasm_smart(&ping_contract, &QueryMsg::GetParticipation(pubkey.clone()))
+        .unwrap();
+    assert_eq!(participation.stages_expected, 0);
+    assert_eq!(participation.weight, 200);
+
+    // serving more than expected cannot pay more than the ping reward
+    app.update_block(skip_ping_interval);
+    set_stats(&mut app, 6, 3500);
+    assert_eq!(ping_and_claim(&mut app), "100");
+
+    // serving none of the stages it was needed for only pays the liveness floor
+    app.update_block(skip_ping_interval);
+    set_stats(&mut app, 6, 13500);
+    assert_eq!(ping_and_claim(&mut app), "20");
+}
+
+// provider bridge charging 1 orai per request, without fee schedules, service versions or service policies
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+struct MockBridgeQueryMsg {
+    service_fee_msg: Option<ServiceMsg>,
+    get_bound_executor_fee: Option<BoundExecutorFeeMsg>,
+}
+
+fn mock_bridge_handle(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<HandleResponse> {
+    Ok(HandleResponse::default())
+}
+
+fn mock_bridge_query(_deps: Deps, _env: Env, msg: MockBridgeQueryMsg) -> StdResult<Binary> {
+    match msg {
+        MockBridgeQueryMsg {
+            service_fee_msg: Some(_),
+            ..
+        } => to_binary(&vec![(
+            HumanAddr::from("provider"),
+            "orai".to_string(),
+            Uint128::from(1u64),
+        )]),
+        MockBridgeQueryMsg {
+            get_bound_executor_fee: Some(_),
+            ..
+        } => to_binary(&coin(1u128, "orai")),
+        _ => Err(StdError::generic_err("unknown query")),
+    }
+}
+
+#[test]
+fn test_claim_weighted_by_aioracle_stages() {
+    let mut app = mock_app();
+    let executor = HumanAddr::from("orai14n3tx8s5ftzhlxvq0w5962v60vd82h30rha573");
+    let pubkey = Binary::from_base64("AipQCudhlHpWnHjSgVKZ+SoSicvjH7Mp5gCFyDdlnQtn").unwrap();
+    let other_pubkey = Binary::from_base64("A6ENA5I5QhHyy1QIOLkgTcf/x31WE+JLFoISgmcQaI0t").unwrap();
+
+    let bridge_id = app.store_code(Box::new(ContractWrapper::new(
+        mock_bridge_handle,
+        mock_aioracle_init,
+        mock_bridge_query,
+    )));
+    let bridge_addr = app
+        .instantiate_contract(bridge_id, AIORACLE_OWNER, &Empty {}, &[], "provider_bridge")
+        .unwrap();
+    let aioracle_addr = init_aioracle(
+        &mut app,
+        bridge_addr,
+        coin(1u128, "orai"),
+        vec![pubkey.clone(), other_pubkey.clone()],
+    );
+    let ping_contract = init_ping(&mut app, aioracle_addr.clone(), coin(100u128, "orai"), 300);
+    app.set_bank_balance(ping_contract.clone(), coins(10000, "orai"))
+        .unwrap();
+    app.set_bank_balance(HumanAddr::from("client"), coins(100, "orai"))
+        .unwrap();
+    app.execute_contract(
+        &HumanAddr::from(PING_OWNER),
+        &ping_contract,
+        &HandleMsg::ChangeState {
+            owner: None,
+            aioracle_addr: None,
+            base_reward: None,
+            ping_jump: None,
+            ping_jump_interval: None,
+            max_reward_claim: Some(Uint128::from(1000u64)),
+            participation_floor: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // 3 requests open to all executors, each needing 1 of the 2. The executor only serves the first one
+    for executors in [vec![pubkey.clone()], vec![other_pubkey.clone()], vec![]] {
+        app.execute_contract(
+            &HumanAddr::from("client"),
+            &aioracle_addr,
+            &aioracle_v2::msg::HandleMsg::Request {
+                service: "price".to_string(),
+                input: None,
+                threshold: 1,
+                preference_executor_fee: coin(1u128, "orai"),
+                min_reputation: None,
+                callback: None,
+                service_version: None,
+            },
+            &coins(3u128, "orai"),
+        )
+        .unwrap();
+        let LatestStageResponse { latest_stage } = app
+            .wrap()
+            .query_wasm_smart(&aioracle_addr, &AioracleQueryMsg::LatestStage {})
+            .unwrap();
+        app.execute_contract(
+            &HumanAddr::from(AIORACLE_OWNER),
+            &aioracle_addr,
+            &aioracle_v2::msg::HandleMsg::RegisterMerkleRoot {
+                stage: latest_stage,
+                merkle_root: "4a2e27a2befb41a0655b8fe98d9c1a9f18ece280dc78b442734ead617e6bf3fc"
+                    .to_string(),
+                executors,
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    // every finalized stage expected half a stage from the executor, it served 1 of the 1.5 stages expected
+    let participation: ParticipationResponse = app
+        .wrap()
+        .query_wasm_smart(&ping_contract, &QueryMsg::GetParticipation(pubkey.clone()))
+        .unwrap();
+    assert_eq!(
+        participation,
+        ParticipationResponse {
+            stages_served: 1,
+            stages_expected: 1500,
+            weight: 666,
+        }
+    );
+    app.execute_contract(
+        &executor,
+        &ping_contract,
+        &HandleMsg::Ping {
+            pubkey: pubkey.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+    let result = app
+        .execute_contract(
+            &executor,
+            &ping_contract,
+            &HandleMsg::ClaimReward {
+                pubkey: pubkey.clone(),
+            },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(result.attributes.iter().last().unwrap().value, "66");
+
+    // no stage has been finalized since the claim, staying alive only pays the liveness floor
+    let participation: ParticipationResponse = app
+        .wrap()
+        .query_wasm_smart(&ping_contract, &QueryMsg::GetParticipation(pubkey))
+        .unwrap();
+    assert_eq!(participation.stages_expected, 0);
+    assert_eq!(participation.weight, 100);
+}
+
+fn sign_heartbeat(signing_key: &SigningKey, contract_addr: &HumanAddr, height: u64) -> Heartbeat {
+    let signature: Signature = signing_key.sign(format!("{}{}", contract_addr, height).as_bytes());
+    Heartbeat {
+        pubkey: Binary::from(signing_key.verify_key().to_bytes().as_slice()),
+        height,
+        signature: Binary::from(signature.as_ref()),
+    }
+}
+
+#[test]
+fn test_submit_heartbeats() {
+    let mut app = mock_app();
+    let aioracle_id = app.store_code(Box::new(ContractWrapper::new(
+        mock_aioracle_handle,
+        mock_aioracle_init,
+        mock_aioracle_query,
+    )));
+    let aioracle_addr = app
+        .instantiate_contract(aioracle_id, AIORACLE_OWNER, &Empty {}, &[], "aioracle")
+        .unwrap();
+    let ping_contract = init_ping(&mut app, aioracle_addr, coin(10u128, "orai"), 300);
+
+    let signing_keys: Vec<SigningKey> = (1u8..3)
+        .map(|i| SigningKey::from_bytes(&[i; 32]).unwrap())
+        .collect();
+    let height = app.block_info().height;
+    let mut forged = sign_heartbeat(&signing_keys[0], &ping_contract, height);
+    forged.pubkey = Binary::from(
+        SigningKey::from_bytes(&[3u8; 32])
+            .unwrap()
+            .verify_key()
+            .to_bytes()
+            .as_slice(),
+    );
+    let heartbeats = vec![
+        sign_heartbeat(&signing_keys[0], &ping_contract, height),
+        sign_heartbeat(&signing_keys[1], &ping_contract, height),
+        forged,
+    ];
+
+    // anyone can relay the heartbeats
+    let result = app
+        .execute_contract(
+            &HumanAddr::from("relayer"),
+            &ping_contract,
+            &HandleMsg::SubmitHeartbeats {
+                heartbeats: heartbeats.clone(),
+            },
+            &[],
+        )
+        .unwrap();
+    let accepted = result
+        .attributes
+        .iter()
+        .find(|attr| attr.key == "accepted")
+        .unwrap();
+    assert_eq!(
+        accepted.value,
+        format!(
+            "{},{}",
+            heartbeats[0].pubkey.to_base64(),
+            heartbeats[1].pubkey.to_base64()
+        )
+    );
+    for heartbeat in heartbeats[..2].iter() {
+        let ping_info: QueryPingInfoResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &ping_contract,
+                &QueryMsg::GetPingInfo(heartbeat.pubkey.clone()),
+            )
+            .unwrap();
+        assert_eq!(ping_info.ping_info.total_ping, 1);
+        assert_eq!(ping_info.ping_info.latest_ping_height, height);
+    }
+
+    // replaying the same heartbeats is too early
+    assert_eq!(
+        app.execute_contract(
+            &HumanAddr::from("relayer"),
+            &ping_contract,
+            &HandleMsg::SubmitHeartbeats {
+                heartbeats: heartbeats[..2].to_vec(),
+            },
+            &[],
+        )
+        .unwrap_err(),
+        ContractError::NoValidHeartbeat {}.to_string()
+    );
+
+    // heartbeats older than a ping jump are rejected, fresh ones are accepted
+    app.update_block(|block| block.height += 300);
+    let stale = sign_heartbeat(&signing_keys[0], &ping_contract, height);
+    let fresh = sign_heartbeat(&signing_keys[1], &ping_contract, height + 300);
+    let result = app
+        .execute_contract(
+            &HumanAddr::from("relayer"),
+            &ping_contract,
+            &HandleMsg::SubmitHeartbeats {
+                heartbeats: vec![stale.clone(), fresh.clone()],
+            },
+            &[],
+        )
+        .unwrap();
+    let rejected = result
+        .attributes
+        .iter()
+        .find(|attr| attr.key == "rejected")
+        .unwrap();
+    assert_eq!(rejected.value, stale.pubkey.to_base64());
+    let ping_info: QueryPingInfoResponse = app
+        .wrap()
+        .query_wasm_smart(&ping_contract, &QueryMsg::GetPingInfo(fresh.pubkey))
+        .unwrap();
+    assert_eq!(ping_info.ping_info.total_ping, 2);
+}
@@ -1,8 +1,8 @@
 use crate::error::ContractError;
 use crate::msg::{HandleMsg, InitMsg, PagingFeesOptions, QueryMsg, UpdateContractMsg};
-use crate::state::{ContractInfo, CONTRACT_INFO, SERVICE_FEES};
+use crate::state::{ContractInfo, CONTRACT_INFO, SERVICE_FEES, SERVICE_FEE_SCHEDULES};
 
-use aioracle_base::ServiceFeesResponse;
+use aioracle_base::{FeeSchedule, ServiceFeesResponse};
 use cosmwasm_std::{
     attr, to_binary, Binary, Deps, DepsMut, Env, HandleResponse, InitResponse, MessageInfo, Order,
     StdError, StdResult,
@@ -41,6 +41,9 @@ pub fn handle(
 ) -> Result<HandleResponse, ContractError> {
     match msg {
         HandleMsg::UpdateServiceFees { fees } => try_update_service_fees(deps, info, env, fees),
+        HandleMsg::UpdateFeeSchedule { schedule } => {
+            try_update_fee_schedule(deps, info, env, schedule)
+        }
         HandleMsg::RemoveServiceFees() => try_remove_service_fees(deps, info, env),
         HandleMsg::UpdateInfo(msg) => try_update_info(deps, info, env, msg),
     }
@@ -53,6 +56,8 @@ pub fn try_update_service_fees(
     fees: Coin,
 ) -> Result<HandleResponse, ContractError> {
     SERVICE_FEES.save(deps.storage, info.sender.as_str(), &fees)?;
+    // a flat fee replaces the fee schedule
+    SERVICE_FEE_SCHEDULES.remove(deps.storage, info.sender.as_str());
     Ok(HandleResponse {
         messages: vec![],
         attributes: vec![
@@ -65,12 +70,48 @@ pub fn try_update_service_fees(
     })
 }
 
+pub fn try_update_fee_schedule(
+    deps: DepsMut,
+    info: MessageInfo,
+    _env: Env,
+    schedule: FeeSchedule,
+) -> Result<HandleResponse, ContractError> {
+    let fees = schedule
+        .accepted
+        .first()
+        .cloned()
+        .ok_or(ContractError::EmptyFeeSchedule {})?;
+    if let Some(tier) = schedule.tiers.iter().find(|tier| tier.discount > 1000) {
+        return Err(ContractError::InvalidDiscount {
+            discount: tier.discount,
+        });
+    }
+    if !schedule.tiers.is_empty() && schedule.period == 0 {
+        return Err(ContractError::InvalidPeriod {});
+    }
+
+    // the default coin stays the flat fee for the contracts reading it only
+    SERVICE_FEES.save(deps.storage, info.sender.as_str(), &fees)?;
+    SERVICE_FEE_SCHEDULES.save(deps.storage, info.sender.as_str(), &schedule)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![
+            attr("action", "update_fee_schedule"),
+            attr("caller", info.sender),
+            attr("accepted", schedule.accepted.len()),
+            attr("tiers", schedule.tiers.len()),
+        ],
+        data: None,
+    })
+}
+
 pub fn try_remove_service_fees(
     deps: DepsMut,
     info: MessageInfo,
     _env: Env,
 ) -> Result<HandleResponse, ContractError> {
     SERVICE_FEES.remove(deps.storage, &info.sender.as_str());
+    SERVICE_FEE_SCHEDULES.remove(deps.storage, info.sender.as_str());
     Ok(HandleResponse {
         messages: vec![],
         attributes: vec![
@@ -107,14 +148,14 @@ pub fn try_update_info(
     })
 }
 
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         // implement Query AiRequest from market base
         QueryMsg::GetListServiceFees(options) => {
-            to_binary(&query_list_service_fees(deps, &options)?)
+            to_binary(&query_list_service_fees(deps, env, &options)?)
         }
         QueryMsg::GetServiceFees { addr: address } => {
-            to_binary(&query_service_fees(deps, address)?)
+            to_binary(&query_service_fees(deps, env, address)?)
         }
         QueryMsg::GetContractInfo {} => to_binary(&query_contract_info(deps)?),
     }
@@ -151,6 +192,7 @@ fn _get_range_fees_params(
 
 pub fn query_list_service_fees(
     deps: Deps,
+    env: Env,
     options: &PagingFeesOptions,
 ) -> StdResult<Vec<ServiceFeesResponse>> {
     let (limit, min, max, order_enum) = _get_range_fees_params(options);
@@ -158,16 +200,25 @@ pub fn query_list_service_fees(
     let res: StdResult<Vec<ServiceFeesResponse>> = SERVICE_FEES
         .range(deps.storage, min, max, order_enum)
         .take(limit)
-        .map(|kv_item| parse_service_fees(kv_item))
+        .map(|kv_item| parse_service_fees(deps, &env, kv_item))
         .collect();
     res
 }
 
 // if bidder is empty, it is pending ai requests
-pub fn query_service_fees(deps: Deps, address: String) -> StdResult<ServiceFeesResponse> {
+pub fn query_service_fees(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<ServiceFeesResponse> {
     let fees: Option<Coin> = SERVICE_FEES.may_load(deps.storage, address.as_str())?;
     if let Some(fees) = fees {
-        return Ok(ServiceFeesResponse { address, fees });
+        let schedule = load_fee_schedule(deps, &env, &address)?;
+        return Ok(ServiceFeesResponse {
+            address,
+            fees,
+            schedule,
+        });
     }
     Err(StdError::generic_err("query service fees not found"))
 }
@@ -176,11 +227,27 @@ pub fn query_contract_info(deps: Deps) -> StdResult<ContractInfo> {
     CONTRACT_INFO.load(deps.storage)
 }
 
-fn parse_service_fees(item: StdResult<KV<Coin>>) -> StdResult<ServiceFeesResponse> {
+// expired schedules are not returned, the flat fee applies instead
+fn load_fee_schedule(deps: Deps, env: &Env, address: &str) -> StdResult<Option<FeeSchedule>> {
+    Ok(SERVICE_FEE_SCHEDULES
+        .may_load(deps.storage, address)?
+        .filter(|schedule| !schedule.is_expired(env.block.height)))
+}
+
+fn parse_service_fees(
+    deps: Deps,
+    env: &Env,
+    item: StdResult<KV<Coin>>,
+) -> StdResult<ServiceFeesResponse> {
     item.and_then(|(addr_vec, fees)| {
         // will panic if length is greater than 8, but we can make sure it is u64
         // try_into will box vector to fixed array
         let address: String = from_utf8(addr_vec.as_slice())?.to_string();
-        Ok(ServiceFeesResponse { address, fees })
+        let schedule = load_fee_schedule(deps, env, &address)?;
+        Ok(ServiceFeesResponse {
+            address,
+            fees,
+            schedule,
+        })
     })
 }
@@ -8,4 +8,13 @@ pub enum ContractError {
 
     #[error("Unauthorized auction storage with sender: {sender}")]
     Unauthorized { sender: String },
+
+    #[error("Fee schedule must accept at least one coin")]
+    EmptyFeeSchedule {},
+
+    #[error("Invalid discount of fee tier: {discount}. Must be at most 1000")]
+    InvalidDiscount { discount: u64 },
+
+    #[error("Fee schedule with tiers must have a non-zero period")]
+    InvalidPeriod {},
 }
@@ -1,3 +1,4 @@
+use aioracle_base::FeeSchedule;
 use cosmwasm_std::{Coin, HumanAddr};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -9,6 +10,7 @@ pub struct InitMsg {}
 #[serde(rename_all = "snake_case")]
 pub enum HandleMsg {
     UpdateServiceFees { fees: Coin },
+    UpdateFeeSchedule { schedule: FeeSchedule },
     RemoveServiceFees(),
     UpdateInfo(UpdateContractMsg),
 }
@@ -1,3 +1,4 @@
+use aioracle_base::FeeSchedule;
 use cosmwasm_std::{Coin, HumanAddr};
 use cw_storage_plus::{Item, Map};
 use schemars::JsonSchema;
@@ -6,6 +7,7 @@ use serde::{Deserialize, Serialize};
 pub const THRESHOLD: Item<u8> = Item::new("report_threhold");
 pub const CONTRACT_INFO: Item<ContractInfo> = Item::new("contract_info");
 pub const SERVICE_FEES: Map<&str, Coin> = Map::new("service_fees");
+pub const SERVICE_FEE_SCHEDULES: Map<&str, FeeSchedule> = Map::new("service_fee_schedules");
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct ContractInfo {
@@ -1,7 +1,8 @@
 use crate::contract::*;
 
 use crate::msg::*;
-use aioracle_base::ServiceFeesResponse;
+use crate::error::ContractError;
+use aioracle_base::{FeeSchedule, FeeTier, ServiceFeesResponse};
 use cosmwasm_std::testing::{
     mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
 };
@@ -105,3 +106,151 @@ fn remove_service_fees() {
         Err(_err)
     ))
 }
+
+#[test]
+fn update_fee_schedule() {
+    let (mut deps, mut contract_env) = setup_contract();
+    let schedule = FeeSchedule {
+        accepted: vec![coin(10u128, DENOM), coin(20u128, "atom")],
+        tiers: vec![FeeTier {
+            min_requests: 10,
+            discount: 200,
+        }],
+        period: 100,
+        expiry: Some(contract_env.block.height + 50),
+    };
+
+    // discount above 100%
+    let mut invalid_schedule = schedule.clone();
+    invalid_schedule.tiers[0].discount = 1001;
+    assert!(matches!(
+        handle(
+            deps.as_mut(),
+            contract_env.clone(),
+            mock_info("provider", &[]),
+            HandleMsg::UpdateFeeSchedule {
+                schedule: invalid_schedule,
+            },
+        ),
+        Err(ContractError::InvalidDiscount { discount: 1001 })
+    ));
+
+    // no accepted coin
+    let mut invalid_schedule = schedule.clone();
+    invalid_schedule.accepted = vec![];
+    assert!(matches!(
+        handle(
+            deps.as_mut(),
+            contract_env.clone(),
+            mock_info("provider", &[]),
+            HandleMsg::UpdateFeeSchedule {
+                schedule: invalid_schedule,
+            },
+        ),
+        Err(ContractError::EmptyFeeSchedule {})
+    ));
+
+    handle(
+        deps.as_mut(),
+        contract_env.clone(),
+        mock_info("provider", &[]),
+        HandleMsg::UpdateFeeSchedule {
+            schedule: schedule.clone(),
+        },
+    )
+    .unwrap();
+
+    // the default coin is the flat fee
+    let service_query: ServiceFeesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            contract_env.clone(),
+            QueryMsg::GetServiceFees {
+                addr: "provider".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(service_query.fees, coin(10u128, DENOM));
+    assert_eq!(service_query.schedule, Some(schedule.clone()));
+
+    // expired schedule falls back to the flat fee
+    contract_env.block.height += 50;
+    let service_queries: Vec<ServiceFeesResponse> = from_binary(
+        &query(
+            deps.as_ref(),
+            contract_env.clone(),
+            QueryMsg::GetListServiceFees(PagingFeesOptions {
+                offset: None,
+                limit: None,
+                order: None,
+            }),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(service_queries[0].fees, coin(10u128, DENOM));
+    assert_eq!(service_queries[0].schedule, None);
+
+    // a flat fee replaces the schedule
+    contract_env.block.height -= 50;
+    handle(
+        deps.as_mut(),
+        contract_env.clone(),
+        mock_info("provider", &[]),
+        HandleMsg::UpdateServiceFees {
+            fees: coin(5u128, DENOM),
+        },
+    )
+    .unwrap();
+    let service_query: ServiceFeesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            contract_env.clone(),
+            QueryMsg::GetServiceFees {
+                addr: "provider".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(service_query.fees, coin(5u128, DENOM));
+    assert_eq!(service_query.schedule, None);
+}
+
+#[test]
+fn price_fee_schedule() {
+    let schedule = FeeSchedule {
+        accepted: vec![coin(100u128, DENOM), coin(300u128, "atom")],
+        tiers: vec![
+            FeeTier {
+                min_requests: 5,
+                discount: 100,
+            },
+            FeeTier {
+                min_requests: 20,
+                discount: 250,
+            },
+        ],
+        period: 100,
+        expiry: None,
+    };
+
+    // default coin when none of the accepted coins is sent
+    assert_eq!(schedule.price(&[], 1), Some(coin(100u128, DENOM)));
+    assert_eq!(
+        schedule.price(&[coin(1000u128, "atom")], 1),
+        Some(coin(300u128, "atom"))
+    );
+    assert_eq!(
+        schedule.price(&[coin(1000u128, DENOM)], 5),
+        Some(coin(90u128, DENOM))
+    );
+    assert_eq!(
+        schedule.price(&[coin(1000u128, "atom")], 25),
+        Some(coin(225u128, "atom"))
+    );
+    assert_eq!(schedule.period_of(250), 2);
+    assert!(!schedule.is_expired(u64::MAX));
+}
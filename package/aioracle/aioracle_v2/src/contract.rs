@@ -1,14 +1,18 @@
-use aioracle_base::{Executor, GetServiceFeesMsg, Reward, ServiceMsg};
+use aioracle_base::{
+    AssetInfo, Executor, GetServiceFeesMsg, Reward, ServiceFeesResponse, ServiceMsg, ServicePolicy,
+    VersionStatus,
+};
 use cosmwasm_std::{
-    attr, from_slice, to_binary, BankMsg, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env,
-    HandleResponse, HumanAddr, InitResponse, MessageInfo, MigrateResponse, Order, StdError,
-    StdResult, Uint128, KV,
+    attr, from_binary, from_slice, to_binary, BankMsg, Binary, Coin, CosmosMsg, Decimal, Deps,
+    DepsMut, Env, HandleResponse, HumanAddr, InitResponse, MessageInfo, MigrateResponse, Order,
+    StdError, StdResult, Storage, Uint128, WasmMsg, KV,
 };
 
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{Cw20HandleMsg, Cw20ReceiveMsg};
 
 use bech32::{self, ToBase32, Variant};
-use cw_storage_plus::Bound;
+use cw_storage_plus::{Bound, U64Key};
 use ripemd::{Digest as RipeDigest, Ripemd160};
 use sha2::Digest;
 use std::convert::TryInto;
@@ -16,20 +20,23 @@ use std::ops::{Add, Mul};
 
 use crate::error::ContractError;
 use crate::executors::{
-    handle_executor_join, handle_executor_leave, handle_prepare_withdraw_pool,
-    process_executors_pool, query_bound_executor_fee, query_executor, query_executor_size,
+    get_reputation, handle_executor_join, handle_executor_leave, handle_prepare_withdraw_pool,
+    load_active_executors, load_executor_stats, process_executors_pool, process_executors_stats,
+    query_bound_executor_fee, query_executor, query_executor_size, query_executor_stats,
     query_executors, query_executors_by_index, query_trusting_pool, query_trusting_pools,
-    remove_executors, save_executors, update_executors,
+    remove_executors, save_executors, slash_executor, update_executors,
 };
-use crate::migrations::migrate_v02_to_v03;
+use crate::migrations::migrate_store;
 use crate::msg::{
-    CurrentStageResponse, GetParticipantFee, GetServiceContracts, GetServiceFees, HandleMsg,
-    InitMsg, IsClaimedResponse, LatestStageResponse, MigrateMsg, QueryMsg, Report, RequestResponse,
-    StageInfo, UpdateConfigMsg,
+    CurrentStageResponse, Cw20HookMsg, Evidence, GetParticipantFee, GetServiceContracts,
+    GetServiceFeeSchedules, GetServiceFees, GetServicePolicy, GetServiceVersion, HandleMsg,
+    InitMsg, IsClaimedResponse, LatestStageResponse, MigrateMsg, OracleCallbackMsg, QueryMsg,
+    Report, RequestResponse, RewardClaim, StageInfo, UpdateConfigMsg, VerifiedResultResponse,
 };
 use crate::state::{
-    executors_map, requests, Config, Contracts, Request, CHECKPOINT, CLAIM, CONFIG, CONTRACT_FEES,
-    EVIDENCES, EXECUTORS_INDEX, LATEST_STAGE,
+    executors_map, requests, Callback, Config, Contracts, Request, RequestVolume, ServiceVersion,
+    CHECKPOINT, CLAIM, CONFIG, CONTRACT_FEES, EVIDENCES, EXECUTORS_INDEX, LATEST_STAGE,
+    PENDING_CALLBACKS, REPORTS, REQUEST_VOLUMES, STAGE_CLAIMS, VERIFIED_RESULTS,
 };
 use std::collections::HashMap;
 
@@ -38,7 +45,11 @@ pub const MAXIMUM_REQ_THRESHOLD: u64 = 67;
 // 7 days in blocks (avg 6 secs / block)
 pub const TRUSTING_PERIOD: u64 = 100800;
 pub const SLASHING_AMOUNT: u64 = 100; // maximum is 1000, aka permilie
+pub const SLASHING_BOUNTY: u64 = 500; // permille of the slashed amount paid to the reporter
+pub const EVIDENCE_PENALTY: u64 = 100; // permille of reputation lost for each evidence
 pub const PENDING_PERIOD: u64 = TRUSTING_PERIOD;
+// 1 day in blocks (avg 6 secs / block)
+pub const REQUEST_TIMEOUT: u64 = 14400;
 pub const DENOM: &str = "orai";
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:aioracle-v2";
@@ -60,8 +71,10 @@ pub fn init(deps: DepsMut, _env: Env, info: MessageInfo, msg: InitMsg) -> StdRes
         max_req_threshold: MAXIMUM_REQ_THRESHOLD,
         trusting_period: TRUSTING_PERIOD,
         slashing_amount: SLASHING_AMOUNT,
+        slashing_bounty: Some(SLASHING_BOUNTY),
         denom: DENOM.to_string(),
         pending_period: PENDING_PERIOD,
+        request_timeout: Some(REQUEST_TIMEOUT),
     };
     CONFIG.save(deps.storage, &config)?;
 
@@ -81,6 +94,7 @@ pub fn init(deps: DepsMut, _env: Env, info: MessageInfo, msg: InitMsg) -> StdRes
                 index: executor_index,
                 is_active: true,
                 left_block: None,
+                jailed_block: None,
             };
             executor_index += 1;
             final_executor
@@ -110,11 +124,20 @@ pub fn handle(
             merkle_root,
             executors,
         } => execute_register_merkle_root(deps, env, info, stage, merkle_root, executors),
+        HandleMsg::SubmitReport {
+            stage,
+            merkle_root,
+            executor,
+            signature,
+        } => handle_submit_report(deps, env, stage, merkle_root, executor, signature),
         HandleMsg::Request {
             service,
             input,
             threshold,
             preference_executor_fee,
+            min_reputation,
+            callback,
+            service_version,
         } => handle_request(
             deps,
             info,
@@ -123,23 +146,29 @@ pub fn handle(
             input,
             threshold,
             preference_executor_fee,
+            min_reputation,
+            callback,
+            service_version,
+            None,
         ),
-        // HandleMsg::ClaimReward {
-        //     stage,
-        //     report,
-        //     proof,
-        // } => handle_claim(deps, env, stage, report, proof),
+        HandleMsg::Receive(cw20_msg) => handle_receive_cw20(deps, env, info, cw20_msg),
+        HandleMsg::ClaimRewards { claims } => handle_claim_rewards(deps, env, claims),
         HandleMsg::WithdrawFees { amount, denom } => handle_withdraw_fees(deps, env, amount, denom),
-        HandleMsg::PrepareWithdrawPool { pubkey } => {
-            handle_prepare_withdraw_pool(deps, env, info, pubkey)
+        HandleMsg::PrepareWithdrawPool { pubkey, asset_info } => {
+            handle_prepare_withdraw_pool(deps, env, info, pubkey, asset_info)
+        }
+        HandleMsg::ReclaimExpiredRequest { stage } => {
+            handle_reclaim_expired_request(deps, env, stage)
+        }
+        HandleMsg::RelayCallback { stage } => handle_relay_callback(deps, env, info, stage),
+        HandleMsg::SubmitResult { stage, data, proof } => {
+            handle_submit_result(deps, stage, data, proof)
         }
         HandleMsg::ExecutorJoin { executor } => handle_executor_join(deps, env, info, executor),
         HandleMsg::ExecutorLeave { executor } => handle_executor_leave(deps, env, info, executor),
-        HandleMsg::SubmitEvidence {
-            stage,
-            report,
-            proof,
-        } => handle_submit_evidence(deps, env, info, stage, report, proof),
+        HandleMsg::SubmitEvidence { stage, evidence } => {
+            handle_submit_evidence(deps, env, info, stage, evidence)
+        }
     }
 }
 
@@ -149,14 +178,13 @@ pub fn migrate(
     _info: MessageInfo,
     _msg: MigrateMsg,
 ) -> StdResult<MigrateResponse> {
-    // migrate_v02_to_v03(deps.storage)?;
-    let executor_index = EXECUTORS_INDEX.load(deps.storage)?;
-    EXECUTORS_INDEX.save(deps.storage, &(executor_index + 1))?;
+    let layout = migrate_store(deps.storage)?;
 
     // once we have "migrated", set the new version and return success
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     Ok(MigrateResponse {
         attributes: vec![
+            attr("migrated_from", layout.as_str()),
             attr("new_contract_name", CONTRACT_NAME),
             attr("new_contract_version", CONTRACT_VERSION),
         ],
@@ -202,8 +230,10 @@ pub fn execute_update_config(
         new_max_req_threshold,
         new_trust_period,
         new_slashing_amount,
+        new_slashing_bounty,
         new_denom,
         new_pending_period,
+        new_request_timeout,
     } = update_config_msg;
     let cfg = CONFIG.load(deps.storage)?;
     let owner = cfg.owner;
@@ -234,12 +264,18 @@ pub fn execute_update_config(
         if let Some(slashing_amount) = new_slashing_amount {
             exists.slashing_amount = slashing_amount;
         }
+        if let Some(slashing_bounty) = new_slashing_bounty {
+            exists.slashing_bounty = Some(slashing_bounty);
+        }
         if let Some(denom) = new_denom {
             exists.denom = denom;
         }
         if let Some(pending_period) = new_pending_period {
             exists.pending_period = pending_period;
         }
+        if let Some(request_timeout) = new_request_timeout {
+            exists.request_timeout = Some(request_timeout);
+        }
         Ok(exists)
     })?;
 
@@ -261,6 +297,54 @@ pub fn execute_update_config(
     })
 }
 
+pub fn handle_receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<HandleResponse, ContractError> {
+    // the sender is the cw20 contract, the tokens are handled like sent funds
+    let asset_info = AssetInfo::Token {
+        contract_addr: info.sender,
+    };
+    let info = MessageInfo {
+        sender: cw20_msg.sender,
+        sent_funds: vec![Coin {
+            denom: asset_info.denom(),
+            amount: cw20_msg.amount,
+        }],
+    };
+    match from_binary(&cw20_msg.msg.unwrap_or_default()) {
+        Ok(Cw20HookMsg::Request {
+            service,
+            input,
+            threshold,
+            preference_executor_fee,
+            min_reputation,
+            callback,
+            service_version,
+        }) => handle_request(
+            deps,
+            info,
+            env,
+            service,
+            input,
+            threshold,
+            Coin {
+                denom: asset_info.denom(),
+                amount: preference_executor_fee,
+            },
+            min_reputation,
+            callback,
+            service_version,
+            Some(asset_info),
+        ),
+        Err(_) => Err(ContractError::Std(StdError::generic_err(
+            "invalid cw20 hook message",
+        ))),
+    }
+}
+
 pub fn handle_request(
     deps: DepsMut,
     info: MessageInfo,
@@ -269,47 +353,122 @@ pub fn handle_request(
     input: Option<String>,
     threshold: u64,
     preference_executor_fee: Coin,
+    min_reputation: Option<u64>,
+    callback: Option<Callback>,
+    service_version: Option<u64>,
+    asset_info: Option<AssetInfo>,
 ) -> Result<HandleResponse, ContractError> {
     let stage = LATEST_STAGE.update(deps.storage, |stage| -> StdResult<_> { Ok(stage + 1) })?;
     let Config {
         contract_fee,
         max_req_threshold,
+        request_timeout,
         ..
     } = CONFIG.load(deps.storage)?;
 
+    // the contract fee & the bound executor fee apply to every request. A cw20 request pays only in its token, so it
+    // is rejected when a fee is charged in another denom
+    if let Some(asset_info) = asset_info.as_ref() {
+        if !contract_fee.amount.is_zero() && contract_fee.denom.ne(&asset_info.denom()) {
+            return Err(ContractError::InsufficientFundsContractFees {});
+        }
+    }
     CONTRACT_FEES.update(deps.storage, |mut fee| -> StdResult<_> {
-        fee.amount = fee.amount + contract_fee.amount;
+        fee.amount += contract_fee.amount;
         Ok(fee)
     })?;
 
     // reward plus preference must match sent funds
     let bound_executor_fee: Coin = query_bound_executor_fee(deps.as_ref())?;
-
-    if preference_executor_fee.denom.ne(&bound_executor_fee.denom)
-        || preference_executor_fee
-            .amount
-            .lt(&bound_executor_fee.amount)
+    let bound_executor_fee_charged = asset_info.is_none() || !bound_executor_fee.amount.is_zero();
+    if bound_executor_fee_charged
+        && (preference_executor_fee.denom.ne(&bound_executor_fee.denom)
+            || preference_executor_fee
+                .amount
+                .lt(&bound_executor_fee.amount))
     {
         return Err(ContractError::InsufficientFundsBoundFees {});
     }
 
-    // collect fees
-    let mut rewards = get_service_fees(deps.as_ref(), &service)?;
+    // the request keeps running against the contracts it has paid for, even if the service is updated
+    let service_version = pin_service_version(deps.as_ref(), &service, service_version)?;
 
-    if !bound_executor_fee.amount.is_zero() {
+    // collect fees, priced with the fee schedules of the providers
+    let fees = get_service_fee_schedules(deps.as_ref(), &service, service_version)?;
+    let mut rewards = price_service_fees(
+        deps.storage,
+        &info.sender,
+        fees,
+        &info.sent_funds,
+        env.block.height,
+    )?;
+
+    let ServicePolicy {
+        min_threshold,
+        executors,
+        max_fee,
+    } = get_service_policy(deps.as_ref(), &service)?;
+    if let Some(min_threshold) = min_threshold {
+        if threshold.lt(&min_threshold) {
+            return Err(ContractError::ThresholdBelowPolicy { min_threshold });
+        }
+    }
+    if let Some(executors) = executors.as_ref() {
+        if threshold.gt(&(executors.len() as u64)) {
+            return Err(ContractError::InvalidThreshold {});
+        }
+    }
+    if let Some(max_fee) = max_fee {
+        let fee: u128 = rewards
+            .iter()
+            .filter(|reward| reward.1.eq(&max_fee.denom))
+            .map(|reward| reward.2.u128())
+            .chain(
+                std::iter::once(preference_executor_fee.amount.u128())
+                    .filter(|_| preference_executor_fee.denom.eq(&max_fee.denom)),
+            )
+            .sum();
+        if fee.gt(&max_fee.amount.u128()) {
+            return Err(ContractError::MaxFeeExceeded {
+                fee,
+                max_fee: max_fee.amount.u128(),
+                denom: max_fee.denom,
+            });
+        }
+    }
+
+    // collect the preference executor fee so it can be refunded if the request expires
+    if !preference_executor_fee.amount.is_zero() {
         rewards.push((
             HumanAddr::from("placeholder"),
-            bound_executor_fee.denom,
-            bound_executor_fee.amount,
+            preference_executor_fee.denom.clone(),
+            preference_executor_fee.amount,
         ));
     }
 
+    // the callback budget is paid once, not per executor
+    let mut sent_funds = info.sent_funds.clone();
+    if let Some(callback) = callback.as_ref() {
+        let fund = sent_funds
+            .iter_mut()
+            .find(|fund| fund.denom.eq(&preference_executor_fee.denom))
+            .ok_or(ContractError::InsufficientFundsRequestFees {})?;
+        fund.amount = Uint128::from(
+            fund.amount
+                .u128()
+                .checked_sub(callback.fee.u128())
+                .ok_or(ContractError::InsufficientFundsRequestFees {})?,
+        );
+    }
+
     // TODO: add substract contract fee & verify against it
-    if !verify_request_fees(&info.sent_funds, &rewards, threshold, &contract_fee) {
+    if !verify_request_fees(&sent_funds, &rewards, threshold, &contract_fee) {
         return Err(ContractError::InsufficientFundsRequestFees {});
     }
 
-    rewards.pop(); // pop so we dont store the placeholder reward in the list
+    if !preference_executor_fee.amount.is_zero() {
+        rewards.pop(); // pop so we dont store the placeholder reward in the list
+    }
 
     // this will keep track of the executor list of the request
     let current_size = query_executor_size(deps.as_ref())?;
@@ -335,8 +494,15 @@ pub fn handle_request(
             merkle_root: String::from(""),
             threshold,
             service: service.clone(),
+            service_version,
             input,
             rewards,
+            expired_height: Some(env.block.height + request_timeout.unwrap_or(REQUEST_TIMEOUT)),
+            min_reputation,
+            asset_info,
+            callback,
+            executors,
+            reclaimed_height: None,
         },
     )?;
 
@@ -352,31 +518,239 @@ pub fn handle_request(
     })
 }
 
+pub fn handle_reclaim_expired_request(
+    deps: DepsMut,
+    env: Env,
+    stage: u64,
+) -> Result<HandleResponse, ContractError> {
+    let mut request = requests().load(deps.storage, &stage.to_be_bytes())?;
+    if !request.merkle_root.is_empty() {
+        return Err(ContractError::AlreadyFinished {});
+    }
+    if request.reclaimed_height.is_some() {
+        return Err(ContractError::RequestReclaimed {});
+    }
+    let expired_height = get_expired_height(deps.as_ref(), &request)?;
+    if env.block.height < expired_height {
+        return Err(ContractError::RequestNotExpired {
+            block: expired_height,
+        });
+    }
+
+    // refund rewards & preference executor fee of every executor the requester has paid for
+    let mut refunds: Vec<Coin> = vec![];
+    let fees = request
+        .rewards
+        .iter()
+        .map(|reward| (&reward.1, reward.2))
+        .chain(std::iter::once((
+            &request.preference_executor_fee.denom,
+            request.preference_executor_fee.amount,
+        )))
+        .map(|(denom, amount)| (denom, amount.u128().mul(request.threshold as u128)))
+        // the callback budget has been paid once
+        .chain(
+            request
+                .callback
+                .iter()
+                .map(|callback| (&request.preference_executor_fee.denom, callback.fee.u128())),
+        );
+    for (denom, amount) in fees {
+        if let Some(refund) = refunds.iter_mut().find(|refund| refund.denom.eq(denom)) {
+            refund.amount = Uint128::from(refund.amount.u128() + amount);
+        } else if amount > 0 {
+            refunds.push(Coin {
+                denom: denom.to_string(),
+                amount: Uint128::from(amount),
+            });
+        }
+    }
+
+    // keep the request so it can neither be finalized nor reclaimed again
+    request.reclaimed_height = Some(env.block.height);
+    requests().save(deps.storage, &stage.to_be_bytes(), &request)?;
+
+    // cw20 refunds are transferred one by one, native ones are sent together
+    let mut cosmos_msgs: Vec<CosmosMsg> = vec![];
+    let mut native_refunds: Vec<Coin> = vec![];
+    for refund in refunds {
+        let asset = get_reward_asset(&request.asset_info, &refund.denom);
+        if let AssetInfo::NativeToken { .. } = asset {
+            native_refunds.push(refund);
+            continue;
+        }
+        cosmos_msgs.push(transfer_asset(
+            &env.contract.address,
+            request.requester.clone(),
+            &asset,
+            refund.amount,
+        )?);
+    }
+    if !native_refunds.is_empty() {
+        cosmos_msgs.push(
+            BankMsg::Send {
+                from_address: env.contract.address,
+                to_address: request.requester.clone(),
+                amount: native_refunds,
+            }
+            .into(),
+        );
+    }
+
+    Ok(HandleResponse {
+        data: None,
+        messages: cosmos_msgs,
+        attributes: vec![
+            attr("action", "reclaim_expired_request"),
+            attr("stage", stage.to_string()),
+            attr("requester", request.requester),
+        ],
+    })
+}
+
+pub fn handle_submit_result(
+    deps: DepsMut,
+    stage: u64,
+    data: Binary,
+    proof: Option<Vec<String>>,
+) -> Result<HandleResponse, ContractError> {
+    // every leaf of the stage can be submitted once
+    let leaf_hash = sha2::Sha256::digest(data.as_slice());
+    let key = (U64Key::new(stage), leaf_hash.as_slice());
+    if VERIFIED_RESULTS
+        .may_load(deps.storage, key.clone())?
+        .is_some()
+    {
+        return Err(ContractError::AlreadySubmitted {});
+    }
+    let is_verified = verify_data(deps.as_ref(), stage, data.clone(), proof)?;
+    if !is_verified {
+        return Err(ContractError::VerificationFailed {});
+    }
+    VERIFIED_RESULTS.save(deps.storage, key, &data)?;
+
+    Ok(HandleResponse {
+        data: None,
+        messages: vec![],
+        attributes: vec![
+            attr("action", "submit_result"),
+            attr("stage", stage.to_string()),
+        ],
+    })
+}
+
+/// a request can only be finalized while it has no merkle root and has neither expired nor been reclaimed
+fn check_request_pending(deps: Deps, env: &Env, request: &Request) -> Result<(), ContractError> {
+    if !request.merkle_root.is_empty() {
+        return Err(ContractError::AlreadyFinished {});
+    }
+    if request.reclaimed_height.is_some() {
+        return Err(ContractError::RequestReclaimed {});
+    }
+    let expired_height = get_expired_height(deps, request)?;
+    if env.block.height >= expired_height {
+        return Err(ContractError::RequestExpired {
+            block: expired_height,
+        });
+    }
+    Ok(())
+}
+
+fn get_expired_height(deps: Deps, request: &Request) -> StdResult<u64> {
+    if let Some(expired_height) = request.expired_height {
+        return Ok(expired_height);
+    }
+    // requests created before timeouts existed expire relative to their request height
+    let Config {
+        request_timeout, ..
+    } = CONFIG.load(deps.storage)?;
+    Ok(request.request_height + request_timeout.unwrap_or(REQUEST_TIMEOUT))
+}
+
 pub fn handle_submit_evidence(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     stage: u64,
-    report: Binary,
-    proofs: Option<Vec<String>>,
+    evidence: Evidence,
 ) -> Result<HandleResponse, ContractError> {
     let Config {
         trusting_period,
         slashing_amount,
-        denom,
+        slashing_bounty,
         ..
     } = CONFIG.load(deps.storage)?;
 
-    let is_verified = verify_data(deps.as_ref(), stage, report.clone(), proofs)?;
-    if !is_verified {
-        return Err(ContractError::Unauthorized {});
+    let Request {
+        submit_merkle_height,
+        rewards,
+        ..
+    } = requests().load(deps.storage, &stage.to_be_bytes())?;
+
+    // evidences can only be submitted while the stage is still in its trusting period
+    if submit_merkle_height.ne(&0u64)
+        && (submit_merkle_height + trusting_period).le(&env.block.height)
+    {
+        return Err(ContractError::EvidenceExpired {});
     }
 
-    let report_struct: Report = from_slice(report.as_slice())
-        .map_err(|err| ContractError::Std(StdError::generic_err(err.to_string())))?;
+    let faulty_executor = match evidence {
+        Evidence::DoubleSign {
+            executor,
+            merkle_root,
+            signature,
+            conflicting_merkle_root,
+            conflicting_signature,
+        } => {
+            if merkle_root.eq(&conflicting_merkle_root)
+                || !verify_report_signature(&env, stage, &merkle_root, &executor, &signature)
+                || !verify_report_signature(
+                    &env,
+                    stage,
+                    &conflicting_merkle_root,
+                    &executor,
+                    &conflicting_signature,
+                )
+            {
+                return Err(ContractError::InvalidEvidence {});
+            }
+            executor
+        }
+        Evidence::InvalidReport {
+            report,
+            proof,
+            signature,
+        } => {
+            let is_verified = verify_data(deps.as_ref(), stage, report.clone(), proof)?;
+            if !is_verified {
+                return Err(ContractError::Unauthorized {});
+            }
+            let report_struct: Report = from_slice(report.as_slice())
+                .map_err(|err| ContractError::Std(StdError::generic_err(err.to_string())))?;
+            // the executor field of the leaf is not trusted until the executor has signed the report
+            let msg = report_data_sign_message(
+                &env.contract.address,
+                &env.block.chain_id,
+                stage,
+                &report,
+            );
+            if !verify_signature(&msg, &report_struct.executor, &signature) {
+                return Err(ContractError::InvalidEvidence {});
+            }
+            // the report is valid if it only claims rewards of the request
+            if report_struct
+                .rewards
+                .iter()
+                .all(|reward| rewards.contains(reward))
+            {
+                return Err(ContractError::InvalidEvidence {});
+            }
+            report_struct.executor
+        }
+    };
 
     // check evidence, only allow evidence per executor
-    let mut evidence_key = report_struct.executor.clone().to_base64();
+    let mut evidence_key = faulty_executor.clone().to_base64();
     evidence_key.push_str(&stage.to_string());
     let is_claimed = EVIDENCES.may_load(deps.storage, evidence_key.as_bytes())?;
 
@@ -386,48 +760,151 @@ pub fn handle_submit_evidence(
         }
     }
 
-    let Request {
-        submit_merkle_height,
-        ..
-    } = requests().load(deps.storage, &stage.to_be_bytes())?;
+    let slashes = slash_executor(
+        deps.storage,
+        env.block.height,
+        &faulty_executor,
+        slashing_amount,
+        slashing_bounty.unwrap_or(SLASHING_BOUNTY),
+    )?;
+    EVIDENCES.save(deps.storage, evidence_key.as_bytes(), &true)?;
 
-    let is_exist = executors_map().may_load(deps.storage, report_struct.executor.as_slice())?;
     let mut cosmos_msgs: Vec<CosmosMsg> = vec![];
+    let mut attributes = vec![
+        attr("action", "handle_submit_evidence"),
+        attr("stage", stage.to_string()),
+        attr("executor", faulty_executor),
+    ];
+    // pay the reporter who discovers the faulty executor, in every slashed asset
+    for (asset_info, slashed_amount, bounty_amount) in slashes {
+        attributes.push(attr(
+            "slashed_amount",
+            format!("{}{}", slashed_amount, asset_info.denom()),
+        ));
+        attributes.push(attr(
+            "bounty_amount",
+            format!("{}{}", bounty_amount, asset_info.denom()),
+        ));
+        if !bounty_amount.is_zero() {
+            cosmos_msgs.push(transfer_asset(
+                &env.contract.address,
+                info.sender.clone(),
+                &asset_info,
+                bounty_amount,
+            )?);
+        }
+    }
+
+    Ok(HandleResponse {
+        data: None,
+        messages: cosmos_msgs,
+        attributes,
+    })
+}
+
+pub fn handle_claim_rewards(
+    deps: DepsMut,
+    env: Env,
+    claims: Vec<RewardClaim>,
+) -> Result<HandleResponse, ContractError> {
+    let Config {
+        trusting_period, ..
+    } = CONFIG.load(deps.storage)?;
+    let total_claims = claims.len();
+
+    // rewards are aggregated per recipient & asset
+    let mut payouts: Vec<(HumanAddr, AssetInfo, Uint128)> = vec![];
+    for RewardClaim {
+        stage,
+        report,
+        proof,
+    } in claims
+    {
+        let is_verified = verify_data(deps.as_ref(), stage, report.clone(), proof)?;
+        if !is_verified {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        let request = requests().load(deps.storage, &stage.to_be_bytes())?;
+        // rewards can only be claimed once evidences cannot be submitted anymore
+        let claimable_height = request.submit_merkle_height + trusting_period;
+        if claimable_height.ge(&env.block.height) {
+            return Err(ContractError::ClaimNotReady {
+                block: claimable_height + 1,
+            });
+        }
 
-    // only slash if the executor cannot be found in the whitelist
-    if is_exist.is_none() && (submit_merkle_height + trusting_period).gt(&env.block.height) {
-        // query contract balance to slash
-        let balance = deps
-            .querier
-            .query_balance(env.contract.address.clone(), denom.as_str())
+        let report_struct: Report = from_slice(report.as_slice())
             .map_err(|err| ContractError::Std(StdError::generic_err(err.to_string())))?;
-        // if executor does not exist & still in trusting period => can slash amount in contract by sending the percentage amount to the reporter who discovers the faulty stage.
-        let total_slash = balance.amount.mul(Decimal::permille(slashing_amount));
-        if !total_slash.is_zero() {
-            let send_msg = BankMsg::Send {
-                from_address: env.contract.address,
-                to_address: info.sender,
-                amount: vec![Coin {
-                    denom: balance.denom,
-                    amount: total_slash,
-                }],
+        if !is_allowed_executor(&request, &report_struct.executor) {
+            return Err(ContractError::Unauthorized {});
+        }
+        let mut claim_key = report_struct.executor.to_base64();
+        claim_key.push_str(&stage.to_string());
+        if CLAIM
+            .may_load(deps.storage, claim_key.as_bytes())?
+            .unwrap_or(false)
+        {
+            return Err(ContractError::Claimed {});
+        }
+        // slashed reports have no rewards
+        if EVIDENCES
+            .may_load(deps.storage, claim_key.as_bytes())?
+            .unwrap_or(false)
+        {
+            return Err(ContractError::Unauthorized {});
+        }
+        CLAIM.save(deps.storage, claim_key.as_bytes(), &true)?;
+
+        // the request has only paid the rewards of threshold executors
+        let stage_claims = STAGE_CLAIMS
+            .may_load(deps.storage, U64Key::new(stage))?
+            .unwrap_or_default();
+        if stage_claims.ge(&request.threshold) {
+            return Err(ContractError::ClaimLimitReached {
+                threshold: request.threshold,
+            });
+        }
+        STAGE_CLAIMS.save(deps.storage, U64Key::new(stage), &(stage_claims + 1))?;
+
+        // only pay the rewards that have been funded by the request, each funded reward once
+        let mut unpaid_rewards: Vec<&Reward> = request.rewards.iter().collect();
+        for reward in report_struct.rewards {
+            match unpaid_rewards.iter().position(|unpaid| reward.eq(unpaid)) {
+                Some(index) => unpaid_rewards.remove(index),
+                None => continue,
             };
-            cosmos_msgs.push(send_msg.into());
+            let (recipient, denom, amount) = reward;
+            let asset = get_reward_asset(&request.asset_info, &denom);
+            match payouts
+                .iter_mut()
+                .find(|payout| payout.0.eq(&recipient) && payout.1.eq(&asset))
+            {
+                Some(payout) => payout.2 += amount,
+                None => payouts.push((recipient, asset, amount)),
+            }
         }
-
-        EVIDENCES.save(deps.storage, evidence_key.as_bytes(), &true)?;
     }
 
+    let cosmos_msgs: StdResult<Vec<CosmosMsg>> = payouts
+        .into_iter()
+        .filter(|(_, _, amount)| !amount.is_zero())
+        .map(|(recipient, asset, amount)| {
+            transfer_asset(&env.contract.address, recipient, &asset, amount)
+        })
+        .collect();
+
     Ok(HandleResponse {
         data: None,
-        messages: cosmos_msgs,
+        messages: cosmos_msgs?,
         attributes: vec![
-            attr("action", "handle_submit_evidence"),
-            attr("stage", stage.to_string()),
+            attr("action", "claim_rewards"),
+            attr("claims", total_claims),
         ],
     })
 }
 
+/// Emergency override: lets the owner force a merkle root when executors cannot reach the threshold
 pub fn execute_register_merkle_root(
     deps: DepsMut,
     env: Env,
@@ -436,31 +913,209 @@ pub fn execute_register_merkle_root(
     mroot: String,
     executors: Vec<Binary>,
 ) -> Result<HandleResponse, ContractError> {
-    let Config {
-        owner,
-        checkpoint_threshold,
-        service_addr,
-        ..
-    } = CONFIG.load(deps.storage)?;
+    let Config { owner, .. } = CONFIG.load(deps.storage)?;
 
     // if owner set validate, otherwise unauthorized
     if info.sender != owner {
         return Err(ContractError::Unauthorized {});
     }
 
+    register_merkle_root(deps, &env, stage, &mroot, executors)?;
+
+    Ok(HandleResponse {
+        data: None,
+        messages: vec![],
+        attributes: vec![
+            attr("action", "register_merkle_root"),
+            attr("current_stage", stage.to_string()),
+            attr("merkle_root", mroot),
+        ],
+    })
+}
+
+pub fn handle_submit_report(
+    deps: DepsMut,
+    env: Env,
+    stage: u64,
+    mroot: String,
+    executor: Binary,
+    signature: Binary,
+) -> Result<HandleResponse, ContractError> {
+    // check merkle root length
+    let mut root_buf: [u8; 32] = [0; 32];
+    hex::decode_to_slice(&mroot, &mut root_buf)?;
+
+    let request = requests().load(deps.storage, &stage.to_be_bytes())?;
+    check_request_pending(deps.as_ref(), &env, &request)?;
+    let Request {
+        threshold,
+        min_reputation,
+        ..
+    } = request;
+
+    // only active executors allowed by the service policy can sign reports
+    let executor_check = executors_map().may_load(deps.storage, executor.as_slice())?;
+    if executor_check.is_none()
+        || !executor_check.unwrap().is_active
+        || !is_allowed_executor(&request, &executor)
+    {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if !verify_report_signature(&env, stage, &mroot, &executor, &signature) {
+        return Err(ContractError::InvalidSignature {});
+    }
+
+    if let Some(min_reputation) = min_reputation {
+        let reputation = get_reputation(&load_executor_stats(deps.storage, &executor)?);
+        if reputation.lt(&min_reputation) {
+            return Err(ContractError::InsufficientReputation {
+                reputation,
+                min_reputation,
+            });
+        }
+    }
+
+    let report_key = (U64Key::new(stage), executor.as_slice());
+    if REPORTS
+        .may_load(deps.storage, report_key.clone())?
+        .is_some()
+    {
+        return Err(ContractError::AlreadySubmitted {});
+    }
+    REPORTS.save(deps.storage, report_key, &mroot)?;
+
+    // collect executors agreeing on the same merkle root
+    let signers: Vec<Binary> = REPORTS
+        .prefix(U64Key::new(stage))
+        .range(deps.storage, None, None, Order::Ascending)
+        .filter_map(|item| match item {
+            Ok((pubkey, root)) if root.eq(&mroot) => Some(Binary::from(pubkey)),
+            _ => None,
+        })
+        .collect();
+    let signatures = signers.len() as u64;
+
+    let mut attributes = vec![
+        attr("action", "submit_report"),
+        attr("stage", stage.to_string()),
+        attr("executor", executor),
+        attr("merkle_root", mroot.clone()),
+        attr("signatures", signatures),
+    ];
+
+    // once enough executors agree, the contract registers the merkle root itself
+    if signatures.ge(&threshold) {
+        register_merkle_root(deps, &env, stage, &mroot, signers)?;
+        attributes.push(attr("finalized", true));
+    }
+
+    Ok(HandleResponse {
+        data: None,
+        messages: vec![],
+        attributes,
+    })
+}
+
+/// The message an executor signs when reporting a merkle root. It binds the report to this contract
+/// and chain so a signature cannot be replayed on another deployment
+pub fn report_sign_message(
+    contract_addr: &HumanAddr,
+    chain_id: &str,
+    stage: u64,
+    merkle_root: &str,
+) -> String {
+    format!("{}:{}:{}:{}", contract_addr, chain_id, stage, merkle_root)
+}
+
+/// The message an executor signs over its own report, the leaf of the merkle tree. The tag before the stage keeps it
+/// apart from a signed merkle root
+pub fn report_data_sign_message(
+    contract_addr: &HumanAddr,
+    chain_id: &str,
+    stage: u64,
+    report: &Binary,
+) -> String {
+    format!(
+        "{}:{}:report:{}:{}",
+        contract_addr,
+        chain_id,
+        stage,
+        hex::encode(sha2::Sha256::digest(report.as_slice()))
+    )
+}
+
+pub fn verify_report_signature(
+    env: &Env,
+    stage: u64,
+    merkle_root: &str,
+    pubkey: &Binary,
+    signature: &Binary,
+) -> bool {
+    let msg = report_sign_message(
+        &env.contract.address,
+        &env.block.chain_id,
+        stage,
+        merkle_root,
+    );
+    verify_signature(&msg, pubkey, signature)
+}
+
+fn verify_signature(msg: &str, pubkey: &Binary, signature: &Binary) -> bool {
+    let msg_hash = sha2::Sha256::digest(msg.as_bytes());
+    cosmwasm_crypto::secp256k1_verify(msg_hash.as_slice(), signature, pubkey).unwrap_or(false)
+}
+
+/// only active executors allowed by the request & above its minimum reputation are rewarded
+fn is_eligible_executor(deps: Deps, request: &Request, executor: &Binary) -> StdResult<bool> {
+    let executor_check = executors_map().may_load(deps.storage, executor)?;
+    if executor_check.is_none()
+        || !executor_check.unwrap().is_active
+        || !is_allowed_executor(request, executor)
+    {
+        return Ok(false);
+    }
+    if let Some(min_reputation) = request.min_reputation {
+        if get_reputation(&load_executor_stats(deps.storage, executor)?).lt(&min_reputation) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn is_allowed_executor(request: &Request, executor: &Binary) -> bool {
+    match request.executors.as_ref() {
+        Some(executors) => executors.contains(executor),
+        None => true,
+    }
+}
+
+/// registers the merkle root of a stage. The callback of the request is left pending, to be delivered with
+/// RelayCallback: without submessages, a callback dispatched here would revert the finalization when it fails
+fn register_merkle_root(
+    deps: DepsMut,
+    env: &Env,
+    stage: u64,
+    mroot: &str,
+    executors: Vec<Binary>,
+) -> Result<(), ContractError> {
+    let Config {
+        checkpoint_threshold,
+        service_addr,
+        ..
+    } = CONFIG.load(deps.storage)?;
+
     // check merkle root length
     let mut root_buf: [u8; 32] = [0; 32];
     hex::decode_to_slice(mroot.to_string(), &mut root_buf)?;
 
-    let Request { merkle_root, .. } = requests().load(deps.storage, &stage.to_be_bytes())?;
-    if merkle_root.ne("") {
-        return Err(ContractError::AlreadyFinished {});
-    }
+    let request = requests().load(deps.storage, &stage.to_be_bytes())?;
+    check_request_pending(deps.as_ref(), env, &request)?;
 
     // if merkle root empty then update new
     let request = requests().update(deps.storage, &stage.to_be_bytes(), |request| {
         if let Some(mut request) = request {
-            request.merkle_root = mroot.clone();
+            request.merkle_root = mroot.to_string();
             request.submit_merkle_height = env.block.height;
             {
                 return Ok(request);
@@ -469,13 +1124,28 @@ pub fn execute_register_merkle_root(
         Err(StdError::generic_err("Invalid request empty"))
     })?;
 
+    // executors that could have taken part in the stage: the ones selected for the request, or every active executor
+    // for a request open to all. Eligibility is checked before the stats of the stage are counted
+    let candidates = match request.executors.as_ref() {
+        Some(executors) => executors.clone(),
+        None => load_active_executors(deps.storage),
+    };
+    let mut eligible_executors: Vec<Binary> = vec![];
+    for executor in candidates {
+        if is_eligible_executor(deps.as_ref(), &request, &executor)? {
+            eligible_executors.push(executor);
+        }
+    }
+
     // add executors' rewards into the pool
+    let mut included_executors: Vec<Binary> = vec![];
     for executor in executors {
-        // only add reward if executor is in list
-        let executor_check = executors_map().may_load(deps.storage, &executor)?;
-        if executor_check.is_none() || !executor_check.unwrap().is_active {
+        if included_executors.contains(&executor)
+            || !is_eligible_executor(deps.as_ref(), &request, &executor)?
+        {
             continue;
         }
+        included_executors.push(executor.clone());
         let executor_reward =
             get_participant_fee(deps.as_ref(), executor.clone(), service_addr.as_str())?;
         process_executors_pool(
@@ -483,19 +1153,73 @@ pub fn execute_register_merkle_root(
             executor,
             &request.preference_executor_fee,
             executor_reward,
+            &request.asset_info,
         )?;
     }
 
+    process_executors_stats(
+        deps.storage,
+        &eligible_executors,
+        &included_executors,
+        request.threshold,
+    )?;
+
+    // a failing callback cannot revert the finalization, it is delivered by its own transaction
+    if request.callback.is_some() {
+        PENDING_CALLBACKS.save(deps.storage, U64Key::new(stage), &true)?;
+    }
+
     // check if can increase checkpoint. Can only increase when all requests in range have merkle root
     process_checkpoint(deps, stage, checkpoint_threshold)?;
+    Ok(())
+}
+
+pub fn handle_relay_callback(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage: u64,
+) -> Result<HandleResponse, ContractError> {
+    if !PENDING_CALLBACKS
+        .may_load(deps.storage, U64Key::new(stage))?
+        .unwrap_or(false)
+    {
+        return Err(ContractError::NoPendingCallback {});
+    }
+    PENDING_CALLBACKS.remove(deps.storage, U64Key::new(stage));
+    let request = requests().load(deps.storage, &stage.to_be_bytes())?;
+    let callback = request
+        .callback
+        .ok_or(ContractError::NoPendingCallback {})?;
+
+    // notify the requester & pay the relayer with the callback budget
+    let mut cosmos_msgs: Vec<CosmosMsg> = vec![WasmMsg::Execute {
+        contract_addr: request.requester.clone(),
+        msg: to_binary(&OracleCallbackMsg::OracleCallback {
+            stage,
+            merkle_root: request.merkle_root,
+            service: request.service,
+        })?,
+        send: vec![],
+    }
+    .into()];
+    if !callback.fee.is_zero() {
+        cosmos_msgs.push(transfer_asset(
+            &env.contract.address,
+            info.sender.clone(),
+            &get_reward_asset(&request.asset_info, &request.preference_executor_fee.denom),
+            callback.fee,
+        )?);
+    }
 
     Ok(HandleResponse {
         data: None,
-        messages: vec![],
+        messages: cosmos_msgs,
         attributes: vec![
-            attr("action", "register_merkle_root"),
-            attr("current_stage", stage.to_string()),
-            attr("merkle_root", mroot),
+            attr("action", "relay_callback"),
+            attr("stage", stage.to_string()),
+            attr("requester", request.requester),
+            attr("relayer", info.sender),
         ],
     })
 }
@@ -503,6 +1227,7 @@ pub fn execute_register_merkle_root(
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::ContractVersion {} => to_binary(&get_contract_version(deps.storage)?),
         QueryMsg::GetExecutors {
             offset,
             limit,
@@ -515,6 +1240,7 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         } => to_binary(&query_executors_by_index(deps, offset, limit, order)?),
         QueryMsg::GetExecutor { pubkey } => to_binary(&query_executor(deps, pubkey)?),
         QueryMsg::GetExecutorSize {} => to_binary(&query_executor_size(deps)?),
+        QueryMsg::GetExecutorStats { pubkey } => to_binary(&query_executor_stats(deps, pubkey)?),
         QueryMsg::Request { stage } => to_binary(&query_request(deps, stage)?),
         QueryMsg::GetRequests {
             offset,
@@ -541,6 +1267,16 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             limit,
             order,
         )?),
+        QueryMsg::GetExpiredRequests {
+            offset,
+            limit,
+            order,
+        } => to_binary(&query_expired_requests(deps, env, offset, limit, order)?),
+        QueryMsg::GetPendingCallbacks {
+            offset,
+            limit,
+            order,
+        } => to_binary(&query_pending_callbacks(deps, offset, limit, order)?),
         QueryMsg::LatestStage {} => to_binary(&query_latest_stage(deps)?),
         QueryMsg::GetServiceContracts { stage } => {
             to_binary(&query_service_contracts(deps, stage)?)
@@ -553,10 +1289,13 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::VerifyData { stage, data, proof } => {
             to_binary(&verify_data(deps, stage, data, proof)?)
         }
+        QueryMsg::GetVerifiedResult { stage } => to_binary(&query_verified_result(deps, stage)?),
         QueryMsg::GetServiceFees { service } => to_binary(&query_service_fees(deps, service)?),
         QueryMsg::GetBoundExecutorFee {} => to_binary(&query_bound_executor_fee(deps)?),
         QueryMsg::GetParticipantFee { pubkey } => to_binary(&query_participant_fee(deps, pubkey)?),
-        QueryMsg::GetTrustingPool { pubkey } => to_binary(&query_trusting_pool(deps, env, pubkey)?),
+        QueryMsg::GetTrustingPool { pubkey, asset_info } => {
+            to_binary(&query_trusting_pool(deps, env, pubkey, asset_info)?)
+        }
         QueryMsg::GetTrustingPools {
             offset,
             limit,
@@ -571,21 +1310,136 @@ pub fn query_participant_fee(deps: Deps, pubkey: Binary) -> StdResult<Coin> {
         .map_err(|err| StdError::generic_err(err.to_string()))
 }
 
-fn get_service_fees(deps: Deps, service: &str) -> StdResult<Vec<Reward>> {
+/// services without a policy have no restriction. The bridge reports them with an empty policy or null, provider bridges
+/// without service policies do not answer the query
+fn get_service_policy(deps: Deps, service: &str) -> StdResult<ServicePolicy> {
+    let Config { service_addr, .. } = CONFIG.load(deps.storage)?;
+    let policy: StdResult<Option<ServicePolicy>> = deps.querier.query_wasm_smart(
+        service_addr,
+        &GetServicePolicy {
+            service_policy_msg: ServiceMsg {
+                service: service.to_string(),
+                version: None,
+            },
+        },
+    );
+    Ok(policy.ok().flatten().unwrap_or_default())
+}
+
+/// version of the service contracts pinned by a new request, None if the provider bridge does not version its services
+fn pin_service_version(
+    deps: Deps,
+    service: &str,
+    version: Option<u64>,
+) -> Result<Option<u64>, ContractError> {
+    let Config { service_addr, .. } = CONFIG.load(deps.storage)?;
+    let service_version: StdResult<ServiceVersion> = deps.querier.query_wasm_smart(
+        service_addr,
+        &GetServiceVersion {
+            service_version_msg: ServiceMsg {
+                service: service.to_string(),
+                version,
+            },
+        },
+    );
+    match service_version {
+        Ok(ServiceVersion {
+            version,
+            status: VersionStatus::Deprecated,
+            ..
+        }) => Err(ContractError::ServiceVersionDeprecated { version }),
+        Ok(service_version) => Ok(Some(service_version.version)),
+        Err(_) if version.is_none() => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn get_service_fees(deps: Deps, service: &str, version: Option<u64>) -> StdResult<Vec<Reward>> {
     let Config { service_addr, .. } = CONFIG.load(deps.storage)?;
     let rewards: Vec<Reward> = deps.querier.query_wasm_smart(
         service_addr,
         &GetServiceFees {
             service_fee_msg: ServiceMsg {
                 service: service.to_string(),
+                version,
             },
         },
     )?;
     Ok(rewards)
 }
 
+fn get_service_fee_schedules(
+    deps: Deps,
+    service: &str,
+    version: Option<u64>,
+) -> StdResult<Vec<ServiceFeesResponse>> {
+    let Config { service_addr, .. } = CONFIG.load(deps.storage)?;
+    let fees: StdResult<Vec<ServiceFeesResponse>> = deps.querier.query_wasm_smart(
+        service_addr,
+        &GetServiceFeeSchedules {
+            service_fee_schedule_msg: ServiceMsg {
+                service: service.to_string(),
+                version,
+            },
+        },
+    );
+    if fees.is_ok() {
+        return fees;
+    }
+
+    // provider bridges without fee schedules only charge the flat fees
+    let fees = get_service_fees(deps, service, version)?
+        .into_iter()
+        .map(|reward| ServiceFeesResponse {
+            address: reward.0.to_string(),
+            fees: Coin {
+                denom: reward.1,
+                amount: reward.2,
+            },
+            schedule: None,
+        })
+        .collect();
+    Ok(fees)
+}
+
+/// rewards of the providers for a new request. Fee schedules are charged in the first accepted coin sent by the requester,
+/// with the discount of the tier reached by the requester in the current period
+pub fn price_service_fees(
+    storage: &mut dyn Storage,
+    requester: &HumanAddr,
+    fees: Vec<ServiceFeesResponse>,
+    sent_funds: &[Coin],
+    height: u64,
+) -> StdResult<Vec<Reward>> {
+    let mut rewards = vec![];
+    for ServiceFeesResponse {
+        address,
+        fees,
+        schedule,
+    } in fees
+    {
+        let fee = match schedule {
+            Some(schedule) => {
+                let key = (address.as_bytes(), requester.as_bytes());
+                let period = schedule.period_of(height);
+                let requests = REQUEST_VOLUMES
+                    .may_load(storage, key)?
+                    .filter(|volume| volume.period == period)
+                    .map(|volume| volume.requests)
+                    .unwrap_or_default()
+                    + 1;
+                REQUEST_VOLUMES.save(storage, key, &RequestVolume { period, requests })?;
+                schedule.price(sent_funds, requests).unwrap_or(fees)
+            }
+            None => fees,
+        };
+        rewards.push((HumanAddr::from(address), fee.denom, fee.amount));
+    }
+    Ok(rewards)
+}
+
 pub fn query_service_fees(deps: Deps, service: String) -> StdResult<Vec<Reward>> {
-    Ok(get_service_fees(deps, &service)?)
+    get_service_fees(deps, &service, None)
 }
 
 pub fn get_stage_info(deps: Deps) -> StdResult<StageInfo> {
@@ -700,7 +1554,14 @@ fn parse_request<'a>(item: StdResult<KV<Request>>) -> StdResult<RequestResponse>
             merkle_root: request.merkle_root,
             threshold: request.threshold,
             service: request.service,
+            service_version: request.service_version,
             rewards: request.rewards,
+            expired_height: request.expired_height,
+            min_reputation: request.min_reputation,
+            asset_info: request.asset_info,
+            callback: request.callback,
+            executors: request.executors,
+            reclaimed_height: request.reclaimed_height,
         })
     })
 }
@@ -756,6 +1617,53 @@ pub fn query_requests_by_merkle_root(
     Ok(request_responses?)
 }
 
+pub fn query_expired_requests(
+    deps: Deps,
+    env: Env,
+    offset: Option<u64>,
+    limit: Option<u8>,
+    order: Option<u8>,
+) -> StdResult<Vec<RequestResponse>> {
+    let (limit, min, max, order_enum) = _get_range_params(limit, offset, order);
+    let mut request_responses: Vec<RequestResponse> = vec![];
+    // pending requests are the ones indexed by an empty merkle root
+    for kv_item in requests()
+        .idx
+        .merkle_root
+        .items(deps.storage, b"", min, max, order_enum)
+    {
+        let (key, request) = kv_item?;
+        if request.reclaimed_height.is_some()
+            || env.block.height < get_expired_height(deps, &request)?
+        {
+            continue;
+        }
+        request_responses.push(parse_request(Ok((key, request)))?);
+        if request_responses.len() >= limit {
+            break;
+        }
+    }
+    Ok(request_responses)
+}
+
+pub fn query_pending_callbacks(
+    deps: Deps,
+    offset: Option<u64>,
+    limit: Option<u8>,
+    order: Option<u8>,
+) -> StdResult<Vec<RequestResponse>> {
+    let (limit, min, max, order_enum) = _get_range_params(limit, offset, order);
+    PENDING_CALLBACKS
+        .range(deps.storage, min, max, order_enum)
+        .take(limit)
+        .map(|kv_item| {
+            let (key, _) = kv_item?;
+            let request = requests().load(deps.storage, &key)?;
+            parse_request(Ok((key, request)))
+        })
+        .collect()
+}
+
 pub fn query_service_contracts(deps: Deps, stage: u64) -> StdResult<Contracts> {
     let Config { service_addr, .. } = CONFIG.load(deps.storage)?;
     let request = requests().load(deps.storage, &stage.to_be_bytes())?;
@@ -764,6 +1672,7 @@ pub fn query_service_contracts(deps: Deps, stage: u64) -> StdResult<Contracts> {
         &GetServiceContracts {
             service_contracts_msg: ServiceMsg {
                 service: request.service,
+                version: request.service_version,
             },
         },
     )?;
@@ -788,6 +1697,20 @@ pub fn query_current_stage(deps: Deps) -> StdResult<CurrentStageResponse> {
     Ok(resp)
 }
 
+pub fn query_verified_result(deps: Deps, stage: u64) -> StdResult<VerifiedResultResponse> {
+    let Request { merkle_root, .. } = requests().load(deps.storage, &stage.to_be_bytes())?;
+    let results = VERIFIED_RESULTS
+        .prefix(U64Key::new(stage))
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|kv_item| kv_item.map(|(_, data)| data))
+        .collect::<StdResult<Vec<Binary>>>()?;
+    Ok(VerifiedResultResponse {
+        stage,
+        merkle_root,
+        results,
+    })
+}
+
 pub fn query_is_claimed(deps: Deps, stage: u64, executor: Binary) -> StdResult<IsClaimedResponse> {
     let mut claim_key = executor.to_base64();
     claim_key.push_str(&stage.to_string());
@@ -833,7 +1756,10 @@ pub fn verify_request_fees(
         }
 
         // special case for contract fee. total fund must >= total request fee + contract fee
-        if let Some(amount) = denoms.get(contract_fee.denom.as_str()) {
+        if let Some(amount) = denoms
+            .get(contract_fee.denom.as_str())
+            .filter(|_| fund.denom.eq(&contract_fee.denom))
+        {
             if fund.amount.u128().lt(&amount
                 .mul(&Uint128::from(threshold).u128())
                 .add(contract_fee.amount.u128()))
@@ -872,6 +1798,47 @@ pub fn get_participant_fee(
     Ok(executor_reward)
 }
 
+/// asset of a reward denom, cw20 rewards are denominated by the token the request has been paid with
+pub fn get_reward_asset(request_asset: &Option<AssetInfo>, denom: &str) -> AssetInfo {
+    if let Some(AssetInfo::Token { contract_addr }) = request_asset {
+        if contract_addr.as_str().eq(denom) {
+            return AssetInfo::Token {
+                contract_addr: contract_addr.clone(),
+            };
+        }
+    }
+    AssetInfo::NativeToken {
+        denom: denom.to_string(),
+    }
+}
+
+pub fn transfer_asset(
+    contract_addr: &HumanAddr,
+    recipient: HumanAddr,
+    asset_info: &AssetInfo,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    match asset_info {
+        AssetInfo::NativeToken { denom } => Ok(BankMsg::Send {
+            from_address: contract_addr.clone(),
+            to_address: recipient,
+            amount: vec![Coin {
+                denom: denom.to_string(),
+                amount,
+            }],
+        }
+        .into()),
+        AssetInfo::Token {
+            contract_addr: token_addr,
+        } => Ok(WasmMsg::Execute {
+            contract_addr: token_addr.clone(),
+            msg: to_binary(&Cw20HandleMsg::Transfer { recipient, amount })?,
+            send: vec![],
+        }
+        .into()),
+    }
+}
+
 pub fn pubkey_to_address(pubkey: &Binary) -> Result<HumanAddr, ContractError> {
     let msg_hash_generic = sha2::Sha256::digest(pubkey.as_slice());
     let msg_hash = msg_hash_generic.as_slice();
@@ -897,10 +1864,10 @@ fn process_checkpoint(deps: DepsMut, stage: u64, checkpoint_threshold: u64) -> S
             Some(checkpoint_threshold as u8),
             Some(1),
         )?;
-        // if we cannot find an empty merkle root request then increase checkpoint
+        // if we cannot find a pending request then increase checkpoint. Reclaimed requests will never have a merkle root
         if requests
             .iter()
-            .find(|req| req.merkle_root.is_empty())
+            .find(|req| req.merkle_root.is_empty() && req.reclaimed_height.is_none())
             .is_none()
         {
             if next_checkpoint.gt(&(latest_stage + 1)) {
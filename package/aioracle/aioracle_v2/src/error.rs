@@ -29,6 +29,27 @@ pub enum ContractError {
     #[error("This executor is already left")]
     ExecutorAlreadyLeft {},
 
+    #[error("This executor has been jailed")]
+    ExecutorJailed {},
+
+    #[error("Executor reputation {reputation} is lower than the minimum {min_reputation}")]
+    InsufficientReputation {
+        reputation: u64,
+        min_reputation: u64,
+    },
+
+    #[error("The service requires a threshold of at least {min_threshold}")]
+    ThresholdBelowPolicy { min_threshold: u64 },
+
+    #[error(
+        "Fee of {fee}{denom} per executor exceeds the maximum of the service: {max_fee}{denom}"
+    )]
+    MaxFeeExceeded {
+        fee: u128,
+        max_fee: u128,
+        denom: String,
+    },
+
     #[error("Empty trusting pool data")]
     EmptyTrustingPool {},
 
@@ -58,18 +79,45 @@ pub enum ContractError {
     #[error("Already claimed")]
     Claimed {},
 
+    #[error("The {threshold} executors paid by the request have already claimed their rewards")]
+    ClaimLimitReached { threshold: u64 },
+
+    #[error("Cannot claim rewards before block {block}")]
+    ClaimNotReady { block: u64 },
+
     #[error("Evidence already submitted & handled")]
     AlreadyFinishedEvidence {},
 
+    #[error("Invalid evidence")]
+    InvalidEvidence {},
+
+    #[error("Cannot submit evidence after the trusting period")]
+    EvidenceExpired {},
+
     #[error("Request already finished")]
     AlreadyFinished {},
 
+    #[error("No pending callback for this request")]
+    NoPendingCallback {},
+
+    #[error("Cannot reclaim request before block {block}")]
+    RequestNotExpired { block: u64 },
+
+    #[error("Request expired at block {block}")]
+    RequestExpired { block: u64 },
+
+    #[error("Request has been reclaimed")]
+    RequestReclaimed {},
+
     #[error("Wrong length")]
     WrongLength {},
 
     #[error("Verification failed")]
     VerificationFailed {},
 
+    #[error("Version {version} of the service has been deprecated")]
+    ServiceVersionDeprecated { version: u64 },
+
     #[error("Cannot migrate from different contract type: {previous_contract}")]
     CannotMigrate { previous_contract: String },
 }
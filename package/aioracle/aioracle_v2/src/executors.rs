@@ -1,17 +1,18 @@
-use std::ops::{Add, Sub};
+use std::ops::{Add, Mul, Sub};
 
-use aioracle_base::Executor;
+use aioracle_base::{AssetInfo, Executor};
 use cosmwasm_std::{
-    attr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, HandleResponse, MessageInfo, Order,
-    StdError, StdResult, Storage, Uint128,
+    attr, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env, HandleResponse, HumanAddr,
+    MessageInfo, Order, StdError, StdResult, Storage, Uint128,
 };
 use cw_storage_plus::Bound;
 
 use crate::{
-    contract::{pubkey_to_address, DEFAULT_LIMIT, MAX_LIMIT},
-    msg::{BoundExecutorFeeMsg, GetBoundExecutorFee, TrustingPoolResponse},
+    contract::{pubkey_to_address, transfer_asset, DEFAULT_LIMIT, EVIDENCE_PENALTY, MAX_LIMIT},
+    msg::{BoundExecutorFeeMsg, ExecutorStatsResponse, GetBoundExecutorFee, TrustingPoolResponse},
     state::{
-        executors_map, Config, TrustingPool, CONFIG, EXECUTORS_INDEX, EXECUTORS_TRUSTING_POOL,
+        executors_map, Config, ExecutorStats, TrustingPool, CONFIG, EXECUTORS_ASSET_TRUSTING_POOL,
+        EXECUTORS_INDEX, EXECUTORS_TRUSTING_POOL, EXECUTOR_STATS,
     },
     ContractError,
 };
@@ -38,11 +39,15 @@ pub fn handle_executor_join(
                     index: executor_index,
                     is_active: true,
                     left_block: None,
+                    jailed_block: None,
                 };
                 executor_index += 1;
                 return Ok(final_executor);
             }
             let mut executor = some_executor.unwrap();
+            if executor.jailed_block.is_some() {
+                return Err(ContractError::ExecutorJailed {});
+            }
             if let Some(left_block) = executor.left_block {
                 if env.block.height < left_block + config.pending_period {
                     return Err(ContractError::RejoinError {
@@ -103,19 +108,95 @@ pub fn handle_executor_leave(
     Err(ContractError::ExecutorAlreadyLeft {})
 }
 
+/// cw20 pools are stored by token, native coins share the executor's default pool
+pub fn load_trusting_pool(
+    storage: &dyn Storage,
+    executor: &[u8],
+    asset_info: &Option<AssetInfo>,
+) -> StdResult<Option<TrustingPool>> {
+    match asset_info {
+        Some(AssetInfo::Token { contract_addr }) => {
+            EXECUTORS_ASSET_TRUSTING_POOL.may_load(storage, (executor, contract_addr.as_bytes()))
+        }
+        _ => EXECUTORS_TRUSTING_POOL.may_load(storage, executor),
+    }
+}
+
+/// every trusting pool of the executor with its asset: the native pool first, then one pool per cw20 token
+pub fn load_trusting_pools(
+    storage: &dyn Storage,
+    executor: &[u8],
+) -> StdResult<Vec<(AssetInfo, TrustingPool)>> {
+    let mut trusting_pools: Vec<(AssetInfo, TrustingPool)> = vec![];
+    if let Some(trusting_pool) = EXECUTORS_TRUSTING_POOL.may_load(storage, executor)? {
+        trusting_pools.push((
+            AssetInfo::NativeToken {
+                denom: trusting_pool.amount_coin.denom.clone(),
+            },
+            trusting_pool,
+        ));
+    }
+    for item in
+        EXECUTORS_ASSET_TRUSTING_POOL
+            .prefix(executor)
+            .range(storage, None, None, Order::Ascending)
+    {
+        let (contract_addr, trusting_pool) = item?;
+        let contract_addr = String::from_utf8(contract_addr)
+            .map_err(|_| StdError::generic_err("Cannot parse asset trusting pool key"))?;
+        trusting_pools.push((
+            AssetInfo::Token {
+                contract_addr: HumanAddr::from(contract_addr),
+            },
+            trusting_pool,
+        ));
+    }
+    Ok(trusting_pools)
+}
+
+/// asset of a pool as used to load & save it: the cw20 token, None for the native pool
+pub fn pool_asset_info(asset_info: &AssetInfo) -> Option<AssetInfo> {
+    match asset_info {
+        AssetInfo::Token { .. } => Some(asset_info.clone()),
+        AssetInfo::NativeToken { .. } => None,
+    }
+}
+
+pub fn save_trusting_pool(
+    storage: &mut dyn Storage,
+    executor: &[u8],
+    asset_info: &Option<AssetInfo>,
+    trusting_pool: &TrustingPool,
+) -> StdResult<()> {
+    match asset_info {
+        Some(AssetInfo::Token { contract_addr }) => EXECUTORS_ASSET_TRUSTING_POOL.save(
+            storage,
+            (executor, contract_addr.as_bytes()),
+            trusting_pool,
+        ),
+        _ => EXECUTORS_TRUSTING_POOL.save(storage, executor, trusting_pool),
+    }
+}
+
 pub fn process_executors_pool(
     storage: &mut dyn Storage,
     executor: Binary,
     preference_executor_fee: &Coin,
     executor_reward: Coin,
+    asset_info: &Option<AssetInfo>,
 ) -> StdResult<()> {
     // add executors' rewards into the pool
-    let existing_executor_reward =
-        EXECUTORS_TRUSTING_POOL.may_load(storage, executor.as_slice())?;
+    let existing_executor_reward = load_trusting_pool(storage, executor.as_slice(), asset_info)?;
 
+    // only collect minimum between the executor fee, when the executor charges in the asset the request has paid
+    let amount = if executor_reward.denom.eq(&preference_executor_fee.denom) {
+        preference_executor_fee.amount.min(executor_reward.amount)
+    } else {
+        preference_executor_fee.amount
+    };
     let mut final_new_executor_reward = Coin {
         denom: preference_executor_fee.denom.clone(),
-        amount: preference_executor_fee.amount.min(executor_reward.amount), // only collect minimum between the executor fee
+        amount,
     };
 
     let mut trusting_pool = TrustingPool {
@@ -139,7 +220,7 @@ pub fn process_executors_pool(
         trusting_pool.withdraw_amount_coin = existing_executor_reward.withdraw_amount_coin;
     }
 
-    return EXECUTORS_TRUSTING_POOL.save(storage, executor.as_slice(), &trusting_pool);
+    save_trusting_pool(storage, executor.as_slice(), asset_info, &trusting_pool)
 }
 
 pub fn save_executors(storage: &mut dyn Storage, executors: Vec<Executor>) -> StdResult<()> {
@@ -168,13 +249,142 @@ pub fn remove_executors(storage: &mut dyn Storage, executors: Vec<Binary>) -> St
     Ok(())
 }
 
+// stats handling
+
+/// stages the executor was needed for, in permille of a stage. Stats recorded before the expected stages were
+/// counted expect every stage the executor was eligible for
+pub fn get_stages_expected(stats: &ExecutorStats) -> u64 {
+    stats
+        .stages_expected
+        .unwrap_or((stats.stages_included + stats.stages_missed) * 1000)
+}
+
+/// reputation in permille: included stages over the stages the executor was needed for, minus a penalty for each
+/// evidence against the executor
+pub fn get_reputation(stats: &ExecutorStats) -> u64 {
+    let stages_expected = get_stages_expected(stats);
+    let uptime = if stages_expected.eq(&0u64) {
+        1000u64
+    } else {
+        (stats.stages_included * 1000 * 1000 / stages_expected).min(1000)
+    };
+    uptime.saturating_sub(stats.evidences * EVIDENCE_PENALTY)
+}
+
+pub fn load_executor_stats(storage: &dyn Storage, executor: &[u8]) -> StdResult<ExecutorStats> {
+    Ok(EXECUTOR_STATS
+        .may_load(storage, executor)?
+        .unwrap_or(ExecutorStats {
+            stages_included: 0u64,
+            stages_missed: 0u64,
+            evidences: 0u64,
+            stages_expected: None,
+        }))
+}
+
+/// included executors took part in the stage, the other eligible executors missed it. The included executors are
+/// part of the eligible ones. Only a threshold of the eligible executors is needed, so each of them is expected to
+/// serve that share of the stage
+pub fn process_executors_stats(
+    storage: &mut dyn Storage,
+    eligible: &[Binary],
+    included: &[Binary],
+    threshold: u64,
+) -> StdResult<()> {
+    let expected_share = (threshold * 1000)
+        .checked_div(eligible.len() as u64)
+        .unwrap_or_default()
+        .min(1000);
+    for executor in eligible {
+        let mut stats = load_executor_stats(storage, executor.as_slice())?;
+        let stages_expected = get_stages_expected(&stats);
+        if included.contains(executor) {
+            stats.stages_included += 1;
+        } else {
+            stats.stages_missed += 1;
+        }
+        stats.stages_expected = Some(stages_expected + expected_share);
+        EXECUTOR_STATS.save(storage, executor.as_slice(), &stats)?;
+    }
+    Ok(())
+}
+
+/// public keys of the active executors. The primary keys of the index are the public keys
+pub fn load_active_executors(storage: &dyn Storage) -> Vec<Binary> {
+    executors_map()
+        .idx
+        .is_active
+        .pks(storage, b"true", None, None, Order::Ascending)
+        .map(Binary::from)
+        .collect()
+}
+
+pub fn add_executor_evidence(storage: &mut dyn Storage, executor: &Binary) -> StdResult<()> {
+    let mut stats = load_executor_stats(storage, executor.as_slice())?;
+    stats.evidences += 1;
+    EXECUTOR_STATS.save(storage, executor.as_slice(), &stats)
+}
+
 // pool handling
 
+/// jail the executor, record the evidence & slash a permille of every trusting pool of the executor. Returns the slashed amount and the reporter's bounty per asset
+pub fn slash_executor(
+    storage: &mut dyn Storage,
+    height: u64,
+    executor: &Binary,
+    slashing_amount: u64,
+    slashing_bounty: u64,
+) -> StdResult<Vec<(AssetInfo, Uint128, Uint128)>> {
+    // the executor is punished even if it has nothing to slash
+    if let Some(mut faulty_executor) = executors_map().may_load(storage, executor.as_slice())? {
+        faulty_executor.is_active = false;
+        faulty_executor.jailed_block = Some(height);
+        executors_map().save(storage, executor.as_slice(), &faulty_executor)?;
+    }
+    add_executor_evidence(storage, executor)?;
+
+    let mut slashes: Vec<(AssetInfo, Uint128, Uint128)> = vec![];
+    for (asset_info, mut trusting_pool) in load_trusting_pools(storage, executor.as_slice())? {
+        let slashed_amount = trusting_pool
+            .amount_coin
+            .amount
+            .mul(Decimal::permille(slashing_amount));
+        trusting_pool.amount_coin.amount = Uint128::from(
+            trusting_pool
+                .amount_coin
+                .amount
+                .u128()
+                .sub(slashed_amount.u128()),
+        );
+        // cannot withdraw more than what is left in the pool
+        trusting_pool.withdraw_amount_coin.amount = trusting_pool
+            .withdraw_amount_coin
+            .amount
+            .min(trusting_pool.amount_coin.amount);
+        save_trusting_pool(
+            storage,
+            executor.as_slice(),
+            &pool_asset_info(&asset_info),
+            &trusting_pool,
+        )?;
+
+        if !slashed_amount.is_zero() {
+            slashes.push((
+                asset_info,
+                slashed_amount,
+                slashed_amount.mul(Decimal::permille(slashing_bounty)),
+            ));
+        }
+    }
+    Ok(slashes)
+}
+
 pub fn handle_prepare_withdraw_pool(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     pubkey: Binary,
+    asset_info: Option<AssetInfo>,
 ) -> Result<HandleResponse, ContractError> {
     let executor_addr = pubkey_to_address(&pubkey)?;
     let Config {
@@ -183,7 +393,8 @@ pub fn handle_prepare_withdraw_pool(
     if executor_addr.ne(&info.sender) {
         return Err(ContractError::Unauthorized {});
     }
-    let mut trusting_pool = EXECUTORS_TRUSTING_POOL.load(deps.storage, pubkey.as_slice())?;
+    let mut trusting_pool = load_trusting_pool(deps.storage, pubkey.as_slice(), &asset_info)?
+        .ok_or(ContractError::EmptyTrustingPool {})?;
     let mut cosmos_msgs: Vec<CosmosMsg> = vec![];
 
     if trusting_pool.withdraw_height.eq(&0u64) {
@@ -197,18 +408,19 @@ pub fn handle_prepare_withdraw_pool(
         if trusting_pool.withdraw_amount_coin.amount.is_zero() {
             return Err(ContractError::EmptyRewardPool {});
         }
-        // add execute tx to automatically withdraw orai from pool
-        cosmos_msgs.push(
-            BankMsg::Send {
-                from_address: env.contract.address.clone(),
-                to_address: executor_addr,
-                amount: vec![Coin {
-                    denom: trusting_pool.withdraw_amount_coin.denom.clone(),
-                    amount: trusting_pool.withdraw_amount_coin.amount.clone(),
-                }],
-            }
-            .into(),
-        );
+        // add execute tx to automatically withdraw the pool in its own asset
+        let withdraw_asset = match asset_info.clone() {
+            Some(AssetInfo::Token { contract_addr }) => AssetInfo::Token { contract_addr },
+            _ => AssetInfo::NativeToken {
+                denom: trusting_pool.withdraw_amount_coin.denom.clone(),
+            },
+        };
+        cosmos_msgs.push(transfer_asset(
+            &env.contract.address,
+            executor_addr,
+            &withdraw_asset,
+            trusting_pool.withdraw_amount_coin.amount,
+        )?);
 
         // reduce amount coin
         trusting_pool.amount_coin = Coin {
@@ -227,7 +439,7 @@ pub fn handle_prepare_withdraw_pool(
         };
         trusting_pool.withdraw_height = 0;
     }
-    EXECUTORS_TRUSTING_POOL.save(deps.storage, pubkey.as_slice(), &trusting_pool)?;
+    save_trusting_pool(deps.storage, pubkey.as_slice(), &asset_info, &trusting_pool)?;
 
     Ok(HandleResponse {
         attributes: vec![attr("action", "handle_withdraw_pool")],
@@ -248,6 +460,7 @@ pub fn update_executors(storage: &mut dyn Storage, executors: Vec<Binary>) -> St
             if let Some(old_executor) = old_executor_option {
                 return Executor {
                     is_active: true,
+                    jailed_block: None,
                     ..old_executor
                 };
             }
@@ -258,6 +471,7 @@ pub fn update_executors(storage: &mut dyn Storage, executors: Vec<Binary>) -> St
                 index: executor_index,
                 is_active: true,
                 left_block: None,
+                jailed_block: None,
             };
             executor_index += 1;
             final_executor
@@ -302,14 +516,17 @@ pub fn query_trusting_pool(
     deps: Deps,
     env: Env,
     pubkey: Binary,
+    asset_info: Option<AssetInfo>,
 ) -> StdResult<TrustingPoolResponse> {
-    let trusting_pool = EXECUTORS_TRUSTING_POOL.load(deps.storage, pubkey.as_slice())?;
+    let trusting_pool = load_trusting_pool(deps.storage, pubkey.as_slice(), &asset_info)?
+        .ok_or_else(|| StdError::not_found("aioracle_v2::state::TrustingPool"))?;
     let Config {
         trusting_period, ..
     } = CONFIG.load(deps.storage)?;
     Ok(TrustingPoolResponse {
         trusting_period,
         pubkey,
+        asset_info: asset_info.as_ref().and_then(pool_asset_info),
         trusting_pool,
         current_height: env.block.height,
     })
@@ -327,23 +544,30 @@ pub fn query_trusting_pools(
     } = CONFIG.load(deps.storage)?;
     let (limit, min, max, order_enum) = get_executors_params(offset, limit, order);
 
-    let res: StdResult<Vec<TrustingPoolResponse>> = EXECUTORS_TRUSTING_POOL
-        .range(deps.storage, min, max, order_enum)
-        .take(limit)
-        .map(|kv_item| {
-            kv_item.and_then(|(pub_vec, trusting_pool)| {
-                // will panic if length is greater than 8, but we can make sure it is u64
-                // try_into will box vector to fixed array
-                Ok(TrustingPoolResponse {
-                    trusting_period,
-                    current_height: env.block.height,
-                    pubkey: Binary::from(pub_vec),
-                    trusting_pool,
-                })
-            })
-        })
-        .collect();
-    res
+    // pages over executors, every pool of an executor is listed together
+    let mut res: Vec<TrustingPoolResponse> = vec![];
+    let mut total_executors = 0usize;
+    for kv_item in executors_map().range(deps.storage, min, max, order_enum) {
+        if total_executors >= limit {
+            break;
+        }
+        let (pub_vec, _) = kv_item?;
+        let trusting_pools = load_trusting_pools(deps.storage, pub_vec.as_slice())?;
+        if trusting_pools.is_empty() {
+            continue;
+        }
+        total_executors += 1;
+        for (asset_info, trusting_pool) in trusting_pools {
+            res.push(TrustingPoolResponse {
+                trusting_period,
+                current_height: env.block.height,
+                pubkey: Binary::from(pub_vec.as_slice()),
+                asset_info: pool_asset_info(&asset_info),
+                trusting_pool,
+            });
+        }
+    }
+    Ok(res)
 }
 
 pub fn query_executor(deps: Deps, pubkey: Binary) -> StdResult<Executor> {
@@ -435,6 +659,18 @@ pub fn query_bound_executor_fee(deps: Deps) -> StdResult<Coin> {
     Ok(fees)
 }
 
+pub fn query_executor_stats(deps: Deps, pubkey: Binary) -> StdResult<ExecutorStatsResponse> {
+    let stats = load_executor_stats(deps.storage, pubkey.as_slice())?;
+    Ok(ExecutorStatsResponse {
+        reputation: get_reputation(&stats),
+        pubkey,
+        stages_included: stats.stages_included,
+        stages_missed: stats.stages_missed,
+        evidences: stats.evidences,
+        stages_expected: get_stages_expected(&stats),
+    })
+}
+
 pub fn query_executor_size(deps: Deps) -> StdResult<u64> {
     let executor_count = executors_map()
         .range(deps.storage, None, None, Order::Ascending)
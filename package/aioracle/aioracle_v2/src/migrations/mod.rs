@@ -2,4 +2,89 @@ mod v01;
 mod v02;
 mod v03;
 
-pub use v03::migrate_v02_to_v03;
+#[cfg(test)]
+mod testing;
+
+use cosmwasm_std::{Order, StdError, StdResult, Storage};
+use cw_storage_plus::Item;
+
+use crate::state::{executors_map, CONFIG, EXECUTORS_INDEX};
+
+pub use v01::migrate_v01_to_v02;
+pub use v02::migrate_v02_to_v03;
+pub use v03::migrate_v03_to_latest;
+
+/// storage layouts the contract has been deployed with
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StoreLayout {
+    V01,
+    V02,
+    V03,
+    Latest,
+}
+
+impl StoreLayout {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StoreLayout::V01 => "v0.1",
+            StoreLayout::V02 => "v0.2",
+            StoreLayout::V03 => "v0.3",
+            StoreLayout::Latest => "latest",
+        }
+    }
+}
+
+/// every layout before the latest one stores its config under the same key, so the layout is
+/// detected by the first config type the stored value can be parsed into
+pub fn detect_layout(storage: &dyn Storage) -> StdResult<StoreLayout> {
+    if CONFIG.may_load(storage)?.is_some() {
+        return Ok(StoreLayout::Latest);
+    }
+    if Item::<v03::OldConfig>::new(v03::OLD_CONFIG_KEY)
+        .load(storage)
+        .is_ok()
+    {
+        return Ok(StoreLayout::V03);
+    }
+    if Item::<v02::OldConfig>::new(v02::OLD_CONFIG_KEY)
+        .load(storage)
+        .is_ok()
+    {
+        return Ok(StoreLayout::V02);
+    }
+    if Item::<v01::OldConfig>::new(v01::OLD_CONFIG_KEY)
+        .load(storage)
+        .is_ok()
+    {
+        return Ok(StoreLayout::V01);
+    }
+    Err(StdError::generic_err("Unknown storage layout"))
+}
+
+/// upgrades the store step by step from its current layout to the latest one and returns the
+/// layout it started from
+pub fn migrate_store(storage: &mut dyn Storage) -> StdResult<StoreLayout> {
+    let layout = detect_layout(storage)?;
+    if layout == StoreLayout::V01 {
+        migrate_v01_to_v02(storage)?;
+    }
+    if layout == StoreLayout::V01 || layout == StoreLayout::V02 {
+        migrate_v02_to_v03(storage)?;
+    }
+    if layout != StoreLayout::Latest {
+        migrate_v03_to_latest(storage)?;
+    }
+    sync_executors_index(storage)?;
+    Ok(layout)
+}
+
+/// the executor index is the next free index, so it must be greater than every stored one.
+/// Older layouts did not store it at all
+fn sync_executors_index(storage: &mut dyn Storage) -> StdResult<()> {
+    let mut executor_index = EXECUTORS_INDEX.may_load(storage)?.unwrap_or_default();
+    for item in executors_map().range(storage, None, None, Order::Ascending) {
+        let (_, executor) = item?;
+        executor_index = executor_index.max(executor.index + 1);
+    }
+    EXECUTORS_INDEX.save(storage, &executor_index)
+}
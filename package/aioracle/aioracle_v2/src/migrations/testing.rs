@@ -0,0 +1,369 @@
+//! Seeds a store in each historical layout, migrates it through the contract entry point and
+//! checks every `Request`, `Executor` and `TrustingPool` against what has been seeded.
+
+use std::collections::HashSet;
+
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+};
+use cosmwasm_std::{
+    coins, from_binary, Binary, Coin, HumanAddr, MigrateResponse, Order, OwnedDeps, Storage,
+    Uint128,
+};
+use cw2::ContractVersion;
+use cw_storage_plus::Item;
+
+use crate::contract::{init, migrate, query, DENOM, SLASHING_AMOUNT, TRUSTING_PERIOD};
+use crate::msg::{InitMsg, MigrateMsg, QueryMsg};
+use crate::state::{
+    executors_map, requests, Config, Request, TrustingPool, CHECKPOINT, CONFIG, EXECUTORS_INDEX,
+    EXECUTORS_TRUSTING_POOL, LATEST_STAGE,
+};
+
+use super::{v01, v02, v03, StoreLayout};
+
+/// data seeded into a historical store, which must survive the migration untouched
+struct Fixture {
+    requests: Vec<(u64, v02::OldRequest)>,
+    executors: Vec<v03::OldExecutor>,
+    pools: Vec<(Binary, Coin)>,
+}
+
+fn old_request(stage: u64, threshold: u64) -> v02::OldRequest {
+    v02::OldRequest {
+        requester: HumanAddr::from(format!("requester{}", stage)),
+        request_height: stage * 10,
+        submit_merkle_height: stage * 10 + 1,
+        merkle_root: format!("root{}", stage),
+        threshold,
+        service: String::from("price"),
+        input: Some(format!("input{}", stage)),
+        rewards: vec![(
+            HumanAddr::from("provider"),
+            DENOM.to_string(),
+            Uint128::from(stage),
+        )],
+    }
+}
+
+fn old_executor(pubkey: &[u8], index: u64, is_active: bool) -> v03::OldExecutor {
+    v03::OldExecutor {
+        pubkey: Binary::from(pubkey),
+        is_active,
+        executing_power: index * 2,
+        index,
+    }
+}
+
+/// writes a store the way a contract deployed with the given layout would have left it
+fn seed_store(storage: &mut dyn Storage, layout: StoreLayout) -> Fixture {
+    let owner = HumanAddr::from("owner");
+    let service_addr = HumanAddr::from("service");
+    let contract_fee = Coin {
+        amount: Uint128::from(5u64),
+        denom: DENOM.to_string(),
+    };
+    match layout {
+        StoreLayout::V01 => Item::<v01::OldConfig>::new(v01::OLD_CONFIG_KEY)
+            .save(
+                storage,
+                &v01::OldConfig {
+                    owner,
+                    service_addr,
+                    contract_fee,
+                    checkpoint_threshold: 5,
+                    max_req_threshold: 67,
+                },
+            )
+            .unwrap(),
+        StoreLayout::V02 => Item::<v02::OldConfig>::new(v02::OLD_CONFIG_KEY)
+            .save(
+                storage,
+                &v02::OldConfig {
+                    owner,
+                    service_addr,
+                    contract_fee,
+                    checkpoint_threshold: 5,
+                    max_req_threshold: 67,
+                    ping_contract: HumanAddr::from("ping"),
+                    trusting_period: 100,
+                },
+            )
+            .unwrap(),
+        StoreLayout::V03 => Item::<v03::OldConfig>::new(v03::OLD_CONFIG_KEY)
+            .save(
+                storage,
+                &v03::OldConfig {
+                    owner,
+                    service_addr,
+                    contract_fee,
+                    checkpoint_threshold: 5,
+                    max_req_threshold: 67,
+                    trusting_period: 100,
+                    slashing_amount: 50,
+                    denom: DENOM.to_string(),
+                },
+            )
+            .unwrap(),
+        StoreLayout::Latest => panic!("the latest layout is seeded by init"),
+    }
+    LATEST_STAGE.save(storage, &3).unwrap();
+    CHECKPOINT.save(storage, &2).unwrap();
+
+    let fixture_requests = vec![
+        (1u64, old_request(1, 1)),
+        (2u64, old_request(2, 2)),
+        (3u64, old_request(3, 1)),
+    ];
+    let pools = vec![
+        (Binary::from(&[1u8]), coins(100, DENOM).remove(0)),
+        (Binary::from(&[2u8]), coins(0, DENOM).remove(0)),
+    ];
+    // executors have been introduced with the v0.3.x layout
+    let executors = if layout == StoreLayout::V03 {
+        vec![
+            old_executor(&[1u8], 0, true),
+            old_executor(&[2u8], 3, false),
+        ]
+    } else {
+        vec![]
+    };
+
+    for (stage, request) in fixture_requests.iter() {
+        if layout == StoreLayout::V03 {
+            requests()
+                .save(
+                    storage,
+                    &stage.to_be_bytes(),
+                    &Request {
+                        requester: request.requester.clone(),
+                        preference_executor_fee: coins(0, DENOM).remove(0),
+                        request_height: request.request_height,
+                        submit_merkle_height: request.submit_merkle_height,
+                        merkle_root: request.merkle_root.clone(),
+                        threshold: request.threshold,
+                        service: request.service.clone(),
+                        service_version: None,
+                        input: request.input.clone(),
+                        rewards: request.rewards.clone(),
+                        expired_height: None,
+                        min_reputation: None,
+                        asset_info: None,
+                        callback: None,
+                        executors: None,
+                        reclaimed_height: None,
+                    },
+                )
+                .unwrap();
+        } else {
+            v02::old_requests()
+                .save(storage, &stage.to_be_bytes(), request)
+                .unwrap();
+        }
+    }
+
+    for (pubkey, amount_coin) in pools.iter() {
+        if layout == StoreLayout::V03 {
+            EXECUTORS_TRUSTING_POOL
+                .save(
+                    storage,
+                    pubkey.as_slice(),
+                    &TrustingPool {
+                        amount_coin: amount_coin.clone(),
+                        withdraw_amount_coin: coins(0, DENOM).remove(0),
+                        withdraw_height: 0,
+                    },
+                )
+                .unwrap();
+        } else {
+            v02::OLD_EXECUTORS_TRUSTING_POOL
+                .save(
+                    storage,
+                    pubkey.as_slice(),
+                    &v02::OldTrustingPool {
+                        amount_coin: amount_coin.clone(),
+                        withdraw_height: 0,
+                    },
+                )
+                .unwrap();
+        }
+    }
+
+    for executor in executors.iter() {
+        v03::old_executors_map()
+            .save(storage, executor.pubkey.as_slice(), executor)
+            .unwrap();
+    }
+
+    Fixture {
+        requests: fixture_requests,
+        executors,
+        pools,
+    }
+}
+
+fn run_migrate(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>) -> MigrateResponse {
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(HumanAddr::from("owner"), &[]),
+        MigrateMsg {},
+    )
+    .unwrap()
+}
+
+fn migrated_from(res: &MigrateResponse) -> String {
+    res.attributes
+        .iter()
+        .find(|attr| attr.key == "migrated_from")
+        .map(|attr| attr.value.clone())
+        .unwrap()
+}
+
+/// checks the invariants every migrated store must hold, then compares the data with the fixture
+fn check_store(storage: &dyn Storage, fixture: &Fixture) {
+    let config = CONFIG.load(storage).unwrap();
+    assert_eq!(config.owner, HumanAddr::from("owner"));
+    assert_eq!(config.denom, DENOM);
+    assert_eq!(LATEST_STAGE.load(storage).unwrap(), 3);
+    assert_eq!(CHECKPOINT.load(storage).unwrap(), 2);
+
+    // requests
+    let stored_requests: Vec<(Vec<u8>, Request)> = requests()
+        .range(storage, None, None, Order::Ascending)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(stored_requests.len(), fixture.requests.len());
+    for ((key, request), (stage, old)) in stored_requests.iter().zip(fixture.requests.iter()) {
+        assert_eq!(key.as_slice(), &stage.to_be_bytes());
+        assert_eq!(request.requester, old.requester);
+        assert_eq!(request.request_height, old.request_height);
+        assert_eq!(request.submit_merkle_height, old.submit_merkle_height);
+        assert_eq!(request.merkle_root, old.merkle_root);
+        assert_eq!(request.threshold, old.threshold);
+        assert_eq!(request.service, old.service);
+        assert_eq!(request.input, old.input);
+        assert_eq!(request.rewards, old.rewards);
+        assert_eq!(request.preference_executor_fee, coins(0, DENOM).remove(0));
+        assert!(request.threshold > 0 && request.threshold <= config.max_req_threshold);
+        assert_eq!(request.expired_height, None);
+        assert_eq!(request.min_reputation, None);
+        assert_eq!(request.asset_info, None);
+        assert_eq!(request.callback, None);
+        assert_eq!(request.executors, None);
+    }
+
+    // executors
+    let executor_index = EXECUTORS_INDEX.load(storage).unwrap();
+    let stored_executors: Vec<(Vec<u8>, _)> = executors_map()
+        .range(storage, None, None, Order::Ascending)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(stored_executors.len(), fixture.executors.len());
+    let mut indexes = HashSet::new();
+    for ((key, executor), old) in stored_executors.iter().zip(fixture.executors.iter()) {
+        assert_eq!(key.as_slice(), executor.pubkey.as_slice());
+        assert!(indexes.insert(executor.index), "duplicated executor index");
+        assert!(executor.index < executor_index);
+        assert_eq!(executor.pubkey, old.pubkey);
+        assert_eq!(executor.is_active, old.is_active);
+        assert_eq!(executor.executing_power, old.executing_power);
+        assert_eq!(executor.index, old.index);
+        assert_eq!(executor.left_block, None);
+        assert_eq!(executor.jailed_block, None);
+    }
+
+    // trusting pools
+    let stored_pools: Vec<(Vec<u8>, TrustingPool)> = EXECUTORS_TRUSTING_POOL
+        .range(storage, None, None, Order::Ascending)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(stored_pools.len(), fixture.pools.len());
+    for ((key, pool), (pubkey, amount_coin)) in stored_pools.iter().zip(fixture.pools.iter()) {
+        assert_eq!(key.as_slice(), pubkey.as_slice());
+        assert_eq!(&pool.amount_coin, amount_coin);
+        assert_eq!(pool.withdraw_amount_coin.denom, pool.amount_coin.denom);
+        assert!(pool.withdraw_amount_coin.amount <= pool.amount_coin.amount);
+        assert_eq!(pool.withdraw_height, 0);
+    }
+}
+
+fn check_contract_version(deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>) {
+    let version: ContractVersion =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::ContractVersion {}).unwrap())
+            .unwrap();
+    assert_eq!(version.contract, "crates.io:aioracle-v2");
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+}
+
+fn test_migrate_layout(layout: StoreLayout) -> Config {
+    let mut deps = mock_dependencies(&[]);
+    deps.api.canonical_length = 54;
+    let fixture = seed_store(&mut deps.storage, layout);
+
+    let res = run_migrate(&mut deps);
+    assert_eq!(migrated_from(&res), layout.as_str());
+    check_store(&deps.storage, &fixture);
+    check_contract_version(&deps);
+
+    // migrating a migrated store must not change anything
+    let res = run_migrate(&mut deps);
+    assert_eq!(migrated_from(&res), StoreLayout::Latest.as_str());
+    check_store(&deps.storage, &fixture);
+
+    CONFIG.load(&deps.storage).unwrap()
+}
+
+#[test]
+fn migrate_v01_store() {
+    let config = test_migrate_layout(StoreLayout::V01);
+    assert_eq!(config.trusting_period, TRUSTING_PERIOD);
+    assert_eq!(config.slashing_amount, SLASHING_AMOUNT);
+}
+
+#[test]
+fn migrate_v02_store() {
+    let config = test_migrate_layout(StoreLayout::V02);
+    assert_eq!(config.trusting_period, 100);
+    assert_eq!(config.slashing_amount, SLASHING_AMOUNT);
+}
+
+#[test]
+fn migrate_v03_store() {
+    let config = test_migrate_layout(StoreLayout::V03);
+    assert_eq!(config.trusting_period, 100);
+    assert_eq!(config.slashing_amount, 50);
+}
+
+#[test]
+fn migrate_v03_store_syncs_executors_index() {
+    let mut deps = mock_dependencies(&[]);
+    seed_store(&mut deps.storage, StoreLayout::V03);
+    run_migrate(&mut deps);
+    // the highest seeded index is 3
+    assert_eq!(EXECUTORS_INDEX.load(&deps.storage).unwrap(), 4);
+}
+
+#[test]
+fn migrate_latest_store() {
+    let mut deps = mock_dependencies(&[]);
+    deps.api.canonical_length = 54;
+    init(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(HumanAddr::from("owner"), &[]),
+        InitMsg {
+            owner: None,
+            service_addr: HumanAddr::from("service"),
+            contract_fee: coins(0, DENOM).remove(0),
+            executors: vec![Binary::from(&[1u8]), Binary::from(&[2u8])],
+        },
+    )
+    .unwrap();
+    check_contract_version(&deps);
+
+    let res = run_migrate(&mut deps);
+    assert_eq!(migrated_from(&res), StoreLayout::Latest.as_str());
+    assert_eq!(EXECUTORS_INDEX.load(&deps.storage).unwrap(), 2);
+    check_contract_version(&deps);
+}
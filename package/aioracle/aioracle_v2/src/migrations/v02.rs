@@ -3,13 +3,15 @@ use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Binary, Coin, HumanAddr, Order, StdResult, Storage, Uint128};
+use cosmwasm_std::{Coin, HumanAddr, Order, StdResult, Storage, Uint128};
 
 use crate::{
-    msg::{MigrateMsg, TrustingPoolResponse},
-    state::{requests, Config, Request, TrustingPool, CONFIG_KEY, EXECUTORS_TRUSTING_POOL},
+    contract::{DENOM, SLASHING_AMOUNT},
+    state::{requests, Request, TrustingPool, EXECUTORS_TRUSTING_POOL},
 };
 
+use super::v03::OldConfig as V03Config;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct OldConfig {
     /// Owner If None set, contract is frozen.
@@ -43,15 +45,6 @@ pub struct OldTrustingPool {
     pub withdraw_height: u64,
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
-#[serde(rename_all = "snake_case")]
-pub struct OldTrustingPoolResponse {
-    pub pubkey: Binary,
-    pub current_height: u64,
-    pub trusting_period: u64,
-    pub trusting_pool: OldTrustingPool,
-}
-
 pub const OLD_EXECUTORS_TRUSTING_POOL_PREFIX: &str = "executors_trusting_pool";
 pub const OLD_EXECUTORS_TRUSTING_POOL: Map<&[u8], OldTrustingPool> =
     Map::new(OLD_EXECUTORS_TRUSTING_POOL_PREFIX);
@@ -87,92 +80,85 @@ pub fn old_requests<'a>() -> IndexedMap<'a, &'a [u8], OldRequest, RequestIndexes
 
 pub const OLD_CONFIG_KEY: &str = "config";
 
-/// this takes a v0.1.x store and converts it to a v0.2.x format
-// pub fn migrate_v02_to_v03(storage: &mut dyn Storage, migrate_msg: MigrateMsg) -> StdResult<()> {
-//     let OldConfig {
-//         owner,
-//         service_addr,
-//         contract_fee,
-//         checkpoint_threshold,
-//         max_req_threshold,
-//         ping_contract,
-//         trusting_period,
-//     } = Item::<OldConfig>::new(OLD_CONFIG_KEY).load(storage)?;
-//     let new_config = Item::<Config>::new(CONFIG_KEY);
-//     let new_config_data = Config {
-//         trusting_period,
-//         owner,
-//         service_addr,
-//         contract_fee,
-//         checkpoint_threshold,
-//         max_req_threshold,
-//         slashing_amount: migrate_msg.slash_amount,
-//         denom: migrate_msg.denom,
-//     };
-//     new_config.save(storage, &new_config_data)?;
+/// this takes a v0.2.x store and converts it to a v0.3.x format
+pub fn migrate_v02_to_v03(storage: &mut dyn Storage) -> StdResult<()> {
+    let OldConfig {
+        owner,
+        service_addr,
+        contract_fee,
+        checkpoint_threshold,
+        max_req_threshold,
+        trusting_period,
+        ..
+    } = Item::<OldConfig>::new(OLD_CONFIG_KEY).load(storage)?;
+    let new_config_data = V03Config {
+        trusting_period,
+        owner,
+        service_addr,
+        contract_fee,
+        checkpoint_threshold,
+        max_req_threshold,
+        slashing_amount: SLASHING_AMOUNT,
+        denom: DENOM.to_string(),
+    };
+    Item::<V03Config>::new(OLD_CONFIG_KEY).save(storage, &new_config_data)?;
 
-//     // // migrate request storage
-//     let request_maps_result: StdResult<Vec<(Vec<u8>, OldRequest)>> = old_requests()
-//         .range(storage, None, None, Order::Ascending)
-//         .collect();
+    // migrate request storage
+    let request_maps_result: StdResult<Vec<(Vec<u8>, OldRequest)>> = old_requests()
+        .range(storage, None, None, Order::Ascending)
+        .collect();
 
-//     let request_maps = request_maps_result?;
+    for (key, old_request) in request_maps_result? {
+        requests().save(
+            storage,
+            key.as_slice(),
+            &Request {
+                requester: old_request.requester,
+                preference_executor_fee: Coin {
+                    denom: DENOM.to_string(),
+                    amount: Uint128::from(0u64),
+                },
+                request_height: old_request.request_height,
+                submit_merkle_height: old_request.submit_merkle_height,
+                merkle_root: old_request.merkle_root,
+                threshold: old_request.threshold,
+                service: old_request.service,
+                service_version: None,
+                input: old_request.input,
+                rewards: old_request.rewards,
+                expired_height: None,
+                min_reputation: None,
+                asset_info: None,
+                callback: None,
+                executors: None,
+                reclaimed_height: None,
+            },
+        )?;
+    }
 
-//     for request_map in request_maps {
-//         requests().save(
-//             storage,
-//             request_map.0.as_slice(),
-//             &Request {
-//                 requester: request_map.1.requester,
-//                 preference_executor_fee: Coin {
-//                     denom: "orai".to_string(),
-//                     amount: Uint128::from(0u64),
-//                 },
-//                 request_height: request_map.1.request_height,
-//                 submit_merkle_height: request_map.1.submit_merkle_height,
-//                 merkle_root: request_map.1.merkle_root,
-//                 threshold: request_map.1.threshold,
-//                 service: request_map.1.service,
-//                 input: request_map.1.input,
-//                 rewards: request_map.1.rewards,
-//             },
-//         )?;
-//     }
+    // migrate trusting pools. Old pools did not track the amount being withdrawn
+    let trusting_pools_result: StdResult<Vec<(Vec<u8>, OldTrustingPool)>> =
+        OLD_EXECUTORS_TRUSTING_POOL
+            .range(storage, None, None, Order::Ascending)
+            .collect();
 
-//     // let trusting_pools_results: StdResult<Vec<OldTrustingPoolResponse>> =
-//     //     OLD_EXECUTORS_TRUSTING_POOL
-//     //         .range(storage, None, None, Order::Ascending)
-//     //         .map(|kv_item| {
-//     //             kv_item.and_then(|(pub_vec, trusting_pool)| {
-//     //                 // will panic if length is greater than 8, but we can make sure it is u64
-//     //                 // try_into will box vector to fixed array
-//     //                 Ok(OldTrustingPoolResponse {
-//     //                     trusting_period: 1,
-//     //                     current_height: 0,
-//     //                     pubkey: Binary::from(pub_vec),
-//     //                     trusting_pool,
-//     //                 })
-//     //             })
-//     //         })
-//     //         .collect();
-//     // let trusting_pools = trusting_pools_results?;
-//     // for pool in trusting_pools {
-//     //     EXECUTORS_TRUSTING_POOL.save(
-//     //         storage,
-//     //         pool.pubkey.as_slice(),
-//     //         &TrustingPool {
-//     //             amount_coin: pool.trusting_pool.amount_coin.clone(),
-//     //             withdraw_height: 0,
-//     //             withdraw_amount_coin: Coin {
-//     //                 denom: pool.trusting_pool.amount_coin.denom,
-//     //                 amount: Uint128::from(0u64),
-//     //             },
-//     //         },
-//     //     )?;
-//     // }
+    for (pubkey, old_pool) in trusting_pools_result? {
+        EXECUTORS_TRUSTING_POOL.save(
+            storage,
+            pubkey.as_slice(),
+            &TrustingPool {
+                withdraw_amount_coin: Coin {
+                    denom: old_pool.amount_coin.denom.clone(),
+                    amount: Uint128::from(0u64),
+                },
+                amount_coin: old_pool.amount_coin,
+                withdraw_height: 0,
+            },
+        )?;
+    }
 
-//     Ok(())
-// }
+    Ok(())
+}
 
 #[cfg(test)]
 mod test {
@@ -249,46 +235,38 @@ mod test {
         deps
     }
 
-    // #[test]
-    // fn test_migrate() {
-    //     let mut deps = setup_old_contract();
-    //     let info = mock_info(HumanAddr::from("foobar"), &[]);
-    //     migrate(
-    //         deps.as_mut(),
-    //         mock_env(),
-    //         info,
-    //         MigrateMsg {
-    //             slash_amount: 50,
-    //             denom: String::from("orai"),
-    //         },
-    //     )
-    //     .unwrap();
-
-    //     // // query trusting pool
-    //     // let pool: TrustingPoolResponse = from_binary(
-    //     //     &query(
-    //     //         deps.as_ref(),
-    //     //         mock_env(),
-    //     //         QueryMsg::GetTrustingPool {
-    //     //             pubkey: Binary::from(&[1]),
-    //     //         },
-    //     //     )
-    //     //     .unwrap(),
-    //     // )
-    //     // .unwrap();
+    #[test]
+    fn test_migrate() {
+        let mut deps = setup_old_contract();
+        let info = mock_info(HumanAddr::from("foobar"), &[]);
+        migrate(deps.as_mut(), mock_env(), info, MigrateMsg {}).unwrap();
 
-    //     // println!("pool: {:?}", pool);
+        // query trusting pool
+        let pool: TrustingPoolResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetTrustingPool {
+                    pubkey: Binary::from(&[1]),
+                    asset_info: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(pool.trusting_pool.amount_coin.denom, "orai");
 
-    //     // // query config
-    //     // let config: Config =
-    //     //     from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
-    //     // println!("config: {:?}", config);
-    //     // assert_eq!(config.slashing_amount, 50);
+        // query config
+        let config: Config =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+        assert_eq!(config.trusting_period, 100);
+        assert_eq!(config.slashing_amount, SLASHING_AMOUNT);
 
-    //     // query requests
-    //     let request: Request =
-    //         from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Request { stage: 1 }).unwrap())
-    //             .unwrap();
-    //     println!("request: {:?}", request);
-    // }
+        // query requests
+        let request: Request =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Request { stage: 1 }).unwrap())
+                .unwrap();
+        assert_eq!(request.requester, HumanAddr::from("hello"));
+        assert_eq!(request.preference_executor_fee.amount, Uint128::from(0u64));
+    }
 }
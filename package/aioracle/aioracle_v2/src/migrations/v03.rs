@@ -6,8 +6,7 @@ use serde::{Deserialize, Serialize};
 use cosmwasm_std::{Binary, Coin, HumanAddr, Order, StdResult, Storage};
 
 use crate::{
-    contract::PENDING_PERIOD,
-    msg::MigrateMsg,
+    contract::{PENDING_PERIOD, REQUEST_TIMEOUT, SLASHING_BOUNTY},
     state::{executors_map, Config, CONFIG_KEY, CONTRACT_FEES},
 };
 
@@ -61,8 +60,8 @@ pub fn old_executors_map<'a>() -> IndexedMap<'a, &'a [u8], OldExecutor, Executor
     IndexedMap::new("executors_v1.1", indexes)
 }
 
-/// this takes a v0.1.x store and converts it to a v0.2.x format
-pub fn migrate_v02_to_v03(storage: &mut dyn Storage) -> StdResult<()> {
+/// this takes a v0.3.x store and converts it to the latest format
+pub fn migrate_v03_to_latest(storage: &mut dyn Storage) -> StdResult<()> {
     let OldConfig {
         owner,
         service_addr,
@@ -82,8 +81,10 @@ pub fn migrate_v02_to_v03(storage: &mut dyn Storage) -> StdResult<()> {
         checkpoint_threshold,
         max_req_threshold,
         slashing_amount,
+        slashing_bounty: Some(SLASHING_BOUNTY),
         denom,
         pending_period: PENDING_PERIOD,
+        request_timeout: Some(REQUEST_TIMEOUT),
     };
     new_config.save(storage, &new_config_data)?;
 
@@ -93,7 +94,6 @@ pub fn migrate_v02_to_v03(storage: &mut dyn Storage) -> StdResult<()> {
     let old_executors = old_executors_maps_result?;
 
     for old_executor in old_executors {
-        println!("old executor index: {}", old_executor.1.index);
         executors_map().save(
             storage,
             old_executor.0.as_slice(),
@@ -103,6 +103,7 @@ pub fn migrate_v02_to_v03(storage: &mut dyn Storage) -> StdResult<()> {
                 executing_power: old_executor.1.executing_power,
                 index: old_executor.1.index,
                 left_block: None,
+                jailed_block: None,
             },
         )?;
     }
@@ -116,7 +117,6 @@ pub fn migrate_v02_to_v03(storage: &mut dyn Storage) -> StdResult<()> {
 mod test {
     use crate::contract::*;
     use crate::msg::*;
-    use crate::state::Request;
     use aioracle_base::Executor;
     use cosmwasm_std::testing::{
         mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
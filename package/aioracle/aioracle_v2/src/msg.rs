@@ -1,10 +1,11 @@
-use aioracle_base::{GetServiceFeesMsg, Reward, ServiceMsg};
+use aioracle_base::{AssetInfo, GetServiceFeesMsg, Reward, ServiceMsg};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_std::{Binary, Coin, HumanAddr, Uint128};
+use cw20::Cw20ReceiveMsg;
 
-use crate::state::TrustingPool;
+use crate::state::{Callback, TrustingPool};
 
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct InitMsg {
@@ -30,23 +31,53 @@ pub enum HandleMsg {
         merkle_root: String,
         executors: Vec<Binary>,
     },
+    SubmitReport {
+        stage: u64,
+        /// MerkleRoot is hex-encoded merkle root.
+        merkle_root: String,
+        /// pubkey of the executor signing the report
+        executor: Binary,
+        /// secp256k1 signature over sha256("{contract_addr}:{chain_id}:{stage}:{merkle_root}"), the merkle root as hex
+        signature: Binary,
+    },
     Request {
         service: String,
         input: Option<String>,
         threshold: u64,
         preference_executor_fee: Coin,
+        min_reputation: Option<u64>,
+        /// OracleCallback sent to the requester once the request is finalized. The callback is not part of the
+        /// finalization: anyone can deliver it with RelayCallback and be paid its budget
+        callback: Option<Callback>,
+        /// version of the service contracts the request runs against, the current one if None
+        service_version: Option<u64>,
+    },
+    /// claim the rewards of many verified reports at once
+    ClaimRewards {
+        claims: Vec<RewardClaim>,
     },
-    // ClaimReward {
-    //     stage: u64,
-    //     report: Binary,
-    //     proof: Option<Vec<String>>,
-    // },
     WithdrawFees {
         amount: Uint128,
         denom: String,
     },
     PrepareWithdrawPool {
         pubkey: Binary,
+        asset_info: Option<AssetInfo>,
+    },
+    ReclaimExpiredRequest {
+        stage: u64,
+    },
+    /// notify the requester of a finalized request & pay the relayer with the callback budget. Permissionless, so the
+    /// requester, an executor or a keeper can relay it. Finalizing the request does not depend on the callback: a
+    /// requester failing to handle it stays pending instead of reverting the merkle root
+    RelayCallback {
+        stage: u64,
+    },
+    /// store a leaf of the stage's merkle tree on chain after verifying it against the merkle root
+    SubmitResult {
+        stage: u64,
+        data: Binary,
+        proof: Option<Vec<String>>,
     },
     ExecutorJoin {
         executor: Binary,
@@ -56,8 +87,62 @@ pub enum HandleMsg {
     },
     SubmitEvidence {
         stage: u64,
+        evidence: Evidence,
+    },
+    Receive(Cw20ReceiveMsg),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// request paid with the sent cw20 tokens. Fees of the service must be in the same token
+    Request {
+        service: String,
+        input: Option<String>,
+        threshold: u64,
+        preference_executor_fee: Uint128,
+        min_reputation: Option<u64>,
+        callback: Option<Callback>,
+        service_version: Option<u64>,
+    },
+}
+
+/// message sent to the requester once the merkle root of its request has been registered
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OracleCallbackMsg {
+    OracleCallback {
+        stage: u64,
+        merkle_root: String,
+        service: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct RewardClaim {
+    pub stage: u64,
+    pub report: Binary,
+    pub proof: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Evidence {
+    /// the executor signed two different merkle roots for the same stage
+    DoubleSign {
+        executor: Binary,
+        merkle_root: String,
+        signature: Binary,
+        conflicting_merkle_root: String,
+        conflicting_signature: Binary,
+    },
+    /// a report included in the merkle root claims rewards that are not part of the request
+    InvalidReport {
         report: Binary,
         proof: Option<Vec<String>>,
+        /// signature of the report by its executor, over `report_data_sign_message`
+        signature: Binary,
     },
 }
 
@@ -65,6 +150,8 @@ pub enum HandleMsg {
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    /// name and version of the contract, from cw2
+    ContractVersion {},
     GetExecutors {
         offset: Option<Binary>,
         limit: Option<u8>,
@@ -79,6 +166,13 @@ pub enum QueryMsg {
         pubkey: Binary,
     },
     GetExecutorSize {},
+    GetExecutorStats {
+        pubkey: Binary,
+    },
+    /// all the results submitted and verified for the stage
+    GetVerifiedResult {
+        stage: u64,
+    },
     Request {
         stage: u64,
     },
@@ -99,6 +193,17 @@ pub enum QueryMsg {
         limit: Option<u8>,
         order: Option<u8>,
     },
+    GetExpiredRequests {
+        offset: Option<u64>,
+        limit: Option<u8>,
+        order: Option<u8>,
+    },
+    /// finalized requests whose callback has not been relayed yet
+    GetPendingCallbacks {
+        offset: Option<u64>,
+        limit: Option<u8>,
+        order: Option<u8>,
+    },
     LatestStage {},
     StageInfo {},
     GetServiceContracts {
@@ -122,7 +227,9 @@ pub enum QueryMsg {
     },
     GetTrustingPool {
         pubkey: Binary,
+        asset_info: Option<AssetInfo>,
     },
+    /// native & cw20 pools of the executors. Offset & limit apply to executors, all pools of an executor are listed
     GetTrustingPools {
         offset: Option<Binary>,
         limit: Option<u8>,
@@ -143,10 +250,34 @@ pub struct ExecutorsResponse {
     pub is_acitve: bool,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct VerifiedResultResponse {
+    pub stage: u64,
+    pub merkle_root: String,
+    /// every leaf submitted for the stage, ordered by leaf hash
+    pub results: Vec<Binary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct ExecutorStatsResponse {
+    pub pubkey: Binary,
+    pub stages_included: u64,
+    pub stages_missed: u64,
+    pub evidences: u64,
+    /// permille score, based on uptime & penalized by evidences
+    pub reputation: u64,
+    /// stages the executor was needed for, in permille of a stage
+    pub stages_expected: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
 pub struct TrustingPoolResponse {
     pub pubkey: Binary,
+    /// cw20 token of the pool. None for the native pool
+    pub asset_info: Option<AssetInfo>,
     pub current_height: u64,
     pub trusting_period: u64,
     pub trusting_pool: TrustingPool,
@@ -180,6 +311,24 @@ pub struct GetServiceFees {
     pub service_fee_msg: ServiceMsg,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct GetServiceFeeSchedules {
+    pub service_fee_schedule_msg: ServiceMsg,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct GetServicePolicy {
+    pub service_policy_msg: ServiceMsg,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct GetServiceVersion {
+    pub service_version_msg: ServiceMsg,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
 pub struct GetBoundExecutorFee {
@@ -207,7 +356,14 @@ pub struct RequestResponse {
     pub merkle_root: String,
     pub threshold: u64,
     pub service: String,
+    pub service_version: Option<u64>,
     pub rewards: Vec<Reward>,
+    pub expired_height: Option<u64>,
+    pub min_reputation: Option<u64>,
+    pub asset_info: Option<AssetInfo>,
+    pub callback: Option<Callback>,
+    pub executors: Option<Vec<Binary>>,
+    pub reclaimed_height: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -240,6 +396,8 @@ pub struct UpdateConfigMsg {
     pub new_max_req_threshold: Option<u64>,
     pub new_trust_period: Option<u64>,
     pub new_slashing_amount: Option<u64>,
+    pub new_slashing_bounty: Option<u64>,
     pub new_denom: Option<String>,
     pub new_pending_period: Option<u64>,
+    pub new_request_timeout: Option<u64>,
 }
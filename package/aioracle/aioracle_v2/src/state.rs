@@ -1,5 +1,5 @@
-use aioracle_base::{Executor, Reward};
-use cosmwasm_std::{Binary, Coin, HumanAddr};
+use aioracle_base::{AssetInfo, Executor, Reward, VersionStatus};
+use cosmwasm_std::{Binary, Coin, HumanAddr, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -16,8 +16,12 @@ pub struct Config {
     pub max_req_threshold: u64,
     pub trusting_period: u64,
     pub slashing_amount: u64,
+    /// permille of the slashed amount paid to the reporter of the evidence
+    pub slashing_bounty: Option<u64>,
     pub denom: String,
     pub pending_period: u64,
+    /// number of blocks a request can wait for its merkle root before the requester can reclaim the fees
+    pub request_timeout: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -28,6 +32,15 @@ pub struct Contracts {
     pub oscript: HumanAddr,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ServiceVersion {
+    pub version: u64,
+    pub contracts: Contracts,
+    pub status: VersionStatus,
+    pub created_height: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Request {
     /// Owner If None set, contract is frozen.
@@ -38,8 +51,43 @@ pub struct Request {
     pub merkle_root: String,
     pub threshold: u64,
     pub service: String,
+    /// version of the service contracts pinned by the request. None if the provider bridge does not version its services
+    pub service_version: Option<u64>,
     pub input: Option<String>,
     pub rewards: Vec<Reward>,
+    pub expired_height: Option<u64>,
+    /// executors with a lower reputation (permille) cannot participate in the request
+    pub min_reputation: Option<u64>,
+    /// cw20 token the request has been paid with. None if paid with native coins
+    pub asset_info: Option<AssetInfo>,
+    pub callback: Option<Callback>,
+    /// executors allowed by the service policy when the request was created. All active executors if None
+    pub executors: Option<Vec<Binary>>,
+    /// block the expired request has been reclaimed at. None if not reclaimed
+    pub reclaimed_height: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Callback {
+    /// budget paid up front, in the denom of the preference executor fee. It goes to whoever relays the callback once the request is finalized
+    pub fee: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExecutorStats {
+    pub stages_included: u64,
+    pub stages_missed: u64,
+    pub evidences: u64,
+    /// stages the executor was needed for, in permille of a stage. Each stage it was eligible for adds the
+    /// threshold of the request shared among the eligible executors
+    pub stages_expected: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RequestVolume {
+    /// period of the fee schedule the requests have been counted in
+    pub period: u64,
+    pub requests: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -64,6 +112,11 @@ pub const CLAIM_PREFIX: &str = "claim";
 // key: executor in base64 string + stage in string
 pub const CLAIM: Map<&[u8], bool> = Map::new(CLAIM_PREFIX);
 
+pub const STAGE_CLAIMS_PREFIX: &str = "stage_claims";
+
+// key: stage. value: number of executors that have claimed the rewards of the stage
+pub const STAGE_CLAIMS: Map<U64Key, u64> = Map::new(STAGE_CLAIMS_PREFIX);
+
 pub const EVIDENCE_PREFIX: &str = "evidence";
 
 // key: executor in base64 string + stage in string
@@ -72,16 +125,45 @@ pub const EVIDENCES: Map<&[u8], bool> = Map::new(EVIDENCE_PREFIX);
 // pub const EXECUTORS_PREFIX: &str = "executors";
 // pub const EXECUTORS: Map<&[u8], bool> = Map::new(EXECUTORS_PREFIX);
 
+pub const REPORTS_PREFIX: &str = "reports";
+
+// key: stage + executor pubkey. value: merkle root signed by the executor
+pub const REPORTS: Map<(U64Key, &[u8]), String> = Map::new(REPORTS_PREFIX);
+
+pub const VERIFIED_RESULTS_PREFIX: &str = "verified_results";
+
+// key: stage + sha256 of the leaf. value: leaf verified against the merkle root of the stage
+pub const VERIFIED_RESULTS: Map<(U64Key, &[u8]), Binary> = Map::new(VERIFIED_RESULTS_PREFIX);
+
+pub const PENDING_CALLBACKS_PREFIX: &str = "pending_callbacks";
+
+// key: stage. value: true while the callback of the finalized request has not been relayed
+pub const PENDING_CALLBACKS: Map<U64Key, bool> = Map::new(PENDING_CALLBACKS_PREFIX);
+
 pub const EXECUTORS_INDEX_PREFIX: &str = "executors_index";
 pub const EXECUTORS_INDEX: Item<u64> = Item::new(EXECUTORS_INDEX_PREFIX);
 
 pub const CONTRACT_FEES_INDEX: &str = "contract_fees_index";
 pub const CONTRACT_FEES: Item<Coin> = Item::new(CONTRACT_FEES_INDEX);
 
+pub const EXECUTOR_STATS_PREFIX: &str = "executor_stats";
+pub const EXECUTOR_STATS: Map<&[u8], ExecutorStats> = Map::new(EXECUTOR_STATS_PREFIX);
+
 pub const EXECUTORS_TRUSTING_POOL_PREFIX: &str = "executors_trusting_pool_v2";
 pub const EXECUTORS_TRUSTING_POOL: Map<&[u8], TrustingPool> =
     Map::new(EXECUTORS_TRUSTING_POOL_PREFIX);
 
+pub const EXECUTORS_ASSET_TRUSTING_POOL_PREFIX: &str = "executors_asset_trusting_pool";
+
+// key: executor pubkey + cw20 contract address
+pub const EXECUTORS_ASSET_TRUSTING_POOL: Map<(&[u8], &[u8]), TrustingPool> =
+    Map::new(EXECUTORS_ASSET_TRUSTING_POOL_PREFIX);
+
+pub const REQUEST_VOLUMES_PREFIX: &str = "request_volumes";
+
+// key: provider address + requester address. value: requests in the current period of the provider fee schedule
+pub const REQUEST_VOLUMES: Map<(&[u8], &[u8]), RequestVolume> = Map::new(REQUEST_VOLUMES_PREFIX);
+
 // indexes requests
 // for structures
 pub struct RequestIndexes<'a> {
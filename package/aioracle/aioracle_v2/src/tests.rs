@@ -1,20 +1,27 @@
-use crate::contract::{init, query, verify_request_fees};
+use crate::contract::{
+    init, pubkey_to_address, query, report_data_sign_message, report_sign_message,
+    verify_request_fees,
+};
 use crate::error::ContractError;
 use crate::msg::{
-    HandleMsg, InitMsg, QueryMsg, RequestResponse, StageInfo, TrustingPoolResponse, UpdateConfigMsg,
+    BoundExecutorFeeMsg, Cw20HookMsg, Evidence, ExecutorStatsResponse, HandleMsg, InitMsg,
+    IsClaimedResponse, LatestStageResponse, OracleCallbackMsg, QueryMsg, Report, RequestResponse,
+    RewardClaim, StageInfo, TrustingPoolResponse, UpdateConfigMsg, VerifiedResultResponse,
 };
-use crate::state::{Config, Request, TrustingPool};
+use crate::state::{Callback, Config, Request, TrustingPool};
 
-use aioracle_base::{Executor, Reward};
+use aioracle_base::{AssetInfo, Executor, FeeSchedule, FeeTier, Reward, ServiceMsg, ServicePolicy};
 use bech32::{self, FromBase32, ToBase32, Variant};
 use cosmwasm_std::testing::{
     mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
 };
 use cosmwasm_std::{
-    coin, coins, from_binary, from_slice, Binary, BlockInfo, Coin, ContractInfo, Env, HumanAddr,
-    OwnedDeps, StdError, Uint128,
+    coin, coins, from_binary, from_slice, to_binary, Binary, BlockInfo, Coin, ContractInfo, Deps,
+    DepsMut, Empty, Env, HandleResponse, HumanAddr, InitResponse, MessageInfo, OwnedDeps, StdError,
+    StdResult, Uint128,
 };
 use cw_multi_test::{next_block, App, Contract, ContractWrapper, SimpleBank};
+use k256::ecdsa::{signature::Signer, Signature, SigningKey};
 use provider_bridge::state::Contracts;
 use ripemd::{Digest as RipeDigest, Ripemd160};
 use serde::Deserialize;
@@ -98,6 +105,15 @@ pub fn contract_provider() -> Box<dyn Contract> {
     Box::new(contract)
 }
 
+pub fn contract_ow20() -> Box<dyn Contract> {
+    let contract = ContractWrapper::new(
+        ow20::contract::handle,
+        ow20::contract::init,
+        ow20::contract::query,
+    );
+    Box::new(contract)
+}
+
 pub fn contract_service_fees() -> Box<dyn Contract> {
     let contract = ContractWrapper::new(
         aioracle_service_fees::contract::handle,
@@ -109,7 +125,10 @@ pub fn contract_service_fees() -> Box<dyn Contract> {
 
 fn mock_app() -> App {
     let env = mock_env();
-    let api = Box::new(MockApi::default());
+    // executor addresses are bech32 addresses, which are longer than the default canonical length
+    let mut api = MockApi::default();
+    api.canonical_length = 54;
+    let api = Box::new(api);
     let bank = SimpleBank {};
 
     App::new(api, env.block, bank, || Box::new(MockStorage::new()))
@@ -262,6 +281,9 @@ fn proper_instantiation() {
             input: None,
             service: "price".to_string(),
             preference_executor_fee: coin(1, "orai"),
+            min_reputation: None,
+            callback: None,
+            service_version: None,
         },
         &coins(6u128, "orai"), // plus 1 for contract fee
     )
@@ -299,8 +321,10 @@ fn update_config() {
             new_max_req_threshold: None,
             new_trust_period: None,
             new_slashing_amount: None,
+            new_slashing_bounty: None,
             new_denom: None,
             new_pending_period: None,
+            new_request_timeout: None,
         },
     };
 
@@ -336,8 +360,10 @@ fn update_config() {
             new_max_req_threshold: None,
             new_trust_period: None,
             new_slashing_amount: None,
+            new_slashing_bounty: None,
             new_denom: None,
             new_pending_period: None,
+            new_request_timeout: None,
         },
     };
 
@@ -362,8 +388,10 @@ fn update_config() {
             new_max_req_threshold: None,
             new_trust_period: None,
             new_slashing_amount: None,
+            new_slashing_bounty: None,
             new_denom: None,
             new_pending_period: None,
+            new_request_timeout: None,
         },
     };
     let res = app
@@ -398,6 +426,9 @@ fn test_request() {
             input: None,
             service: "price".to_string(),
             preference_executor_fee: coin(1, "orai"),
+            min_reputation: None,
+            callback: None,
+            service_version: None,
         },
         &coins(6u128, "orai"),
     )
@@ -411,6 +442,9 @@ fn test_request() {
             input: None,
             service: "price".to_string(),
             preference_executor_fee: coin(1, "orai"),
+            min_reputation: None,
+            callback: None,
+            service_version: None,
         },
         &coins(6u128, "orai"), // plus 1 for contract fee
     )
@@ -434,6 +468,9 @@ fn test_request() {
                 input: None,
                 service: "price".to_string(),
                 preference_executor_fee: coin(1, "orai"),
+                min_reputation: None,
+                callback: None,
+                service_version: None,
             },
             &coins(20u128, "orai"),
         )
@@ -477,6 +514,9 @@ fn register_merkle_root() {
             input: None,
             service: "price".to_string(),
             preference_executor_fee: coin(1, "orai"),
+            min_reputation: None,
+            callback: None,
+            service_version: None,
         },
         &coins(6u128, "orai"), // plus 1 for contract fee
     )
@@ -538,6 +578,9 @@ fn verify_data() {
             input: None,
             service: "price".to_string(),
             preference_executor_fee: coin(1, "orai"),
+            min_reputation: None,
+            callback: None,
+            service_version: None,
         },
         &coins(6u128, "orai"),
     )
@@ -575,6 +618,126 @@ fn verify_data() {
     assert_eq!(verified, true);
 }
 
+#[test]
+fn test_submit_result() {
+    let test_data: Encoded = from_slice(TEST_DATA_1).unwrap();
+    // merkle tree of two leaves, each one being the proof of the other
+    let other_data = Binary::from(b"other result".to_vec());
+    let leaf_hash = sha2::Sha256::digest(test_data.data.as_slice());
+    let other_hash = sha2::Sha256::digest(other_data.as_slice());
+    let mut leaves = [leaf_hash.as_slice(), other_hash.as_slice()];
+    leaves.sort_unstable();
+    let root = hex::encode(sha2::Sha256::digest(&leaves.concat()));
+
+    let mut app = mock_app();
+    let (_, _, aioracle_addr) = setup_test_case(&mut app);
+
+    app.execute_contract(
+        &HumanAddr::from("client"),
+        &aioracle_addr,
+        &HandleMsg::Request {
+            threshold: 1,
+            input: None,
+            service: "price".to_string(),
+            preference_executor_fee: coin(1, "orai"),
+            min_reputation: None,
+            callback: None,
+            service_version: None,
+        },
+        &coins(6u128, "orai"),
+    )
+    .unwrap();
+
+    let msg = HandleMsg::SubmitResult {
+        stage: 1,
+        data: test_data.data.clone(),
+        proof: Some(vec![hex::encode(other_hash)]),
+    };
+
+    // cannot submit a result before the merkle root is registered
+    assert_eq!(
+        app.execute_contract(HumanAddr::from(CLIENT), aioracle_addr.clone(), &msg, &[])
+            .is_err(),
+        true
+    );
+
+    app.execute_contract(
+        HumanAddr::from(AIORACLE_OWNER),
+        aioracle_addr.clone(),
+        &HandleMsg::RegisterMerkleRoot {
+            stage: 1,
+            merkle_root: root.clone(),
+            executors: vec![],
+        },
+        &[],
+    )
+    .unwrap();
+
+    // wrong proof case
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from(CLIENT),
+            aioracle_addr.clone(),
+            &HandleMsg::SubmitResult {
+                stage: 1,
+                data: test_data.data.clone(),
+                proof: None,
+            },
+            &[],
+        )
+        .unwrap_err(),
+        ContractError::VerificationFailed {}.to_string()
+    );
+
+    app.execute_contract(HumanAddr::from(CLIENT), aioracle_addr.clone(), &msg, &[])
+        .unwrap();
+    let result: VerifiedResultResponse = app
+        .wrap()
+        .query_wasm_smart(
+            aioracle_addr.clone(),
+            &QueryMsg::GetVerifiedResult { stage: 1 },
+        )
+        .unwrap();
+    assert_eq!(result.results, vec![test_data.data.clone()]);
+    assert_eq!(result.merkle_root, root);
+
+    // cannot submit the same leaf twice
+    assert_eq!(
+        app.execute_contract(HumanAddr::from(CLIENT), aioracle_addr.clone(), &msg, &[])
+            .unwrap_err(),
+        ContractError::AlreadySubmitted {}.to_string()
+    );
+
+    // another leaf of the stage is stored next to the first one
+    app.execute_contract(
+        HumanAddr::from(CLIENT),
+        aioracle_addr.clone(),
+        &HandleMsg::SubmitResult {
+            stage: 1,
+            data: other_data.clone(),
+            proof: Some(vec![hex::encode(leaf_hash)]),
+        },
+        &[],
+    )
+    .unwrap();
+    let result: VerifiedResultResponse = app
+        .wrap()
+        .query_wasm_smart(
+            aioracle_addr.clone(),
+            &QueryMsg::GetVerifiedResult { stage: 1 },
+        )
+        .unwrap();
+    let mut expected = vec![(leaf_hash, test_data.data), (other_hash, other_data)];
+    expected.sort_by_key(|(hash, _)| *hash);
+    assert_eq!(
+        result.results,
+        expected
+            .into_iter()
+            .map(|(_, data)| data)
+            .collect::<Vec<Binary>>()
+    );
+}
+
 #[test]
 fn test_checkpoint() {
     // Run test 2
@@ -594,6 +757,9 @@ fn test_checkpoint() {
                 input: None,
                 service: "price".to_string(),
                 preference_executor_fee: coin(1, "orai"),
+                min_reputation: None,
+                callback: None,
+                service_version: None,
             },
             &coins(6u128, "orai"), // plus 1 for contract fee
         )
@@ -726,6 +892,9 @@ fn test_checkpoint_no_new_request() {
             input: None,
             service: "price".to_string(),
             preference_executor_fee: coin(1, "orai"),
+            min_reputation: None,
+            callback: None,
+            service_version: None,
         },
         &coins(6u128, "orai"), // plus 1 for contract fee
     )
@@ -1046,6 +1215,9 @@ fn test_query_requests_indexes() {
                 input: None,
                 service,
                 preference_executor_fee: coin(1, "orai"),
+                min_reputation: None,
+                callback: None,
+                service_version: None,
             },
             &coins(5u128, "orai"),
         )
@@ -1177,8 +1349,10 @@ fn test_query_executor() {
             new_max_req_threshold: None,
             new_trust_period: None,
             new_slashing_amount: None,
+            new_slashing_bounty: None,
             new_denom: None,
             new_pending_period: None,
+            new_request_timeout: None,
         },
     };
 
@@ -1219,8 +1393,10 @@ fn test_executor_size() {
                 new_max_req_threshold: None,
                 new_trust_period: None,
                 new_slashing_amount: None,
+                new_slashing_bounty: None,
                 new_denom: None,
                 new_pending_period: None,
+                new_request_timeout: None,
             },
         },
         &[],
@@ -1253,6 +1429,9 @@ fn test_handle_withdraw_pool() {
             input: None,
             service: "price".to_string(),
             preference_executor_fee: coin(1, "orai"),
+            min_reputation: None,
+            callback: None,
+            service_version: None,
         },
         &coins(6u128, "orai"),
     )
@@ -1281,6 +1460,7 @@ fn test_handle_withdraw_pool() {
             &HandleMsg::PrepareWithdrawPool {
                 pubkey: Binary::from_base64("AipQCudhlHpWnHjSgVKZ+SoSicvjH7Mp5gCFyDdlnQtn")
                     .unwrap(),
+                asset_info: None,
             },
             &[],
         )
@@ -1294,6 +1474,7 @@ fn test_handle_withdraw_pool() {
         aioracle_addr.clone(),
         &HandleMsg::PrepareWithdrawPool {
             pubkey: pubkey.clone(),
+            asset_info: None,
         },
         &[],
     )
@@ -1307,6 +1488,7 @@ fn test_handle_withdraw_pool() {
             &HandleMsg::PrepareWithdrawPool {
                 pubkey: Binary::from_base64("AipQCudhlHpWnHjSgVKZ+SoSicvjH7Mp5gCFyDdlnQtn")
                     .unwrap(),
+                asset_info: None,
             },
             &[],
         )
@@ -1324,6 +1506,9 @@ fn test_handle_withdraw_pool() {
             input: None,
             service: "price".to_string(),
             preference_executor_fee: coin(1, "orai"),
+            min_reputation: None,
+            callback: None,
+            service_version: None,
         },
         &coins(6u128, "orai"),
     )
@@ -1354,6 +1539,7 @@ fn test_handle_withdraw_pool() {
             aioracle_addr.clone(),
             &QueryMsg::GetTrustingPool {
                 pubkey: pubkey.clone(),
+                asset_info: None,
             },
         )
         .unwrap();
@@ -1372,6 +1558,7 @@ fn test_handle_withdraw_pool() {
         aioracle_addr.clone(),
         &HandleMsg::PrepareWithdrawPool {
             pubkey: pubkey.clone(),
+            asset_info: None,
         },
         &[],
     )
@@ -1386,6 +1573,7 @@ fn test_handle_withdraw_pool() {
             aioracle_addr.clone(),
             &QueryMsg::GetTrustingPool {
                 pubkey: pubkey.clone(),
+                asset_info: None,
             },
         )
         .unwrap();
@@ -1419,6 +1607,9 @@ fn test_increment_executor_when_register_merkle() {
             input: None,
             service: "price".to_string(),
             preference_executor_fee: coin(1, "orai"),
+            min_reputation: None,
+            callback: None,
+            service_version: None,
         },
         &coins(6u128, "orai"),
     )
@@ -1462,6 +1653,9 @@ fn test_increment_executor_when_register_merkle() {
             input: None,
             service: "price".to_string(),
             preference_executor_fee: coin(1, "orai"),
+            min_reputation: None,
+            callback: None,
+            service_version: None,
         },
         &coins(6u128, "orai"),
     )
@@ -1487,6 +1681,7 @@ fn test_increment_executor_when_register_merkle() {
             aioracle_addr.clone(),
             &QueryMsg::GetTrustingPool {
                 pubkey: pubkey.clone(),
+                asset_info: None,
             },
         )
         .unwrap();
@@ -1525,6 +1720,9 @@ fn test_increment_executor_when_register_merkle() {
                 input: None,
                 service: "price".to_string(),
                 preference_executor_fee: coin(19, "orai"),
+                min_reputation: None,
+                callback: None,
+                service_version: None,
             },
             &coins(26u128, "orai"),
         )
@@ -1541,6 +1739,9 @@ fn test_increment_executor_when_register_merkle() {
             input: None,
             service: "price".to_string(),
             preference_executor_fee: coin(20, "orai"),
+            min_reputation: None,
+            callback: None,
+            service_version: None,
         },
         &coins(26u128, "orai"),
     )
@@ -1566,6 +1767,7 @@ fn test_increment_executor_when_register_merkle() {
             aioracle_addr.clone(),
             &QueryMsg::GetTrustingPool {
                 pubkey: pubkey.clone(),
+                asset_info: None,
             },
         )
         .unwrap();
@@ -1686,7 +1888,7 @@ fn test_executor_join() {
         .execute_contract(info.sender.clone(), aioracle_addr.clone(), &msg, &[])
         .unwrap_err();
     assert_eq!(res, ContractError::Unauthorized {}.to_string());
-    
+
     // Join a new executor
     let info = mock_info("orai12lj8y27tmsag6hhjsucffvqrldfxjpja4sx84u", &[]);
     let res = app
@@ -1775,3 +1977,2322 @@ fn test_executor_leave() {
         .unwrap_err();
     assert_eq!(res, ContractError::ExecutorAlreadyLeft {}.to_string());
 }
+
+fn sign_report(
+    signing_key: &SigningKey,
+    aioracle_addr: &HumanAddr,
+    stage: u64,
+    merkle_root: &str,
+) -> Binary {
+    let msg = report_sign_message(
+        aioracle_addr,
+        &mock_env().block.chain_id,
+        stage,
+        merkle_root,
+    );
+    let signature: Signature = signing_key.sign(msg.as_bytes());
+    Binary::from(signature.as_ref())
+}
+
+fn sign_report_data(
+    signing_key: &SigningKey,
+    aioracle_addr: &HumanAddr,
+    stage: u64,
+    report: &Binary,
+) -> Binary {
+    let msg = report_data_sign_message(aioracle_addr, &mock_env().block.chain_id, stage, report);
+    let signature: Signature = signing_key.sign(msg.as_bytes());
+    Binary::from(signature.as_ref())
+}
+
+#[test]
+fn test_submit_report() {
+    let test_data: Encoded = from_slice(TEST_DATA_1).unwrap();
+
+    let mut app = mock_app();
+    let (_, _, aioracle_addr) = setup_test_case(&mut app);
+
+    // register executors that we hold the private keys of
+    let signing_keys: Vec<SigningKey> = (1u8..4)
+        .map(|i| SigningKey::from_bytes(&[i; 32]).unwrap())
+        .collect();
+    let pubkeys: Vec<Binary> = signing_keys
+        .iter()
+        .map(|key| Binary::from(key.verify_key().to_bytes().as_slice()))
+        .collect();
+    app.execute_contract(
+        HumanAddr::from(AIORACLE_OWNER),
+        aioracle_addr.clone(),
+        &HandleMsg::UpdateConfig {
+            update_config_msg: UpdateConfigMsg {
+                new_owner: None,
+                new_service_addr: None,
+                new_contract_fee: None,
+                new_executors: Some(pubkeys.clone()),
+                old_executors: None,
+                new_checkpoint: None,
+                new_checkpoint_threshold: None,
+                new_max_req_threshold: None,
+                new_trust_period: None,
+                new_slashing_amount: None,
+                new_slashing_bounty: None,
+                new_denom: None,
+                new_pending_period: None,
+                new_request_timeout: None,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    // create a new request that needs 2 signatures
+    app.execute_contract(
+        &HumanAddr::from("client"),
+        &aioracle_addr,
+        &HandleMsg::Request {
+            threshold: 2,
+            input: None,
+            service: "price".to_string(),
+            preference_executor_fee: coin(1, "orai"),
+            min_reputation: None,
+            callback: None,
+            service_version: None,
+        },
+        &coins(11u128, "orai"),
+    )
+    .unwrap();
+
+    // invalid signature case
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from(CLIENT),
+            aioracle_addr.clone(),
+            &HandleMsg::SubmitReport {
+                stage: 1,
+                merkle_root: test_data.root.clone(),
+                executor: pubkeys[0].clone(),
+                signature: sign_report(&signing_keys[1], &aioracle_addr, 1, &test_data.root),
+            },
+            &[],
+        )
+        .unwrap_err(),
+        ContractError::InvalidSignature {}.to_string()
+    );
+
+    // a signature made for another deployment cannot be replayed
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from(CLIENT),
+            aioracle_addr.clone(),
+            &HandleMsg::SubmitReport {
+                stage: 1,
+                merkle_root: test_data.root.clone(),
+                executor: pubkeys[0].clone(),
+                signature: sign_report(
+                    &signing_keys[0],
+                    &HumanAddr::from("other_aioracle"),
+                    1,
+                    &test_data.root
+                ),
+            },
+            &[],
+        )
+        .unwrap_err(),
+        ContractError::InvalidSignature {}.to_string()
+    );
+
+    // unknown executor case
+    let unknown_key = SigningKey::from_bytes(&[9u8; 32]).unwrap();
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from(CLIENT),
+            aioracle_addr.clone(),
+            &HandleMsg::SubmitReport {
+                stage: 1,
+                merkle_root: test_data.root.clone(),
+                executor: Binary::from(unknown_key.verify_key().to_bytes().as_slice()),
+                signature: sign_report(&unknown_key, &aioracle_addr, 1, &test_data.root),
+            },
+            &[],
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {}.to_string()
+    );
+
+    // first signature, anyone can relay it
+    let msg = HandleMsg::SubmitReport {
+        stage: 1,
+        merkle_root: test_data.root.clone(),
+        executor: pubkeys[0].clone(),
+        signature: sign_report(&signing_keys[0], &aioracle_addr, 1, &test_data.root),
+    };
+    app.execute_contract(HumanAddr::from(CLIENT), aioracle_addr.clone(), &msg, &[])
+        .unwrap();
+
+    // cannot submit twice
+    assert_eq!(
+        app.execute_contract(HumanAddr::from(CLIENT), aioracle_addr.clone(), &msg, &[])
+            .unwrap_err(),
+        ContractError::AlreadySubmitted {}.to_string()
+    );
+
+    // a second executor disagreeing does not finalize the stage
+    let other_root = "4a2e27a2befb41a0655b8fe98d9c1a9f18ece280dc78b442734ead617e6bf3fc";
+    app.execute_contract(
+        HumanAddr::from(CLIENT),
+        aioracle_addr.clone(),
+        &HandleMsg::SubmitReport {
+            stage: 1,
+            merkle_root: other_root.to_string(),
+            executor: pubkeys[1].clone(),
+            signature: sign_report(&signing_keys[1], &aioracle_addr, 1, other_root),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let request: Request = app
+        .wrap()
+        .query_wasm_smart(aioracle_addr.clone(), &QueryMsg::Request { stage: 1u64 })
+        .unwrap();
+    assert_eq!(request.merkle_root, "");
+
+    // threshold reached, the merkle root is registered without the owner
+    app.execute_contract(
+        HumanAddr::from(CLIENT),
+        aioracle_addr.clone(),
+        &HandleMsg::SubmitReport {
+            stage: 1,
+            merkle_root: test_data.root.clone(),
+            executor: pubkeys[2].clone(),
+            signature: sign_report(&signing_keys[2], &aioracle_addr, 1, &test_data.root),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let request: Request = app
+        .wrap()
+        .query_wasm_smart(aioracle_addr.clone(), &QueryMsg::Request { stage: 1u64 })
+        .unwrap();
+    assert_eq!(request.merkle_root, test_data.root);
+
+    // only the agreeing executors join the trusting pool
+    let trusting_pool: Result<TrustingPoolResponse, StdError> = app.wrap().query_wasm_smart(
+        aioracle_addr.clone(),
+        &QueryMsg::GetTrustingPool {
+            pubkey: pubkeys[2].clone(),
+            asset_info: None,
+        },
+    );
+    assert_eq!(trusting_pool.is_ok(), true);
+    let trusting_pool: Result<TrustingPoolResponse, StdError> = app.wrap().query_wasm_smart(
+        aioracle_addr.clone(),
+        &QueryMsg::GetTrustingPool {
+            pubkey: pubkeys[1].clone(),
+            asset_info: None,
+        },
+    );
+    assert_eq!(trusting_pool.is_err(), true);
+
+    // stage already finished
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from(CLIENT),
+            aioracle_addr.clone(),
+            &HandleMsg::SubmitReport {
+                stage: 1,
+                merkle_root: other_root.to_string(),
+                executor: pubkeys[0].clone(),
+                signature: sign_report(&signing_keys[0], &aioracle_addr, 1, other_root),
+            },
+            &[],
+        )
+        .unwrap_err(),
+        ContractError::AlreadyFinished {}.to_string()
+    );
+}
+
+#[test]
+fn test_executor_stats() {
+    let test_data: Encoded = from_slice(TEST_DATA_1).unwrap();
+
+    let mut app = mock_app();
+    let (_, provider_addr, aioracle_addr) = setup_test_case(&mut app);
+
+    let signing_keys: Vec<SigningKey> = (1u8..3)
+        .map(|i| SigningKey::from_bytes(&[i; 32]).unwrap())
+        .collect();
+    let pubkeys: Vec<Binary> = signing_keys
+        .iter()
+        .map(|key| Binary::from(key.verify_key().to_bytes().as_slice()))
+        .collect();
+    app.execute_contract(
+        HumanAddr::from(AIORACLE_OWNER),
+        aioracle_addr.clone(),
+        &HandleMsg::UpdateConfig {
+            update_config_msg: UpdateConfigMsg {
+                new_owner: None,
+                new_service_addr: None,
+                new_contract_fee: None,
+                new_executors: Some(pubkeys.clone()),
+                old_executors: None,
+                new_checkpoint: None,
+                new_checkpoint_threshold: None,
+                new_max_req_threshold: None,
+                new_trust_period: None,
+                new_slashing_amount: None,
+                new_slashing_bounty: None,
+                new_denom: None,
+                new_pending_period: None,
+                new_request_timeout: None,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    // new executors have no history yet
+    let stats: ExecutorStatsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            aioracle_addr.clone(),
+            &QueryMsg::GetExecutorStats {
+                pubkey: pubkeys[1].clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(stats.reputation, 1000u64);
+
+    // requests of the service are handled by the new executors only
+    app.execute_contract(
+        HumanAddr::from(PROVIDER_OWNER),
+        provider_addr,
+        &provider_bridge::msg::HandleMsg::UpdateServicePolicy {
+            service: "price".to_string(),
+            policy: ServicePolicy {
+                min_threshold: None,
+                executors: Some(pubkeys.clone()),
+                max_fee: None,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    // only the first executor takes part in the first stage
+    for min_reputation in vec![None, Some(500u64)] {
+        app.execute_contract(
+            &HumanAddr::from("client"),
+            &aioracle_addr,
+            &HandleMsg::Request {
+                threshold: 1,
+                input: None,
+                service: "price".to_string(),
+                preference_executor_fee: coin(1, "orai"),
+                min_reputation,
+                callback: None,
+                service_version: None,
+            },
+            &coins(11u128, "orai"),
+        )
+        .unwrap();
+    }
+    app.execute_contract(
+        HumanAddr::from(CLIENT),
+        aioracle_addr.clone(),
+        &HandleMsg::SubmitReport {
+            stage: 1,
+            merkle_root: test_data.root.clone(),
+            executor: pubkeys[0].clone(),
+            signature: sign_report(&signing_keys[0], &aioracle_addr, 1, &test_data.root),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let stats: ExecutorStatsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            aioracle_addr.clone(),
+            &QueryMsg::GetExecutorStats {
+                pubkey: pubkeys[0].clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(stats.stages_included, 1u64);
+    assert_eq!(stats.stages_missed, 0u64);
+    assert_eq!(stats.reputation, 1000u64);
+    // one of the two selected executors was needed
+    assert_eq!(stats.stages_expected, 500u64);
+
+    let stats: ExecutorStatsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            aioracle_addr.clone(),
+            &QueryMsg::GetExecutorStats {
+                pubkey: pubkeys[1].clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(stats.stages_included, 0u64);
+    assert_eq!(stats.stages_missed, 1u64);
+    assert_eq!(stats.reputation, 0u64);
+    assert_eq!(stats.stages_expected, 500u64);
+
+    // executors that were not selected for the request have not missed it
+    let stats: ExecutorStatsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            aioracle_addr.clone(),
+            &QueryMsg::GetExecutorStats {
+                pubkey: Binary::from_base64("A6ENA5I5QhHyy1QIOLkgTcf/x31WE+JLFoISgmcQaI0t")
+                    .unwrap(),
+            },
+        )
+        .unwrap();
+    assert_eq!(stats.stages_missed, 0u64);
+
+    // the second request rejects executors below its minimum reputation
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from(CLIENT),
+            aioracle_addr.clone(),
+            &HandleMsg::SubmitReport {
+                stage: 2,
+                merkle_root: test_data.root.clone(),
+                executor: pubkeys[1].clone(),
+                signature: sign_report(&signing_keys[1], &aioracle_addr, 2, &test_data.root),
+            },
+            &[],
+        )
+        .unwrap_err(),
+        ContractError::InsufficientReputation {
+            reputation: 0u64,
+            min_reputation: 500u64
+        }
+        .to_string()
+    );
+    app.execute_contract(
+        HumanAddr::from(CLIENT),
+        aioracle_addr.clone(),
+        &HandleMsg::SubmitReport {
+            stage: 2,
+            merkle_root: test_data.root.clone(),
+            executor: pubkeys[0].clone(),
+            signature: sign_report(&signing_keys[0], &aioracle_addr, 2, &test_data.root),
+        },
+        &[],
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_executor_stats_unrestricted_request() {
+    let test_data: Encoded = from_slice(TEST_DATA_1).unwrap();
+
+    let mut app = mock_app();
+    let (_, _, aioracle_addr) = setup_test_case(&mut app);
+
+    let signing_keys: Vec<SigningKey> = (1u8..3)
+        .map(|i| SigningKey::from_bytes(&[i; 32]).unwrap())
+        .collect();
+    let pubkeys: Vec<Binary> = signing_keys
+        .iter()
+        .map(|key| Binary::from(key.verify_key().to_bytes().as_slice()))
+        .collect();
+    app.execute_contract(
+        HumanAddr::from(AIORACLE_OWNER),
+        aioracle_addr.clone(),
+        &HandleMsg::UpdateConfig {
+            update_config_msg: UpdateConfigMsg {
+                new_owner: None,
+                new_service_addr: None,
+                new_contract_fee: None,
+                new_executors: Some(pubkeys.clone()),
+                old_executors: None,
+                new_checkpoint: None,
+                new_checkpoint_threshold: None,
+                new_max_req_threshold: None,
+                new_trust_period: None,
+                new_slashing_amount: None,
+                new_slashing_bounty: None,
+                new_denom: None,
+                new_pending_period: None,
+                new_request_timeout: None,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    // the request is open to every active executor, only the first new executor reports
+    app.execute_contract(
+        &HumanAddr::from("client"),
+        &aioracle_addr,
+        &HandleMsg::Request {
+            threshold: 1,
+            input: None,
+            service: "price".to_string(),
+            preference_executor_fee: coin(1, "orai"),
+            min_reputation: None,
+            callback: None,
+            service_version: None,
+        },
+        &coins(11u128, "orai"),
+    )
+    .unwrap();
+    app.execute_contract(
+        HumanAddr::from(CLIENT),
+        aioracle_addr.clone(),
+        &HandleMsg::SubmitReport {
+            stage: 1,
+            merkle_root: test_data.root.clone(),
+            executor: pubkeys[0].clone(),
+            signature: sign_report(&signing_keys[0], &aioracle_addr, 1, &test_data.root),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let stats: ExecutorStatsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            aioracle_addr.clone(),
+            &QueryMsg::GetExecutorStats {
+                pubkey: pubkeys[0].clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(stats.stages_included, 1u64);
+    assert_eq!(stats.reputation, 1000u64);
+
+    // the active executor that never reports loses its reputation
+    let stats: ExecutorStatsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            aioracle_addr.clone(),
+            &QueryMsg::GetExecutorStats {
+                pubkey: pubkeys[1].clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(stats.stages_included, 0u64);
+    assert_eq!(stats.stages_missed, 1u64);
+    assert!(stats.stages_expected > 0u64);
+    assert_eq!(stats.reputation, 0u64);
+}
+
+#[test]
+fn test_reclaim_expired_request() {
+    let test_data: Encoded = from_slice(TEST_DATA_1).unwrap();
+
+    let mut app = mock_app();
+    let (_, _, aioracle_addr) = setup_test_case(&mut app);
+
+    // create two requests, only the first one will expire without a merkle root
+    for _ in 0..2 {
+        app.execute_contract(
+            &HumanAddr::from(CLIENT),
+            &aioracle_addr,
+            &HandleMsg::Request {
+                threshold: 1,
+                input: None,
+                service: "price".to_string(),
+                preference_executor_fee: coin(1, "orai"),
+                min_reputation: None,
+                callback: None,
+                service_version: None,
+            },
+            &coins(6u128, "orai"),
+        )
+        .unwrap();
+    }
+    app.execute_contract(
+        HumanAddr::from(AIORACLE_OWNER),
+        aioracle_addr.clone(),
+        &HandleMsg::RegisterMerkleRoot {
+            stage: 2,
+            merkle_root: test_data.root.clone(),
+            executors: vec![],
+        },
+        &[],
+    )
+    .unwrap();
+
+    let request: Request = app
+        .wrap()
+        .query_wasm_smart(aioracle_addr.clone(), &QueryMsg::Request { stage: 1u64 })
+        .unwrap();
+    let expired_height = request.expired_height.unwrap();
+
+    // cannot reclaim before the deadline
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from(CLIENT),
+            aioracle_addr.clone(),
+            &HandleMsg::ReclaimExpiredRequest { stage: 1 },
+            &[],
+        )
+        .unwrap_err(),
+        ContractError::RequestNotExpired {
+            block: expired_height
+        }
+        .to_string()
+    );
+
+    let expired_requests: Vec<RequestResponse> = app
+        .wrap()
+        .query_wasm_smart(
+            aioracle_addr.clone(),
+            &QueryMsg::GetExpiredRequests {
+                offset: None,
+                limit: None,
+                order: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(expired_requests.len(), 0);
+
+    app.update_block(|block| block.height = expired_height);
+
+    // only the unfinished request can be reclaimed
+    let expired_requests: Vec<RequestResponse> = app
+        .wrap()
+        .query_wasm_smart(
+            aioracle_addr.clone(),
+            &QueryMsg::GetExpiredRequests {
+                offset: None,
+                limit: None,
+                order: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(expired_requests.len(), 1);
+    assert_eq!(expired_requests[0].stage, 1);
+
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from(CLIENT),
+            aioracle_addr.clone(),
+            &HandleMsg::ReclaimExpiredRequest { stage: 2 },
+            &[],
+        )
+        .unwrap_err(),
+        ContractError::AlreadyFinished {}.to_string()
+    );
+
+    // an expired request cannot be finalized anymore
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from(AIORACLE_OWNER),
+            aioracle_addr.clone(),
+            &HandleMsg::RegisterMerkleRoot {
+                stage: 1,
+                merkle_root: test_data.root.clone(),
+                executors: vec![],
+            },
+            &[],
+        )
+        .unwrap_err(),
+        ContractError::RequestExpired {
+            block: expired_height
+        }
+        .to_string()
+    );
+
+    // anyone can trigger the refund, funds go back to the requester
+    let balance_before = app.wrap().query_balance(CLIENT, "orai").unwrap();
+    app.execute_contract(
+        HumanAddr::from(AIORACLE_OWNER),
+        aioracle_addr.clone(),
+        &HandleMsg::ReclaimExpiredRequest { stage: 1 },
+        &[],
+    )
+    .unwrap();
+    let balance_after = app.wrap().query_balance(CLIENT, "orai").unwrap();
+    // service fees (1 + 2 + 1) + preference executor fee (1), contract fee is kept
+    assert_eq!(
+        balance_after.amount,
+        Uint128::from(balance_before.amount.u128() + 5)
+    );
+
+    // the request is kept & marked as reclaimed
+    let request: Request = app
+        .wrap()
+        .query_wasm_smart(aioracle_addr.clone(), &QueryMsg::Request { stage: 1u64 })
+        .unwrap();
+    assert_eq!(request.reclaimed_height, Some(expired_height));
+    let expired_requests: Vec<RequestResponse> = app
+        .wrap()
+        .query_wasm_smart(
+            aioracle_addr.clone(),
+            &QueryMsg::GetExpiredRequests {
+                offset: None,
+                limit: None,
+                order: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(expired_requests.len(), 0);
+
+    // cannot reclaim twice
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from(CLIENT),
+            aioracle_addr.clone(),
+            &HandleMsg::ReclaimExpiredRequest { stage: 1 },
+            &[],
+        )
+        .unwrap_err(),
+        ContractError::RequestReclaimed {}.to_string()
+    );
+
+    // nor finalize a reclaimed request
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from(AIORACLE_OWNER),
+            aioracle_addr.clone(),
+            &HandleMsg::RegisterMerkleRoot {
+                stage: 1,
+                merkle_root: test_data.root.clone(),
+                executors: vec![],
+            },
+            &[],
+        )
+        .unwrap_err(),
+        ContractError::RequestReclaimed {}.to_string()
+    );
+}
+
+// registers a new executor with an executor fee of 1000 orai, then fills its trusting pool through a finished request
+fn setup_funded_executor(
+    app: &mut App,
+    service_fees_addr: &HumanAddr,
+    aioracle_addr: &HumanAddr,
+    signing_key: &SigningKey,
+    merkle_root: &str,
+) -> Binary {
+    let pubkey = Binary::from(signing_key.verify_key().to_bytes().as_slice());
+    app.execute_contract(
+        HumanAddr::from(AIORACLE_OWNER),
+        aioracle_addr.clone(),
+        &HandleMsg::UpdateConfig {
+            update_config_msg: UpdateConfigMsg {
+                new_owner: None,
+                new_service_addr: None,
+                new_contract_fee: None,
+                new_executors: Some(vec![pubkey.clone()]),
+                old_executors: None,
+                new_checkpoint: None,
+                new_checkpoint_threshold: None,
+                new_max_req_threshold: None,
+                new_trust_period: None,
+                new_slashing_amount: None,
+                new_slashing_bounty: None,
+                new_denom: None,
+                new_pending_period: None,
+                new_request_timeout: None,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        pubkey_to_address(&pubkey).unwrap(),
+        service_fees_addr.clone(),
+        &aioracle_service_fees::msg::HandleMsg::UpdateServiceFees {
+            fees: coin(1000u128, "orai"),
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        &HumanAddr::from(CLIENT),
+        aioracle_addr,
+        &HandleMsg::Request {
+            threshold: 1,
+            input: None,
+            service: "price".to_string(),
+            preference_executor_fee: coin(1000, "orai"),
+            min_reputation: None,
+            callback: None,
+            service_version: None,
+        },
+        &coins(1005u128, "orai"),
+    )
+    .unwrap();
+    let LatestStageResponse { latest_stage } = app
+        .wrap()
+        .query_wasm_smart(aioracle_addr.clone(), &QueryMsg::LatestStage {})
+        .unwrap();
+    app.execute_contract(
+        HumanAddr::from(AIORACLE_OWNER),
+        aioracle_addr.clone(),
+        &HandleMsg::RegisterMerkleRoot {
+            stage: latest_stage,
+            merkle_root: merkle_root.to_string(),
+            executors: vec![pubkey.clone()],
+        },
+        &[],
+    )
+    .unwrap();
+    pubkey
+}
+
+#[test]
+fn test_submit_evidence_double_sign() {
+    let test_data: Encoded = from_slice(TEST_DATA_1).unwrap();
+
+    let mut app = mock_app();
+    let (service_fees_addr, _, aioracle_addr) = setup_test_case(&mut app);
+    let signing_key = SigningKey::from_bytes(&[1u8; 32]).unwrap();
+    let pubkey = setup_funded_executor(
+        &mut app,
+        &service_fees_addr,
+        &aioracle_addr,
+        &signing_key,
+        &test_data.root,
+    );
+
+    let other_root = "4a2e27a2befb41a0655b8fe98d9c1a9f18ece280dc78b442734ead617e6bf3fc";
+    let double_sign = Evidence::DoubleSign {
+        executor: pubkey.clone(),
+        merkle_root: test_data.root.clone(),
+        signature: sign_report(&signing_key, &aioracle_addr, 1, &test_data.root),
+        conflicting_merkle_root: other_root.to_string(),
+        conflicting_signature: sign_report(&signing_key, &aioracle_addr, 1, other_root),
+    };
+
+    // signing the same root twice is not an evidence
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from(CLIENT),
+            aioracle_addr.clone(),
+            &HandleMsg::SubmitEvidence {
+                stage: 1,
+                evidence: Evidence::DoubleSign {
+                    executor: pubkey.clone(),
+                    merkle_root: test_data.root.clone(),
+                    signature: sign_report(&signing_key, &aioracle_addr, 1, &test_data.root),
+                    conflicting_merkle_root: test_data.root.clone(),
+                    conflicting_signature: sign_report(
+                        &signing_key,
+                        &aioracle_addr,
+                        1,
+                        &test_data.root
+                    ),
+                },
+            },
+            &[],
+        )
+        .unwrap_err(),
+        ContractError::InvalidEvidence {}.to_string()
+    );
+
+    let balance_before = app.wrap().query_balance("reporter", "orai").unwrap();
+    app.execute_contract(
+        HumanAddr::from("reporter"),
+        aioracle_addr.clone(),
+        &HandleMsg::SubmitEvidence {
+            stage: 1,
+            evidence: double_sign.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    // 10% of the pool is slashed, the reporter receives half of it
+    let balance_after = app.wrap().query_balance("reporter", "orai").unwrap();
+    assert_eq!(
+        balance_after.amount,
+        Uint128::from(balance_before.amount.u128() + 50)
+    );
+    let trusting_pool: TrustingPoolResponse = app
+        .wrap()
+        .query_wasm_smart(
+            aioracle_addr.clone(),
+            &QueryMsg::GetTrustingPool {
+                pubkey: pubkey.clone(),
+                asset_info: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        trusting_pool.trusting_pool.amount_coin.amount,
+        Uint128::from(900u64)
+    );
+
+    // the executor is jailed
+    let executor: Executor = app
+        .wrap()
+        .query_wasm_smart(
+            aioracle_addr.clone(),
+            &QueryMsg::GetExecutor {
+                pubkey: pubkey.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(executor.is_active, false);
+    assert_eq!(executor.jailed_block.is_some(), true);
+
+    // cannot submit the same evidence twice
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from("reporter"),
+            aioracle_addr.clone(),
+            &HandleMsg::SubmitEvidence {
+                stage: 1,
+                evidence: double_sign,
+            },
+            &[],
+        )
+        .unwrap_err(),
+        ContractError::AlreadyFinishedEvidence {}.to_string()
+    );
+
+    // a jailed executor cannot rejoin by itself
+    assert_eq!(
+        app.execute_contract(
+            pubkey_to_address(&pubkey).unwrap(),
+            aioracle_addr.clone(),
+            &HandleMsg::ExecutorJoin {
+                executor: pubkey.clone(),
+            },
+            &[],
+        )
+        .unwrap_err(),
+        ContractError::ExecutorJailed {}.to_string()
+    );
+}
+
+#[test]
+fn test_submit_evidence_without_trusting_pool() {
+    let test_data: Encoded = from_slice(TEST_DATA_1).unwrap();
+
+    let mut app = mock_app();
+    let (_, _, aioracle_addr) = setup_test_case(&mut app);
+
+    // an executor that has never been rewarded
+    let signing_key = SigningKey::from_bytes(&[1u8; 32]).unwrap();
+    let pubkey = Binary::from(signing_key.verify_key().to_bytes().as_slice());
+    app.execute_contract(
+        HumanAddr::from(AIORACLE_OWNER),
+        aioracle_addr.clone(),
+        &HandleMsg::UpdateConfig {
+            update_config_msg: UpdateConfigMsg {
+                new_owner: None,
+                new_service_addr: None,
+                new_contract_fee: None,
+                new_executors: Some(vec![pubkey.clone()]),
+                old_executors: None,
+                new_checkpoint: None,
+                new_checkpoint_threshold: None,
+                new_max_req_threshold: None,
+                new_trust_period: None,
+                new_slashing_amount: None,
+                new_slashing_bounty: None,
+                new_denom: None,
+                new_pending_period: None,
+                new_request_timeout: None,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        &HumanAddr::from(CLIENT),
+        &aioracle_addr,
+        &HandleMsg::Request {
+            threshold: 1,
+            input: None,
+            service: "price".to_string(),
+            preference_executor_fee: coin(1, "orai"),
+            min_reputation: None,
+            callback: None,
+            service_version: None,
+        },
+        &coins(6u128, "orai"),
+    )
+    .unwrap();
+
+    let other_root = "4a2e27a2befb41a0655b8fe98d9c1a9f18ece280dc78b442734ead617e6bf3fc";
+    let res = app
+        .execute_contract(
+            HumanAddr::from("reporter"),
+            aioracle_addr.clone(),
+            &HandleMsg::SubmitEvidence {
+                stage: 1,
+                evidence: Evidence::DoubleSign {
+                    executor: pubkey.clone(),
+                    merkle_root: test_data.root.clone(),
+                    signature: sign_report(&signing_key, &aioracle_addr, 1, &test_data.root),
+                    conflicting_merkle_root: other_root.to_string(),
+                    conflicting_signature: sign_report(&signing_key, &aioracle_addr, 1, other_root),
+                },
+            },
+            &[],
+        )
+        .unwrap();
+    // nothing to slash nor to pay
+    assert_eq!(
+        res.attributes
+            .iter()
+            .any(|attribute| attribute.key.eq("slashed_amount")),
+        false
+    );
+
+    // the executor is still jailed & the evidence recorded
+    let executor: Executor = app
+        .wrap()
+        .query_wasm_smart(
+            aioracle_addr.clone(),
+            &QueryMsg::GetExecutor {
+                pubkey: pubkey.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(executor.jailed_block.is_some(), true);
+    let stats: ExecutorStatsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            aioracle_addr.clone(),
+            &QueryMsg::GetExecutorStats { pubkey },
+        )
+        .unwrap();
+    assert_eq!(stats.evidences, 1);
+}
+
+#[test]
+fn test_submit_evidence_invalid_report() {
+    let signing_key = SigningKey::from_bytes(&[1u8; 32]).unwrap();
+    let pubkey = Binary::from(signing_key.verify_key().to_bytes().as_slice());
+
+    // single leaf merkle trees, the root is the hash of the report
+    let invalid_report = to_binary(&Report {
+        executor: pubkey.clone(),
+        data: Binary::from_base64("eyJkYXRhIjoiNTAwMCJ9").unwrap(),
+        rewards: vec![(
+            HumanAddr::from(CLIENT),
+            "orai".to_string(),
+            Uint128::from(1u64),
+        )],
+    })
+    .unwrap();
+    let valid_report = to_binary(&Report {
+        executor: pubkey.clone(),
+        data: Binary::from_base64("eyJkYXRhIjoiNTAwMCJ9").unwrap(),
+        rewards: vec![(
+            HumanAddr::from("orai188efpndge9hqayll4cp9gzv0dw6rvj25e4slkp"),
+            "orai".to_string(),
+            Uint128::from(1u64),
+        )],
+    })
+    .unwrap();
+
+    let mut app = mock_app();
+    let (service_fees_addr, _, aioracle_addr) = setup_test_case(&mut app);
+    setup_funded_executor(
+        &mut app,
+        &service_fees_addr,
+        &aioracle_addr,
+        &signing_key,
+        &hex::encode(sha2::Sha256::digest(invalid_report.as_slice())),
+    );
+    setup_funded_executor(
+        &mut app,
+        &service_fees_addr,
+        &aioracle_addr,
+        &signing_key,
+        &hex::encode(sha2::Sha256::digest(valid_report.as_slice())),
+    );
+
+    // the report only claims rewards of the request
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from("reporter"),
+            aioracle_addr.clone(),
+            &HandleMsg::SubmitEvidence {
+                stage: 2,
+                evidence: Evidence::InvalidReport {
+                    signature: sign_report_data(&signing_key, &aioracle_addr, 2, &valid_report),
+                    report: valid_report,
+                    proof: None,
+                },
+            },
+            &[],
+        )
+        .unwrap_err(),
+        ContractError::InvalidEvidence {}.to_string()
+    );
+
+    // the executor named in the report has to have signed it
+    let other_key = SigningKey::from_bytes(&[2u8; 32]).unwrap();
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from("reporter"),
+            aioracle_addr.clone(),
+            &HandleMsg::SubmitEvidence {
+                stage: 1,
+                evidence: Evidence::InvalidReport {
+                    signature: sign_report_data(&other_key, &aioracle_addr, 1, &invalid_report),
+                    report: invalid_report.clone(),
+                    proof: None,
+                },
+            },
+            &[],
+        )
+        .unwrap_err(),
+        ContractError::InvalidEvidence {}.to_string()
+    );
+
+    app.execute_contract(
+        HumanAddr::from("reporter"),
+        aioracle_addr.clone(),
+        &HandleMsg::SubmitEvidence {
+            stage: 1,
+            evidence: Evidence::InvalidReport {
+                signature: sign_report_data(&signing_key, &aioracle_addr, 1, &invalid_report),
+                report: invalid_report,
+                proof: None,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    // pool of 2 requests, 10% is slashed
+    let trusting_pool: TrustingPoolResponse = app
+        .wrap()
+        .query_wasm_smart(
+            aioracle_addr.clone(),
+            &QueryMsg::GetTrustingPool {
+                pubkey,
+                asset_info: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        trusting_pool.trusting_pool.amount_coin.amount,
+        Uint128::from(1800u64)
+    );
+}
+
+#[test]
+fn test_claim_rewards() {
+    let signing_key = SigningKey::from_bytes(&[1u8; 32]).unwrap();
+    let pubkey = Binary::from(signing_key.verify_key().to_bytes().as_slice());
+    let provider = "orai188efpndge9hqayll4cp9gzv0dw6rvj25e4slkp";
+
+    // single leaf merkle trees, the root is the hash of the report
+    let reports: Vec<Binary> = vec!["eyJkYXRhIjoiNTAwMCJ9", "eyJkYXRhIjoiNjAwMCJ9"]
+        .into_iter()
+        .map(|data| {
+            to_binary(&Report {
+                executor: pubkey.clone(),
+                data: Binary::from_base64(data).unwrap(),
+                rewards: vec![(
+                    HumanAddr::from(provider),
+                    "orai".to_string(),
+                    Uint128::from(1u64),
+                )],
+            })
+            .unwrap()
+        })
+        .collect();
+
+    let mut app = mock_app();
+    let (service_fees_addr, _, aioracle_addr) = setup_test_case(&mut app);
+    for report in reports.iter() {
+        setup_funded_executor(
+            &mut app,
+            &service_fees_addr,
+            &aioracle_addr,
+            &signing_key,
+            &hex::encode(sha2::Sha256::digest(report.as_slice())),
+        );
+    }
+    let msg = HandleMsg::ClaimRewards {
+        claims: reports
+            .iter()
+            .enumerate()
+            .map(|(i, report)| RewardClaim {
+                stage: i as u64 + 1,
+                report: report.clone(),
+                proof: None,
+            })
+            .collect(),
+    };
+
+    // cannot claim during the trusting period
+    assert_eq!(
+        app.execute_contract(HumanAddr::from(CLIENT), aioracle_addr.clone(), &msg, &[])
+            .unwrap_err()
+            .contains("Cannot claim rewards before block"),
+        true
+    );
+
+    app.update_block(skip_trusting_period);
+
+    // a report that is not part of the merkle root cannot be claimed
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from(CLIENT),
+            aioracle_addr.clone(),
+            &HandleMsg::ClaimRewards {
+                claims: vec![RewardClaim {
+                    stage: 1,
+                    report: reports[1].clone(),
+                    proof: None,
+                }],
+            },
+            &[],
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {}.to_string()
+    );
+
+    let balance_before = app.wrap().query_balance(provider, "orai").unwrap();
+    app.execute_contract(HumanAddr::from(CLIENT), aioracle_addr.clone(), &msg, &[])
+        .unwrap();
+    let balance_after = app.wrap().query_balance(provider, "orai").unwrap();
+    assert_eq!(
+        balance_after.amount,
+        balance_before.amount + Uint128::from(2u64)
+    );
+
+    let is_claimed: IsClaimedResponse = app
+        .wrap()
+        .query_wasm_smart(
+            aioracle_addr.clone(),
+            &QueryMsg::IsClaimed {
+                stage: 2,
+                executor: pubkey,
+            },
+        )
+        .unwrap();
+    assert_eq!(is_claimed.is_claimed, true);
+
+    // cannot claim twice
+    assert_eq!(
+        app.execute_contract(HumanAddr::from(CLIENT), aioracle_addr.clone(), &msg, &[])
+            .unwrap_err(),
+        ContractError::Claimed {}.to_string()
+    );
+}
+
+#[test]
+fn test_claim_rewards_bounded_by_request() {
+    let provider = "orai188efpndge9hqayll4cp9gzv0dw6rvj25e4slkp";
+    let signing_keys: Vec<SigningKey> = (1u8..3)
+        .map(|i| SigningKey::from_bytes(&[i; 32]).unwrap())
+        .collect();
+    let pubkeys: Vec<Binary> = signing_keys
+        .iter()
+        .map(|key| Binary::from(key.verify_key().to_bytes().as_slice()))
+        .collect();
+
+    // the first report claims the same reward twice, the second one is from an extra executor
+    let reward = (
+        HumanAddr::from(provider),
+        "orai".to_string(),
+        Uint128::from(1u64),
+    );
+    let reports = vec![
+        to_binary(&Report {
+            executor: pubkeys[0].clone(),
+            data: Binary::from_base64("eyJkYXRhIjoiNTAwMCJ9").unwrap(),
+            rewards: vec![reward.clone(), reward.clone()],
+        })
+        .unwrap(),
+        to_binary(&Report {
+            executor: pubkeys[1].clone(),
+            data: Binary::from_base64("eyJkYXRhIjoiNTAwMCJ9").unwrap(),
+            rewards: vec![reward],
+        })
+        .unwrap(),
+    ];
+    let leaves: Vec<Vec<u8>> = reports
+        .iter()
+        .map(|report| sha2::Sha256::digest(report.as_slice()).to_vec())
+        .collect();
+    let mut sorted_leaves = leaves.clone();
+    sorted_leaves.sort();
+    let merkle_root = hex::encode(sha2::Sha256::digest(&sorted_leaves.concat()));
+
+    let mut app = mock_app();
+    let (service_fees_addr, _, aioracle_addr) = setup_test_case(&mut app);
+    setup_funded_executor(
+        &mut app,
+        &service_fees_addr,
+        &aioracle_addr,
+        &signing_keys[0],
+        &merkle_root,
+    );
+    app.update_block(skip_trusting_period);
+
+    // the reward funded by the request is paid once
+    let balance_before = app.wrap().query_balance(provider, "orai").unwrap();
+    app.execute_contract(
+        HumanAddr::from(CLIENT),
+        aioracle_addr.clone(),
+        &HandleMsg::ClaimRewards {
+            claims: vec![RewardClaim {
+                stage: 1,
+                report: reports[0].clone(),
+                proof: Some(vec![hex::encode(&leaves[1])]),
+            }],
+        },
+        &[],
+    )
+    .unwrap();
+    let balance_after = app.wrap().query_balance(provider, "orai").unwrap();
+    assert_eq!(
+        balance_after.amount,
+        balance_before.amount + Uint128::from(1u64)
+    );
+
+    // the request has only paid a single executor
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from(CLIENT),
+            aioracle_addr.clone(),
+            &HandleMsg::ClaimRewards {
+                claims: vec![RewardClaim {
+                    stage: 1,
+                    report: reports[1].clone(),
+                    proof: Some(vec![hex::encode(&leaves[0])]),
+                }],
+            },
+            &[],
+        )
+        .unwrap_err(),
+        ContractError::ClaimLimitReached { threshold: 1 }.to_string()
+    );
+}
+
+#[test]
+fn test_cw20_request() {
+    let mut app = mock_app();
+    let (service_fees_addr, provider_addr, aioracle_addr) = setup_test_case(&mut app);
+
+    let ow20_id = app.store_code(contract_ow20());
+    let token_addr = app
+        .instantiate_contract(
+            ow20_id,
+            CLIENT,
+            &ow20::msg::InitMsg {
+                name: "Oracle Token".to_string(),
+                symbol: "ORT".to_string(),
+                decimals: 6,
+                initial_balances: vec![cw20::Cw20CoinHuman {
+                    address: HumanAddr::from(CLIENT),
+                    amount: Uint128::from(100000u64),
+                }],
+                mint: None,
+            },
+            &[],
+            "ow20",
+        )
+        .unwrap();
+    let asset_info = AssetInfo::Token {
+        contract_addr: token_addr.clone(),
+    };
+
+    // providers of the service & the executor are paid with the token
+    for provider in vec![
+        "orai188efpndge9hqayll4cp9gzv0dw6rvj25e4slkp",
+        "orai18hr8jggl3xnrutfujy2jwpeu0l76azprlvgrwt",
+        "orai1nc6eqvnczmtqq8keplyrha9z7vnd5v9vvsxxgj",
+    ] {
+        app.execute_contract(
+            HumanAddr::from(provider),
+            service_fees_addr.clone(),
+            &aioracle_service_fees::msg::HandleMsg::UpdateServiceFees {
+                fees: coin(1u128, token_addr.as_str()),
+            },
+            &[],
+        )
+        .unwrap();
+    }
+    let signing_key = SigningKey::from_bytes(&[1u8; 32]).unwrap();
+    let pubkey = Binary::from(signing_key.verify_key().to_bytes().as_slice());
+    let executor_addr = pubkey_to_address(&pubkey).unwrap();
+    app.execute_contract(
+        executor_addr.clone(),
+        service_fees_addr.clone(),
+        &aioracle_service_fees::msg::HandleMsg::UpdateServiceFees {
+            fees: coin(1000u128, token_addr.as_str()),
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        HumanAddr::from(AIORACLE_OWNER),
+        aioracle_addr.clone(),
+        &HandleMsg::UpdateConfig {
+            update_config_msg: UpdateConfigMsg {
+                new_owner: None,
+                new_service_addr: None,
+                new_contract_fee: None,
+                new_executors: Some(vec![pubkey.clone()]),
+                old_executors: None,
+                new_checkpoint: None,
+                new_checkpoint_threshold: None,
+                new_max_req_threshold: None,
+                new_trust_period: None,
+                new_slashing_amount: None,
+                new_slashing_bounty: None,
+                new_denom: None,
+                new_pending_period: None,
+                new_request_timeout: None,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    let request_msg = to_binary(&Cw20HookMsg::Request {
+        service: "price".to_string(),
+        input: None,
+        threshold: 1,
+        preference_executor_fee: Uint128::from(1000u64),
+        min_reputation: None,
+        callback: None,
+        service_version: None,
+    })
+    .unwrap();
+
+    let send_request = |app: &mut App, amount: u64| {
+        app.execute_contract(
+            HumanAddr::from(CLIENT),
+            token_addr.clone(),
+            &ow20::msg::HandleMsg::Send {
+                contract: aioracle_addr.clone(),
+                amount: Uint128::from(amount),
+                msg: Some(request_msg.clone()),
+            },
+            &[],
+        )
+    };
+
+    // the contract fee & the bound executor fee cannot be paid with the token
+    assert_eq!(
+        send_request(&mut app, 1004).unwrap_err(),
+        ContractError::InsufficientFundsContractFees {}.to_string()
+    );
+    app.execute_contract(
+        HumanAddr::from(AIORACLE_OWNER),
+        aioracle_addr.clone(),
+        &HandleMsg::UpdateConfig {
+            update_config_msg: UpdateConfigMsg {
+                new_owner: None,
+                new_service_addr: None,
+                new_contract_fee: Some(coin(1u128, token_addr.as_str())),
+                new_executors: None,
+                old_executors: None,
+                new_checkpoint: None,
+                new_checkpoint_threshold: None,
+                new_max_req_threshold: None,
+                new_trust_period: None,
+                new_slashing_amount: None,
+                new_slashing_bounty: None,
+                new_denom: None,
+                new_pending_period: None,
+                new_request_timeout: None,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        send_request(&mut app, 1004).unwrap_err(),
+        ContractError::InsufficientFundsBoundFees {}.to_string()
+    );
+    app.execute_contract(
+        HumanAddr::from(PROVIDER_OWNER),
+        provider_addr,
+        &provider_bridge::msg::HandleMsg::UpdateConfig {
+            bound_executor_fee: Some(coin(1u128, token_addr.as_str())),
+            owner: None,
+            service_fees_contract: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // rewards of 3 providers plus the preference executor fee plus the contract fee
+    assert_eq!(
+        send_request(&mut app, 1003).unwrap_err(),
+        ContractError::InsufficientFundsRequestFees {}.to_string()
+    );
+    send_request(&mut app, 1004).unwrap();
+
+    let LatestStageResponse { latest_stage } = app
+        .wrap()
+        .query_wasm_smart(aioracle_addr.clone(), &QueryMsg::LatestStage {})
+        .unwrap();
+    let request: Request = app
+        .wrap()
+        .query_wasm_smart(
+            aioracle_addr.clone(),
+            &QueryMsg::Request {
+                stage: latest_stage,
+            },
+        )
+        .unwrap();
+    assert_eq!(request.requester, HumanAddr::from(CLIENT));
+    assert_eq!(request.asset_info, Some(asset_info.clone()));
+
+    let test_data: Encoded = from_slice(TEST_DATA_1).unwrap();
+    app.execute_contract(
+        HumanAddr::from(AIORACLE_OWNER),
+        aioracle_addr.clone(),
+        &HandleMsg::RegisterMerkleRoot {
+            stage: latest_stage,
+            merkle_root: test_data.root.clone(),
+            executors: vec![pubkey.clone()],
+        },
+        &[],
+    )
+    .unwrap();
+
+    // the executor's reward goes into the pool of the token
+    let trusting_pool: TrustingPoolResponse = app
+        .wrap()
+        .query_wasm_smart(
+            aioracle_addr.clone(),
+            &QueryMsg::GetTrustingPool {
+                pubkey: pubkey.clone(),
+                asset_info: Some(asset_info.clone()),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        trusting_pool.trusting_pool.amount_coin,
+        coin(1000u128, token_addr.as_str())
+    );
+    let native_pool: Result<TrustingPoolResponse, StdError> = app.wrap().query_wasm_smart(
+        aioracle_addr.clone(),
+        &QueryMsg::GetTrustingPool {
+            pubkey: pubkey.clone(),
+            asset_info: None,
+        },
+    );
+    assert_eq!(native_pool.is_err(), true);
+
+    // the pool of the token is slashed as well, the reporter is paid in the token
+    let other_root = "4a2e27a2befb41a0655b8fe98d9c1a9f18ece280dc78b442734ead617e6bf3fc";
+    app.execute_contract(
+        HumanAddr::from("reporter"),
+        aioracle_addr.clone(),
+        &HandleMsg::SubmitEvidence {
+            stage: latest_stage,
+            evidence: Evidence::DoubleSign {
+                executor: pubkey.clone(),
+                merkle_root: test_data.root.clone(),
+                signature: sign_report(&signing_key, &aioracle_addr, latest_stage, &test_data.root),
+                conflicting_merkle_root: other_root.to_string(),
+                conflicting_signature: sign_report(
+                    &signing_key,
+                    &aioracle_addr,
+                    latest_stage,
+                    other_root,
+                ),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+    let balance: cw20::BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            token_addr.clone(),
+            &ow20::msg::QueryMsg::Balance {
+                address: HumanAddr::from("reporter"),
+            },
+        )
+        .unwrap();
+    assert_eq!(balance.balance, Uint128::from(50u64));
+
+    // withdraw the pool in the token
+    let msg = HandleMsg::PrepareWithdrawPool {
+        pubkey: pubkey.clone(),
+        asset_info: Some(asset_info.clone()),
+    };
+    app.execute_contract(executor_addr.clone(), aioracle_addr.clone(), &msg, &[])
+        .unwrap();
+    app.update_block(skip_trusting_period);
+    app.execute_contract(executor_addr.clone(), aioracle_addr.clone(), &msg, &[])
+        .unwrap();
+
+    let balance: cw20::BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            token_addr.clone(),
+            &ow20::msg::QueryMsg::Balance {
+                address: executor_addr.clone(),
+            },
+        )
+        .unwrap();
+    // what is left after slashing
+    assert_eq!(balance.balance, Uint128::from(900u64));
+
+    // an executor fee in another asset is not compared with the preference fee paid in the token
+    app.execute_contract(
+        executor_addr.clone(),
+        service_fees_addr.clone(),
+        &aioracle_service_fees::msg::HandleMsg::UpdateServiceFees {
+            fees: coin(5u128, "orai"),
+        },
+        &[],
+    )
+    .unwrap();
+    // the jailed executor is added back by the owner
+    app.execute_contract(
+        HumanAddr::from(AIORACLE_OWNER),
+        aioracle_addr.clone(),
+        &HandleMsg::UpdateConfig {
+            update_config_msg: UpdateConfigMsg {
+                new_owner: None,
+                new_service_addr: None,
+                new_contract_fee: None,
+                new_executors: Some(vec![pubkey.clone()]),
+                old_executors: None,
+                new_checkpoint: None,
+                new_checkpoint_threshold: None,
+                new_max_req_threshold: None,
+                new_trust_period: None,
+                new_slashing_amount: None,
+                new_slashing_bounty: None,
+                new_denom: None,
+                new_pending_period: None,
+                new_request_timeout: None,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        HumanAddr::from(CLIENT),
+        token_addr.clone(),
+        &ow20::msg::HandleMsg::Send {
+            contract: aioracle_addr.clone(),
+            amount: Uint128::from(1004u64),
+            msg: Some(
+                to_binary(&Cw20HookMsg::Request {
+                    service: "price".to_string(),
+                    input: None,
+                    threshold: 1,
+                    preference_executor_fee: Uint128::from(1000u64),
+                    min_reputation: None,
+                    callback: None,
+                    service_version: None,
+                })
+                .unwrap(),
+            ),
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        HumanAddr::from(AIORACLE_OWNER),
+        aioracle_addr.clone(),
+        &HandleMsg::RegisterMerkleRoot {
+            stage: latest_stage + 1,
+            merkle_root: test_data.root,
+            executors: vec![pubkey.clone()],
+        },
+        &[],
+    )
+    .unwrap();
+
+    // pools in cw20 tokens are listed with their asset
+    let trusting_pools: Vec<TrustingPoolResponse> = app
+        .wrap()
+        .query_wasm_smart(
+            aioracle_addr.clone(),
+            &QueryMsg::GetTrustingPools {
+                offset: None,
+                limit: None,
+                order: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(trusting_pools.len(), 1);
+    assert_eq!(trusting_pools[0].pubkey, pubkey);
+    assert_eq!(trusting_pools[0].asset_info, Some(asset_info));
+    assert_eq!(
+        trusting_pools[0].trusting_pool.amount_coin,
+        coin(1000u128, token_addr.as_str())
+    );
+}
+
+fn consumer_init(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<InitResponse> {
+    Ok(InitResponse::default())
+}
+
+fn consumer_handle(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: OracleCallbackMsg,
+) -> StdResult<HandleResponse> {
+    match msg {
+        OracleCallbackMsg::OracleCallback { merkle_root, .. } => {
+            deps.storage.set(b"merkle_root", merkle_root.as_bytes())
+        }
+    }
+    Ok(HandleResponse::default())
+}
+
+fn consumer_query(deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+    to_binary(&String::from_utf8(deps.storage.get(b"merkle_root").unwrap_or_default()).unwrap())
+}
+
+#[test]
+fn test_request_callback() {
+    let mut app = mock_app();
+    let (_, _, aioracle_addr) = setup_test_case(&mut app);
+
+    // a consumer contract storing the merkle root it is notified with
+    let consumer_id = app.store_code(Box::new(ContractWrapper::new(
+        consumer_handle,
+        consumer_init,
+        consumer_query,
+    )));
+    let consumer_addr = app
+        .instantiate_contract(consumer_id, CLIENT, &Empty {}, &[], "consumer")
+        .unwrap();
+    app.set_bank_balance(consumer_addr.clone(), coins(100, "orai"))
+        .unwrap();
+
+    // rewards of 4 orai, preference fee of 1 orai, contract fee of 1 orai & the callback budget of 10 orai
+    let msg = HandleMsg::Request {
+        threshold: 1,
+        input: None,
+        service: "price".to_string(),
+        preference_executor_fee: coin(1, "orai"),
+        min_reputation: None,
+        callback: Some(Callback {
+            fee: Uint128::from(10u64),
+        }),
+        service_version: None,
+    };
+    assert_eq!(
+        app.execute_contract(
+            consumer_addr.clone(),
+            aioracle_addr.clone(),
+            &msg,
+            &coins(15u128, "orai"),
+        )
+        .unwrap_err(),
+        ContractError::InsufficientFundsRequestFees {}.to_string()
+    );
+    app.execute_contract(
+        consumer_addr.clone(),
+        aioracle_addr.clone(),
+        &msg,
+        &coins(16u128, "orai"),
+    )
+    .unwrap();
+
+    // a requester that cannot handle the callback does not block the finalization
+    app.execute_contract(
+        HumanAddr::from(CLIENT),
+        aioracle_addr.clone(),
+        &msg,
+        &coins(16u128, "orai"),
+    )
+    .unwrap();
+
+    let test_data: Encoded = from_slice(TEST_DATA_1).unwrap();
+    for stage in 1..3 {
+        app.execute_contract(
+            HumanAddr::from(AIORACLE_OWNER),
+            aioracle_addr.clone(),
+            &HandleMsg::RegisterMerkleRoot {
+                stage,
+                merkle_root: test_data.root.clone(),
+                executors: vec![],
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    // callbacks wait to be relayed
+    let merkle_root: String = app
+        .wrap()
+        .query_wasm_smart(consumer_addr.clone(), &Empty {})
+        .unwrap();
+    assert_eq!(merkle_root, "");
+    let pending_callbacks: Vec<RequestResponse> = app
+        .wrap()
+        .query_wasm_smart(
+            aioracle_addr.clone(),
+            &QueryMsg::GetPendingCallbacks {
+                offset: None,
+                limit: None,
+                order: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        pending_callbacks
+            .iter()
+            .map(|request| request.stage)
+            .collect::<Vec<u64>>(),
+        vec![1, 2]
+    );
+
+    // the requester is notified & the relayer paid with the callback budget
+    let balance_before = app.wrap().query_balance("relayer", "orai").unwrap();
+    app.execute_contract(
+        HumanAddr::from("relayer"),
+        aioracle_addr.clone(),
+        &HandleMsg::RelayCallback { stage: 1 },
+        &[],
+    )
+    .unwrap();
+    let merkle_root: String = app
+        .wrap()
+        .query_wasm_smart(consumer_addr, &Empty {})
+        .unwrap();
+    assert_eq!(merkle_root, test_data.root);
+    let balance_after = app.wrap().query_balance("relayer", "orai").unwrap();
+    assert_eq!(
+        balance_after.amount,
+        balance_before.amount + Uint128::from(10u64)
+    );
+
+    // a callback is relayed once
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from("relayer"),
+            aioracle_addr.clone(),
+            &HandleMsg::RelayCallback { stage: 1 },
+            &[],
+        )
+        .unwrap_err(),
+        ContractError::NoPendingCallback {}.to_string()
+    );
+
+    // a requester failing the callback only fails the relay, its request stays finalized & the callback pending
+    app.execute_contract(
+        HumanAddr::from("relayer"),
+        aioracle_addr.clone(),
+        &HandleMsg::RelayCallback { stage: 2 },
+        &[],
+    )
+    .unwrap_err();
+    let request: Request = app
+        .wrap()
+        .query_wasm_smart(aioracle_addr.clone(), &QueryMsg::Request { stage: 2 })
+        .unwrap();
+    assert_eq!(request.merkle_root, test_data.root);
+    let pending_callbacks: Vec<RequestResponse> = app
+        .wrap()
+        .query_wasm_smart(
+            aioracle_addr,
+            &QueryMsg::GetPendingCallbacks {
+                offset: None,
+                limit: None,
+                order: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        pending_callbacks
+            .iter()
+            .map(|request| request.stage)
+            .collect::<Vec<u64>>(),
+        vec![2]
+    );
+}
+
+#[test]
+fn test_service_policy() {
+    let test_data: Encoded = from_slice(TEST_DATA_1).unwrap();
+
+    let mut app = mock_app();
+    let (_, provider_addr, aioracle_addr) = setup_test_case(&mut app);
+
+    let signing_keys: Vec<SigningKey> = (1u8..4)
+        .map(|i| SigningKey::from_bytes(&[i; 32]).unwrap())
+        .collect();
+    let pubkeys: Vec<Binary> = signing_keys
+        .iter()
+        .map(|key| Binary::from(key.verify_key().to_bytes().as_slice()))
+        .collect();
+    app.execute_contract(
+        HumanAddr::from(AIORACLE_OWNER),
+        aioracle_addr.clone(),
+        &HandleMsg::UpdateConfig {
+            update_config_msg: UpdateConfigMsg {
+                new_owner: None,
+                new_service_addr: None,
+                new_contract_fee: None,
+                new_executors: Some(pubkeys.clone()),
+                old_executors: None,
+                new_checkpoint: None,
+                new_checkpoint_threshold: None,
+                new_max_req_threshold: None,
+                new_trust_period: None,
+                new_slashing_amount: None,
+                new_slashing_bounty: None,
+                new_denom: None,
+                new_pending_period: None,
+                new_request_timeout: None,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    // only the first two executors can handle the price service
+    app.execute_contract(
+        HumanAddr::from(PROVIDER_OWNER),
+        provider_addr,
+        &provider_bridge::msg::HandleMsg::UpdateServicePolicy {
+            service: "price".to_string(),
+            policy: ServicePolicy {
+                min_threshold: Some(2),
+                executors: Some(pubkeys[0..2].to_vec()),
+                max_fee: Some(coin(10u128, "orai")),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    let request_msg = |threshold: u64, preference_executor_fee: u128| HandleMsg::Request {
+        threshold,
+        input: None,
+        service: "price".to_string(),
+        preference_executor_fee: coin(preference_executor_fee, "orai"),
+        min_reputation: None,
+        callback: None,
+        service_version: None,
+    };
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from(CLIENT),
+            aioracle_addr.clone(),
+            &request_msg(1, 1),
+            &coins(6u128, "orai"),
+        )
+        .unwrap_err(),
+        ContractError::ThresholdBelowPolicy { min_threshold: 2 }.to_string()
+    );
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from(CLIENT),
+            aioracle_addr.clone(),
+            &request_msg(3, 1),
+            &coins(16u128, "orai"),
+        )
+        .unwrap_err(),
+        ContractError::InvalidThreshold {}.to_string()
+    );
+    // rewards of 4 orai plus a preference fee of 7 orai
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from(CLIENT),
+            aioracle_addr.clone(),
+            &request_msg(2, 7),
+            &coins(23u128, "orai"),
+        )
+        .unwrap_err(),
+        ContractError::MaxFeeExceeded {
+            fee: 11,
+            max_fee: 10,
+            denom: "orai".to_string()
+        }
+        .to_string()
+    );
+    app.execute_contract(
+        HumanAddr::from(CLIENT),
+        aioracle_addr.clone(),
+        &request_msg(2, 1),
+        &coins(11u128, "orai"),
+    )
+    .unwrap();
+
+    // executors outside of the policy cannot report
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from(CLIENT),
+            aioracle_addr.clone(),
+            &HandleMsg::SubmitReport {
+                stage: 1,
+                merkle_root: test_data.root.clone(),
+                executor: pubkeys[2].clone(),
+                signature: sign_report(&signing_keys[2], &aioracle_addr, 1, &test_data.root),
+            },
+            &[],
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {}.to_string()
+    );
+    for i in 0..2 {
+        app.execute_contract(
+            HumanAddr::from(CLIENT),
+            aioracle_addr.clone(),
+            &HandleMsg::SubmitReport {
+                stage: 1,
+                merkle_root: test_data.root.clone(),
+                executor: pubkeys[i].clone(),
+                signature: sign_report(&signing_keys[i], &aioracle_addr, 1, &test_data.root),
+            },
+            &[],
+        )
+        .unwrap();
+    }
+    let request: Request = app
+        .wrap()
+        .query_wasm_smart(aioracle_addr.clone(), &QueryMsg::Request { stage: 1u64 })
+        .unwrap();
+    assert_eq!(request.merkle_root, test_data.root);
+}
+
+/// queries answered by a provider bridge predating fee schedules, service versions & service policies
+#[derive(Deserialize)]
+struct LegacyBridgeQueryMsg {
+    service_fee_msg: Option<ServiceMsg>,
+    get_bound_executor_fee: Option<BoundExecutorFeeMsg>,
+}
+
+fn legacy_bridge_handle(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<HandleResponse> {
+    Ok(HandleResponse::default())
+}
+
+fn legacy_bridge_query(_deps: Deps, _env: Env, msg: LegacyBridgeQueryMsg) -> StdResult<Binary> {
+    match msg {
+        LegacyBridgeQueryMsg {
+            service_fee_msg: Some(_),
+            ..
+        } => to_binary(&vec![(
+            HumanAddr::from("provider"),
+            "orai".to_string(),
+            Uint128::from(1u64),
+        )]),
+        LegacyBridgeQueryMsg {
+            get_bound_executor_fee: Some(_),
+            ..
+        } => to_binary(&coin(1u128, "orai")),
+        _ => Err(StdError::generic_err("unknown query")),
+    }
+}
+
+#[test]
+fn test_legacy_provider_bridge() {
+    let mut app = mock_app();
+    app.set_bank_balance(HumanAddr::from(CLIENT), coins(100, "orai"))
+        .unwrap();
+    let bridge_id = app.store_code(Box::new(ContractWrapper::new(
+        legacy_bridge_handle,
+        consumer_init,
+        legacy_bridge_query,
+    )));
+    let bridge_addr = app
+        .instantiate_contract(bridge_id, PROVIDER_OWNER, &Empty {}, &[], "legacy_bridge")
+        .unwrap();
+    let aioracle_addr = init_aioracle(
+        &mut app,
+        bridge_addr,
+        coin(1u128, "orai"),
+        vec![
+            Binary::from_base64("A6ENA5I5QhHyy1QIOLkgTcf/x31WE+JLFoISgmcQaI0t").unwrap(),
+            Binary::from_base64("A3PR7VXxp/lU5cQRctmDRjmyuMi50M+qiy1lKl3GYgeA").unwrap(),
+        ],
+    );
+
+    // the service has no policy, the flat fees are charged: 1 orai for the provider, the preference fee & the contract fee
+    let msg = HandleMsg::Request {
+        threshold: 1,
+        input: None,
+        service: "price".to_string(),
+        preference_executor_fee: coin(1, "orai"),
+        min_reputation: None,
+        callback: None,
+        service_version: None,
+    };
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from(CLIENT),
+            aioracle_addr.clone(),
+            &msg,
+            &coins(2u128, "orai"),
+        )
+        .unwrap_err(),
+        ContractError::InsufficientFundsRequestFees {}.to_string()
+    );
+    app.execute_contract(
+        HumanAddr::from(CLIENT),
+        aioracle_addr.clone(),
+        &msg,
+        &coins(3u128, "orai"),
+    )
+    .unwrap();
+    let request: Request = app
+        .wrap()
+        .query_wasm_smart(aioracle_addr, &QueryMsg::Request { stage: 1u64 })
+        .unwrap();
+    assert_eq!(request.executors, None);
+    assert_eq!(request.service_version, None);
+}
+
+#[test]
+fn test_fee_schedule() {
+    let mut app = mock_app();
+    let (service_fees_addr, _, aioracle_addr) = setup_test_case(&mut app);
+    app.set_bank_balance(
+        HumanAddr::from(CLIENT),
+        vec![coin(10000000000, "atom"), coin(10000000000, "orai")],
+    )
+    .unwrap();
+
+    // the data source halves its fee from the second request of the period
+    app.execute_contract(
+        HumanAddr::from("orai188efpndge9hqayll4cp9gzv0dw6rvj25e4slkp"),
+        service_fees_addr.clone(),
+        &aioracle_service_fees::msg::HandleMsg::UpdateFeeSchedule {
+            schedule: FeeSchedule {
+                accepted: vec![coin(4u128, "orai")],
+                tiers: vec![FeeTier {
+                    min_requests: 2,
+                    discount: 500,
+                }],
+                period: 1000,
+                expiry: None,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+    // the oracle script prefers atom, orai is accepted as well
+    app.execute_contract(
+        HumanAddr::from("orai1nc6eqvnczmtqq8keplyrha9z7vnd5v9vvsxxgj"),
+        service_fees_addr.clone(),
+        &aioracle_service_fees::msg::HandleMsg::UpdateFeeSchedule {
+            schedule: FeeSchedule {
+                accepted: vec![coin(3u128, "atom"), coin(1u128, "orai")],
+                tiers: vec![],
+                period: 0,
+                expiry: None,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    let request_msg = HandleMsg::Request {
+        threshold: 1,
+        input: None,
+        service: "price".to_string(),
+        preference_executor_fee: coin(1, "orai"),
+        min_reputation: None,
+        callback: None,
+        service_version: None,
+    };
+    // rewards of 7 orai, plus the preference & contract fees
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from(CLIENT),
+            aioracle_addr.clone(),
+            &request_msg,
+            &coins(8u128, "orai"),
+        )
+        .unwrap_err(),
+        ContractError::InsufficientFundsRequestFees {}.to_string()
+    );
+    app.execute_contract(
+        HumanAddr::from(CLIENT),
+        aioracle_addr.clone(),
+        &request_msg,
+        &coins(9u128, "orai"),
+    )
+    .unwrap();
+    let request: Request = app
+        .wrap()
+        .query_wasm_smart(aioracle_addr.clone(), &QueryMsg::Request { stage: 1u64 })
+        .unwrap();
+    assert_eq!(
+        request.rewards,
+        vec![
+            (
+                HumanAddr::from("orai188efpndge9hqayll4cp9gzv0dw6rvj25e4slkp"),
+                "orai".to_string(),
+                Uint128::from(4u64)
+            ),
+            (
+                HumanAddr::from("orai18hr8jggl3xnrutfujy2jwpeu0l76azprlvgrwt"),
+                "orai".to_string(),
+                Uint128::from(2u64)
+            ),
+            (
+                HumanAddr::from("orai1nc6eqvnczmtqq8keplyrha9z7vnd5v9vvsxxgj"),
+                "orai".to_string(),
+                Uint128::from(1u64)
+            ),
+        ]
+    );
+
+    // second request of the period, the oracle script is paid in atom
+    app.execute_contract(
+        HumanAddr::from(CLIENT),
+        aioracle_addr.clone(),
+        &request_msg,
+        &[coin(3u128, "atom"), coin(6u128, "orai")],
+    )
+    .unwrap();
+    let request: Request = app
+        .wrap()
+        .query_wasm_smart(aioracle_addr.clone(), &QueryMsg::Request { stage: 2u64 })
+        .unwrap();
+    assert_eq!(
+        request.rewards,
+        vec![
+            (
+                HumanAddr::from("orai188efpndge9hqayll4cp9gzv0dw6rvj25e4slkp"),
+                "orai".to_string(),
+                Uint128::from(2u64)
+            ),
+            (
+                HumanAddr::from("orai18hr8jggl3xnrutfujy2jwpeu0l76azprlvgrwt"),
+                "orai".to_string(),
+                Uint128::from(2u64)
+            ),
+            (
+                HumanAddr::from("orai1nc6eqvnczmtqq8keplyrha9z7vnd5v9vvsxxgj"),
+                "atom".to_string(),
+                Uint128::from(3u64)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_service_version() {
+    let mut app = mock_app();
+    let (_, provider_addr, aioracle_addr) = setup_test_case(&mut app);
+
+    let request_msg = |service_version: Option<u64>| HandleMsg::Request {
+        threshold: 1,
+        input: None,
+        service: "price".to_string(),
+        preference_executor_fee: coin(1, "orai"),
+        min_reputation: None,
+        callback: None,
+        service_version,
+    };
+    app.execute_contract(
+        HumanAddr::from(CLIENT),
+        aioracle_addr.clone(),
+        &request_msg(None),
+        &coins(6u128, "orai"),
+    )
+    .unwrap();
+
+    // the service is updated while the first request is pending
+    let contracts = Contracts {
+        dsources: vec![HumanAddr::from("dsource_v2")],
+        tcases: vec![],
+        oscript: HumanAddr::from("oscript_v2"),
+    };
+    app.execute_contract(
+        HumanAddr::from(PROVIDER_OWNER),
+        provider_addr.clone(),
+        &provider_bridge::msg::HandleMsg::UpdateServiceContracts {
+            service: "price".to_string(),
+            contracts: contracts.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        HumanAddr::from(CLIENT),
+        aioracle_addr.clone(),
+        &request_msg(None),
+        &coins(2u128, "orai"),
+    )
+    .unwrap();
+
+    let request: Request = app
+        .wrap()
+        .query_wasm_smart(aioracle_addr.clone(), &QueryMsg::Request { stage: 1u64 })
+        .unwrap();
+    assert_eq!(request.service_version, Some(1));
+    let res: Contracts = app
+        .wrap()
+        .query_wasm_smart(&aioracle_addr, &QueryMsg::GetServiceContracts { stage: 1 })
+        .unwrap();
+    assert_eq!(
+        res.oscript,
+        HumanAddr::from("orai1nc6eqvnczmtqq8keplyrha9z7vnd5v9vvsxxgj")
+    );
+    let res: Contracts = app
+        .wrap()
+        .query_wasm_smart(&aioracle_addr, &QueryMsg::GetServiceContracts { stage: 2 })
+        .unwrap();
+    assert_eq!(res, contracts);
+
+    // the old version can still be pinned until it is deprecated
+    app.execute_contract(
+        HumanAddr::from(CLIENT),
+        aioracle_addr.clone(),
+        &request_msg(Some(1)),
+        &coins(6u128, "orai"),
+    )
+    .unwrap();
+    app.execute_contract(
+        HumanAddr::from(PROVIDER_OWNER),
+        provider_addr.clone(),
+        &provider_bridge::msg::HandleMsg::DeprecateServiceVersion {
+            service: "price".to_string(),
+            version: 1,
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from(CLIENT),
+            aioracle_addr.clone(),
+            &request_msg(Some(1)),
+            &coins(6u128, "orai"),
+        )
+        .unwrap_err(),
+        ContractError::ServiceVersionDeprecated { version: 1 }.to_string()
+    );
+    assert!(app
+        .execute_contract(
+            HumanAddr::from(CLIENT),
+            aioracle_addr.clone(),
+            &request_msg(Some(5)),
+            &coins(6u128, "orai"),
+        )
+        .is_err());
+
+    // pending requests keep their contracts
+    let res: Contracts = app
+        .wrap()
+        .query_wasm_smart(&aioracle_addr, &QueryMsg::GetServiceContracts { stage: 3 })
+        .unwrap();
+    assert_eq!(
+        res.oscript,
+        HumanAddr::from("orai1nc6eqvnczmtqq8keplyrha9z7vnd5v9vvsxxgj")
+    );
+}
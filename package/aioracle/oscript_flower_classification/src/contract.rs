@@ -1,8 +1,8 @@
 use crate::error::ContractError;
-use crate::msg::{Data, DataResult, HandleMsg, InitMsg, QueryMsg};
+use crate::msg::{HandleMsg, InitMsg, QueryMsg};
+use aioracle::{Aggregator, TopLabels};
 use cosmwasm_std::{
-    from_slice, to_binary, Binary, Deps, DepsMut, Env, HandleResponse, InitResponse, MessageInfo,
-    StdError, StdResult,
+    Binary, Deps, DepsMut, Env, HandleResponse, InitResponse, MessageInfo, StdResult,
 };
 
 // make use of the custom errors
@@ -27,34 +27,14 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     }
 }
 
+// collect the highest score label of each data source
 fn query_aggregation(_deps: Deps, results: Vec<String>) -> StdResult<Binary> {
-    let mut aggregation_result: Vec<Data> = Vec::new();
-    for result in results {
-        let result_inputs: Vec<DataResult> = from_slice(result.as_bytes())?;
-        if result_inputs.len() > 0 {
-            // collect the last data result only because it is the result from user input
-            let result_input = result_inputs.last().unwrap().clone();
-            if result_input.data.len() > 0 {
-                // only collect the highest score label
-                let mut highest_score_data: Data = Data {
-                    label: String::from(""),
-                    score: 0,
-                };
-                for data in result_input.data {
-                    if data.score.gt(&highest_score_data.score) {
-                        highest_score_data.score = data.score;
-                        highest_score_data.label = data.label;
-                    }
-                }
-                aggregation_result.push(highest_score_data)
-            }
-        }
-    }
-    Ok(to_binary(&aggregation_result)?)
+    TopLabels::default().aggregate_results(&results)
 }
 
 #[cfg(test)]
 mod tests {
+    use aioracle::LabelScore;
     use cosmwasm_std::{
         from_binary,
         testing::{mock_dependencies, mock_env, mock_info},
@@ -71,8 +51,42 @@ mod tests {
         let mut results: Vec<String> = Vec::new();
         results.push(resp);
         results.push(resp_two);
-        let final_data: Vec<Data> =
+        let final_data: Vec<LabelScore> =
             from_binary(&query_aggregation(deps.as_ref(), results.clone()).unwrap()).unwrap();
-        println!("final data: {:?}", final_data);
+        assert_eq!(
+            final_data,
+            vec![
+                LabelScore {
+                    label: String::from("foo"),
+                    score: 88,
+                },
+                LabelScore {
+                    label: String::from("foo"),
+                    score: 77,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn assert_aggregate_data_results() {
+        let deps = mock_dependencies(&[]);
+        // each data source returns its results, the last one being the result of the user input
+        let results = vec![
+            String::from(
+                r#"[{"data":[{"label":"noob","score":99}],"status":"success"},{"data":[{"label":"foo","score":88},{"label":"noob","score":66}],"status":"success"}]"#,
+            ),
+            String::from(
+                r#"[{"data":[{"label":"xyz","score":55},{"label":"foo","score":77}],"status":"success"}]"#,
+            ),
+            String::from(
+                r#"[{"data":[{"label":"foo","score":77}],"status":"success"},{"data":[],"status":"success"}]"#,
+            ),
+        ];
+        let final_data = query_aggregation(deps.as_ref(), results).unwrap();
+        assert_eq!(
+            String::from_utf8(final_data.to_vec()).unwrap(),
+            r#"[{"label":"foo","score":88},{"label":"foo","score":77}]"#
+        );
     }
 }
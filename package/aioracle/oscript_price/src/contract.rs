@@ -1,202 +1,282 @@
 use std::collections::HashMap;
-use std::num::ParseIntError;
-use std::ops::Add;
+use std::str::FromStr;
 
 use crate::error::ContractError;
-use crate::msg::{HandleMsg, InitMsg, Input, Output, QueryMsg};
+use crate::msg::{
+    AggregationMode, Confidence, HandleMsg, InitMsg, Input, Output, QueryMsg, SourceWeight,
+};
+use crate::state::{config, config_read, State};
 use cosmwasm_std::{
-    from_slice, to_binary, Binary, Deps, DepsMut, Env, HandleResponse, InitResponse, MessageInfo,
-    StdError, StdResult,
+    from_slice, to_binary, Binary, Deps, DepsMut, Env, HandleResponse, HumanAddr, InitResponse,
+    MessageInfo, StdError, StdResult, Storage,
 };
+use price::{Price, RoundingMode};
+
+// a price reported for a symbol, with the source it comes from if known
+struct SourcedPrice {
+    price: String,
+    source: Option<String>,
+}
 
-// make use of the custom errors
 pub fn init(deps: DepsMut, _env: Env, info: MessageInfo, msg: InitMsg) -> StdResult<InitResponse> {
+    let state = State {
+        owner: info.sender,
+        mode: msg.mode.unwrap_or_default(),
+        max_deviation: msg.max_deviation.unwrap_or(0),
+        weights: msg.weights.unwrap_or_default(),
+    };
+    config(deps.storage).save(&state)?;
     Ok(InitResponse::default())
 }
 
-// And declare a custom Error variant for the ones where you will want to make use of it
-// And declare a custom Error variant for the ones where you will want to make use of it
 pub fn handle(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
     msg: HandleMsg,
 ) -> Result<HandleResponse, ContractError> {
-    match msg {}
+    match msg {
+        HandleMsg::UpdateConfig {
+            owner,
+            mode,
+            max_deviation,
+            weights,
+        } => try_update_config(deps, info, owner, mode, max_deviation, weights),
+    }
+}
+
+pub fn try_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: Option<HumanAddr>,
+    mode: Option<AggregationMode>,
+    max_deviation: Option<u64>,
+    weights: Option<Vec<SourceWeight>>,
+) -> Result<HandleResponse, ContractError> {
+    let mut state = config_read(deps.storage).load()?;
+    if info.sender.ne(&state.owner) {
+        return Err(ContractError::Unauthorized {});
+    }
+    if let Some(owner) = owner {
+        state.owner = owner;
+    }
+    if let Some(mode) = mode {
+        state.mode = mode;
+    }
+    if let Some(max_deviation) = max_deviation {
+        state.max_deviation = max_deviation;
+    }
+    if let Some(weights) = weights {
+        state.weights = weights;
+    }
+    config(deps.storage).save(&state)?;
+    Ok(HandleResponse::default())
 }
 
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
-        QueryMsg::Aggregate { results } => query_aggregation(deps, results),
+        QueryMsg::Aggregate { results } => to_binary(
+            &query_aggregation(deps, results)
+                .map_err(|err| StdError::generic_err(err.to_string()))?,
+        ),
+        QueryMsg::Config {} => to_binary(&load_config(deps.storage)?),
     }
 }
 
-fn query_aggregation(_deps: Deps, results: Vec<String>) -> StdResult<Binary> {
-    let mut aggregation_result: Vec<Output> = Vec::new();
-    let result_str = aggregate_prices_str(results);
-    let price_data: Vec<Input> = from_slice(result_str.as_bytes()).unwrap();
-    for res in price_data {
-        // split to calculate largest precision of the price
-        let mut largest_precision: usize = 0;
-        for mut price in res.prices.clone() {
-            let dot_pos = get_dot_pos(price.as_str());
-            if dot_pos != 0 {
-                price = price[dot_pos..].to_string();
-                if price.len() > largest_precision {
-                    largest_precision = price.len();
-                }
-            }
-        }
-        let mut sum: u128 = 0;
-        let mut count = 0;
-        for mut price in res.prices {
-            println!("original price: {}", price);
-            let price_check = price_check(price.as_str());
-            if !price_check.0 {
-                continue;
-            }
-            let mut dot_pos = price_check.1;
-            // it means price is integer => force it to be float
-            if dot_pos == 0 {
-                dot_pos = price.len();
-                price.push_str(".0");
-            }
-            // plus one because postiion starts at 0
-            let dot_add = dot_pos.add(largest_precision + 1);
-            if price.len() > dot_add {
-                price.insert(dot_add, '.');
-                price = price[..dot_add].to_string();
-            } else {
-                while price.len() < dot_add {
-                    price.push('0');
-                }
-            }
-            price.remove(dot_pos);
-            let price_int_result: Result<u128, ParseIntError> = price.parse();
-            if price_int_result.is_err() {
-                continue;
-            }
-            let price_int = price_int_result.unwrap();
-            sum += price_int;
-            count += 1;
-        }
-        println!("sum: {}", sum);
-        let mean = sum / count;
-        let mut mean_price = mean.to_string();
-        while mean_price.len() <= largest_precision {
-            mean_price.insert(0, '0');
-        }
-        mean_price.insert(mean_price.len().wrapping_sub(largest_precision), '.');
-        println!("mean price: {}", mean_price);
+// contracts instantiated before the aggregation modes existed have no config stored
+fn load_config(storage: &dyn Storage) -> StdResult<State> {
+    Ok(config_read(storage).may_load()?.unwrap_or(State {
+        owner: HumanAddr::default(),
+        mode: AggregationMode::default(),
+        max_deviation: 0,
+        weights: vec![],
+    }))
+}
 
-        let data: Output = Output {
-            name: res.name,
-            price: mean_price,
-        };
-        aggregation_result.push(data.clone());
-    }
-    let result_bin = to_binary(&aggregation_result).unwrap();
-    Ok(result_bin)
+fn query_aggregation(deps: Deps, results: Vec<String>) -> Result<Vec<Output>, ContractError> {
+    let state = load_config(deps.storage)?;
+    merge_prices(results)
+        .into_iter()
+        .map(|(name, prices)| aggregate_symbol(&state, name, prices))
+        .collect()
 }
 
-fn get_dot_pos(price: &str) -> usize {
-    let dot_pos_options = price.find('.');
-    let dot_pos = match dot_pos_options {
-        Some(pos) => pos,
-        None => 0,
+fn aggregate_symbol(
+    state: &State,
+    name: String,
+    prices: Vec<SourcedPrice>,
+) -> Result<Output, ContractError> {
+    let total = prices.len() as u64;
+    let mut values: Vec<(Price, u64)> = prices
+        .iter()
+        .filter_map(|sourced| {
+            Price::from_str(sourced.price.as_str())
+                .ok()
+                // a negative price cannot be a valid quote
+                .filter(|price| !price.is_negative())
+                .map(|price| (price, get_weight(&state.weights, &sourced.source)))
+        })
+        .collect();
+    if values.is_empty() {
+        return Err(ContractError::NoValidPrice { name });
+    }
+    values.sort_by(|a, b| a.0.cmp(&b.0));
+    // results are given with the largest precision of the prices
+    let precision = values
+        .iter()
+        .map(|(price, _)| price.scale())
+        .max()
+        .unwrap_or(0);
+
+    if state.max_deviation > 0 {
+        let median = median(&values, precision);
+        // the deviation is relative to the median, a zero median cannot bound it so every price is kept
+        if !median.is_zero() {
+            let max_diff = &median * &Price::from(state.max_deviation);
+            let permille = Price::from(1000u64);
+            values.retain(|(price, _)| &(price - &median).abs() * &permille <= max_diff);
+        }
+    }
+
+    let result = match state.mode {
+        AggregationMode::Mean => mean(&values, precision),
+        AggregationMode::Median => median(&values, precision),
+        AggregationMode::TrimmedMean => {
+            let trim = values.len() / 4;
+            mean(&values[trim..values.len() - trim], precision)
+        }
+        AggregationMode::WeightedMean => weighted_mean(&values, precision)
+            .ok_or_else(|| ContractError::ZeroWeight { name: name.clone() })?,
     };
-    return dot_pos;
+
+    let accepted = values.len() as u64;
+    let spread = &values[values.len() - 1].0 - &values[0].0;
+    Ok(Output {
+        name,
+        price: result.rescale(precision, RoundingMode::HalfUp).to_string(),
+        confidence: Confidence {
+            accepted,
+            rejected: total - accepted,
+            spread: spread.rescale(precision, RoundingMode::HalfUp).to_string(),
+        },
+    })
 }
 
-fn price_check(price: &str) -> (bool, usize) {
-    let dot_pos = get_dot_pos(price);
-    // if there's no dot, then it may be an integer or it is not numeric
-    if dot_pos == 0 {
-        let price_check = price.parse::<u64>();
-        // if price is not integer then we return false
-        if price_check.is_err() {
-            return (false, 0);
-        }
-        return (true, 0);
+// values must be sorted and not empty
+fn median(values: &[(Price, u64)], precision: u32) -> Price {
+    let mid = values.len() / 2;
+    if values.len() % 2 == 1 {
+        values[mid].0.clone()
     } else {
-        let price_split: Vec<&str> = price.split('.').collect();
-        // in case price is 0.1.1 for example
-        if price_split.len() != 2 {
-            return (false, 0);
-        } else {
-            let price_first = price_split[0].parse::<u64>();
-            if price_first.is_err() {
-                return (false, 0);
-            } else {
-                let price_second = price_split[1].parse::<u64>();
-                if price_second.is_err() {
-                    return (false, 0);
-                }
-                return (true, dot_pos);
-            }
-        }
+        mean(&values[mid - 1..=mid], precision)
     }
 }
 
-fn aggregate_prices_str(results: Vec<String>) -> String {
-    let mut symbols: HashMap<String, Vec<String>> = HashMap::new();
+// values must not be empty
+fn mean(values: &[(Price, u64)], precision: u32) -> Price {
+    let sum: Price = values.iter().map(|(price, _)| price).sum();
+    // the count cannot be zero
+    sum.checked_div(
+        &Price::from(values.len() as u64),
+        precision,
+        RoundingMode::HalfUp,
+    )
+    .unwrap_or_default()
+}
+
+// returns None if the total weight is zero
+fn weighted_mean(values: &[(Price, u64)], precision: u32) -> Option<Price> {
+    let sum: Price = values
+        .iter()
+        .map(|(price, weight)| price * &Price::from(*weight))
+        .sum();
+    let total_weight: Price = values.iter().map(|(_, weight)| Price::from(*weight)).sum();
+    sum.checked_div(&total_weight, precision, RoundingMode::HalfUp)
+}
+
+fn get_weight(weights: &[SourceWeight], source: &Option<String>) -> u64 {
+    source
+        .as_ref()
+        .and_then(|source| weights.iter().find(|weight| weight.source.eq(source)))
+        .map(|weight| weight.weight)
+        .unwrap_or(1)
+}
+
+// merges the prices of every result by symbol, keeping the order in which the symbols appear.
+// Results that cannot be parsed are ignored
+fn merge_prices(results: Vec<String>) -> Vec<(String, Vec<SourcedPrice>)> {
+    let mut symbols: HashMap<String, Vec<SourcedPrice>> = HashMap::new();
     let mut symbol_vec: Vec<String> = Vec::new();
-    let mut inputs: Vec<Input> = Vec::new();
     for result in results {
         let price_data_result: Result<Vec<Input>, StdError> = from_slice(result.as_bytes());
-        if price_data_result.is_err() {
-            continue;
-        }
-        let price_data = price_data_result.unwrap();
-        for mut input in price_data {
-            // if first time we get symbol
-            let key = input.name.clone();
-            if !symbols.contains_key(key.as_str()) {
-                let name = key.clone();
-                symbols.insert(name, input.clone().prices);
+        let price_data = match price_data_result {
+            Ok(price_data) => price_data,
+            Err(_) => continue,
+        };
+        for input in price_data {
+            // sources are ignored if they do not match the prices one by one
+            let price_count = input.prices.len();
+            let sources = input.sources.filter(|sources| sources.len() == price_count);
+            if !symbols.contains_key(input.name.as_str()) {
                 symbol_vec.push(input.name.clone());
-            } else {
-                let mut temp_vec = vec![String::from("")];
-                let mut symbols_clone = symbols.clone();
-                let prices = match symbols_clone.get_mut(input.name.as_str()) {
-                    Some(prices) => prices,
-                    None => temp_vec.as_mut(),
-                };
-                if prices.is_empty() {
-                    continue;
-                }
-                prices.append(input.prices.as_mut());
-                symbols.remove(input.name.as_str());
-                symbols.insert(input.name, prices.clone());
+            }
+            let prices = symbols.entry(input.name).or_default();
+            for (i, price) in input.prices.into_iter().enumerate() {
+                prices.push(SourcedPrice {
+                    price,
+                    source: sources.as_ref().map(|sources| sources[i].clone()),
+                });
             }
         }
     }
-    for symbol in symbol_vec {
-        let mut temp_vec = vec![String::from("")];
-        let prices = match symbols.get(symbol.as_str()) {
-            Some(prices) => prices,
-            None => temp_vec.as_mut(),
-        };
-        if prices.is_empty() {
-            continue;
-        }
-        let input: Input = Input {
-            name: symbol.to_string(),
-            prices: prices.clone(),
-        };
-        inputs.push(input);
-    }
-    let response_bin = to_binary(&inputs).unwrap();
-    let response_str = String::from_utf8(response_bin.to_vec()).unwrap();
-    return response_str;
+    symbol_vec
+        .into_iter()
+        .filter_map(|symbol| {
+            let prices = symbols.remove(symbol.as_str())?;
+            if prices.is_empty() {
+                return None;
+            }
+            Some((symbol, prices))
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{from_binary, OwnedDeps};
 
     use super::*;
 
+    fn setup_contract(
+        mode: AggregationMode,
+        max_deviation: u64,
+        weights: Vec<SourceWeight>,
+    ) -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies(&[]);
+        init(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            InitMsg {
+                mode: Some(mode),
+                max_deviation: Some(max_deviation),
+                weights: Some(weights),
+            },
+        )
+        .unwrap();
+        deps
+    }
+
+    fn aggregate(deps: Deps, results: Vec<String>) -> StdResult<Vec<Output>> {
+        from_binary(&query(deps, mock_env(), QueryMsg::Aggregate { results })?)
+    }
+
+    fn price_result(name: &str, prices: &[&str]) -> String {
+        format!("[{{\"name\":\"{}\",\"prices\":{:?}}}]", name, prices)
+    }
+
     #[test]
     fn assert_aggregate() {
         let deps = mock_dependencies(&[]);
@@ -215,10 +295,177 @@ mod tests {
         results.push(resp_two);
         results.push(resp_three);
         results.push(resp_four);
-        let final_str = aggregate_prices_str(results.clone());
-        println!("final string: {}", final_str);
-        let query_result = query_aggregation(deps.as_ref(), results).unwrap();
-        let query_result_str = query_result.to_string();
-        println!("query result str: {}", query_result_str);
+        let merged = merge_prices(results.clone());
+        assert_eq!(
+            merged
+                .iter()
+                .map(|(name, prices)| (name.as_str(), prices.len()))
+                .collect::<Vec<_>>(),
+            vec![("ETH", 6), ("BTC", 2), ("LINK", 2), ("ORAI", 8)]
+        );
+
+        // contracts without config fall back to the plain mean
+        let outputs = aggregate(deps.as_ref(), results).unwrap();
+        let prices: Vec<(&str, &str)> = outputs
+            .iter()
+            .map(|output| (output.name.as_str(), output.price.as_str()))
+            .collect();
+        assert_eq!(
+            prices,
+            vec![
+                ("ETH", "0.16666667103672967"),
+                ("BTC", "1500.6671"),
+                ("LINK", "44"),
+                ("ORAI", "100.6671")
+            ]
+        );
+        assert_eq!(
+            outputs[3].confidence,
+            Confidence {
+                accepted: 2,
+                rejected: 6,
+                spread: String::from("198.8659"),
+            }
+        );
+    }
+
+    #[test]
+    fn test_median_rejects_outliers() {
+        let deps = setup_contract(AggregationMode::Median, 100, vec![]);
+        let outputs = aggregate(
+            deps.as_ref(),
+            vec![
+                price_result("ETH", &["100", "101"]),
+                price_result("ETH", &["99", "1000"]),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            outputs,
+            vec![Output {
+                name: String::from("ETH"),
+                price: String::from("100"),
+                confidence: Confidence {
+                    accepted: 3,
+                    rejected: 1,
+                    spread: String::from("2"),
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn test_zero_median_keeps_prices() {
+        let deps = setup_contract(AggregationMode::Mean, 100, vec![]);
+        let outputs = aggregate(
+            deps.as_ref(),
+            vec![price_result("ETH", &["0", "0", "0", "4"])],
+        )
+        .unwrap();
+        assert_eq!(
+            outputs,
+            vec![Output {
+                name: String::from("ETH"),
+                price: String::from("1"),
+                confidence: Confidence {
+                    accepted: 4,
+                    rejected: 0,
+                    spread: String::from("4"),
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn test_trimmed_mean() {
+        let deps = setup_contract(AggregationMode::TrimmedMean, 0, vec![]);
+        let outputs = aggregate(
+            deps.as_ref(),
+            vec![price_result(
+                "ETH",
+                &[
+                    "1.0", "10.0", "11.0", "12.0", "13.0", "14.0", "15.0", "100.0",
+                ],
+            )],
+        )
+        .unwrap();
+        assert_eq!(outputs[0].price, "12.5");
+        assert_eq!(outputs[0].confidence.accepted, 8);
+        assert_eq!(outputs[0].confidence.spread, "99.0");
+    }
+
+    #[test]
+    fn test_weighted_mean() {
+        let deps = setup_contract(
+            AggregationMode::WeightedMean,
+            0,
+            vec![
+                SourceWeight {
+                    source: String::from("binance"),
+                    weight: 3,
+                },
+                SourceWeight {
+                    source: String::from("unreliable"),
+                    weight: 0,
+                },
+            ],
+        );
+        let outputs = aggregate(
+            deps.as_ref(),
+            vec![
+                String::from("[{\"name\":\"ETH\",\"prices\":[\"10.0\",\"20.0\",\"500.0\"],\"sources\":[\"binance\",\"coingecko\",\"unreliable\"]}]"),
+                // prices without sources have a weight of 1
+                price_result("ETH", &["10.0"]),
+            ],
+        )
+        .unwrap();
+        // (10 * 3 + 20 + 10) / 5
+        assert_eq!(outputs[0].price, "12.0");
+
+        // every price has a weight of zero
+        let err = aggregate(
+            deps.as_ref(),
+            vec![String::from(
+                "[{\"name\":\"ETH\",\"prices\":[\"500.0\"],\"sources\":[\"unreliable\"]}]",
+            )],
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Total weight of the prices for ETH is zero")
+        );
+    }
+
+    #[test]
+    fn test_no_valid_price() {
+        let deps = setup_contract(AggregationMode::Mean, 0, vec![]);
+        let err =
+            aggregate(deps.as_ref(), vec![price_result("ETH", &["a.b", "1.1.1"])]).unwrap_err();
+        assert_eq!(err, StdError::generic_err("No valid price for ETH"));
+    }
+
+    #[test]
+    fn test_update_config() {
+        let mut deps = setup_contract(AggregationMode::Mean, 0, vec![]);
+        let msg = HandleMsg::UpdateConfig {
+            owner: None,
+            mode: Some(AggregationMode::Median),
+            max_deviation: Some(50),
+            weights: None,
+        };
+        let err = handle(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("someone", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        handle(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        let state: State =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+        assert_eq!(state.mode, AggregationMode::Median);
+        assert_eq!(state.max_deviation, 50);
     }
 }
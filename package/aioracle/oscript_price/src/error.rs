@@ -8,6 +8,10 @@ pub enum ContractError {
 
     #[error("Unauthorized")]
     Unauthorized {},
-    // Add any other custom errors you like here.
-    // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
+
+    #[error("No valid price for {name}")]
+    NoValidPrice { name: String },
+
+    #[error("Total weight of the prices for {name} is zero")]
+    ZeroWeight { name: String },
 }
@@ -1,14 +1,40 @@
+use cosmwasm_std::HumanAddr;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct InitMsg {}
+pub struct InitMsg {
+    pub mode: Option<AggregationMode>,
+    pub max_deviation: Option<u64>,
+    pub weights: Option<Vec<SourceWeight>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregationMode {
+    #[default]
+    Mean,
+    Median,
+    /// mean of the prices left once the lowest and highest quarters have been dropped
+    TrimmedMean,
+    /// mean weighted by the source of each price
+    WeightedMean,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct SourceWeight {
+    pub source: String,
+    pub weight: u64,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct Input {
     pub name: String,
     pub prices: Vec<String>,
+    /// source of each price, in the same order as the prices
+    pub sources: Option<Vec<String>>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -16,14 +42,34 @@ pub struct Input {
 pub struct Output {
     pub name: String,
     pub price: String,
+    pub confidence: Confidence,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct Confidence {
+    /// number of prices used to compute the result
+    pub accepted: u64,
+    /// number of prices that could not be parsed or deviated too much from the median
+    pub rejected: u64,
+    /// difference between the highest and the lowest accepted price
+    pub spread: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub enum HandleMsg {}
+pub enum HandleMsg {
+    UpdateConfig {
+        owner: Option<HumanAddr>,
+        mode: Option<AggregationMode>,
+        max_deviation: Option<u64>,
+        weights: Option<Vec<SourceWeight>>,
+    },
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Aggregate { results: Vec<String> },
+    Config {},
 }
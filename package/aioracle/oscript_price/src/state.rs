@@ -0,0 +1,26 @@
+use cosmwasm_std::{HumanAddr, Storage};
+use cosmwasm_storage::{singleton, singleton_read, ReadonlySingleton, Singleton};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::msg::{AggregationMode, SourceWeight};
+
+pub static CONFIG_KEY: &[u8] = b"config";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct State {
+    pub owner: HumanAddr,
+    pub mode: AggregationMode,
+    /// maximum deviation from the median in permille before a price is rejected. 0 disables the rejection
+    pub max_deviation: u64,
+    /// weights used by the weighted mean. Sources not listed here have a weight of 1
+    pub weights: Vec<SourceWeight>,
+}
+
+pub fn config(storage: &mut dyn Storage) -> Singleton<'_, State> {
+    singleton(storage, CONFIG_KEY)
+}
+
+pub fn config_read(storage: &dyn Storage) -> ReadonlySingleton<'_, State> {
+    singleton_read(storage, CONFIG_KEY)
+}
@@ -1,16 +1,28 @@
 use crate::error::ContractError;
 use crate::msg::{GetServiceFees, HandleMsg, InitMsg, MigrateMsg, QueryMsg};
 use crate::state::{
-    Contracts, BOUND_EXECUTOR_FEE, OWNER, SERVICE_CONTRACTS, SERVICE_FEES_CONTRACT,
+    Contracts, ServiceVersion, BOUND_EXECUTOR_FEE, CURRENT_SERVICE_VERSIONS, OWNER,
+    SERVICE_CONTRACTS, SERVICE_FEES_CONTRACT, SERVICE_POLICIES, SERVICE_VERSIONS,
 };
-use aioracle_base::{GetServiceFeesMsg, Reward, ServiceFeesResponse};
+use aioracle_base::{GetServiceFeesMsg, Reward, ServiceFeesResponse, ServicePolicy, VersionStatus};
 use cosmwasm_std::{
     attr, to_binary, Binary, Coin, Deps, DepsMut, Env, HandleResponse, HumanAddr, InitResponse,
-    MessageInfo, MigrateResponse, StdResult,
+    MessageInfo, MigrateResponse, Order, StdError, StdResult, Storage,
 };
+use cw_storage_plus::{Bound, U64Key};
 
-pub fn init(deps: DepsMut, _env: Env, info: MessageInfo, msg: InitMsg) -> StdResult<InitResponse> {
-    SERVICE_CONTRACTS.save(deps.storage, msg.service.as_bytes(), &msg.service_contracts)?;
+// settings for pagination
+const MAX_LIMIT: u8 = 50;
+const DEFAULT_LIMIT: u8 = 20;
+
+pub fn init(deps: DepsMut, env: Env, info: MessageInfo, msg: InitMsg) -> StdResult<InitResponse> {
+    let version = register_service_version(
+        deps.storage,
+        &msg.service,
+        msg.service_contracts,
+        env.block.height,
+    )?;
+    CURRENT_SERVICE_VERSIONS.save(deps.storage, msg.service.as_bytes(), &version)?;
     SERVICE_FEES_CONTRACT.save(deps.storage, &msg.service_fees_contract)?;
     OWNER.save(deps.storage, &info.sender)?;
     BOUND_EXECUTOR_FEE.save(
@@ -26,13 +38,25 @@ pub fn init(deps: DepsMut, _env: Env, info: MessageInfo, msg: InitMsg) -> StdRes
 // And declare a custom Error variant for the ones where you will want to make use of it
 pub fn handle(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: HandleMsg,
 ) -> Result<HandleResponse, ContractError> {
     match msg {
         HandleMsg::UpdateServiceContracts { service, contracts } => {
-            handle_update_service_contracts(deps, info, service, contracts)
+            handle_update_service_contracts(deps, env, info, service, contracts)
+        }
+        HandleMsg::RegisterServiceVersion { service, contracts } => {
+            handle_register_service_version(deps, env, info, service, contracts)
+        }
+        HandleMsg::ActivateServiceVersion { service, version } => {
+            handle_activate_service_version(deps, info, service, version)
+        }
+        HandleMsg::DeprecateServiceVersion { service, version } => {
+            handle_deprecate_service_version(deps, info, service, version)
+        }
+        HandleMsg::UpdateServicePolicy { service, policy } => {
+            handle_update_service_policy(deps, info, service, policy)
         }
         HandleMsg::UpdateConfig {
             owner,
@@ -43,8 +67,8 @@ pub fn handle(
 }
 
 pub fn migrate(
-    _deps: DepsMut,
-    _env: Env,
+    deps: DepsMut,
+    env: Env,
     _info: MessageInfo,
     _msg: MigrateMsg,
 ) -> StdResult<MigrateResponse> {
@@ -57,6 +81,24 @@ pub fn migrate(
 
     // migrate_v02_to_v03(deps.storage, msg)?;
 
+    // the contracts of the services registered before versioning become their first version
+    let services: StdResult<Vec<(Vec<u8>, Contracts)>> = SERVICE_CONTRACTS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect();
+    for (service, contracts) in services? {
+        if CURRENT_SERVICE_VERSIONS
+            .may_load(deps.storage, &service)?
+            .is_some()
+        {
+            continue;
+        }
+        let service = String::from_utf8(service)?;
+        let version =
+            register_service_version(deps.storage, &service, contracts, env.block.height)?;
+        CURRENT_SERVICE_VERSIONS.save(deps.storage, service.as_bytes(), &version)?;
+        SERVICE_CONTRACTS.remove(deps.storage, service.as_bytes());
+    }
+
     // once we have "migrated", set the new version and return success
     Ok(MigrateResponse {
         attributes: vec![],
@@ -93,10 +135,25 @@ pub fn handle_update_config(
 
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
-        QueryMsg::ServiceContractsMsg { service } => {
-            to_binary(&get_service_contracts(deps, service)?)
+        QueryMsg::ServiceContractsMsg { service, version } => {
+            to_binary(&get_service_contracts(deps, service, version)?)
+        }
+        QueryMsg::ServiceFeeMsg { service, version } => {
+            to_binary(&get_service_fees(deps, service, version)?)
+        }
+        QueryMsg::ServiceFeeScheduleMsg { service, version } => {
+            to_binary(&get_service_fee_schedules(deps, service, version)?)
+        }
+        QueryMsg::ServiceVersionMsg { service, version } => {
+            to_binary(&load_service_version(deps.storage, &service, version)?)
         }
-        QueryMsg::ServiceFeeMsg { service } => to_binary(&get_service_fees(deps, service)?),
+        QueryMsg::ServiceVersionsMsg {
+            service,
+            offset,
+            limit,
+            order,
+        } => to_binary(&get_service_versions(deps, service, offset, limit, order)?),
+        QueryMsg::ServicePolicyMsg { service } => to_binary(&get_service_policy(deps, service)?),
         QueryMsg::GetParticipantFee { addr } => to_binary(&get_participant_fee(deps, addr)?),
         QueryMsg::GetBoundExecutorFee {} => to_binary(&get_bound_executor_fee(deps)?),
     }
@@ -108,6 +165,7 @@ pub fn get_bound_executor_fee(deps: Deps) -> StdResult<Coin> {
 
 pub fn handle_update_service_contracts(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     service: String,
     contracts: Contracts,
@@ -116,36 +174,253 @@ pub fn handle_update_service_contracts(
     if info.sender.ne(&owner) {
         return Err(ContractError::Unauthorized {});
     }
-    SERVICE_CONTRACTS.save(deps.storage, service.as_bytes(), &contracts)?;
+    let version = register_service_version(deps.storage, &service, contracts, env.block.height)?;
+    CURRENT_SERVICE_VERSIONS.save(deps.storage, service.as_bytes(), &version)?;
     Ok(HandleResponse {
         attributes: vec![
             attr("action", "update_service_contracts"),
             attr("service", service),
+            attr("version", version),
+        ],
+        ..HandleResponse::default()
+    })
+}
+
+pub fn handle_register_service_version(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    service: String,
+    contracts: Contracts,
+) -> Result<HandleResponse, ContractError> {
+    let owner = OWNER.load(deps.storage)?;
+    if info.sender.ne(&owner) {
+        return Err(ContractError::Unauthorized {});
+    }
+    let version = register_service_version(deps.storage, &service, contracts, env.block.height)?;
+    Ok(HandleResponse {
+        attributes: vec![
+            attr("action", "register_service_version"),
+            attr("service", service),
+            attr("version", version),
+        ],
+        ..HandleResponse::default()
+    })
+}
+
+pub fn handle_activate_service_version(
+    deps: DepsMut,
+    info: MessageInfo,
+    service: String,
+    version: u64,
+) -> Result<HandleResponse, ContractError> {
+    let owner = OWNER.load(deps.storage)?;
+    if info.sender.ne(&owner) {
+        return Err(ContractError::Unauthorized {});
+    }
+    SERVICE_VERSIONS.update(
+        deps.storage,
+        (service.as_bytes(), U64Key::new(version)),
+        |service_version| -> StdResult<_> {
+            let mut service_version = service_version.ok_or(StdError::not_found(
+                "provider_bridge::state::ServiceVersion",
+            ))?;
+            service_version.status = VersionStatus::Active;
+            Ok(service_version)
+        },
+    )?;
+    CURRENT_SERVICE_VERSIONS.save(deps.storage, service.as_bytes(), &version)?;
+    Ok(HandleResponse {
+        attributes: vec![
+            attr("action", "activate_service_version"),
+            attr("service", service),
+            attr("version", version),
+        ],
+        ..HandleResponse::default()
+    })
+}
+
+pub fn handle_deprecate_service_version(
+    deps: DepsMut,
+    info: MessageInfo,
+    service: String,
+    version: u64,
+) -> Result<HandleResponse, ContractError> {
+    let owner = OWNER.load(deps.storage)?;
+    if info.sender.ne(&owner) {
+        return Err(ContractError::Unauthorized {});
+    }
+    // requests not pinning a version must always have one to run against
+    if CURRENT_SERVICE_VERSIONS
+        .may_load(deps.storage, service.as_bytes())?
+        .eq(&Some(version))
+    {
+        return Err(ContractError::DeprecateCurrentVersion { version });
+    }
+    SERVICE_VERSIONS.update(
+        deps.storage,
+        (service.as_bytes(), U64Key::new(version)),
+        |service_version| -> StdResult<_> {
+            let mut service_version = service_version.ok_or(StdError::not_found(
+                "provider_bridge::state::ServiceVersion",
+            ))?;
+            service_version.status = VersionStatus::Deprecated;
+            Ok(service_version)
+        },
+    )?;
+    Ok(HandleResponse {
+        attributes: vec![
+            attr("action", "deprecate_service_version"),
+            attr("service", service),
+            attr("version", version),
+        ],
+        ..HandleResponse::default()
+    })
+}
+
+/// stores the contracts as the next version of the service
+fn register_service_version(
+    storage: &mut dyn Storage,
+    service: &str,
+    contracts: Contracts,
+    height: u64,
+) -> StdResult<u64> {
+    let latest_version = SERVICE_VERSIONS
+        .prefix(service.as_bytes())
+        .range(storage, None, None, Order::Descending)
+        .next()
+        .transpose()?
+        .map(|(_, service_version)| service_version.version)
+        .unwrap_or_default();
+    let version = latest_version + 1;
+    SERVICE_VERSIONS.save(
+        storage,
+        (service.as_bytes(), U64Key::new(version)),
+        &ServiceVersion {
+            version,
+            contracts,
+            status: VersionStatus::Active,
+            created_height: height,
+        },
+    )?;
+    Ok(version)
+}
+
+fn load_service_version(
+    storage: &dyn Storage,
+    service: &str,
+    version: Option<u64>,
+) -> StdResult<ServiceVersion> {
+    let version = match version {
+        Some(version) => version,
+        None => CURRENT_SERVICE_VERSIONS.load(storage, service.as_bytes())?,
+    };
+    SERVICE_VERSIONS.load(storage, (service.as_bytes(), U64Key::new(version)))
+}
+
+pub fn handle_update_service_policy(
+    deps: DepsMut,
+    info: MessageInfo,
+    service: String,
+    policy: ServicePolicy,
+) -> Result<HandleResponse, ContractError> {
+    let owner = OWNER.load(deps.storage)?;
+    if info.sender.ne(&owner) {
+        return Err(ContractError::Unauthorized {});
+    }
+    SERVICE_POLICIES.save(deps.storage, service.as_bytes(), &policy)?;
+    Ok(HandleResponse {
+        attributes: vec![
+            attr("action", "update_service_policy"),
+            attr("service", service),
         ],
         ..HandleResponse::default()
     })
 }
 
-fn get_service_contracts(deps: Deps, service: String) -> StdResult<Contracts> {
-    let contracts = SERVICE_CONTRACTS.load(deps.storage, service.as_bytes())?;
+fn get_service_policy(deps: Deps, service: String) -> StdResult<ServicePolicy> {
+    let policy = SERVICE_POLICIES
+        .may_load(deps.storage, service.as_bytes())?
+        .unwrap_or_default();
+    Ok(policy)
+}
+
+fn get_service_contracts(
+    deps: Deps,
+    service: String,
+    version: Option<u64>,
+) -> StdResult<Contracts> {
+    let ServiceVersion { contracts, .. } = load_service_version(deps.storage, &service, version)?;
     Ok(contracts)
 }
 
-fn get_service_fees(deps: Deps, service: String) -> StdResult<Vec<Reward>> {
-    let contracts = SERVICE_CONTRACTS.load(deps.storage, service.as_bytes())?;
-    let mut rewards = vec![];
+fn get_service_versions(
+    deps: Deps,
+    service: String,
+    offset: Option<u64>,
+    limit: Option<u8>,
+    order: Option<u8>,
+) -> StdResult<Vec<ServiceVersion>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let mut min: Option<Bound> = None;
+    let mut max: Option<Bound> = None;
+    let mut order_enum = Order::Ascending;
+    if let Some(num) = order {
+        if num == 2 {
+            order_enum = Order::Descending;
+        }
+    }
+
+    // if there is offset, assign to min or max
+    if let Some(offset) = offset {
+        let offset_value = Some(Bound::Exclusive(offset.to_be_bytes().to_vec()));
+        match order_enum {
+            Order::Ascending => min = offset_value,
+            Order::Descending => max = offset_value,
+        }
+    };
+    SERVICE_VERSIONS
+        .prefix(service.as_bytes())
+        .range(deps.storage, min, max, order_enum)
+        .take(limit)
+        .map(|item| item.map(|(_, service_version)| service_version))
+        .collect()
+}
+
+fn get_service_fees(deps: Deps, service: String, version: Option<u64>) -> StdResult<Vec<Reward>> {
+    let rewards = get_service_fee_schedules(deps, service, version)?
+        .into_iter()
+        .map(|fees| {
+            (
+                HumanAddr::from(fees.address),
+                fees.fees.denom,
+                fees.fees.amount,
+            )
+        })
+        .collect();
+    Ok(rewards)
+}
+
+/// fees of the providers of the service, including their fee schedules so the request can be priced
+fn get_service_fee_schedules(
+    deps: Deps,
+    service: String,
+    version: Option<u64>,
+) -> StdResult<Vec<ServiceFeesResponse>> {
+    let contracts = get_service_contracts(deps, service, version)?;
+    let mut fees = vec![];
     let service_fees_contract = SERVICE_FEES_CONTRACT.load(deps.storage)?;
-    rewards.append(&mut collect_rewards(
+    fees.append(&mut collect_service_fees(
         deps,
         &contracts.dsources,
         &service_fees_contract,
     )?);
-    rewards.append(&mut collect_rewards(
+    fees.append(&mut collect_service_fees(
         deps,
         &contracts.tcases,
         &service_fees_contract,
     )?);
-    rewards.append(&mut collect_rewards(
+    fees.append(&mut collect_service_fees(
         deps,
         &vec![contracts.oscript],
         &service_fees_contract,
@@ -159,7 +434,7 @@ fn get_service_fees(deps: Deps, service: String) -> StdResult<Vec<Reward>> {
     //     bound_executor_fee.amount,
     // ));
 
-    Ok(rewards)
+    Ok(fees)
 }
 
 fn get_participant_fee(deps: Deps, addr: HumanAddr) -> StdResult<Coin> {
@@ -178,12 +453,12 @@ fn get_participant_fee(deps: Deps, addr: HumanAddr) -> StdResult<Coin> {
     })
 }
 
-fn collect_rewards(
+fn collect_service_fees(
     deps: Deps,
     addrs: &[HumanAddr],
     service_fees_contract: &HumanAddr,
-) -> StdResult<Vec<Reward>> {
-    let mut rewards = vec![];
+) -> StdResult<Vec<ServiceFeesResponse>> {
+    let mut fees = vec![];
     for addr in addrs {
         let reward_result: StdResult<ServiceFeesResponse> = deps.querier.query_wasm_smart(
             service_fees_contract.clone(),
@@ -193,15 +468,11 @@ fn collect_rewards(
                 },
             },
         );
-        if !reward_result.is_err() {
-            let reward = reward_result.unwrap();
-            rewards.push((
-                HumanAddr::from(reward.address),
-                reward.fees.denom,
-                reward.fees.amount,
-            ));
+        // contracts without fees are skipped
+        if let Ok(reward) = reward_result {
+            fees.push(reward);
         }
     }
 
-    Ok(rewards)
+    Ok(fees)
 }
@@ -1,4 +1,4 @@
-use aioracle_base::GetServiceFeesMsg;
+use aioracle_base::{GetServiceFeesMsg, ServicePolicy};
 use cosmwasm_std::{Binary, Coin, HumanAddr, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -31,10 +31,28 @@ pub struct Response {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum HandleMsg {
+    /// registers a new version of the service contracts & makes it the current one
     UpdateServiceContracts {
         service: String,
         contracts: Contracts,
     },
+    /// registers a new version of the service contracts that requests can pin, without making it the current one
+    RegisterServiceVersion {
+        service: String,
+        contracts: Contracts,
+    },
+    ActivateServiceVersion {
+        service: String,
+        version: u64,
+    },
+    DeprecateServiceVersion {
+        service: String,
+        version: u64,
+    },
+    UpdateServicePolicy {
+        service: String,
+        policy: ServicePolicy,
+    },
     UpdateConfig {
         owner: Option<HumanAddr>,
         service_fees_contract: Option<HumanAddr>,
@@ -50,9 +68,34 @@ pub struct MigrateMsg {}
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    ServiceContractsMsg { service: String },
-    ServiceFeeMsg { service: String },
-    GetParticipantFee { addr: HumanAddr },
+    ServiceContractsMsg {
+        service: String,
+        version: Option<u64>,
+    },
+    ServiceFeeMsg {
+        service: String,
+        version: Option<u64>,
+    },
+    ServiceFeeScheduleMsg {
+        service: String,
+        version: Option<u64>,
+    },
+    ServiceVersionMsg {
+        service: String,
+        version: Option<u64>,
+    },
+    ServiceVersionsMsg {
+        service: String,
+        offset: Option<u64>,
+        limit: Option<u8>,
+        order: Option<u8>,
+    },
+    ServicePolicyMsg {
+        service: String,
+    },
+    GetParticipantFee {
+        addr: HumanAddr,
+    },
     GetBoundExecutorFee {},
 }
 
@@ -1,5 +1,6 @@
+use aioracle_base::{ServicePolicy, VersionStatus};
 use cosmwasm_std::{Coin, HumanAddr};
-use cw_storage_plus::{Item, Map};
+use cw_storage_plus::{Item, Map, U64Key};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -11,7 +12,23 @@ pub struct Contracts {
     pub oscript: HumanAddr,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ServiceVersion {
+    pub version: u64,
+    pub contracts: Contracts,
+    pub status: VersionStatus,
+    /// block height at which the version has been registered
+    pub created_height: u64,
+}
+
+// contracts of the services before they were versioned, only read when migrating
 pub const SERVICE_CONTRACTS: Map<&[u8], Contracts> = Map::new("service_contracts");
+// key: service + version. Versions are immutable once registered, only their status changes
+pub const SERVICE_VERSIONS: Map<(&[u8], U64Key), ServiceVersion> = Map::new("service_versions");
+// version used by the requests not pinning one
+pub const CURRENT_SERVICE_VERSIONS: Map<&[u8], u64> = Map::new("current_service_versions");
+pub const SERVICE_POLICIES: Map<&[u8], ServicePolicy> = Map::new("service_policies");
 pub const SERVICE_FEES_CONTRACT: Item<HumanAddr> = Item::new("service_fees_contract");
 pub const BOUND_EXECUTOR_FEE: Item<Coin> = Item::new("bound_executor_fee");
 
@@ -1,9 +1,11 @@
-use crate::msg::{InitMsg, QueryMsg};
-use crate::state::Contracts;
+use crate::contract::{migrate, query};
+use crate::error::ContractError;
+use crate::msg::{HandleMsg, InitMsg, MigrateMsg, QueryMsg};
+use crate::state::{Contracts, ServiceVersion, SERVICE_CONTRACTS};
 
-use aioracle_base::Reward;
-use cosmwasm_std::testing::{mock_env, MockApi, MockStorage};
-use cosmwasm_std::{coin, coins, HumanAddr, Uint128};
+use aioracle_base::{Reward, ServicePolicy, VersionStatus};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockStorage};
+use cosmwasm_std::{coin, coins, from_binary, Binary, HumanAddr, Uint128};
 use cw_multi_test::{next_block, App, Contract, ContractWrapper, SimpleBank};
 
 const PROVIDER_OWNER: &str = "admin0001";
@@ -139,9 +141,219 @@ fn proper_instantiation() {
             provider,
             &QueryMsg::ServiceFeeMsg {
                 service: String::from("price"),
+                version: None,
             },
         )
         .unwrap();
 
     println!("fees: {:?}", fees);
 }
+
+#[test]
+fn test_update_service_policy() {
+    let mut app = mock_app();
+    let (_, provider) = setup_test_case(&mut app);
+
+    // no policy by default
+    let policy: ServicePolicy = app
+        .wrap()
+        .query_wasm_smart(
+            provider.clone(),
+            &QueryMsg::ServicePolicyMsg {
+                service: String::from("price"),
+            },
+        )
+        .unwrap();
+    assert_eq!(policy, ServicePolicy::default());
+
+    let new_policy = ServicePolicy {
+        min_threshold: Some(2),
+        executors: Some(vec![Binary::from_base64(
+            "AipQCudhlHpWnHjSgVKZ+SoSicvjH7Mp5gCFyDdlnQtn",
+        )
+        .unwrap()]),
+        max_fee: Some(coin(10u128, "orai")),
+    };
+    let msg = HandleMsg::UpdateServicePolicy {
+        service: String::from("price"),
+        policy: new_policy.clone(),
+    };
+
+    // only the owner can update the policy
+    assert_eq!(
+        app.execute_contract(HumanAddr::from(CLIENT), provider.clone(), &msg, &[])
+            .is_err(),
+        true
+    );
+    app.execute_contract(HumanAddr::from(PROVIDER_OWNER), provider.clone(), &msg, &[])
+        .unwrap();
+
+    let policy: ServicePolicy = app
+        .wrap()
+        .query_wasm_smart(
+            provider,
+            &QueryMsg::ServicePolicyMsg {
+                service: String::from("price"),
+            },
+        )
+        .unwrap();
+    assert_eq!(policy, new_policy);
+}
+
+#[test]
+fn test_service_versions() {
+    let mut app = mock_app();
+    let (_, provider) = setup_test_case(&mut app);
+    let contracts = Contracts {
+        dsources: vec![HumanAddr::from("dsource_v2")],
+        tcases: vec![HumanAddr::from("tcase_v2")],
+        oscript: HumanAddr::from("oscript_v2"),
+    };
+    let query_contracts = |app: &App, version: Option<u64>| -> Contracts {
+        app.wrap()
+            .query_wasm_smart(
+                provider.clone(),
+                &QueryMsg::ServiceContractsMsg {
+                    service: String::from("price"),
+                    version,
+                },
+            )
+            .unwrap()
+    };
+
+    // a registered version can be pinned but is not the current one yet
+    let msg = HandleMsg::RegisterServiceVersion {
+        service: String::from("price"),
+        contracts: contracts.clone(),
+    };
+    assert_eq!(
+        app.execute_contract(HumanAddr::from(CLIENT), provider.clone(), &msg, &[])
+            .unwrap_err(),
+        ContractError::Unauthorized {}.to_string()
+    );
+    app.execute_contract(HumanAddr::from(PROVIDER_OWNER), provider.clone(), &msg, &[])
+        .unwrap();
+    assert_eq!(
+        query_contracts(&app, None).oscript,
+        HumanAddr::from("orai1nc6eqvnczmtqq8keplyrha9z7vnd5v9vvsxxgj")
+    );
+    assert_eq!(query_contracts(&app, Some(2)), contracts);
+
+    // the current version cannot be deprecated
+    assert_eq!(
+        app.execute_contract(
+            HumanAddr::from(PROVIDER_OWNER),
+            provider.clone(),
+            &HandleMsg::DeprecateServiceVersion {
+                service: String::from("price"),
+                version: 1,
+            },
+            &[],
+        )
+        .unwrap_err(),
+        ContractError::DeprecateCurrentVersion { version: 1 }.to_string()
+    );
+
+    // roll out the second version, then deprecate the first one
+    app.execute_contract(
+        HumanAddr::from(PROVIDER_OWNER),
+        provider.clone(),
+        &HandleMsg::ActivateServiceVersion {
+            service: String::from("price"),
+            version: 2,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        HumanAddr::from(PROVIDER_OWNER),
+        provider.clone(),
+        &HandleMsg::DeprecateServiceVersion {
+            service: String::from("price"),
+            version: 1,
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_contracts(&app, None), contracts);
+
+    // versions are immutable, updating the contracts adds a third version
+    app.execute_contract(
+        HumanAddr::from(PROVIDER_OWNER),
+        provider.clone(),
+        &HandleMsg::UpdateServiceContracts {
+            service: String::from("price"),
+            contracts: contracts.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+    let current: ServiceVersion = app
+        .wrap()
+        .query_wasm_smart(
+            provider.clone(),
+            &QueryMsg::ServiceVersionMsg {
+                service: String::from("price"),
+                version: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(current.version, 3);
+
+    let versions: Vec<ServiceVersion> = app
+        .wrap()
+        .query_wasm_smart(
+            provider.clone(),
+            &QueryMsg::ServiceVersionsMsg {
+                service: String::from("price"),
+                offset: Some(3),
+                limit: None,
+                order: Some(2),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        versions
+            .iter()
+            .map(|version| (version.version, version.status.clone()))
+            .collect::<Vec<(u64, VersionStatus)>>(),
+        vec![(2, VersionStatus::Active), (1, VersionStatus::Deprecated)]
+    );
+}
+
+#[test]
+fn test_migrate_service_versions() {
+    let mut deps = mock_dependencies(&[]);
+    let contracts = Contracts {
+        dsources: vec![HumanAddr::from("dsource")],
+        tcases: vec![HumanAddr::from("tcase")],
+        oscript: HumanAddr::from("oscript"),
+    };
+    SERVICE_CONTRACTS
+        .save(deps.as_mut().storage, b"price", &contracts)
+        .unwrap();
+
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(PROVIDER_OWNER, &[]),
+        MigrateMsg {},
+    )
+    .unwrap();
+
+    let current: ServiceVersion = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ServiceVersionMsg {
+                service: String::from("price"),
+                version: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(current.version, 1);
+    assert_eq!(current.contracts, contracts);
+    assert_eq!(current.status, VersionStatus::Active);
+}
@@ -2,4 +2,4 @@ pub mod contract;
 pub mod msg;
 
 #[cfg(target_arch = "wasm32")]
-cosmwasm_std::create_entry_points!(contract);
+cosmwasm_std::create_entry_points_with_migration!(contract);
@@ -0,0 +1,439 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use cosmwasm_std::{from_slice, to_binary, Binary, DepsMut, Env, MessageInfo, StdError, StdResult};
+use price::{Price, RoundingMode};
+use schemars::schema::RootSchema;
+use schemars::{schema_for, JsonSchema};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// permille of the votes
+const PERMILLE: u64 = 1000;
+
+/// aggregates the data source results of a request. An oscript only has to pick an aggregator and call aggregate_results
+pub trait Aggregator {
+    type Input: DeserializeOwned + JsonSchema;
+    type Output: Serialize + JsonSchema;
+
+    fn aggregate(&self, inputs: Vec<Self::Input>) -> StdResult<Self::Output>;
+
+    /// parses a data source result, None if it does not follow the input schema
+    fn parse(&self, result: &str) -> Option<Self::Input> {
+        from_slice(result.as_bytes()).ok()
+    }
+
+    /// aggregates the results following the input schema, the others are skipped
+    fn aggregate_results(&self, results: &[String]) -> StdResult<Binary> {
+        let inputs = results
+            .iter()
+            .filter_map(|result| self.parse(result))
+            .collect();
+        to_binary(&self.aggregate(inputs)?)
+    }
+
+    fn input_schema() -> RootSchema
+    where
+        Self: Sized,
+    {
+        schema_for!(Self::Input)
+    }
+
+    fn output_schema() -> RootSchema
+    where
+        Self: Sized,
+    {
+        schema_for!(Self::Output)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LabelScore {
+    pub label: String,
+    pub score: u64,
+}
+
+/// result of a classification data source, other fields like the status are ignored
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Classification {
+    pub data: Vec<LabelScore>,
+}
+
+impl Classification {
+    /// parses a classification, or the list of results of a data source whose last one answers the request
+    pub fn parse(result: &str) -> Option<Classification> {
+        from_slice(result.as_bytes()).ok().or_else(|| {
+            from_slice::<Vec<Classification>>(result.as_bytes())
+                .ok()
+                .and_then(|mut results| results.pop())
+        })
+    }
+
+    /// the label with the highest score, the first one on ties
+    pub fn top_label(&self) -> Option<&LabelScore> {
+        self.data
+            .iter()
+            .fold(None, |top: Option<&LabelScore>, data| match top {
+                Some(top) if top.score >= data.score => Some(top),
+                _ => Some(data),
+            })
+    }
+}
+
+/// adapts an aggregator with its default configuration to the AggregateHandler of AiOracleHandle::aggregate
+pub fn aggregate_with<A: Aggregator + Default>(
+    _deps: &mut DepsMut,
+    _env: &Env,
+    _info: &MessageInfo,
+    results: &[String],
+) -> StdResult<Binary> {
+    A::default().aggregate_results(results)
+}
+
+/// each result votes for its top label, the label with the most votes wins if it reaches the quorum and is not tied
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct MajorityVote {
+    /// permille of the votes the winning label needs
+    pub quorum: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Vote {
+    /// None if no label reached the quorum or the most voted labels are tied
+    pub label: Option<String>,
+    pub votes: u64,
+    pub total: u64,
+}
+
+impl Aggregator for MajorityVote {
+    type Input = Classification;
+    type Output = Vote;
+
+    fn parse(&self, result: &str) -> Option<Classification> {
+        Classification::parse(result)
+    }
+
+    fn aggregate(&self, inputs: Vec<Classification>) -> StdResult<Vote> {
+        if self.quorum > PERMILLE {
+            return Err(StdError::generic_err("quorum must be at most 1000"));
+        }
+        let mut votes: BTreeMap<String, u64> = BTreeMap::new();
+        for input in inputs.iter() {
+            if let Some(top) = input.top_label() {
+                *votes.entry(top.label.clone()).or_default() += 1;
+            }
+        }
+        let total = votes.values().sum();
+        let mut winner: Option<(String, u64)> = None;
+        let mut tied = false;
+        for (label, count) in votes {
+            match winner.as_ref().map(|winner| count.cmp(&winner.1)) {
+                None | Some(Ordering::Greater) => {
+                    winner = Some((label, count));
+                    tied = false;
+                }
+                Some(Ordering::Equal) => tied = true,
+                Some(Ordering::Less) => {}
+            }
+        }
+        Ok(match winner {
+            Some((label, count)) if !tied && count * PERMILLE >= self.quorum * total => Vote {
+                label: Some(label),
+                votes: count,
+                total,
+            },
+            winner => Vote {
+                label: None,
+                votes: winner.map(|winner| winner.1).unwrap_or(0),
+                total,
+            },
+        })
+    }
+}
+
+/// averages the score of every label over all the results, a label missing from a result scores 0 there
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct ScoreAverage {}
+
+impl Aggregator for ScoreAverage {
+    type Input = Classification;
+    /// sorted by score then label
+    type Output = Vec<LabelScore>;
+
+    fn parse(&self, result: &str) -> Option<Classification> {
+        Classification::parse(result)
+    }
+
+    fn aggregate(&self, inputs: Vec<Classification>) -> StdResult<Vec<LabelScore>> {
+        let count = inputs.len() as u64;
+        let mut sums: BTreeMap<String, u64> = BTreeMap::new();
+        for input in inputs {
+            for data in input.data {
+                let sum = sums.entry(data.label).or_default();
+                *sum = sum.saturating_add(data.score);
+            }
+        }
+        let mut averages: Vec<LabelScore> = sums
+            .into_iter()
+            .map(|(label, sum)| LabelScore {
+                label,
+                score: sum / count,
+            })
+            .collect();
+        averages.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.label.cmp(&b.label)));
+        Ok(averages)
+    }
+}
+
+/// the top label of each result, in the order of the results
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct TopLabels {}
+
+impl Aggregator for TopLabels {
+    type Input = Classification;
+    type Output = Vec<LabelScore>;
+
+    fn parse(&self, result: &str) -> Option<Classification> {
+        Classification::parse(result)
+    }
+
+    fn aggregate(&self, inputs: Vec<Classification>) -> StdResult<Vec<LabelScore>> {
+        Ok(inputs
+            .iter()
+            .filter_map(|input| input.top_label().cloned())
+            .collect())
+    }
+}
+
+/// statistics of numeric results, given with the largest precision of the inputs
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct NumericStats {}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Stats {
+    pub count: u64,
+    pub min: Price,
+    pub max: Price,
+    pub mean: Price,
+    pub median: Price,
+}
+
+impl Aggregator for NumericStats {
+    type Input = Price;
+    type Output = Stats;
+
+    /// accepts json strings as well as bare numbers
+    fn parse(&self, result: &str) -> Option<Price> {
+        from_slice(result.as_bytes())
+            .ok()
+            .or_else(|| Price::from_str(result.trim()).ok())
+    }
+
+    fn aggregate(&self, mut inputs: Vec<Price>) -> StdResult<Stats> {
+        if inputs.is_empty() {
+            return Err(StdError::generic_err("no numeric result to aggregate"));
+        }
+        inputs.sort();
+        let precision = inputs.iter().map(Price::scale).max().unwrap_or(0);
+        let count = inputs.len();
+        let mean = |values: &[Price]| -> StdResult<Price> {
+            let sum: Price = values.iter().sum();
+            sum.checked_div(
+                &Price::from(values.len() as u64),
+                precision,
+                RoundingMode::HalfUp,
+            )
+            .ok_or_else(|| StdError::generic_err("cannot divide by zero"))
+        };
+        let median = if count % 2 == 1 {
+            inputs[count / 2].clone()
+        } else {
+            mean(&inputs[count / 2 - 1..=count / 2])?
+        };
+        Ok(Stats {
+            count: count as u64,
+            min: inputs[0].clone(),
+            max: inputs[count - 1].clone(),
+            mean: mean(&inputs)?,
+            median,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::from_binary;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+
+    use super::*;
+    use crate::traits::AggregateHandler;
+
+    fn results(results: &[&str]) -> Vec<String> {
+        results.iter().map(|result| result.to_string()).collect()
+    }
+
+    #[test]
+    fn majority_vote() {
+        let results = results(&[
+            r#"{"data":[{"label":"rose","score":88},{"label":"daisy","score":66}],"status":"success"}"#,
+            r#"{"data":[{"label":"rose","score":70},{"label":"tulip","score":72}],"status":"success"}"#,
+            r#"{"data":[{"label":"rose","score":90}],"status":"success"}"#,
+            // the last result of a data source answers the request
+            r#"[{"data":[{"label":"rose","score":90}],"status":"success"},{"data":[{"label":"daisy","score":80}],"status":"success"}]"#,
+            "not a classification",
+        ]);
+        let vote: Vote = from_binary(
+            &MajorityVote { quorum: 500 }
+                .aggregate_results(&results)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            vote,
+            Vote {
+                label: Some(String::from("rose")),
+                votes: 2,
+                total: 4,
+            }
+        );
+
+        // half of the votes do not reach a quorum of 70%
+        let vote: Vote = from_binary(
+            &MajorityVote { quorum: 700 }
+                .aggregate_results(&results)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(vote.label, None);
+        assert_eq!(vote.votes, 2);
+
+        assert!(MajorityVote { quorum: 1001 }.aggregate(vec![]).is_err());
+    }
+
+    #[test]
+    fn majority_vote_tie() {
+        // a tie for the most votes has no winner, whatever the labels
+        let vote: Vote = from_binary(
+            &MajorityVote { quorum: 0 }
+                .aggregate_results(&results(&[
+                    r#"{"data":[{"label":"rose","score":88}]}"#,
+                    r#"{"data":[{"label":"tulip","score":72}]}"#,
+                    r#"{"data":[{"label":"daisy","score":60}]}"#,
+                    r#"{"data":[{"label":"tulip","score":90}]}"#,
+                    r#"{"data":[{"label":"rose","score":70}]}"#,
+                ]))
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            vote,
+            Vote {
+                label: None,
+                votes: 2,
+                total: 5,
+            }
+        );
+    }
+
+    #[test]
+    fn aggregate_handler() {
+        let mut deps = mock_dependencies(&[]);
+        let handler: AggregateHandler = aggregate_with::<NumericStats>;
+        let stats: Stats = from_binary(
+            &handler(
+                &mut deps.as_mut(),
+                &mock_env(),
+                &mock_info("executor", &[]),
+                &results(&["1", "3"]),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(stats.mean.to_string(), "2");
+    }
+
+    #[test]
+    fn score_average() {
+        let averages: Vec<LabelScore> = from_binary(
+            &ScoreAverage {}
+                .aggregate_results(&results(&[
+                    r#"{"data":[{"label":"rose","score":80},{"label":"daisy","score":60}]}"#,
+                    r#"{"data":[{"label":"rose","score":70}]}"#,
+                ]))
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            averages,
+            vec![
+                LabelScore {
+                    label: String::from("rose"),
+                    score: 75,
+                },
+                LabelScore {
+                    label: String::from("daisy"),
+                    score: 30,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn top_labels() {
+        let labels: Vec<LabelScore> = from_binary(
+            &TopLabels {}
+                .aggregate_results(&results(&[
+                    r#"{"data":[{"label":"rose","score":80},{"label":"daisy","score":90}]}"#,
+                    r#"[{"data":[{"label":"rose","score":70}]},{"data":[]}]"#,
+                    r#"[{"data":[{"label":"daisy","score":70}]},{"data":[{"label":"tulip","score":60}]}]"#,
+                ]))
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            labels,
+            vec![
+                LabelScore {
+                    label: String::from("daisy"),
+                    score: 90,
+                },
+                LabelScore {
+                    label: String::from("tulip"),
+                    score: 60,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn numeric_stats() {
+        let stats: Stats = from_binary(
+            &NumericStats {}
+                .aggregate_results(&results(&["10.5", r#""12""#, "9", "11.25", "abc"]))
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(stats.count, 4);
+        assert_eq!(stats.min.to_string(), "9");
+        assert_eq!(stats.max.to_string(), "12");
+        assert_eq!(stats.mean.to_string(), "10.69");
+        assert_eq!(stats.median.to_string(), "10.88");
+
+        assert!(NumericStats {}
+            .aggregate_results(&results(&["abc"]))
+            .is_err());
+    }
+
+    #[test]
+    fn schemas() {
+        let schema = schema_title(MajorityVote::input_schema());
+        assert_eq!(schema, Some(String::from("Classification")));
+        let schema = schema_title(NumericStats::output_schema());
+        assert_eq!(schema, Some(String::from("Stats")));
+    }
+
+    fn schema_title(schema: RootSchema) -> Option<String> {
+        schema.schema.metadata.and_then(|metadata| metadata.title)
+    }
+}
@@ -261,6 +261,7 @@ impl AiOracleTestCaseContract {
             limit,
             offset,
             order,
+            prefix: None,
         })?;
         let res: TestCaseResponse = querier.query(&query)?;
         Ok(res)
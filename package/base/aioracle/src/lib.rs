@@ -1,3 +1,4 @@
+pub use crate::aggregators::*;
 pub use crate::error::*;
 pub use crate::helpers::*;
 pub use crate::msg::*;
@@ -5,6 +6,7 @@ pub use crate::query::*;
 pub use crate::state::*;
 pub use crate::traits::*;
 
+mod aggregators;
 mod error;
 mod helpers;
 mod msg;
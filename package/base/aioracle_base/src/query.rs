@@ -1,4 +1,5 @@
 use cosmwasm_std::Coin;
+use crate::FeeSchedule;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -6,4 +7,6 @@ use serde::{Deserialize, Serialize};
 pub struct ServiceFeesResponse {
     pub address: String,
     pub fees: Coin,
+    /// fee schedule of the provider, None if it only charges the flat fee or the schedule has expired
+    pub schedule: Option<FeeSchedule>,
 }
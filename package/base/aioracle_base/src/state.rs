@@ -1,5 +1,5 @@
 use cosmwasm_std::HumanAddr;
-use cosmwasm_std::{Binary, Uint128};
+use cosmwasm_std::{Binary, Coin, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -13,6 +13,42 @@ use serde::{Deserialize, Serialize};
 // 0: recipient, 1: receive denom, 2: receive amount
 pub type Reward = (HumanAddr, String, Uint128);
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct ServicePolicy {
+    /// requests of the service need at least this threshold
+    pub min_threshold: Option<u64>,
+    /// only these executors can handle the requests of the service. All active executors if None
+    pub executors: Option<Vec<Binary>>,
+    /// maximum fee paid per executor, provider rewards plus the preference executor fee
+    pub max_fee: Option<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionStatus {
+    /// requests can pin the version
+    Active,
+    /// new requests cannot pin the version anymore, pending requests keep using it
+    Deprecated,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetInfo {
+    Token { contract_addr: HumanAddr },
+    NativeToken { denom: String },
+}
+
+impl AssetInfo {
+    /// denom of the asset in rewards & coins. Cw20 tokens are identified by their contract address
+    pub fn denom(&self) -> String {
+        match self {
+            AssetInfo::Token { contract_addr } => contract_addr.to_string(),
+            AssetInfo::NativeToken { denom } => denom.to_string(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Executor {
     /// Owner If None set, contract is frozen.
@@ -21,4 +57,62 @@ pub struct Executor {
     pub executing_power: u64,
     pub index: u64,
     pub left_block: Option<u64>,
+    /// block at which the executor has been jailed for misbehaving
+    pub jailed_block: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeTier {
+    /// number of requests in the current period from which the tier applies
+    pub min_requests: u64,
+    /// discount on the accepted coins, in permille
+    pub discount: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeSchedule {
+    /// coins the provider accepts as fee, the first one is the default
+    pub accepted: Vec<Coin>,
+    pub tiers: Vec<FeeTier>,
+    /// number of blocks over which the requests are counted to pick a tier
+    pub period: u64,
+    /// block height from which the schedule no longer applies
+    pub expiry: Option<u64>,
+}
+
+impl FeeSchedule {
+    pub fn is_expired(&self, height: u64) -> bool {
+        self.expiry.map(|expiry| height >= expiry).unwrap_or(false)
+    }
+
+    /// index of the period the block height belongs to
+    pub fn period_of(&self, height: u64) -> u64 {
+        height.checked_div(self.period).unwrap_or_default()
+    }
+
+    /// discount of the highest tier reached with the given number of requests
+    pub fn discount(&self, requests: u64) -> u64 {
+        self.tiers
+            .iter()
+            .filter(|tier| tier.min_requests <= requests)
+            .map(|tier| tier.discount)
+            .max()
+            .unwrap_or_default()
+            .min(1000)
+    }
+
+    /// fee charged for a request paid with the given funds. The first accepted coin sent is picked, the default one otherwise
+    pub fn price(&self, funds: &[Coin], requests: u64) -> Option<Coin> {
+        let coin = self
+            .accepted
+            .iter()
+            .find(|coin| funds.iter().any(|fund| fund.denom.eq(&coin.denom)))
+            .or_else(|| self.accepted.first())?;
+        Some(Coin {
+            denom: coin.denom.clone(),
+            amount: coin
+                .amount
+                .multiply_ratio(1000 - self.discount(requests), 1000u64),
+        })
+    }
 }
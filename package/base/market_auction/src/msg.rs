@@ -36,6 +36,100 @@ pub struct Auction {
     pub start_timestamp: Uint128,
     pub end_timestamp: Uint128,
     pub step_price: u64,
+    // None for auctions created before the auction types, which are english auctions
+    pub auction_type: Option<AuctionType>,
+    // None if bids at the last moment do not extend the auction
+    pub extension: Option<AuctionExtension>,
+}
+
+/// a bid less than window seconds before the end pushes the end to duration seconds after the bid, up to max_end_timestamp
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AuctionExtension {
+    pub window: u64,
+    pub duration: u64,
+    pub max_end_timestamp: Option<Uint128>,
+    // number of times the auction has been extended
+    pub extended: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum AuctionType {
+    // bids go up by step_price, the highest bidder wins when the auction ends
+    English {},
+    // the price goes down from price to floor_price, the first buyer wins at once
+    Dutch { floor_price: Uint128, decay: Decay },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Decay {
+    Linear {},
+    // the price goes down every interval seconds
+    Stepwise { interval: u64 },
+}
+
+impl Auction {
+    /// pushes the end of the auction forward if the bid at the given time is inside the extension window. Returns true if extended.
+    /// The end block moves along with the end timestamp, at the blocks per second rate of the auction
+    pub fn extend(&mut self, time: u64) -> bool {
+        let extension = match self.extension.as_mut() {
+            Some(extension) => extension,
+            None => return false,
+        };
+        let time = time as u128;
+        if time + (extension.window as u128) < self.end_timestamp.u128() {
+            return false;
+        }
+        let mut end_timestamp = Uint128::from(time + extension.duration as u128);
+        if let Some(max_end_timestamp) = extension.max_end_timestamp {
+            end_timestamp = end_timestamp.min(max_end_timestamp);
+        }
+        if end_timestamp <= self.end_timestamp {
+            return false;
+        }
+        let span = self
+            .end_timestamp
+            .u128()
+            .saturating_sub(self.start_timestamp.u128());
+        if span > 0 {
+            let blocks = Uint128::from(self.end.saturating_sub(self.start))
+                .multiply_ratio(end_timestamp.u128() - self.end_timestamp.u128(), span);
+            self.end += blocks.u128() as u64;
+        }
+        self.end_timestamp = end_timestamp;
+        extension.extended += 1;
+        true
+    }
+
+    pub fn is_dutch(&self) -> bool {
+        matches!(self.auction_type, Some(AuctionType::Dutch { .. }))
+    }
+
+    /// price of a dutch auction at the given time, the price of the auction for other types
+    pub fn current_price(&self, time: u64) -> Uint128 {
+        let (floor_price, decay) = match &self.auction_type {
+            Some(AuctionType::Dutch { floor_price, decay }) => (*floor_price, decay),
+            _ => return self.price,
+        };
+        let start = self.start_timestamp.u128();
+        let end = self.end_timestamp.u128();
+        let time = time as u128;
+        if time <= start || end <= start || self.price <= floor_price {
+            return self.price;
+        }
+        if time >= end {
+            return floor_price;
+        }
+        let mut elapsed = time - start;
+        if let Decay::Stepwise { interval } = decay {
+            let interval = (*interval as u128).max(1);
+            elapsed = elapsed / interval * interval;
+        }
+        let range = self.price.u128() - floor_price.u128();
+        let decayed = Uint128::from(range).multiply_ratio(elapsed, end - start);
+        Uint128::from(self.price.u128() - decayed.u128())
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -1,4 +1,4 @@
-use crate::msg::PagingOptions;
+use crate::msg::{AuctionExtension, AuctionType, PagingOptions};
 use cosmwasm_std::{HumanAddr, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -50,4 +50,6 @@ pub struct QueryAuctionsResult {
     pub start_timestamp: Uint128,
     pub end_timestamp: Uint128,
     pub step_price: u64,
+    pub auction_type: Option<AuctionType>,
+    pub extension: Option<AuctionExtension>,
 }
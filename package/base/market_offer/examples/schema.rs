@@ -0,0 +1,19 @@
+use std::env::current_dir;
+use std::fs::create_dir_all;
+
+use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
+
+use market_offer::{Offer, OfferHandleMsg, OfferQueryMsg, OffersResponse, PagingOptions};
+
+fn main() {
+    let mut out_dir = current_dir().unwrap();
+    out_dir.push("schema");
+    create_dir_all(&out_dir).unwrap();
+    remove_schemas(&out_dir).unwrap();
+
+    export_schema(&schema_for!(Offer), &out_dir);
+    export_schema(&schema_for!(OfferHandleMsg), &out_dir);
+    export_schema(&schema_for!(OfferQueryMsg), &out_dir);
+    export_schema(&schema_for!(OffersResponse), &out_dir);
+    export_schema(&schema_for!(PagingOptions), &out_dir);
+}
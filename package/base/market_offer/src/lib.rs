@@ -0,0 +1,6 @@
+mod msg;
+mod query;
+
+pub use crate::msg::*;
+pub use crate::query::*;
+pub use market::*;
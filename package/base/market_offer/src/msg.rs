@@ -0,0 +1,47 @@
+use cosmwasm_std::{CanonicalAddr, Uint128};
+use market::AssetInfo;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::QueryOffersResult;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PagingOptions {
+    pub offset: Option<u64>,
+    pub limit: Option<u8>,
+    pub order: Option<u8>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OffersResponse {
+    pub items: Vec<QueryOffersResult>,
+}
+
+/// funds locked by a buyer for a token that is not on sale, the owner of the token accepts it by sending the token to the market
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Offer {
+    pub id: Option<u64>,
+    pub contract_addr: CanonicalAddr,
+    // None if any token of the collection can be sold
+    pub token_id: Option<String>,
+    // who locks the funds
+    pub buyer: CanonicalAddr,
+    pub asset_info: AssetInfo,
+    pub amount: Uint128,
+    // the offer cannot be accepted from this timestamp on
+    pub end_timestamp: Uint128,
+}
+
+impl Offer {
+    pub fn is_expired(&self, time: u64) -> bool {
+        self.end_timestamp.u128() <= time as u128
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OfferHandleMsg {
+    // this allow implementation contract to update the storage
+    UpdateOffer { offer: Offer },
+    RemoveOffer { id: u64 },
+}
@@ -0,0 +1,45 @@
+use crate::msg::PagingOptions;
+use cosmwasm_std::{HumanAddr, Uint128};
+use market::AssetInfo;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OfferQueryMsg {
+    GetOffers {
+        options: PagingOptions,
+    },
+    GetOffersByBuyer {
+        buyer: HumanAddr,
+        options: PagingOptions,
+    },
+    // both the collection offers and the token offers of the contract
+    GetOffersByContract {
+        contract: HumanAddr,
+        options: PagingOptions,
+    },
+    // collection offers if token_id is None
+    GetOffersByContractTokenId {
+        contract: HumanAddr,
+        token_id: Option<String>,
+        options: PagingOptions,
+    },
+    GetOfferRaw {
+        offer_id: u64,
+    },
+    GetOffer {
+        offer_id: u64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct QueryOffersResult {
+    pub id: u64,
+    pub contract_addr: HumanAddr,
+    pub token_id: Option<String>,
+    pub buyer: HumanAddr,
+    pub asset_info: AssetInfo,
+    pub amount: Uint128,
+    pub end_timestamp: Uint128,
+}
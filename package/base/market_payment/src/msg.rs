@@ -1,4 +1,4 @@
-use cosmwasm_std::HumanAddr;
+use cosmwasm_std::{HumanAddr, Uint128};
 use market::AssetInfo;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -12,11 +12,29 @@ pub struct Payment {
     pub asset_info: AssetInfo,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AssetPrice {
+    pub asset_info: AssetInfo,
+    pub price: Uint128,
+}
+
+// every asset accepted for an offering, the first one is also its payment
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PaymentPrices {
+    pub contract_addr: HumanAddr,
+    pub token_id: String,
+    pub sender: Option<HumanAddr>,
+    pub prices: Vec<AssetPrice>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum PaymentHandleMsg {
     // this allow implementation to update the storage
     UpdateOfferingPayment(Payment),
+    UpdateOfferingPrices(PaymentPrices),
     UpdateAuctionPayment(Payment),
     RemoveOfferingPayment {
         contract_addr: HumanAddr,
@@ -12,6 +12,12 @@ pub enum PaymentQueryMsg {
         token_id: String,
         sender: Option<HumanAddr>,
     },
+    // empty if the offering only accepts its payment asset
+    GetOfferingPrices {
+        contract_addr: HumanAddr,
+        token_id: String,
+        sender: Option<HumanAddr>,
+    },
     GetOfferingPayments {
         offset: Option<Binary>,
         limit: Option<u8>,
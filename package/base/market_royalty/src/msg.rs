@@ -7,8 +7,18 @@ use serde::{Deserialize, Serialize};
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Cw20HookMsg {
-    BuyNft { offering_id: u64 },
-    BidNft { auction_id: u64 },
+    BuyNft {
+        offering_id: u64,
+    },
+    BidNft {
+        auction_id: u64,
+    },
+    // lock the cw20 funds in an offer, the token_id is None for a collection offer
+    MakeOffer {
+        contract_addr: HumanAddr,
+        token_id: Option<String>,
+        end_timestamp: Uint128,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -54,7 +64,14 @@ pub struct Offering {
     pub contract_addr: CanonicalAddr,
     pub seller: CanonicalAddr,
     pub price: Uint128,
-    // percentage for seller(previous-owner) of the NFT
+    // every nft sold together for the price, the first one is also the token_id of the offering. None for a single nft
+    pub bundle: Option<Vec<BundleItem>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct BundleItem {
+    pub contract_addr: HumanAddr,
+    pub token_id: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
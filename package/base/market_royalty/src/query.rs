@@ -1,5 +1,6 @@
 use cosmwasm_std::{HumanAddr, Uint128};
 
+use crate::BundleItem;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -68,6 +69,7 @@ pub struct QueryOfferingsResult {
     pub price: Uint128,
     pub contract_addr: HumanAddr,
     pub seller: HumanAddr,
+    pub bundle: Option<Vec<BundleItem>>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
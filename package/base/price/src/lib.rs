@@ -0,0 +1,3 @@
+mod price;
+
+pub use crate::price::*;
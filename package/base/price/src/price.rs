@@ -0,0 +1,615 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+
+use cosmwasm_std::{StdError, StdResult, Uint128};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::{de, ser, Deserialize, Deserializer, Serialize};
+
+/// How the digits dropped by a rescale or a division are rounded. Rounding is applied to the
+/// absolute value, so `Down` rounds towards zero and `HalfUp` rounds halves away from zero.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingMode {
+    Down,
+    Up,
+    HalfUp,
+    HalfEven,
+}
+
+/// A signed decimal number with an arbitrary number of digits.
+/// Equality and ordering compare values, so `1.5` equals `1.50`, but the scale
+/// (number of decimals) is kept and used when printing.
+#[derive(Clone, Debug)]
+pub struct Price {
+    negative: bool,
+    // decimal digits of the absolute value, least significant first, without leading zeros
+    digits: Vec<u8>,
+    scale: u32,
+}
+
+impl Price {
+    pub fn zero() -> Self {
+        Price {
+            negative: false,
+            digits: vec![],
+            scale: 0,
+        }
+    }
+
+    /// builds a price from an amount of atomic units with the given number of decimals,
+    /// eg. 1500 with 3 decimals is 1.500
+    pub fn from_atomics(amount: Uint128, decimals: u32) -> Self {
+        Price::new(false, digits_of(amount.u128()), decimals)
+    }
+
+    /// converts the price into atomic units with the given number of decimals
+    pub fn to_atomics(&self, decimals: u32, mode: RoundingMode) -> StdResult<Uint128> {
+        let rescaled = self.rescale(decimals, mode);
+        if rescaled.is_negative() {
+            return Err(StdError::generic_err(format!(
+                "Negative price {} cannot be converted to atomics",
+                self
+            )));
+        }
+        let mut amount: u128 = 0;
+        for digit in rescaled.digits.iter().rev() {
+            amount = amount
+                .checked_mul(10)
+                .and_then(|amount| amount.checked_add(*digit as u128))
+                .ok_or_else(|| StdError::generic_err(format!("Price {} is too large", self)))?;
+        }
+        Ok(Uint128::from(amount))
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn abs(&self) -> Self {
+        Price::new(false, self.digits.clone(), self.scale)
+    }
+
+    /// changes the number of decimals, rounding the dropped digits with the given mode
+    pub fn rescale(&self, scale: u32, mode: RoundingMode) -> Self {
+        if scale >= self.scale {
+            return Price::new(
+                self.negative,
+                shift(&self.digits, scale - self.scale),
+                scale,
+            );
+        }
+        let divisor = shift(&[1], self.scale - scale);
+        let digits = div_round(&self.digits, &divisor, mode);
+        Price::new(self.negative, digits, scale)
+    }
+
+    /// divides the price by another one and returns the result with the given number of
+    /// decimals. Returns None when dividing by zero
+    pub fn checked_div(&self, other: &Price, scale: u32, mode: RoundingMode) -> Option<Self> {
+        if other.is_zero() {
+            return None;
+        }
+        // a / 10^sa / (b / 10^sb) * 10^scale = a * 10^(sb + scale) / (b * 10^sa)
+        let numerator = shift(&self.digits, other.scale + scale);
+        let denominator = shift(&other.digits, self.scale);
+        let digits = div_round(&numerator, &denominator, mode);
+        Some(Price::new(self.negative != other.negative, digits, scale))
+    }
+
+    fn new(negative: bool, mut digits: Vec<u8>, scale: u32) -> Self {
+        trim(&mut digits);
+        Price {
+            // zero has no sign
+            negative: negative && !digits.is_empty(),
+            digits,
+            scale,
+        }
+    }
+}
+
+impl Default for Price {
+    fn default() -> Self {
+        Price::zero()
+    }
+}
+
+impl From<u64> for Price {
+    fn from(value: u64) -> Self {
+        Price::new(false, digits_of(value as u128), 0)
+    }
+}
+
+impl From<i64> for Price {
+    fn from(value: i64) -> Self {
+        Price::new(value < 0, digits_of(value.unsigned_abs() as u128), 0)
+    }
+}
+
+impl FromStr for Price {
+    type Err = StdError;
+
+    /// parses `-?[0-9]+(\.[0-9]+)?` exactly, keeping every decimal
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = || StdError::generic_err(format!("Invalid price: {}", input));
+        let (negative, unsigned) = match input.strip_prefix('-') {
+            Some(unsigned) => (true, unsigned),
+            None => (false, input),
+        };
+        let mut parts = unsigned.split('.');
+        let integer = parts.next().unwrap_or_default();
+        let fraction = parts.next();
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        let all_digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+        if !all_digits(integer) || !fraction.map(all_digits).unwrap_or(true) {
+            return Err(invalid());
+        }
+        let fraction = fraction.unwrap_or_default();
+        let digits = integer
+            .bytes()
+            .chain(fraction.bytes())
+            .rev()
+            .map(|b| b - b'0')
+            .collect();
+        Ok(Price::new(negative, digits, fraction.len() as u32))
+    }
+}
+
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let scale = self.scale as usize;
+        let mut digits: String = self
+            .digits
+            .iter()
+            .rev()
+            .map(|digit| (b'0' + digit) as char)
+            .collect();
+        while digits.len() <= scale {
+            digits.insert(0, '0');
+        }
+        if scale > 0 {
+            digits.insert(digits.len() - scale, '.');
+        }
+        if self.negative {
+            digits.insert(0, '-');
+        }
+        f.write_str(&digits)
+    }
+}
+
+impl PartialEq for Price {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Price {}
+
+impl PartialOrd for Price {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Price {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (negative, _) => {
+                let (a, b) = align(self, other);
+                let ordering = cmp_digits(&a, &b);
+                if negative {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            }
+        }
+    }
+}
+
+impl Add for &Price {
+    type Output = Price;
+
+    fn add(self, other: &Price) -> Price {
+        let scale = self.scale.max(other.scale);
+        let (a, b) = align(self, other);
+        if self.negative == other.negative {
+            return Price::new(self.negative, add_digits(&a, &b), scale);
+        }
+        match cmp_digits(&a, &b) {
+            Ordering::Less => Price::new(other.negative, sub_digits(&b, &a), scale),
+            _ => Price::new(self.negative, sub_digits(&a, &b), scale),
+        }
+    }
+}
+
+impl Add for Price {
+    type Output = Price;
+
+    fn add(self, other: Price) -> Price {
+        &self + &other
+    }
+}
+
+impl Sub for &Price {
+    type Output = Price;
+
+    fn sub(self, other: &Price) -> Price {
+        self + &(-other)
+    }
+}
+
+impl Sub for Price {
+    type Output = Price;
+
+    fn sub(self, other: Price) -> Price {
+        &self - &other
+    }
+}
+
+impl Neg for &Price {
+    type Output = Price;
+
+    fn neg(self) -> Price {
+        Price::new(!self.negative, self.digits.clone(), self.scale)
+    }
+}
+
+impl Neg for Price {
+    type Output = Price;
+
+    fn neg(self) -> Price {
+        -&self
+    }
+}
+
+/// the scale of the product is the sum of both scales, so no digit is lost
+impl Mul for &Price {
+    type Output = Price;
+
+    fn mul(self, other: &Price) -> Price {
+        Price::new(
+            self.negative != other.negative,
+            mul_digits(&self.digits, &other.digits),
+            self.scale + other.scale,
+        )
+    }
+}
+
+impl Mul for Price {
+    type Output = Price;
+
+    fn mul(self, other: Price) -> Price {
+        &self * &other
+    }
+}
+
+impl Sum for Price {
+    fn sum<I: Iterator<Item = Price>>(iter: I) -> Price {
+        iter.fold(Price::zero(), |sum, price| &sum + &price)
+    }
+}
+
+impl<'a> Sum<&'a Price> for Price {
+    fn sum<I: Iterator<Item = &'a Price>>(iter: I) -> Price {
+        iter.fold(Price::zero(), |sum, price| &sum + price)
+    }
+}
+
+/// Serializes as a decimal string
+impl Serialize for Price {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+/// Deserializes from a decimal string
+impl<'de> Deserialize<'de> for Price {
+    fn deserialize<D>(deserializer: D) -> Result<Price, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(PriceVisitor)
+    }
+}
+
+struct PriceVisitor;
+
+impl<'de> de::Visitor<'de> for PriceVisitor {
+    type Value = Price;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("string-encoded decimal price")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Price::from_str(v).map_err(|err| E::custom(err.to_string()))
+    }
+}
+
+impl JsonSchema for Price {
+    fn schema_name() -> String {
+        "Price".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        String::json_schema(gen)
+    }
+}
+
+fn digits_of(mut value: u128) -> Vec<u8> {
+    let mut digits = vec![];
+    while value > 0 {
+        digits.push((value % 10) as u8);
+        value /= 10;
+    }
+    digits
+}
+
+fn trim(digits: &mut Vec<u8>) {
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+}
+
+// multiplies by 10^n
+fn shift(digits: &[u8], n: u32) -> Vec<u8> {
+    if digits.is_empty() {
+        return vec![];
+    }
+    let mut shifted = vec![0; n as usize];
+    shifted.extend_from_slice(digits);
+    shifted
+}
+
+// digits of both prices at their common scale
+fn align(a: &Price, b: &Price) -> (Vec<u8>, Vec<u8>) {
+    let scale = a.scale.max(b.scale);
+    (
+        shift(&a.digits, scale - a.scale),
+        shift(&b.digits, scale - b.scale),
+    )
+}
+
+fn cmp_digits(a: &[u8], b: &[u8]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_digits(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut sum = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0;
+    for i in 0..a.len().max(b.len()) {
+        let digit = a.get(i).unwrap_or(&0) + b.get(i).unwrap_or(&0) + carry;
+        sum.push(digit % 10);
+        carry = digit / 10;
+    }
+    if carry > 0 {
+        sum.push(carry);
+    }
+    sum
+}
+
+// a must not be lower than b
+fn sub_digits(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut diff = Vec::with_capacity(a.len());
+    let mut borrow = 0;
+    for (i, digit) in a.iter().enumerate() {
+        let sub = b.get(i).unwrap_or(&0) + borrow;
+        if *digit >= sub {
+            diff.push(digit - sub);
+            borrow = 0;
+        } else {
+            diff.push(digit + 10 - sub);
+            borrow = 1;
+        }
+    }
+    trim(&mut diff);
+    diff
+}
+
+fn mul_digits(a: &[u8], b: &[u8]) -> Vec<u8> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let mut product = vec![0u32; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0;
+        for (j, y) in b.iter().enumerate() {
+            let value = product[i + j] + (*x as u32) * (*y as u32) + carry;
+            product[i + j] = value % 10;
+            carry = value / 10;
+        }
+        product[i + b.len()] += carry;
+    }
+    let mut digits: Vec<u8> = product.into_iter().map(|digit| digit as u8).collect();
+    trim(&mut digits);
+    digits
+}
+
+// long division, the divisor must not be zero
+fn div_rem(a: &[u8], b: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let mut quotient = vec![0; a.len()];
+    let mut rem: Vec<u8> = vec![];
+    for i in (0..a.len()).rev() {
+        rem = shift(&rem, 1);
+        if rem.is_empty() {
+            rem.push(a[i]);
+        } else {
+            rem[0] = a[i];
+        }
+        trim(&mut rem);
+        while cmp_digits(&rem, b) != Ordering::Less {
+            rem = sub_digits(&rem, b);
+            quotient[i] += 1;
+        }
+    }
+    trim(&mut quotient);
+    (quotient, rem)
+}
+
+fn div_round(a: &[u8], b: &[u8], mode: RoundingMode) -> Vec<u8> {
+    let (quotient, rem) = div_rem(a, b);
+    if rem.is_empty() {
+        return quotient;
+    }
+    // compare the remainder with half of the divisor
+    let half = cmp_digits(&add_digits(&rem, &rem), b);
+    let round_up = match mode {
+        RoundingMode::Down => false,
+        RoundingMode::Up => true,
+        RoundingMode::HalfUp => half != Ordering::Less,
+        RoundingMode::HalfEven => {
+            half == Ordering::Greater
+                || (half == Ordering::Equal && quotient.first().unwrap_or(&0) % 2 == 1)
+        }
+    };
+    if round_up {
+        add_digits(&quotient, &[1])
+    } else {
+        quotient
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{from_slice, to_vec};
+
+    fn price(input: &str) -> Price {
+        Price::from_str(input).unwrap()
+    }
+
+    #[test]
+    fn parse_and_display() {
+        for input in &[
+            "0",
+            "44",
+            "-44",
+            "1500.6670",
+            "0.00000000000018900",
+            "-0.5",
+            "340282366920938463463374607431768211456.000000000000000000001",
+        ] {
+            assert_eq!(price(input).to_string(), *input);
+        }
+        assert_eq!(price("007.10").to_string(), "7.10");
+        assert_eq!(price("-0.00").to_string(), "0.00");
+        assert_eq!(price("1.50").scale(), 2);
+
+        for input in &[
+            "", "-", ".1", "1.", "a.b", "a..b", "1.a", "1.1.1", "+1", "1e5", " 1", "--1",
+        ] {
+            assert!(
+                Price::from_str(input).is_err(),
+                "{} should be invalid",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn compare() {
+        assert_eq!(price("1.5"), price("1.500"));
+        assert!(price("-2") < price("-1.99"));
+        assert!(price("-0.1") < price("0"));
+        assert!(price("10.01") > price("9.999"));
+        assert_eq!(price("-0"), Price::zero());
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!((price("1.05") + price("2.9")).to_string(), "3.95");
+        assert_eq!((price("1.05") - price("2.9")).to_string(), "-1.85");
+        assert_eq!((price("-1.05") - price("-1.05")).to_string(), "0.00");
+        assert_eq!((price("-1.5") * price("2.25")).to_string(), "-3.375");
+        assert_eq!(
+            (price("99999999999999999999999999999999999999999") + price("1")).to_string(),
+            "100000000000000000000000000000000000000000"
+        );
+        let sum: Price = vec![price("0.1"), price("0.2"), price("-0.05")]
+            .into_iter()
+            .sum();
+        assert_eq!(sum.to_string(), "0.25");
+    }
+
+    #[test]
+    fn rescale_and_divide() {
+        let value = price("-2.345");
+        assert_eq!(value.rescale(5, RoundingMode::Down).to_string(), "-2.34500");
+        assert_eq!(value.rescale(2, RoundingMode::Down).to_string(), "-2.34");
+        assert_eq!(value.rescale(2, RoundingMode::Up).to_string(), "-2.35");
+        assert_eq!(value.rescale(2, RoundingMode::HalfUp).to_string(), "-2.35");
+        assert_eq!(
+            value.rescale(2, RoundingMode::HalfEven).to_string(),
+            "-2.34"
+        );
+        assert_eq!(
+            price("2.355")
+                .rescale(2, RoundingMode::HalfEven)
+                .to_string(),
+            "2.36"
+        );
+        assert_eq!(
+            price("0.004").rescale(2, RoundingMode::HalfUp).to_string(),
+            "0.00"
+        );
+
+        let third = price("1").checked_div(&price("3"), 4, RoundingMode::HalfUp);
+        assert_eq!(third.unwrap().to_string(), "0.3333");
+        let ratio = price("-1.5").checked_div(&price("0.25"), 0, RoundingMode::Down);
+        assert_eq!(ratio.unwrap().to_string(), "-6");
+        assert!(price("1")
+            .checked_div(&Price::zero(), 2, RoundingMode::Down)
+            .is_none());
+    }
+
+    #[test]
+    fn atomics() {
+        let value = Price::from_atomics(Uint128::from(1500u64), 3);
+        assert_eq!(value.to_string(), "1.500");
+        assert_eq!(
+            price("1.23456")
+                .to_atomics(3, RoundingMode::HalfUp)
+                .unwrap(),
+            Uint128::from(1235u64)
+        );
+        assert!(price("-1").to_atomics(0, RoundingMode::Down).is_err());
+        assert!(price("340282366920938463463374607431768211456")
+            .to_atomics(0, RoundingMode::Down)
+            .is_err());
+    }
+
+    #[test]
+    fn serde() {
+        let value = price("-1500.6670");
+        let serialized = to_vec(&value).unwrap();
+        assert_eq!(serialized, br#""-1500.6670""#);
+        let parsed: Price = from_slice(&serialized).unwrap();
+        assert_eq!(parsed.to_string(), "-1500.6670");
+        assert!(from_slice::<Price>(br#""1.""#).is_err());
+    }
+}
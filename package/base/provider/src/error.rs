@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -8,6 +8,18 @@ pub enum ContractError {
 
     #[error("Unauthorized")]
     Unauthorized {},
+
+    #[error("Script hash must be a hex encoded sha256: {script_hash}")]
+    InvalidScriptHash { script_hash: String },
+
+    #[error("Input schema names must be unique and not empty: {name}")]
+    InvalidInputSchema { name: String },
+
+    #[error("Version must be greater than the current one {current}")]
+    VersionNotIncreased { current: u64 },
+
+    #[error("Cannot withdraw more than the available {available} {denom}")]
+    InsufficientEarnings { denom: String, available: Uint128 },
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
@@ -1,10 +1,22 @@
 use crate::error::ContractError;
-use crate::msg::{HandleMsg, InitMsg, QueryMsg, StateMsg, UpdateServiceFees, UpdateServiceFeesMsg};
-use crate::state::{config, config_read, State, OWNER};
+use crate::msg::{
+    EarningsHistoryResponse, EarningsResponse, HandleMsg, InitMsg, QueryMsg, StateMsg,
+    UpdateServiceFees, UpdateServiceFeesMsg,
+};
+use crate::state::{
+    config, config_read, EarningsKind, EarningsRecord, State, EARNINGS, EARNINGS_HISTORY,
+    EARNINGS_HISTORY_COUNT, OWNER, WITHDRAWALS,
+};
 use cosmwasm_std::{
     attr, to_binary, BankMsg, Binary, Coin, Deps, DepsMut, Env, HandleResponse, HumanAddr,
-    InitResponse, MessageInfo, StdResult, WasmMsg,
+    InitResponse, MessageInfo, Order, QuerierWrapper, StdError, StdResult, Storage, Uint128,
+    WasmMsg,
 };
+use cw_storage_plus::{Bound, U64Key};
+
+// settings for pagination
+const MAX_LIMIT: u8 = 50;
+const DEFAULT_LIMIT: u8 = 20;
 
 pub fn init_provider(
     deps: DepsMut,
@@ -13,6 +25,7 @@ pub fn init_provider(
     msg: InitMsg,
 ) -> StdResult<InitResponse> {
     let state: State = msg.0;
+    validate_profile(&state).map_err(|err| StdError::generic_err(err.to_string()))?;
     // let state: State = State {
     //     language: "node".to_string(),
     //     script_url: "https://gist.githubusercontent.com/tubackkhoa/4ab5353a5b44118ccd697f14df65733f/raw/4a27d2ac4255d23463286898b161eda87d1b95bb/datasource_coingecko.js".to_string(),
@@ -41,6 +54,7 @@ pub fn handle_provider(
         }
         HandleMsg::WithdrawFees { fee } => try_withdraw_fees(deps, info, env, fee),
         HandleMsg::SetOwner { owner } => try_set_owner(deps, info, owner),
+        HandleMsg::SyncEarnings { denoms } => try_sync_earnings(deps, env, denoms),
     }
 }
 
@@ -67,19 +81,100 @@ fn try_withdraw_fees(
     if !info.sender.eq(&owner) {
         return Err(ContractError::Unauthorized {});
     }
+    // the fees sent before this withdrawal are recorded as earnings first
+    let available = sync_earnings(deps.storage, &deps.querier, &env, &fees.denom)?;
+    if fees.amount > available {
+        return Err(ContractError::InsufficientEarnings {
+            denom: fees.denom,
+            available,
+        });
+    }
+    let withdrawn = WITHDRAWALS
+        .may_load(deps.storage, fees.denom.as_bytes())?
+        .unwrap_or_default();
+    WITHDRAWALS.save(
+        deps.storage,
+        fees.denom.as_bytes(),
+        &(withdrawn + fees.amount),
+    )?;
+    save_earnings_record(deps.storage, EarningsKind::Withdrawal, fees.clone(), &env)?;
+
     let cosmos_msg = BankMsg::Send {
         from_address: env.contract.address,
         to_address: owner,
-        amount: vec![fees],
+        amount: vec![fees.clone()],
     }
     .into();
     Ok(HandleResponse {
         messages: vec![cosmos_msg],
-        attributes: vec![attr("action", "withdraw_fees")],
+        attributes: vec![
+            attr("action", "withdraw_fees"),
+            attr("denom", fees.denom),
+            attr("amount", fees.amount),
+        ],
+        ..HandleResponse::default()
+    })
+}
+
+fn try_sync_earnings(
+    deps: DepsMut,
+    env: Env,
+    denoms: Vec<String>,
+) -> Result<HandleResponse, ContractError> {
+    for denom in denoms {
+        sync_earnings(deps.storage, &deps.querier, &env, &denom)?;
+    }
+    Ok(HandleResponse {
+        attributes: vec![attr("action", "sync_earnings")],
         ..HandleResponse::default()
     })
 }
 
+/// fees are sent to the contract with bank messages, so whatever the balance grew by since the last sync was earned. Returns the balance
+fn sync_earnings(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    env: &Env,
+    denom: &str,
+) -> StdResult<Uint128> {
+    let earnings = query_earnings_amounts(storage, querier, env, denom)?;
+    let recorded = EARNINGS
+        .may_load(storage, denom.as_bytes())?
+        .unwrap_or_default();
+    if earnings.earned > recorded {
+        EARNINGS.save(storage, denom.as_bytes(), &earnings.earned)?;
+        let amount = Coin {
+            denom: denom.to_string(),
+            amount: Uint128::from(earnings.earned.u128() - recorded.u128()),
+        };
+        save_earnings_record(storage, EarningsKind::Earning, amount, env)?;
+    }
+    Ok(earnings.available)
+}
+
+fn save_earnings_record(
+    storage: &mut dyn Storage,
+    kind: EarningsKind,
+    amount: Coin,
+    env: &Env,
+) -> StdResult<()> {
+    let id = EARNINGS_HISTORY_COUNT
+        .may_load(storage)?
+        .unwrap_or_default()
+        + 1;
+    EARNINGS_HISTORY.save(
+        storage,
+        U64Key::new(id),
+        &EarningsRecord {
+            id,
+            kind,
+            amount,
+            height: env.block.height,
+        },
+    )?;
+    EARNINGS_HISTORY_COUNT.save(storage, &id)
+}
+
 fn try_set_fees(
     deps: DepsMut,
     info: MessageInfo,
@@ -118,20 +213,138 @@ fn try_set_state(
         state.language = language;
     }
     if let Some(script_url) = state_msg.script_url {
+        // the hash of the previous script does not apply anymore
+        if script_url.ne(&state.script_url) {
+            state.script_hash = None;
+        }
         state.script_url = script_url;
     }
     if let Some(parameters) = state_msg.parameters {
         state.parameters = parameters;
     }
+    if let Some(script_hash) = state_msg.script_hash {
+        state.script_hash = Some(script_hash);
+    }
+    if let Some(version) = state_msg.version {
+        if let Some(current) = state.version {
+            if version <= current {
+                return Err(ContractError::VersionNotIncreased { current });
+            }
+        }
+        state.version = Some(version);
+    }
+    if let Some(input_schemas) = state_msg.input_schemas {
+        state.input_schemas = Some(input_schemas);
+    }
+    validate_profile(&state)?;
     config(deps.storage).save(&state)?;
     Ok(HandleResponse::default())
 }
 
-pub fn query_provider(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+fn validate_profile(state: &State) -> Result<(), ContractError> {
+    if let Some(script_hash) = &state.script_hash {
+        if script_hash.len() != 64 || !script_hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ContractError::InvalidScriptHash {
+                script_hash: script_hash.clone(),
+            });
+        }
+    }
+    if let Some(input_schemas) = &state.input_schemas {
+        for (i, input_schema) in input_schemas.iter().enumerate() {
+            if input_schema.name.is_empty()
+                || input_schemas[..i]
+                    .iter()
+                    .any(|other| other.name.eq(&input_schema.name))
+            {
+                return Err(ContractError::InvalidInputSchema {
+                    name: input_schema.name.clone(),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn query_provider(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetState {} => to_binary(&query_state(deps)?),
         QueryMsg::GetOwner {} => to_binary(&query_owner(deps)?),
+        QueryMsg::GetEarnings { denom } => to_binary(&query_earnings_amounts(
+            deps.storage,
+            &deps.querier,
+            &env,
+            &denom,
+        )?),
+        QueryMsg::GetEarningsHistory {
+            offset,
+            limit,
+            order,
+        } => to_binary(&query_earnings_history(deps, offset, limit, order)?),
+    }
+}
+
+/// earnings including what was sent since the last sync
+fn query_earnings_amounts(
+    storage: &dyn Storage,
+    querier: &QuerierWrapper,
+    env: &Env,
+    denom: &str,
+) -> StdResult<EarningsResponse> {
+    let available = querier
+        .query_balance(env.contract.address.clone(), denom)?
+        .amount;
+    let withdrawn = WITHDRAWALS
+        .may_load(storage, denom.as_bytes())?
+        .unwrap_or_default();
+    let recorded = EARNINGS
+        .may_load(storage, denom.as_bytes())?
+        .unwrap_or_default();
+    Ok(EarningsResponse {
+        denom: denom.to_string(),
+        earned: recorded.max(available + withdrawn),
+        withdrawn,
+        available,
+    })
+}
+
+fn query_earnings_history(
+    deps: Deps,
+    offset: Option<u64>,
+    limit: Option<u8>,
+    order: Option<u8>,
+) -> StdResult<EarningsHistoryResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let mut min: Option<Bound> = None;
+    let mut max: Option<Bound> = None;
+    let mut order_enum = Order::Ascending;
+    if let Some(num) = order {
+        if num == 2 {
+            order_enum = Order::Descending;
+        }
     }
+
+    // if there is offset, assign to min or max
+    if let Some(offset) = offset {
+        let offset_value = Some(Bound::Exclusive(offset.to_be_bytes().to_vec()));
+        match order_enum {
+            Order::Ascending => min = offset_value,
+            Order::Descending => max = offset_value,
+        }
+    };
+
+    let records: StdResult<Vec<EarningsRecord>> = EARNINGS_HISTORY
+        .range(deps.storage, min, max, order_enum)
+        .take(limit)
+        .map(|item| item.map(|(_, record)| record))
+        .collect();
+
+    Ok(EarningsHistoryResponse {
+        total: EARNINGS_HISTORY_COUNT
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+        records: records?,
+    })
 }
 
 fn query_owner(deps: Deps) -> StdResult<HumanAddr> {
@@ -147,12 +360,17 @@ fn query_state(deps: Deps) -> StdResult<State> {
 #[cfg(test)]
 mod tests {
     use cosmwasm_std::{
-        coins, from_binary,
-        testing::{mock_dependencies, mock_env, mock_info},
+        coin, coins, from_binary,
+        testing::{mock_dependencies, mock_env, mock_info, MOCK_CONTRACT_ADDR},
+        Uint128,
     };
 
     use crate::{
-        handle_provider, init_provider, msg::StateMsg, query_provider, state::State, InitMsg,
+        handle_provider, init_provider,
+        msg::{EarningsHistoryResponse, EarningsResponse, StateMsg},
+        query_provider,
+        state::{EarningsKind, InputSchema, State},
+        InitMsg,
     };
 
     // use cosmwasm_std::from_slice;
@@ -169,6 +387,9 @@ mod tests {
                 language: String::from("node"),
                 script_url: String::from("url"),
                 parameters: vec![String::from("param")],
+                script_hash: None,
+                version: None,
+                input_schemas: None,
             }),
         )
         .unwrap();
@@ -191,6 +412,9 @@ mod tests {
                 language: String::from("node"),
                 script_url: String::from("url"),
                 parameters: vec![String::from("param")],
+                script_hash: None,
+                version: None,
+                input_schemas: None,
             }),
         )
         .unwrap();
@@ -205,6 +429,9 @@ mod tests {
                     parameters: Some(vec![]),
                     language: None,
                     script_url: None,
+                    script_hash: None,
+                    version: None,
+                    input_schemas: None,
                 }),
             ),
             Err(crate::ContractError::Unauthorized {})
@@ -219,6 +446,9 @@ mod tests {
                 parameters: Some(vec![]),
                 language: None,
                 script_url: None,
+                script_hash: None,
+                version: None,
+                input_schemas: None,
             }),
         )
         .unwrap();
@@ -229,4 +459,215 @@ mod tests {
         .unwrap();
         assert_eq!(state.parameters, vec![] as Vec<String>);
     }
+
+    #[test]
+    fn update_profile() {
+        let mut deps = mock_dependencies(&[]);
+        let script_hash =
+            String::from("9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08");
+
+        // malformed hashes are rejected on init
+        assert!(init_provider(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            InitMsg(State {
+                language: String::from("node"),
+                script_url: String::from("url"),
+                parameters: vec![],
+                script_hash: Some(String::from("not a hash")),
+                version: None,
+                input_schemas: None,
+            }),
+        )
+        .is_err());
+        init_provider(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            InitMsg(State {
+                language: String::from("node"),
+                script_url: String::from("url"),
+                parameters: vec![],
+                script_hash: Some(script_hash.clone()),
+                version: Some(1),
+                input_schemas: Some(vec![InputSchema {
+                    name: String::from("price"),
+                    schema: String::from(r#"{"type":"string"}"#),
+                }]),
+            }),
+        )
+        .unwrap();
+
+        let set_state = |state_msg: StateMsg, deps: cosmwasm_std::DepsMut| {
+            handle_provider(
+                deps,
+                mock_env(),
+                mock_info("creator", &[]),
+                crate::HandleMsg::SetState(state_msg),
+            )
+        };
+        let state_msg = StateMsg {
+            language: None,
+            script_url: None,
+            parameters: None,
+            script_hash: None,
+            version: None,
+            input_schemas: None,
+        };
+
+        // the version only goes up
+        assert!(matches!(
+            set_state(
+                StateMsg {
+                    version: Some(1),
+                    ..state_msg.clone()
+                },
+                deps.as_mut()
+            ),
+            Err(crate::ContractError::VersionNotIncreased { current: 1 })
+        ));
+
+        // input schema names are unique
+        let input_schema = InputSchema {
+            name: String::from("image"),
+            schema: String::from(r#"{"type":"string"}"#),
+        };
+        assert!(matches!(
+            set_state(
+                StateMsg {
+                    input_schemas: Some(vec![input_schema.clone(), input_schema.clone()]),
+                    ..state_msg.clone()
+                },
+                deps.as_mut()
+            ),
+            Err(crate::ContractError::InvalidInputSchema { .. })
+        ));
+
+        // a new script without its hash clears the previous one
+        set_state(
+            StateMsg {
+                script_url: Some(String::from("new url")),
+                version: Some(2),
+                input_schemas: Some(vec![input_schema.clone()]),
+                ..state_msg
+            },
+            deps.as_mut(),
+        )
+        .unwrap();
+        let state: State = from_binary(
+            &query_provider(deps.as_ref(), mock_env(), crate::QueryMsg::GetState {}).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(state.script_hash, None);
+        assert_eq!(state.version, Some(2));
+        assert_eq!(state.input_schemas, Some(vec![input_schema]));
+    }
+
+    #[test]
+    fn earnings_accounting() {
+        let mut deps = mock_dependencies(&[]);
+        init_provider(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            InitMsg(State {
+                language: String::from("node"),
+                script_url: String::from("url"),
+                parameters: vec![],
+                script_hash: None,
+                version: None,
+                input_schemas: None,
+            }),
+        )
+        .unwrap();
+
+        let query_earnings = |deps: cosmwasm_std::Deps| -> EarningsResponse {
+            from_binary(
+                &query_provider(
+                    deps,
+                    mock_env(),
+                    crate::QueryMsg::GetEarnings {
+                        denom: String::from("orai"),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap()
+        };
+
+        // fees are paid to the contract
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(100u128, "orai"));
+        handle_provider(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            crate::HandleMsg::SyncEarnings {
+                denoms: vec![String::from("orai")],
+            },
+        )
+        .unwrap();
+
+        // cannot withdraw more than the balance
+        assert!(matches!(
+            handle_provider(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("creator", &[]),
+                crate::HandleMsg::WithdrawFees {
+                    fee: coin(150u128, "orai")
+                },
+            ),
+            Err(crate::ContractError::InsufficientEarnings { .. })
+        ));
+
+        // more fees arrive before the withdrawal, they are recorded first
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(130u128, "orai"));
+        handle_provider(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            crate::HandleMsg::WithdrawFees {
+                fee: coin(80u128, "orai"),
+            },
+        )
+        .unwrap();
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(50u128, "orai"));
+
+        let earnings = query_earnings(deps.as_ref());
+        assert_eq!(earnings.earned, Uint128::from(130u64));
+        assert_eq!(earnings.withdrawn, Uint128::from(80u64));
+        assert_eq!(earnings.available, Uint128::from(50u64));
+
+        // fees not synced yet are part of the earnings
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(60u128, "orai"));
+        assert_eq!(query_earnings(deps.as_ref()).earned, Uint128::from(140u64));
+
+        let history: EarningsHistoryResponse = from_binary(
+            &query_provider(
+                deps.as_ref(),
+                mock_env(),
+                crate::QueryMsg::GetEarningsHistory {
+                    offset: Some(1),
+                    limit: None,
+                    order: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(history.total, 3);
+        assert_eq!(
+            history
+                .records
+                .iter()
+                .map(|record| (record.kind.clone(), record.amount.amount.u128()))
+                .collect::<Vec<_>>(),
+            vec![(EarningsKind::Earning, 30), (EarningsKind::Withdrawal, 80)]
+        );
+    }
 }
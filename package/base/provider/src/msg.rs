@@ -1,8 +1,8 @@
-use cosmwasm_std::{Coin, HumanAddr};
+use cosmwasm_std::{Coin, HumanAddr, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::state::State;
+use crate::state::{EarningsRecord, InputSchema, State};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InitMsg(pub State);
@@ -11,9 +11,20 @@ pub struct InitMsg(pub State);
 #[serde(rename_all = "snake_case")]
 pub enum HandleMsg {
     SetState(StateMsg),
-    SetServiceFees { contract_addr: HumanAddr, fee: Coin },
-    WithdrawFees { fee: Coin },
-    SetOwner { owner: String },
+    SetServiceFees {
+        contract_addr: HumanAddr,
+        fee: Coin,
+    },
+    WithdrawFees {
+        fee: Coin,
+    },
+    SetOwner {
+        owner: String,
+    },
+    /// records what has been earned in the denoms since the last sync
+    SyncEarnings {
+        denoms: Vec<String>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -21,6 +32,14 @@ pub enum HandleMsg {
 pub enum QueryMsg {
     GetState {},
     GetOwner {},
+    GetEarnings {
+        denom: String,
+    },
+    GetEarningsHistory {
+        offset: Option<u64>,
+        limit: Option<u8>,
+        order: Option<u8>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -28,6 +47,24 @@ pub struct StateMsg {
     pub language: Option<String>,
     pub script_url: Option<String>,
     pub parameters: Option<Vec<String>>,
+    /// the hash is cleared when the script url changes without a new one
+    pub script_hash: Option<String>,
+    pub version: Option<u64>,
+    pub input_schemas: Option<Vec<InputSchema>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EarningsResponse {
+    pub denom: String,
+    pub earned: Uint128,
+    pub withdrawn: Uint128,
+    pub available: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EarningsHistoryResponse {
+    pub total: u64,
+    pub records: Vec<EarningsRecord>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -1,10 +1,10 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{HumanAddr, Storage};
+use cosmwasm_std::{Coin, HumanAddr, Storage, Uint128};
 use cosmwasm_storage::{singleton, singleton_read, ReadonlySingleton, Singleton};
 
-use cw_storage_plus::Item;
+use cw_storage_plus::{Item, Map, U64Key};
 
 pub static CONFIG_KEY: &[u8] = b"config";
 
@@ -13,10 +13,46 @@ pub struct State {
     pub language: String,
     pub script_url: String,
     pub parameters: Vec<String>,
+    /// hex encoded sha256 of the script content, so executors can check the script they download
+    pub script_hash: Option<String>,
+    pub version: Option<u64>,
+    pub input_schemas: Option<Vec<InputSchema>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InputSchema {
+    pub name: String,
+    /// json schema of the input
+    pub schema: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EarningsKind {
+    Earning,
+    Withdrawal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EarningsRecord {
+    pub id: u64,
+    pub kind: EarningsKind,
+    pub amount: Coin,
+    pub height: u64,
 }
 
 pub const OWNER: Item<HumanAddr> = Item::new("owner");
 
+// key: denom. Total earned as of the last sync, which is the balance plus everything withdrawn
+pub const EARNINGS: Map<&[u8], Uint128> = Map::new("earnings");
+
+// key: denom. Total withdrawn by the owner
+pub const WITHDRAWALS: Map<&[u8], Uint128> = Map::new("withdrawals");
+
+pub const EARNINGS_HISTORY: Map<U64Key, EarningsRecord> = Map::new("earnings_history");
+
+pub const EARNINGS_HISTORY_COUNT: Item<u64> = Item::new("earnings_history_count");
+
 pub fn config(storage: &mut dyn Storage) -> Singleton<State> {
     singleton(storage, CONFIG_KEY)
 }
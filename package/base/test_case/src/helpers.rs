@@ -1,11 +1,12 @@
 use crate::error::ContractError;
 use crate::msg::{
-    AssertOutput, HandleMsg, InitMsg, QueryMsg, Response, TestCaseMsg, TestCaseResponse,
+    AssertOutput, HandleMsg, InitMsg, MatchInput, MigrateMsg, QueryMsg, Response, TestCaseMsg,
+    TestCaseResponse,
 };
-use crate::state::{FEES, OWNER, TEST_CASES};
+use crate::state::{FEES, OWNER, TEST_CASES, TEST_CASES_COUNT, TEST_CASE_MATCHERS};
 use cosmwasm_std::{
-    from_binary, from_slice, to_binary, Api, Binary, Deps, DepsMut, Env, HandleResponse, HumanAddr,
-    InitResponse, MessageInfo, Order, StdResult, KV,
+    from_binary, from_slice, to_binary, Binary, Deps, DepsMut, Env, HandleResponse, HumanAddr,
+    InitResponse, MessageInfo, MigrateResponse, Order, StdError, StdResult, Storage, KV,
 };
 use cw_storage_plus::Bound;
 
@@ -22,12 +23,8 @@ pub fn init_testcase(
     msg: InitMsg,
 ) -> StdResult<InitResponse> {
     for test_case in msg.test_cases {
-        let input_bin = to_binary(&test_case.parameters)?;
-        TEST_CASES.save(
-            deps.storage,
-            input_bin.as_slice(),
-            &test_case.expected_output,
-        )?;
+        save_test_case(deps.storage, test_case)
+            .map_err(|err| StdError::generic_err(err.to_string()))?;
     }
     if let Some(fees) = msg.fees {
         FEES.save(deps.storage, &fees)?;
@@ -36,6 +33,20 @@ pub fn init_testcase(
     Ok(InitResponse::default())
 }
 
+/// stores deployed before the test case counter was maintained are counted once here
+pub fn migrate_testcase(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: MigrateMsg,
+) -> StdResult<MigrateResponse> {
+    let count = TEST_CASES
+        .range(deps.storage, None, None, Order::Ascending)
+        .count() as u64;
+    TEST_CASES_COUNT.save(deps.storage, &count)?;
+    Ok(MigrateResponse::default())
+}
+
 // And declare a custom Error variant for the ones where you will want to make use of it
 pub fn handle_testcase(
     deps: DepsMut,
@@ -46,6 +57,8 @@ pub fn handle_testcase(
     match msg {
         HandleMsg::AddTestCase { test_case } => try_add_test_case(deps, info, test_case),
         HandleMsg::RemoveTestCase { input } => try_remove_test_case(deps, info, input),
+        HandleMsg::AddTestCases { test_cases } => try_add_test_cases(deps, info, test_cases),
+        HandleMsg::RemoveTestCases { inputs } => try_remove_test_cases(deps, info, inputs),
         HandleMsg::SetOwner { owner } => try_set_owner(deps, info, owner),
     }
 }
@@ -59,15 +72,45 @@ fn try_add_test_case(
     if !info.sender.eq(&owner) {
         return Err(ContractError::Unauthorized {});
     }
-    let input_bin = to_binary(&test_case.parameters)?;
-    TEST_CASES.save(
-        deps.storage,
-        input_bin.as_slice(),
-        &test_case.expected_output,
-    )?;
+    save_test_case(deps.storage, test_case)?;
     Ok(HandleResponse::default())
 }
 
+fn try_add_test_cases(
+    deps: DepsMut,
+    info: MessageInfo,
+    test_cases: Vec<TestCaseMsg>,
+) -> Result<HandleResponse, ContractError> {
+    let owner: HumanAddr = OWNER.load(deps.storage)?;
+    if !info.sender.eq(&owner) {
+        return Err(ContractError::Unauthorized {});
+    }
+    for test_case in test_cases {
+        save_test_case(deps.storage, test_case)?;
+    }
+    Ok(HandleResponse::default())
+}
+
+fn save_test_case(storage: &mut dyn Storage, test_case: TestCaseMsg) -> Result<(), ContractError> {
+    let input_bin = to_binary(&test_case.parameters)?;
+    if TEST_CASES
+        .may_load(storage, input_bin.as_slice())?
+        .is_none()
+    {
+        let count = load_test_case_count(storage)?;
+        TEST_CASES_COUNT.save(storage, &(count + 1))?;
+    }
+    match test_case.matcher {
+        Some(matcher) => {
+            matcher.validate(&test_case.expected_output)?;
+            TEST_CASE_MATCHERS.save(storage, input_bin.as_slice(), &matcher)?;
+        }
+        None => TEST_CASE_MATCHERS.remove(storage, input_bin.as_slice()),
+    }
+    TEST_CASES.save(storage, input_bin.as_slice(), &test_case.expected_output)?;
+    Ok(())
+}
+
 fn try_remove_test_case(
     deps: DepsMut,
     info: MessageInfo,
@@ -77,11 +120,45 @@ fn try_remove_test_case(
     if !info.sender.eq(&owner) {
         return Err(ContractError::Unauthorized {});
     }
-    let input_bin = to_binary(&input)?;
-    TEST_CASES.remove(deps.storage, input_bin.as_slice());
+    remove_test_case(deps.storage, input)?;
     Ok(HandleResponse::default())
 }
 
+fn try_remove_test_cases(
+    deps: DepsMut,
+    info: MessageInfo,
+    inputs: Vec<Vec<String>>,
+) -> Result<HandleResponse, ContractError> {
+    let owner: HumanAddr = OWNER.load(deps.storage)?;
+    if !info.sender.eq(&owner) {
+        return Err(ContractError::Unauthorized {});
+    }
+    for input in inputs {
+        remove_test_case(deps.storage, input)?;
+    }
+    Ok(HandleResponse::default())
+}
+
+fn remove_test_case(storage: &mut dyn Storage, input: Vec<String>) -> StdResult<()> {
+    let input_bin = to_binary(&input)?;
+    if TEST_CASES
+        .may_load(storage, input_bin.as_slice())?
+        .is_none()
+    {
+        return Ok(());
+    }
+    let count = load_test_case_count(storage)?;
+    TEST_CASES.remove(storage, input_bin.as_slice());
+    TEST_CASE_MATCHERS.remove(storage, input_bin.as_slice());
+    TEST_CASES_COUNT.save(storage, &count.saturating_sub(1))?;
+    Ok(())
+}
+
+/// the counter is set by the migration for stores deployed before it was maintained
+fn load_test_case_count(storage: &dyn Storage) -> StdResult<u64> {
+    Ok(TEST_CASES_COUNT.may_load(storage)?.unwrap_or_default())
+}
+
 fn try_set_owner(
     deps: DepsMut,
     info: MessageInfo,
@@ -106,10 +183,11 @@ pub fn query_testcase(
             limit,
             offset,
             order,
-        } => to_binary(&query_testcases(deps, limit, offset, order)?),
+            prefix,
+        } => to_binary(&query_testcases(deps, limit, offset, order, prefix)?),
         QueryMsg::GetOwner {} => query_owner(deps),
         QueryMsg::Assert { assert_inputs } => {
-            to_binary(&assert(env, assert_inputs, assert_handler)?)
+            to_binary(&assert(deps, env, assert_inputs, assert_handler)?)
         }
     }
 }
@@ -120,42 +198,80 @@ fn query_owner(deps: Deps) -> StdResult<Binary> {
 }
 
 fn assert(
+    deps: Deps,
     env: Env,
     assert_inputs: Vec<String>,
     assert_handler: AssertHandler,
 ) -> StdResult<Response> {
-    // force all assert handler output to follow the AssertOutput struct
-    let result_handler_result = assert_handler(assert_inputs.as_slice());
-    if result_handler_result.is_err() {
-        return Ok(Response {
-            contract: env.contract.address.clone(),
-            dsource_status: true,
-            tcase_status: false,
-        });
+    let mut result = AssertOutput {
+        dsource_status: true,
+        tcase_status: true,
+    };
+    // test cases with a built-in matcher are graded without the assert handler, which grades the other inputs
+    let mut handler_inputs: Vec<String> = vec![];
+    for assert_input in assert_inputs.iter() {
+        match assert_with_matcher(deps, assert_input)? {
+            Some(assert) => {
+                result.dsource_status &= assert.dsource_status;
+                result.tcase_status &= assert.tcase_status;
+            }
+            None => handler_inputs.push(assert_input.clone()),
+        }
     }
-    let result_handler = result_handler_result.unwrap();
-    let assert_result = from_binary(&result_handler);
-    if assert_result.is_err() {
-        return Ok(Response {
-            contract: env.contract.address.clone(),
+    if !handler_inputs.is_empty() || assert_inputs.is_empty() {
+        let assert = assert_with_handler(&handler_inputs, assert_handler);
+        result.dsource_status &= assert.dsource_status;
+        result.tcase_status &= assert.tcase_status;
+    }
+
+    Ok(Response {
+        contract: env.contract.address,
+        dsource_status: result.dsource_status,
+        tcase_status: result.tcase_status,
+    })
+}
+
+/// force all assert handler output to follow the AssertOutput struct, the test case is failed otherwise
+fn assert_with_handler(assert_inputs: &[String], assert_handler: AssertHandler) -> AssertOutput {
+    assert_handler(assert_inputs)
+        .and_then(|result| from_binary(&result))
+        .unwrap_or(AssertOutput {
             dsource_status: true,
             tcase_status: false,
-        });
+        })
+}
+
+/// grades the input with the matcher of its test case. None if the input does not refer to a test case with a matcher
+fn assert_with_matcher(deps: Deps, assert_input: &str) -> StdResult<Option<AssertOutput>> {
+    let MatchInput { parameters, output } = match from_slice(assert_input.as_bytes()) {
+        Ok(match_input) => match_input,
+        Err(_) => return Ok(None),
     };
-    let assert: AssertOutput = assert_result.unwrap();
-    let response = Response {
-        contract: env.contract.address,
-        dsource_status: assert.dsource_status,
-        tcase_status: assert.tcase_status,
+    let input_bin = to_binary(&parameters)?;
+    let matcher = match TEST_CASE_MATCHERS.may_load(deps.storage, input_bin.as_slice())? {
+        Some(matcher) => matcher,
+        None => return Ok(None),
     };
-    Ok(response)
+    let expected_output = TEST_CASES.load(deps.storage, input_bin.as_slice())?;
+    // a test case that cannot be graded is failing, not the data source
+    if matcher.validate(&expected_output).is_err() {
+        return Ok(Some(AssertOutput {
+            dsource_status: true,
+            tcase_status: false,
+        }));
+    }
+    Ok(Some(AssertOutput {
+        dsource_status: matcher.matches(&output, &expected_output),
+        tcase_status: true,
+    }))
 }
 
-fn parse_testcase(_api: &dyn Api, item: StdResult<KV<String>>) -> StdResult<TestCaseMsg> {
+fn parse_testcase(storage: &dyn Storage, item: StdResult<KV<String>>) -> StdResult<TestCaseMsg> {
     item.and_then(|(parameters, expected_output)| {
         // will panic if length is greater than 8, but we can make sure it is u64
         // try_into will box vector to fixed array
         Ok(TestCaseMsg {
+            matcher: TEST_CASE_MATCHERS.may_load(storage, &parameters)?,
             parameters: from_slice(&parameters)?,
             expected_output,
         })
@@ -167,6 +283,7 @@ fn query_testcases(
     limit: Option<u8>,
     offset: Option<Binary>,
     order: Option<u8>,
+    prefix: Option<String>,
 ) -> StdResult<TestCaseResponse> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
 
@@ -179,32 +296,70 @@ fn query_testcases(
         }
     }
 
-    // if there is offset, assign to min or max
+    // keys are the json encoded parameters, so the prefix is the encoded first parameter without its closing quote
+    let mut prefix_range: Option<(Vec<u8>, Vec<u8>)> = None;
+    if let Some(prefix) = prefix {
+        let mut start = to_binary(&vec![prefix])?.to_vec();
+        start.truncate(start.len() - 2);
+        let mut end = start.clone();
+        // the last byte is utf-8 so it cannot overflow
+        if let Some(last) = end.last_mut() {
+            *last += 1;
+        }
+        min = Some(Bound::Inclusive(start.clone()));
+        max = Some(Bound::Exclusive(end.clone()));
+        prefix_range = Some((start, end));
+    }
+
+    // if there is offset, assign to min or max, staying within the prefix
     if let Some(offset) = offset {
-        let offset_value = Some(Bound::Exclusive(offset.to_vec()));
+        let offset = offset.to_vec();
         match order_enum {
-            Order::Ascending => min = offset_value,
-            Order::Descending => max = offset_value,
+            Order::Ascending => {
+                if prefix_range
+                    .as_ref()
+                    .map(|(start, _)| offset.ge(start))
+                    .unwrap_or(true)
+                {
+                    min = Some(Bound::Exclusive(offset))
+                }
+            }
+            Order::Descending => {
+                if prefix_range
+                    .as_ref()
+                    .map(|(_, end)| offset.lt(end))
+                    .unwrap_or(true)
+                {
+                    max = Some(Bound::Exclusive(offset))
+                }
+            }
         }
     };
-    // calculate total test case sizes
-    let list = TEST_CASES
-        .range(deps.storage, None, None, order_enum)
-        .enumerate();
-    let mut total = 0;
-    for _ in list {
-        total += 1;
-    }
 
-    let res: StdResult<Vec<TestCaseMsg>> = TEST_CASES
+    // take one more to know if there is a next page
+    let mut items: Vec<StdResult<KV<String>>> = TEST_CASES
         .range(deps.storage, min, max, order_enum)
-        .take(limit)
-        .map(|kv_item| parse_testcase(deps.api, kv_item))
+        .take(limit + 1)
+        .collect();
+    let next_offset = if items.len() > limit {
+        items.truncate(limit);
+        match items.last() {
+            Some(Ok((key, _))) => Some(Binary::from(key.as_slice())),
+            _ => None,
+        }
+    } else {
+        None
+    };
+
+    let res: StdResult<Vec<TestCaseMsg>> = items
+        .into_iter()
+        .map(|kv_item| parse_testcase(deps.storage, kv_item))
         .collect();
 
     Ok(TestCaseResponse {
-        total,
-        test_cases: res?, // Placeholder
+        total: load_test_case_count(deps.storage)?,
+        test_cases: res?,
+        next_offset,
     })
 }
 
@@ -216,8 +371,14 @@ mod tests {
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
     use cosmwasm_std::{coin, coins, from_binary};
 
-    use crate::msg::{TestCaseMsg, TestCaseResponse};
-    use crate::{init_testcase, query_testcase, InitMsg, QueryMsg};
+    use crate::msg::{
+        AssertOutput, MatchInput, MigrateMsg, Response, TestCaseMsg, TestCaseResponse,
+    };
+    use crate::state::TEST_CASES_COUNT;
+    use crate::{
+        handle_testcase, init_testcase, migrate_testcase, query_testcase, HandleMsg, InitMsg,
+        Matcher, QueryMsg,
+    };
 
     use cosmwasm_std::{to_binary, Binary, StdResult};
 
@@ -225,6 +386,13 @@ mod tests {
         to_binary("hi")
     }
 
+    pub fn assert_pass(_: &[String]) -> StdResult<Binary> {
+        to_binary(&AssertOutput {
+            dsource_status: true,
+            tcase_status: true,
+        })
+    }
+
     #[test]
     fn query_list_test_cases() {
         let mut deps = mock_dependencies(&coins(5, "orai"));
@@ -234,6 +402,7 @@ mod tests {
             let test_case_msg = TestCaseMsg {
                 parameters: vec![format!("ethereum {}", i)],
                 expected_output: format!("hello{:?}", i),
+                matcher: None,
             };
             test_cases.push(test_case_msg);
             // code goes here
@@ -254,6 +423,7 @@ mod tests {
                 limit: Some(1),
                 offset: None,
                 order: None,
+                prefix: None,
             },
             assert,
         )
@@ -274,6 +444,7 @@ mod tests {
                     limit: Some(1),
                     offset: Some(to_binary(&vec![String::from("ethereum 0")]).unwrap()),
                     order: None,
+                    prefix: None,
                 },
                 assert,
             )
@@ -286,4 +457,248 @@ mod tests {
             String::from("hello1")
         );
     }
+
+    #[test]
+    fn paginate_test_cases() {
+        let mut deps = mock_dependencies(&coins(5, "orai"));
+        let test_case = |parameter: &str| TestCaseMsg {
+            parameters: vec![parameter.to_string()],
+            expected_output: format!("{} output", parameter),
+            matcher: None,
+        };
+        let msg = InitMsg {
+            test_cases: vec![test_case("btc 1"), test_case("eth 1")],
+            fees: None,
+        };
+        init_testcase(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // only the owner can add test cases in bulk
+        assert!(handle_testcase(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("someone", &[]),
+            HandleMsg::AddTestCases {
+                test_cases: vec![test_case("eth 2")],
+            },
+        )
+        .is_err());
+        handle_testcase(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            HandleMsg::AddTestCases {
+                // updating an existing test case does not change the count
+                test_cases: vec![test_case("eth 1"), test_case("eth 2"), test_case("eth 3")],
+            },
+        )
+        .unwrap();
+
+        let query_page = |deps: cosmwasm_std::Deps,
+                          offset: Option<Binary>,
+                          order: Option<u8>,
+                          prefix: Option<&str>|
+         -> TestCaseResponse {
+            from_binary(
+                &query_testcase(
+                    deps,
+                    mock_env(),
+                    QueryMsg::GetTestCases {
+                        limit: Some(2),
+                        offset,
+                        order,
+                        prefix: prefix.map(String::from),
+                    },
+                    assert,
+                )
+                .unwrap(),
+            )
+            .unwrap()
+        };
+        let outputs = |res: &TestCaseResponse| -> Vec<String> {
+            res.test_cases
+                .iter()
+                .map(|test_case| test_case.parameters[0].clone())
+                .collect()
+        };
+
+        // the total is not filtered by the prefix
+        let page = query_page(deps.as_ref(), None, None, Some("eth"));
+        assert_eq!(page.total, 4);
+        assert_eq!(outputs(&page), vec!["eth 1", "eth 2"]);
+        let page = query_page(deps.as_ref(), page.next_offset, None, Some("eth"));
+        assert_eq!(outputs(&page), vec!["eth 3"]);
+        assert_eq!(page.next_offset, None);
+
+        // descending within the prefix
+        let page = query_page(deps.as_ref(), None, Some(2), Some("eth"));
+        assert_eq!(outputs(&page), vec!["eth 3", "eth 2"]);
+        let page = query_page(deps.as_ref(), page.next_offset, Some(2), Some("eth"));
+        assert_eq!(outputs(&page), vec!["eth 1"]);
+        assert_eq!(page.next_offset, None);
+
+        // an offset before the prefix does not leave it
+        let offset = to_binary(&vec![String::from("btc 1")]).unwrap();
+        let page = query_page(deps.as_ref(), Some(offset), None, Some("eth"));
+        assert_eq!(outputs(&page), vec!["eth 1", "eth 2"]);
+
+        let page = query_page(deps.as_ref(), None, None, Some("doge"));
+        assert!(page.test_cases.is_empty());
+
+        // removing unknown test cases does not change the count
+        handle_testcase(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            HandleMsg::RemoveTestCases {
+                inputs: vec![
+                    vec![String::from("eth 1")],
+                    vec![String::from("eth 2")],
+                    vec![String::from("doge 1")],
+                ],
+            },
+        )
+        .unwrap();
+        let page = query_page(deps.as_ref(), None, None, None);
+        assert_eq!(page.total, 2);
+        assert_eq!(outputs(&page), vec!["btc 1", "eth 3"]);
+        assert_eq!(page.next_offset, None);
+
+        // stores without a counter are counted by the migration
+        TEST_CASES_COUNT.remove(deps.as_mut().storage);
+        assert_eq!(query_page(deps.as_ref(), None, None, None).total, 0);
+        migrate_testcase(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            MigrateMsg {},
+        )
+        .unwrap();
+        assert_eq!(query_page(deps.as_ref(), None, None, None).total, 2);
+    }
+
+    #[test]
+    fn assert_with_matchers() {
+        let mut deps = mock_dependencies(&coins(5, "orai"));
+        let msg = InitMsg {
+            test_cases: vec![TestCaseMsg {
+                parameters: vec![String::from("BTC")],
+                expected_output: String::from("40000"),
+                matcher: Some(Matcher::Tolerance {
+                    absolute: None,
+                    relative: Some(50),
+                }),
+            }],
+            fees: None,
+        };
+        init_testcase(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // invalid matchers are rejected
+        assert!(handle_testcase(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            HandleMsg::AddTestCase {
+                test_case: TestCaseMsg {
+                    parameters: vec![String::from("ETH")],
+                    expected_output: String::from("(unclosed"),
+                    matcher: Some(Matcher::Regex {}),
+                },
+            },
+        )
+        .is_err());
+        handle_testcase(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            HandleMsg::AddTestCase {
+                test_case: TestCaseMsg {
+                    parameters: vec![String::from("ETH")],
+                    expected_output: String::from(r"^\d+(\.\d+)?$"),
+                    matcher: Some(Matcher::Regex {}),
+                },
+            },
+        )
+        .unwrap();
+
+        let assert_inputs = |outputs: &[(&str, &str)]| QueryMsg::Assert {
+            assert_inputs: outputs
+                .iter()
+                .map(|(parameter, output)| {
+                    String::from_utf8(
+                        to_binary(&MatchInput {
+                            parameters: vec![parameter.to_string()],
+                            output: output.to_string(),
+                        })
+                        .unwrap()
+                        .to_vec(),
+                    )
+                    .unwrap()
+                })
+                .collect(),
+        };
+        let response: Response = from_binary(
+            &query_testcase(
+                deps.as_ref(),
+                mock_env(),
+                assert_inputs(&[("BTC", "41000"), ("ETH", "3000.5")]),
+                assert,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(response.dsource_status);
+        assert!(response.tcase_status);
+
+        let response: Response = from_binary(
+            &query_testcase(
+                deps.as_ref(),
+                mock_env(),
+                assert_inputs(&[("BTC", "43000"), ("ETH", "3000.5")]),
+                assert,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(!response.dsource_status);
+
+        // test cases without a matcher fall back to the assert handler, failing to parse its output here
+        let response: Response = from_binary(
+            &query_testcase(
+                deps.as_ref(),
+                mock_env(),
+                assert_inputs(&[("BTC", "41000"), ("DOGE", "1")]),
+                assert,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(response.dsource_status);
+        assert!(!response.tcase_status);
+
+        // inputs with a matcher are still graded when the others go to the assert handler
+        let response: Response = from_binary(
+            &query_testcase(
+                deps.as_ref(),
+                mock_env(),
+                assert_inputs(&[("BTC", "43000"), ("DOGE", "1")]),
+                assert_pass,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(!response.dsource_status);
+        assert!(response.tcase_status);
+        let response: Response = from_binary(
+            &query_testcase(
+                deps.as_ref(),
+                mock_env(),
+                assert_inputs(&[("BTC", "41000"), ("DOGE", "1")]),
+                assert_pass,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(response.dsource_status);
+        assert!(response.tcase_status);
+    }
 }
@@ -0,0 +1,356 @@
+use std::collections::BTreeMap;
+
+/// arrays & objects nested deeper are rejected, so that a document cannot exhaust the stack
+pub const MAX_DEPTH: usize = 64;
+
+/// JSON value read by the matchers. Numbers are kept as written, so parsing never goes through floats
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Value>),
+    Object(BTreeMap<String, Value>),
+}
+
+impl Value {
+    /// compact JSON representation, object keys are sorted
+    pub fn to_json(&self) -> String {
+        match self {
+            Value::Null => String::from("null"),
+            Value::Bool(value) => value.to_string(),
+            Value::Number(number) => number.clone(),
+            Value::String(value) => quote(value),
+            Value::Array(values) => format!(
+                "[{}]",
+                values
+                    .iter()
+                    .map(Value::to_json)
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+            Value::Object(map) => format!(
+                "{{{}}}",
+                map.iter()
+                    .map(|(key, value)| format!("{}:{}", quote(key), value.to_json()))
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+        }
+    }
+}
+
+/// parses a whole JSON document, trailing characters other than whitespaces are rejected
+pub fn parse(input: &str) -> Result<Value, String> {
+    let mut parser = Parser {
+        bytes: input.as_bytes(),
+        pos: 0,
+        depth: 0,
+    };
+    let value = parser.parse_value()?;
+    parser.skip_whitespaces();
+    if parser.pos < parser.bytes.len() {
+        return Err(parser.error("trailing characters"));
+    }
+    Ok(value)
+}
+
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '\u{8}' => quoted.push_str("\\b"),
+            '\u{c}' => quoted.push_str("\\f"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    /// arrays & objects the parser is in
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, reason: &str) -> String {
+        format!("invalid JSON at {}: {}", self.pos, reason)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn skip_whitespaces(&mut self) {
+        while let Some(b' ') | Some(b'\n') | Some(b'\r') | Some(b'\t') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        if self.peek() != Some(byte) {
+            return Err(self.error(&format!("expected '{}'", byte as char)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn parse_ident(&mut self, ident: &str, value: Value) -> Result<Value, String> {
+        if !self.bytes[self.pos..].starts_with(ident.as_bytes()) {
+            return Err(self.error("unknown identifier"));
+        }
+        self.pos += ident.len();
+        Ok(value)
+    }
+
+    fn parse_value(&mut self) -> Result<Value, String> {
+        self.skip_whitespaces();
+        match self.peek() {
+            Some(b'n') => self.parse_ident("null", Value::Null),
+            Some(b't') => self.parse_ident("true", Value::Bool(true)),
+            Some(b'f') => self.parse_ident("false", Value::Bool(false)),
+            Some(b'"') => self.parse_string().map(Value::String),
+            Some(b'[') => self.parse_nested(Self::parse_array),
+            Some(b'{') => self.parse_nested(Self::parse_object),
+            Some(b'-') | Some(b'0'..=b'9') => self.parse_number(),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn parse_nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<Value, String>,
+    ) -> Result<Value, String> {
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error("too deeply nested"));
+        }
+        self.depth += 1;
+        let value = parse(self)?;
+        self.depth -= 1;
+        Ok(value)
+    }
+
+    fn parse_array(&mut self) -> Result<Value, String> {
+        self.expect(b'[')?;
+        let mut values = vec![];
+        self.skip_whitespaces();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Value::Array(values));
+        }
+        loop {
+            values.push(self.parse_value()?);
+            self.skip_whitespaces();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Value::Array(values));
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Value, String> {
+        self.expect(b'{')?;
+        let mut map = BTreeMap::new();
+        self.skip_whitespaces();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Value::Object(map));
+        }
+        loop {
+            self.skip_whitespaces();
+            let key = self.parse_string()?;
+            self.skip_whitespaces();
+            self.expect(b':')?;
+            // the last value of a duplicated key is kept
+            map.insert(key, self.parse_value()?);
+            self.skip_whitespaces();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Value::Object(map));
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn parse_digits(&mut self) -> usize {
+        let start = self.pos;
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+
+    fn parse_number(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        match self.peek() {
+            Some(b'0') => self.pos += 1,
+            Some(b'1'..=b'9') => {
+                self.parse_digits();
+            }
+            _ => return Err(self.error("invalid number")),
+        }
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            if self.parse_digits() == 0 {
+                return Err(self.error("invalid number"));
+            }
+        }
+        if let Some(b'e') | Some(b'E') = self.peek() {
+            self.pos += 1;
+            if let Some(b'+') | Some(b'-') = self.peek() {
+                self.pos += 1;
+            }
+            if self.parse_digits() == 0 {
+                return Err(self.error("invalid number"));
+            }
+        }
+        // only ascii characters have been read
+        Ok(Value::Number(
+            String::from_utf8_lossy(&self.bytes[start..self.pos]).into_owned(),
+        ))
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, String> {
+        let hex = self
+            .bytes
+            .get(self.pos..self.pos + 4)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        self.pos += 4;
+        Ok(hex)
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.expect(b'"')?;
+        let mut bytes: Vec<u8> = vec![];
+        loop {
+            let byte = self
+                .peek()
+                .ok_or_else(|| self.error("unterminated string"))?;
+            self.pos += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let escape = self
+                        .peek()
+                        .ok_or_else(|| self.error("unterminated string"))?;
+                    self.pos += 1;
+                    let c = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let mut code = self.parse_hex4()?;
+                            // characters outside the basic plane are escaped as a surrogate pair
+                            if (0xD800..0xDC00).contains(&code) {
+                                if !self.bytes[self.pos..].starts_with(b"\\u") {
+                                    return Err(self.error("lone surrogate"));
+                                }
+                                self.pos += 2;
+                                let low = self.parse_hex4()?;
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return Err(self.error("invalid surrogate pair"));
+                                }
+                                code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                            }
+                            std::char::from_u32(code)
+                                .ok_or_else(|| self.error("invalid unicode escape"))?
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    let mut buf = [0; 4];
+                    bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                byte if byte < 0x20 => return Err(self.error("control character in string")),
+                byte => bytes.push(byte),
+            }
+        }
+        // the input is a str and escapes are encoded as utf8
+        String::from_utf8(bytes).map_err(|_| self.error("invalid utf8"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_documents() {
+        let value = parse(r#" {"b": [1, -2.50, 3e-2, true, null], "a": "x\"\u00e9\ud83d\ude00"} "#)
+            .unwrap();
+        let mut map = BTreeMap::new();
+        map.insert(
+            String::from("a"),
+            Value::String(String::from("x\"\u{e9}\u{1f600}")),
+        );
+        map.insert(
+            String::from("b"),
+            Value::Array(vec![
+                Value::Number(String::from("1")),
+                Value::Number(String::from("-2.50")),
+                Value::Number(String::from("3e-2")),
+                Value::Bool(true),
+                Value::Null,
+            ]),
+        );
+        assert_eq!(value, Value::Object(map));
+        // numbers are written back as they were read
+        assert_eq!(
+            value.to_json(),
+            "{\"a\":\"x\\\"\u{e9}\u{1f600}\",\"b\":[1,-2.50,3e-2,true,null]}"
+        );
+
+        for invalid in &[
+            "", "[1,]", "{\"a\":1", "01", "1.", "-", "\"\\x\"", "[] []", "nul",
+        ] {
+            assert!(parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn parse_nested_documents() {
+        // each level of nesting is an array holding an object: [{"a":
+        let nested = |depth: usize| {
+            format!(
+                "{}null{}",
+                "[{\"a\":".repeat(depth / 2),
+                "}]".repeat(depth / 2)
+            )
+        };
+        assert!(parse(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(
+            parse(&nested(MAX_DEPTH + 2)).unwrap_err(),
+            format!("invalid JSON at {}: too deeply nested", MAX_DEPTH / 2 * 6)
+        );
+        // deep documents fail without overflowing the stack
+        assert!(parse(&"[".repeat(100_000)).is_err());
+    }
+}
@@ -1,16 +1,19 @@
 pub mod error;
 pub mod helpers;
+mod json;
+pub mod matcher;
 pub mod msg;
 pub mod state;
 
 pub use crate::error::ContractError;
-pub use crate::msg::{HandleMsg, InitMsg, QueryMsg};
+pub use crate::matcher::Matcher;
+pub use crate::msg::{HandleMsg, InitMsg, MigrateMsg, QueryMsg};
 pub use cosmwasm_std::{
     to_binary, Binary, Deps, DepsMut, Env, HandleResponse, HumanAddr, InitResponse, MessageInfo,
-    StdResult,
+    MigrateResponse, StdResult,
 };
 
-pub use crate::helpers::{handle_testcase, init_testcase, query_testcase};
+pub use crate::helpers::{handle_testcase, init_testcase, migrate_testcase, query_testcase};
 
 // You can override some logic, except pub use crate, other variable should use namespace prefix
 #[macro_export]
@@ -35,6 +38,15 @@ macro_rules! create_contract_with_assert {
             $crate::handle_testcase(deps, env, info, msg)
         }
 
+        pub fn migrate(
+            deps: cosmwasm_std::DepsMut,
+            env: cosmwasm_std::Env,
+            info: cosmwasm_std::MessageInfo,
+            msg: $crate::MigrateMsg,
+        ) -> cosmwasm_std::StdResult<cosmwasm_std::MigrateResponse> {
+            $crate::migrate_testcase(deps, env, info, msg)
+        }
+
         pub fn query(
             deps: cosmwasm_std::Deps,
             env: cosmwasm_std::Env,
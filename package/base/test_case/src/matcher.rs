@@ -0,0 +1,309 @@
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+use price::Price;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ContractError;
+use crate::json::{self, Value};
+
+/// permille of the expected value
+const PERMILLE: u64 = 1000;
+
+/// how the output of a data source is compared with the expected output of a test case
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Matcher {
+    /// the output is the expected output
+    Exact {},
+    /// the output is a number within the tolerance of the expected output. Relative tolerance is in permille
+    Tolerance {
+        absolute: Option<Price>,
+        relative: Option<u64>,
+    },
+    /// the output matches the expected output as a regular expression
+    Regex {},
+    /// the values found at the path in the output are the ones found in the expected output, both being JSON
+    JsonPath { path: String },
+    /// the labels found at the path in the output cover at least min_overlap permille of the expected labels
+    SetOverlap {
+        path: Option<String>,
+        min_overlap: u64,
+    },
+}
+
+impl Matcher {
+    /// checks the matcher can grade outputs against the expected output
+    pub fn validate(&self, expected_output: &str) -> Result<(), ContractError> {
+        match self {
+            Matcher::Exact {} => Ok(()),
+            Matcher::Tolerance { absolute, relative } => {
+                if absolute.is_none() && relative.is_none() {
+                    return Err(invalid_matcher(
+                        "tolerance needs an absolute or relative bound",
+                    ));
+                }
+                if absolute.as_ref().map(Price::is_negative).unwrap_or(false) {
+                    return Err(invalid_matcher("absolute tolerance cannot be negative"));
+                }
+                Price::from_str(expected_output.trim())
+                    .map(|_| ())
+                    .map_err(|_| invalid_matcher("expected output is not a number"))
+            }
+            Matcher::Regex {} => Regex::new(expected_output)
+                .map(|_| ())
+                .map_err(|err| invalid_matcher(&err.to_string())),
+            Matcher::JsonPath { path } => {
+                parse_path(path)?;
+                parse_json(expected_output).map(|_| ())
+            }
+            Matcher::SetOverlap { path, min_overlap } => {
+                if *min_overlap > PERMILLE {
+                    return Err(invalid_matcher("min overlap must be at most 1000"));
+                }
+                if let Some(path) = path {
+                    parse_path(path)?;
+                }
+                parse_json(expected_output).map(|_| ())
+            }
+        }
+    }
+
+    /// true if the output passes the test case. Outputs that cannot be parsed never pass
+    pub fn matches(&self, output: &str, expected_output: &str) -> bool {
+        match self {
+            Matcher::Exact {} => output.eq(expected_output),
+            Matcher::Tolerance { absolute, relative } => {
+                let (output, expected_output) = match (
+                    Price::from_str(output.trim()),
+                    Price::from_str(expected_output.trim()),
+                ) {
+                    (Ok(output), Ok(expected_output)) => (output, expected_output),
+                    _ => return false,
+                };
+                let difference = (output - expected_output.clone()).abs();
+                let within_absolute = absolute
+                    .as_ref()
+                    .map(|absolute| difference.le(absolute))
+                    .unwrap_or(false);
+                let within_relative = relative
+                    .map(|relative| {
+                        (difference.clone() * Price::from(PERMILLE))
+                            .le(&(expected_output.abs() * Price::from(relative)))
+                    })
+                    .unwrap_or(false);
+                within_absolute || within_relative
+            }
+            Matcher::Regex {} => Regex::new(expected_output)
+                .map(|regex| regex.is_match(output))
+                .unwrap_or(false),
+            Matcher::JsonPath { path } => match (
+                parse_path(path),
+                parse_json(output),
+                parse_json(expected_output),
+            ) {
+                (Ok(path), Ok(output), Ok(expected_output)) => {
+                    let values = select(&output, &path);
+                    !values.is_empty() && values.eq(&select(&expected_output, &path))
+                }
+                _ => false,
+            },
+            Matcher::SetOverlap { path, min_overlap } => {
+                let path = match path.as_ref().map(|path| parse_path(path)).transpose() {
+                    Ok(path) => path.unwrap_or_else(|| vec![Segment::All]),
+                    Err(_) => return false,
+                };
+                let (output, expected_output) =
+                    match (parse_json(output), parse_json(expected_output)) {
+                        (Ok(output), Ok(expected_output)) => (output, expected_output),
+                        _ => return false,
+                    };
+                let output_labels = labels(&output, &path);
+                let expected_labels = labels(&expected_output, &path);
+                if expected_labels.is_empty() {
+                    return output_labels.is_empty();
+                }
+                let overlap = expected_labels.intersection(&output_labels).count() as u64;
+                overlap * PERMILLE >= min_overlap * expected_labels.len() as u64
+            }
+        }
+    }
+}
+
+fn invalid_matcher(reason: &str) -> ContractError {
+    ContractError::InvalidMatcher {
+        reason: reason.to_string(),
+    }
+}
+
+fn parse_json(value: &str) -> Result<Value, ContractError> {
+    json::parse(value).map_err(|err| invalid_matcher(&err))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+    /// every element of an array
+    All,
+}
+
+/// parses a path like `$.data[0].label` or `data[*].label`. The leading `$` is optional
+fn parse_path(path: &str) -> Result<Vec<Segment>, ContractError> {
+    let path = path.trim();
+    let path = path.strip_prefix('$').unwrap_or(path);
+    let mut segments = vec![];
+    for part in path.split('.').filter(|part| !part.is_empty()) {
+        let (key, indexes) = match part.find('[') {
+            Some(pos) => part.split_at(pos),
+            None => (part, ""),
+        };
+        if !key.is_empty() {
+            segments.push(Segment::Key(key.to_string()));
+        }
+        let mut indexes = indexes;
+        while !indexes.is_empty() {
+            let end = indexes
+                .find(']')
+                .filter(|_| indexes.starts_with('['))
+                .ok_or_else(|| invalid_matcher(&format!("invalid path segment: {}", part)))?;
+            let index = &indexes[1..end];
+            segments.push(match index {
+                "*" => Segment::All,
+                index => Segment::Index(
+                    index
+                        .parse()
+                        .map_err(|_| invalid_matcher(&format!("invalid path index: {}", index)))?,
+                ),
+            });
+            indexes = &indexes[end + 1..];
+        }
+    }
+    Ok(segments)
+}
+
+/// values found at the path, missing keys & indexes are skipped
+fn select<'a>(value: &'a Value, path: &[Segment]) -> Vec<&'a Value> {
+    let (segment, rest) = match path.split_first() {
+        Some(split) => split,
+        None => return vec![value],
+    };
+    match (segment, value) {
+        (Segment::Key(key), Value::Object(map)) => map
+            .get(key)
+            .map(|value| select(value, rest))
+            .unwrap_or_default(),
+        (Segment::Index(index), Value::Array(values)) => values
+            .get(*index)
+            .map(|value| select(value, rest))
+            .unwrap_or_default(),
+        (Segment::All, Value::Array(values)) => values
+            .iter()
+            .flat_map(|value| select(value, rest))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// labels found at the path. Strings are taken as is, other values as their JSON representation
+fn labels(value: &Value, path: &[Segment]) -> BTreeSet<String> {
+    select(value, path)
+        .into_iter()
+        .map(|value| match value {
+            Value::String(label) => label.clone(),
+            value => value.to_json(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_outputs() {
+        assert!(Matcher::Exact {}.matches("hello", "hello"));
+        assert!(!Matcher::Exact {}.matches("hello ", "hello"));
+
+        let tolerance = Matcher::Tolerance {
+            absolute: Some(Price::from_str("0.5").unwrap()),
+            relative: Some(10),
+        };
+        // within the absolute bound
+        assert!(tolerance.matches("10.4", "10"));
+        // within 1% of the expected output
+        assert!(tolerance.matches("990", "1000"));
+        assert!(!tolerance.matches("989.9", "1000"));
+        assert!(!tolerance.matches("not a number", "1000"));
+
+        assert!(Matcher::Regex {}.matches("ETH: 3000.12", r"^ETH: \d+\.\d{2}$"));
+        assert!(!Matcher::Regex {}.matches("ETH: 3000", r"^ETH: \d+\.\d{2}$"));
+
+        let json_path = Matcher::JsonPath {
+            path: String::from("$.data[0].label"),
+        };
+        assert!(json_path.matches(
+            r#"{"data":[{"label":"sunflower","score":90}]}"#,
+            r#"{"data":[{"label":"sunflower","score":96}]}"#
+        ));
+        assert!(!json_path.matches(
+            r#"{"data":[{"label":"rose","score":96}]}"#,
+            r#"{"data":[{"label":"sunflower","score":96}]}"#
+        ));
+        // the path must exist in the output
+        assert!(!json_path.matches(r#"{"data":[]}"#, r#"{"data":[]}"#));
+
+        let set_overlap = Matcher::SetOverlap {
+            path: Some(String::from("[*].data[*].label")),
+            min_overlap: 500,
+        };
+        assert!(set_overlap.matches(
+            r#"[{"data":[{"label":"sunflower"},{"label":"daisy"}]}]"#,
+            r#"[{"data":[{"label":"sunflower"},{"label":"rose"}]}]"#
+        ));
+        assert!(!set_overlap.matches(
+            r#"[{"data":[{"label":"tulip"},{"label":"daisy"}]}]"#,
+            r#"[{"data":[{"label":"sunflower"},{"label":"rose"}]}]"#
+        ));
+        assert!(Matcher::SetOverlap {
+            path: None,
+            min_overlap: 1000,
+        }
+        .matches(r#"["b","a","c"]"#, r#"["a","b"]"#));
+    }
+
+    #[test]
+    fn validate_matchers() {
+        assert!(Matcher::Tolerance {
+            absolute: None,
+            relative: None,
+        }
+        .validate("10")
+        .is_err());
+        assert!(Matcher::Tolerance {
+            absolute: None,
+            relative: Some(10),
+        }
+        .validate("ten")
+        .is_err());
+        assert!(Matcher::Regex {}.validate("(unclosed").is_err());
+        assert!(Matcher::JsonPath {
+            path: String::from("data[x]"),
+        }
+        .validate("{}")
+        .is_err());
+        assert!(Matcher::SetOverlap {
+            path: None,
+            min_overlap: 1001,
+        }
+        .validate("[]")
+        .is_err());
+        assert!(Matcher::JsonPath {
+            path: String::from("$.data[0].label"),
+        }
+        .validate(r#"{"data":[]}"#)
+        .is_ok());
+    }
+}
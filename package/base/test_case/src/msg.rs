@@ -1,3 +1,4 @@
+use crate::matcher::Matcher;
 use cosmwasm_std::{Binary, Coin, HumanAddr};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -14,6 +15,8 @@ pub enum HandleMsg {
     SetOwner { owner: String },
     AddTestCase { test_case: TestCaseMsg },
     RemoveTestCase { input: Vec<String> },
+    AddTestCases { test_cases: Vec<TestCaseMsg> },
+    RemoveTestCases { inputs: Vec<Vec<String>> },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -24,17 +27,26 @@ pub enum QueryMsg {
         offset: Option<Binary>,
         limit: Option<u8>,
         order: Option<u8>,
+        /// only the test cases whose first parameter starts with the prefix
+        prefix: Option<String>,
     },
     Assert {
         assert_inputs: Vec<String>,
     },
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct TestCaseResponse {
+    /// number of test cases in the contract, the prefix does not apply to it
     pub total: u64,
     pub test_cases: Vec<TestCaseMsg>,
+    /// offset of the next page, None if this is the last one
+    pub next_offset: Option<Binary>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -42,6 +54,16 @@ pub struct TestCaseResponse {
 pub struct TestCaseMsg {
     pub parameters: Vec<String>,
     pub expected_output: String,
+    /// built-in matcher grading the outputs, the assert handler of the contract is used if None
+    pub matcher: Option<Matcher>,
+}
+
+/// assert input graded by the matcher of the test case with the same parameters
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MatchInput {
+    pub parameters: Vec<String>,
+    pub output: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -1,3 +1,4 @@
+use crate::matcher::Matcher;
 use cosmwasm_std::{Coin, HumanAddr};
 
 use cw_storage_plus::{Item, Map};
@@ -5,6 +6,12 @@ use cw_storage_plus::{Item, Map};
 /// OFFERINGS is a map which maps the offering_id to an offering. Offering_id is derived from OFFERINGS_COUNT.
 pub const TEST_CASES: Map<&[u8], String> = Map::new("test_cases");
 
+// key: parameters of the test case, like TEST_CASES. Test cases without a matcher use the assert handler
+pub const TEST_CASE_MATCHERS: Map<&[u8], Matcher> = Map::new("test_case_matchers");
+
+// number of entries in TEST_CASES, maintained on add & remove so queries do not have to count them
+pub const TEST_CASES_COUNT: Item<u64> = Item::new("test_cases_count");
+
 pub static CONFIG_KEY: &[u8] = b"config";
 
 pub const FEES: Item<Coin> = Item::new("fees");
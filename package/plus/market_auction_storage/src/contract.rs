@@ -317,6 +317,8 @@ fn parse_auction(api: &dyn Api, item: StdResult<KV<Auction>>) -> StdResult<Query
             cancel_fee: auction.cancel_fee,
             buyout_price: auction.buyout_price,
             step_price: auction.step_price,
+            auction_type: auction.auction_type,
+            extension: auction.extension,
         })
     })
 }
@@ -56,6 +56,8 @@ fn sort_auction() {
             asker: asker.clone(),
             orig_price: Uint128(i),
             bidder: None,
+            auction_type: None,
+            extension: None,
         };
         let msg = HandleMsg::Auction(AuctionHandleMsg::UpdateAuction { auction });
         let _res = handle(deps.as_mut(), contract_env.clone(), info.clone(), msg).unwrap();
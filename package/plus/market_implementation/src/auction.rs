@@ -1,13 +1,14 @@
 use crate::contract::{
-    get_asset_info, get_handle_msg, get_storage_addr, query_auction_payment_asset_info,
-    verify_funds, verify_nft, verify_owner, PAYMENT_STORAGE,
+    add_market_fees, get_asset_info, get_handle_msg, get_storage_addr,
+    query_auction_payment_asset_info, verify_funds, verify_nft, verify_owner, verify_token_addr,
+    PAYMENT_STORAGE,
 };
 use crate::error::ContractError;
 use crate::msg::{ProxyHandleMsg, ProxyQueryMsg};
 // use crate::offering::OFFERING_STORAGE;
 use crate::ai_royalty::get_royalties;
 use crate::offering::{get_offering_handle_msg, OFFERING_STORAGE};
-use crate::state::{ContractInfo, CONTRACT_INFO, MARKET_FEES};
+use crate::state::{ContractInfo, CONTRACT_INFO};
 use cosmwasm_std::HumanAddr;
 use cosmwasm_std::{
     attr, to_binary, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env, HandleResponse, MessageInfo,
@@ -16,7 +17,9 @@ use cosmwasm_std::{
 use cw721::Cw721HandleMsg;
 use market::{query_proxy, AssetInfo, Funds, StorageHandleMsg};
 use market_ai_royalty::{parse_transfer_msg, pay_royalties, sanitize_royalty, Royalty};
-use market_auction::{Auction, AuctionHandleMsg, AuctionQueryMsg};
+use market_auction::{
+    Auction, AuctionExtension, AuctionHandleMsg, AuctionQueryMsg, AuctionType, Decay,
+};
 use market_payment::{Payment, PaymentHandleMsg};
 use market_royalty::{OfferingHandleMsg, OfferingQueryMsg, OfferingRoyalty};
 // use market_royalty::OfferingQueryMsg;
@@ -36,6 +39,8 @@ pub fn try_bid_nft(
     funds: Funds,
     // token_funds: Option<Uint128>,
     // native_funds: Option<Vec<Coin>>,
+    // the cw20 contract when paying with cw20
+    token_addr: Option<HumanAddr>,
 ) -> Result<HandleResponse, ContractError> {
     let ContractInfo {
         denom, governance, ..
@@ -58,6 +63,7 @@ pub fn try_bid_nft(
         deps.api.human_address(&off.contract_addr)?,
         token_id.as_str(),
     )?;
+    verify_token_addr(&asset_info, token_addr.as_ref())?;
 
     // check auction started or finished, both means auction not started anymore
     if off.start_timestamp.gt(&Uint128::from(env.block.time)) {
@@ -67,6 +73,11 @@ pub fn try_bid_nft(
         return Err(ContractError::AuctionHasEnded {});
     }
 
+    // the first buyer of a dutch auction wins it at the current price
+    if off.is_dutch() {
+        return try_buy_dutch(deps, sender, env, auction_id, off, asset_info, funds);
+    }
+
     // check if price already >= buyout price. If yes => wont allow to bid
     if let Some(buyout_price) = off.buyout_price {
         if off.price.ge(&buyout_price) {
@@ -78,6 +89,7 @@ pub fn try_bid_nft(
     }
 
     let mut cosmos_msgs = vec![];
+    let mut extended_attrs = vec![];
     // check minimum price
     // check for enough coins, if has price then payout to all participants
     if !off.price.is_zero() {
@@ -123,6 +135,10 @@ pub fn try_bid_nft(
         // update new price and new bidder
         off.bidder = deps.api.canonical_address(&sender).ok();
         off.price = amount;
+        // a bid at the last moment gives the others time to answer
+        if off.extend(env.block.time) {
+            extended_attrs.push(attr("end_timestamp", off.end_timestamp));
+        }
         // push save message to auction_storage
         cosmos_msgs.push(get_auction_handle_msg(
             governance,
@@ -133,14 +149,16 @@ pub fn try_bid_nft(
         return Err(ContractError::InvalidZeroAmount {});
     }
 
+    let mut attributes = vec![
+        attr("action", "bid_nft"),
+        attr("bidder", sender),
+        attr("auction_id", auction_id),
+        attr("token_id", token_id),
+    ];
+    attributes.extend(extended_attrs);
     Ok(HandleResponse {
         messages: cosmos_msgs,
-        attributes: vec![
-            attr("action", "bid_nft"),
-            attr("bidder", sender),
-            attr("auction_id", auction_id),
-            attr("token_id", token_id),
-        ],
+        attributes,
         data: None,
     })
 }
@@ -152,12 +170,7 @@ pub fn try_claim_winner(
     env: Env,
     auction_id: u64,
 ) -> Result<HandleResponse, ContractError> {
-    let ContractInfo {
-        fee,
-        governance,
-        decimal_point,
-        ..
-    } = CONTRACT_INFO.load(deps.storage)?;
+    let ContractInfo { governance, .. } = CONTRACT_INFO.load(deps.storage)?;
 
     // check if auction exists
     let off: Auction = deps
@@ -180,10 +193,97 @@ pub fn try_claim_winner(
         }
     }
 
-    // get royalties
     let mut rsp = HandleResponse::default();
     rsp.attributes.extend(vec![attr("action", "claim_winner")]);
 
+    let token_id = off.token_id.clone();
+    let total_price = off.price;
+    settle_auction(deps, &env, auction_id, off, &mut rsp)?;
+
+    rsp.attributes.extend(vec![
+        attr("claimer", info.sender),
+        attr("token_id", token_id),
+        attr("auction_id", auction_id),
+        attr("total_price", total_price),
+        attr("royalty", true),
+    ]);
+
+    Ok(rsp)
+}
+
+/// the dutch auction is settled at once, what was sent above the current price goes back to the buyer
+fn try_buy_dutch(
+    deps: DepsMut,
+    sender: HumanAddr,
+    env: Env,
+    auction_id: u64,
+    mut off: Auction,
+    asset_info: AssetInfo,
+    funds: Funds,
+) -> Result<HandleResponse, ContractError> {
+    let price = off.current_price(env.block.time);
+    if price.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    verify_funds(&funds, asset_info.clone(), &price)?;
+
+    let amount = match funds {
+        Funds::Native { fund } => match &asset_info {
+            AssetInfo::NativeToken { denom } => fund
+                .iter()
+                .find(|coin| coin.denom.eq(denom))
+                .map(|coin| coin.amount)
+                .unwrap_or_default(),
+            AssetInfo::Token { .. } => fund.first().map(|coin| coin.amount).unwrap_or_default(),
+        },
+        Funds::Cw20 { fund } => fund,
+    };
+
+    let mut rsp = HandleResponse::default();
+    rsp.attributes
+        .extend(vec![attr("action", "buy_dutch_auction")]);
+
+    let token_id = off.token_id.clone();
+    off.bidder = deps.api.canonical_address(&sender).ok();
+    off.price = price;
+    settle_auction(deps, &env, auction_id, off, &mut rsp)?;
+
+    if amount.gt(&price) {
+        rsp.messages.push(parse_transfer_msg(
+            asset_info,
+            amount.sub(&price)?,
+            env.contract.address.as_str(),
+            sender.clone(),
+        )?);
+    }
+
+    rsp.attributes.extend(vec![
+        attr("bidder", sender),
+        attr("token_id", token_id),
+        attr("auction_id", auction_id),
+        attr("total_price", price),
+        attr("royalty", true),
+    ]);
+
+    Ok(rsp)
+}
+
+/// transfers the nft to the bidder and pays the asker, royalties and market fees, or returns the nft to the asker if there is no bidder. Then removes the auction
+fn settle_auction(
+    deps: DepsMut,
+    env: &Env,
+    auction_id: u64,
+    off: Auction,
+    rsp: &mut HandleResponse,
+) -> Result<(), ContractError> {
+    let ContractInfo {
+        fee,
+        governance,
+        decimal_point,
+        ..
+    } = CONTRACT_INFO.load(deps.storage)?;
+
+    // get royalties
     let asker_addr = deps.api.human_address(&off.asker)?;
     let contract_addr = deps.api.human_address(&off.contract_addr)?;
     let token_id = off.token_id;
@@ -208,10 +308,6 @@ pub fn try_claim_winner(
         // minus market fees
         let fee_amount = off.price.mul(Decimal::permille(fee));
 
-        MARKET_FEES.update(deps.storage, |current_fees| -> StdResult<_> {
-            Ok(current_fees.add(fee_amount))
-        })?;
-
         fund_amount = fund_amount.mul(Decimal::permille(1000 - fee));
         let remaining_for_royalties = fund_amount;
 
@@ -221,6 +317,7 @@ pub fn try_claim_winner(
             deps.api.human_address(&off.contract_addr)?,
             token_id.as_str(),
         )?;
+        add_market_fees(deps.storage, &asset_info, fee_amount)?;
 
         let mut offering_royalty: OfferingRoyalty = deps
             .querier
@@ -253,7 +350,7 @@ pub fn try_claim_winner(
                 decimal_point,
                 &mut fund_amount,
                 &mut cosmos_msgs,
-                &mut rsp,
+                rsp,
                 env.contract.address.as_str(),
                 &to_binary(&asset_info)?.to_base64(),
                 asset_info.clone(),
@@ -313,16 +410,8 @@ pub fn try_claim_winner(
         AuctionHandleMsg::RemoveAuction { id: auction_id },
     )?);
 
-    rsp.messages = cosmos_msgs;
-    rsp.attributes.extend(vec![
-        attr("claimer", info.sender),
-        attr("token_id", token_id),
-        attr("auction_id", auction_id),
-        attr("total_price", off.price),
-        attr("royalty", true),
-    ]);
-
-    Ok(rsp)
+    rsp.messages.extend(cosmos_msgs);
+    Ok(())
 }
 
 pub fn try_handle_ask_aution(
@@ -340,6 +429,10 @@ pub fn try_handle_ask_aution(
     buyout_price: Option<Uint128>,
     step_price: Option<u64>,
     royalty: Option<u64>,
+    auction_type: Option<AuctionType>,
+    extension_window: Option<u64>,
+    extension_duration: Option<u64>,
+    max_end_timestamp: Option<Uint128>,
 ) -> Result<HandleResponse, ContractError> {
     let ContractInfo {
         auction_duration,
@@ -405,6 +498,50 @@ pub fn try_handle_ask_aution(
         });
     }
 
+    // a dutch auction goes down to its floor price, which cannot be above the starting price
+    if let Some(AuctionType::Dutch { floor_price, decay }) = &auction_type {
+        if floor_price.gt(&price) {
+            return Err(ContractError::InvalidArgument {
+                arg: "floor_price".to_string(),
+            });
+        }
+        if let Decay::Stepwise { interval: 0 } = decay {
+            return Err(ContractError::InvalidArgument {
+                arg: "interval".to_string(),
+            });
+        }
+    }
+
+    // the window and the duration go together, the hard cap cannot be before the planned end.
+    // A dutch auction is sold to its first buyer, so it cannot be extended
+    let extension = match (extension_window, extension_duration) {
+        (None, None) => None,
+        (Some(window), Some(duration))
+            if window > 0
+                && duration > 0
+                && !matches!(auction_type, Some(AuctionType::Dutch { .. })) =>
+        {
+            if let Some(max_end_timestamp) = max_end_timestamp {
+                if max_end_timestamp.lt(&end_timestamp) {
+                    return Err(ContractError::InvalidArgument {
+                        arg: "max_end_timestamp".to_string(),
+                    });
+                }
+            }
+            Some(AuctionExtension {
+                window,
+                duration,
+                max_end_timestamp,
+                extended: 0,
+            })
+        }
+        _ => {
+            return Err(ContractError::InvalidArgument {
+                arg: "extension".to_string(),
+            })
+        }
+    };
+
     // save Auction, waiting for finished
     let off = Auction {
         id: None,
@@ -421,6 +558,8 @@ pub fn try_handle_ask_aution(
         start_timestamp,
         end_timestamp,
         step_price: step_price.unwrap_or(default_step_price),
+        auction_type,
+        extension,
     };
 
     // add first level royalty
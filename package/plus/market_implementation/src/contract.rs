@@ -9,27 +9,31 @@ use crate::auction::{
     try_handle_ask_aution, AUCTION_STORAGE,
 };
 
+use crate::offer::{
+    parse_native_offer_funds, query_offer, try_accept_offer, try_cancel_offer, try_make_offer,
+};
 use crate::offering::{
-    query_offering, try_buy, try_handle_mint, try_handle_sell_nft, try_withdraw, OFFERING_STORAGE,
+    query_offering, try_buy, try_handle_mint, try_handle_sell_bundle, try_handle_sell_nft,
+    try_withdraw, OFFERING_STORAGE,
 };
 
 use crate::error::ContractError;
 use crate::msg::{
-    GiftNft, HandleMsg, InitMsg, MigrateMsg, ProxyHandleMsg, ProxyQueryMsg, QueryMsg,
-    UpdateContractMsg,
+    AcceptOfferMsg, GiftNft, HandleMsg, InitMsg, MigrateMsg, ProxyHandleMsg, ProxyQueryMsg,
+    QueryMsg, SellNft, UpdateContractMsg,
 };
-use crate::state::{ContractInfo, CONTRACT_INFO, MARKET_FEES};
+use crate::state::{ContractInfo, CONTRACT_INFO, LEGACY_MARKET_FEES, MARKET_FEES};
 use cosmwasm_std::{
-    attr, to_binary, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Empty, Env, HandleResponse,
-    InitResponse, MessageInfo, MigrateResponse, StdError, StdResult, Uint128, WasmMsg,
+    attr, to_binary, to_vec, Binary, Coin, CosmosMsg, Deps, DepsMut, Empty, Env, HandleResponse,
+    InitResponse, MessageInfo, MigrateResponse, StdError, StdResult, Storage, Uint128, WasmMsg,
 };
 use cosmwasm_std::{from_binary, HumanAddr};
 use cw20::Cw20ReceiveMsg;
-use cw721::{Cw721HandleMsg, Cw721QueryMsg, OwnerOfResponse};
+use cw721::{Cw721HandleMsg, Cw721QueryMsg, Cw721ReceiveMsg, OwnerOfResponse};
 use market::{parse_token_id, AssetInfo, Funds, StorageHandleMsg, StorageQueryMsg, TokenInfo};
-use market_ai_royalty::sanitize_royalty;
+use market_ai_royalty::{parse_transfer_msg, sanitize_royalty};
 use market_auction::{AuctionQueryMsg, QueryAuctionsResult};
-use market_payment::PaymentQueryMsg;
+use market_payment::{AssetPrice, PaymentQueryMsg};
 use market_royalty::{Cw20HookMsg, ExtraData, OfferingQueryMsg, QueryOfferingsResult};
 use market_whitelist::{IsApprovedForAllResponse, MarketWhiteListdQueryMsg};
 use schemars::JsonSchema;
@@ -72,7 +76,6 @@ pub fn init(
         decimal_point: msg.max_decimal_point,
     };
     CONTRACT_INFO.save(deps.storage, &info)?;
-    MARKET_FEES.save(deps.storage, &Uint128::from(0u128))?;
     Ok(InitResponse::default())
 }
 
@@ -85,6 +88,7 @@ pub fn handle(
 ) -> Result<HandleResponse, ContractError> {
     match msg {
         HandleMsg::Receive(msg) => try_receive_cw20(deps, info, env, msg),
+        HandleMsg::ReceiveNft(msg) => try_receive_nft(deps, info, env, msg),
         // auction
         HandleMsg::BidNft { auction_id } => try_bid_nft(
             deps,
@@ -95,6 +99,7 @@ pub fn handle(
                 fund: info.sent_funds,
             },
             // Some(info.sent_funds),
+            None,
         ),
         HandleMsg::ClaimWinner { auction_id } => try_claim_winner(deps, info, env, auction_id),
         // HandleMsg::WithdrawNft { auction_id } => try_withdraw_nft(deps, info, env, auction_id),
@@ -113,6 +118,10 @@ pub fn handle(
             cancel_fee,
             royalty,
             step_price,
+            auction_type,
+            extension_window,
+            extension_duration,
+            max_end_timestamp,
         } => try_handle_ask_aution(
             deps,
             info,
@@ -128,15 +137,49 @@ pub fn handle(
             buyout_price,
             step_price,
             royalty,
+            auction_type,
+            extension_window,
+            extension_duration,
+            max_end_timestamp,
         ),
         HandleMsg::SellNft {
             contract_addr,
             token_id,
             royalty,
             off_price,
-        } => try_handle_sell_nft(deps, env, info, contract_addr, token_id, off_price, royalty),
+            prices,
+        } => try_handle_sell_nft(
+            deps,
+            env,
+            info,
+            contract_addr,
+            token_id,
+            SellNft {
+                off_price,
+                royalty,
+                prices,
+            },
+        ),
+        HandleMsg::SellBundle {
+            items,
+            off_price,
+            royalty,
+            prices,
+        } => try_handle_sell_bundle(
+            deps,
+            env,
+            info,
+            items,
+            SellNft {
+                off_price,
+                royalty,
+                prices,
+            },
+        ),
         HandleMsg::CancelBid { auction_id } => try_cancel_bid(deps, info, env, auction_id),
-        HandleMsg::WithdrawFunds { funds } => try_withdraw_funds(deps, info, env, funds),
+        HandleMsg::WithdrawFunds { asset_info, amount } => {
+            try_withdraw_funds(deps, info, env, asset_info, amount)
+        }
         HandleMsg::UpdateInfo(msg) => try_update_info(deps, info, env, msg),
         // royalty
         HandleMsg::MintNft(msg) => try_handle_mint(deps, info, msg),
@@ -150,6 +193,7 @@ pub fn handle(
                 fund: info.sent_funds,
             },
             // Some(info.sent_funds),
+            None,
         ),
         HandleMsg::MigrateVersion {
             nft_contract_addr,
@@ -172,6 +216,24 @@ pub fn handle(
             operator,
         } => try_approve_all(deps, info, contract_addr, operator),
         HandleMsg::TransferNftDirectly(gift_msg) => handle_transfer_nft(deps, info, gift_msg),
+        // offer
+        HandleMsg::MakeOffer {
+            contract_addr,
+            token_id,
+            end_timestamp,
+        } => {
+            let funds = parse_native_offer_funds(&info.sent_funds)?;
+            try_make_offer(
+                deps,
+                info.sender,
+                env,
+                contract_addr,
+                token_id,
+                end_timestamp,
+                funds,
+            )
+        }
+        HandleMsg::CancelOffer { offer_id } => try_cancel_offer(deps, info, env, offer_id),
     }
 }
 
@@ -180,11 +242,12 @@ pub fn handle(
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetContractInfo {} => to_binary(&query_contract_info(deps)?),
-        QueryMsg::GetMarketFees {} => to_binary(&query_market_fees(deps)?),
+        QueryMsg::GetMarketFees { asset_info } => to_binary(&query_market_fees(deps, asset_info)?),
         QueryMsg::Auction(auction_msg) => query_auction(deps, auction_msg),
         QueryMsg::Offering(offering_msg) => query_offering(deps, offering_msg),
         QueryMsg::AiRoyalty(ai_royalty_msg) => query_ai_royalty(deps, ai_royalty_msg),
         QueryMsg::FirstLvRoyalty(first_lv_msg) => query_first_level_royalty(deps, first_lv_msg),
+        QueryMsg::Offer(offer_msg) => query_offer(deps, offer_msg),
     }
 }
 
@@ -194,7 +257,12 @@ pub fn migrate(
     _info: MessageInfo,
     _msg: MigrateMsg,
 ) -> StdResult<MigrateResponse> {
-    // MARKET_FEES.save(deps.storage, &Uint128::from(0u128))?;
+    // the fees collected before are counted in the market denom
+    if let Some(fees) = LEGACY_MARKET_FEES.may_load(deps.storage)? {
+        let ContractInfo { denom, .. } = CONTRACT_INFO.load(deps.storage)?;
+        add_market_fees(deps.storage, &AssetInfo::NativeToken { denom }, fees)?;
+        LEGACY_MARKET_FEES.remove(deps.storage);
+    }
     Ok(MigrateResponse::default())
 }
 
@@ -202,7 +270,7 @@ pub fn migrate(
 
 pub fn try_receive_cw20(
     deps: DepsMut,
-    _info: MessageInfo,
+    info: MessageInfo,
     env: Env,
     cw20_msg: Cw20ReceiveMsg,
 ) -> Result<HandleResponse, ContractError> {
@@ -216,6 +284,8 @@ pub fn try_receive_cw20(
             Funds::Cw20 {
                 fund: cw20_msg.amount,
             },
+            // the sender is the cw20 contract
+            Some(info.sender),
         ),
         Ok(Cw20HookMsg::BidNft { auction_id }) => try_bid_nft(
             deps,
@@ -226,6 +296,27 @@ pub fn try_receive_cw20(
             Funds::Cw20 {
                 fund: cw20_msg.amount,
             },
+            // the sender is the cw20 contract
+            Some(info.sender),
+        ),
+        Ok(Cw20HookMsg::MakeOffer {
+            contract_addr,
+            token_id,
+            end_timestamp,
+        }) => try_make_offer(
+            deps,
+            cw20_msg.sender,
+            env,
+            contract_addr,
+            token_id,
+            end_timestamp,
+            // the sender is the cw20 contract
+            (
+                AssetInfo::Token {
+                    contract_addr: info.sender,
+                },
+                cw20_msg.amount,
+            ),
         ),
         Err(_) => Err(ContractError::Std(StdError::generic_err(
             "invalid cw20 hook message",
@@ -233,26 +324,68 @@ pub fn try_receive_cw20(
     }
 }
 
+// when the owner of a nft accepts an offer
+pub fn try_receive_nft(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    rcv_msg: Cw721ReceiveMsg,
+) -> Result<HandleResponse, ContractError> {
+    let msg = rcv_msg.msg.ok_or(ContractError::NoData {})?;
+    let AcceptOfferMsg { offer_id, royalty } = from_binary(&msg)?;
+    try_accept_offer(
+        deps,
+        info,
+        env,
+        rcv_msg.sender,
+        rcv_msg.token_id,
+        offer_id,
+        royalty,
+    )
+}
+
+/// adds the fees of a sale paid in the asset
+pub fn add_market_fees(
+    storage: &mut dyn Storage,
+    asset_info: &AssetInfo,
+    amount: Uint128,
+) -> StdResult<()> {
+    MARKET_FEES.update(storage, &to_vec(asset_info)?, |fees| -> StdResult<_> {
+        Ok(fees.unwrap_or_default() + amount)
+    })?;
+    Ok(())
+}
+
+/// only the collected fees can be withdrawn, the funds of the bids and the offers stay in the contract
 pub fn try_withdraw_funds(
     deps: DepsMut,
     _info: MessageInfo,
     env: Env,
-    fund: Coin,
+    asset_info: AssetInfo,
+    amount: Uint128,
 ) -> Result<HandleResponse, ContractError> {
     let contract_info = CONTRACT_INFO.load(deps.storage)?;
-    let bank_msg: CosmosMsg = BankMsg::Send {
-        from_address: env.contract.address,
-        to_address: HumanAddr::from(contract_info.creator.clone()), // as long as we send to the contract info creator => anyone can help us withdraw the fees
-        amount: vec![fund.clone()],
-    }
-    .into();
+    MARKET_FEES.update(deps.storage, &to_vec(&asset_info)?, |fees| {
+        let fees = fees.unwrap_or_default();
+        if fees.lt(&amount) {
+            return Err(ContractError::InsufficientFunds {});
+        }
+        Ok((fees - amount)?)
+    })?;
+    // as long as we send to the contract info creator => anyone can help us withdraw the fees
+    let transfer_msg = parse_transfer_msg(
+        asset_info.clone(),
+        amount,
+        env.contract.address.as_str(),
+        HumanAddr::from(contract_info.creator.clone()),
+    )?;
 
     Ok(HandleResponse {
-        messages: vec![bank_msg],
+        messages: vec![transfer_msg],
         attributes: vec![
             attr("action", "withdraw_funds"),
-            attr("denom", fund.denom),
-            attr("amount", fund.amount),
+            attr("asset_info", to_binary(&asset_info)?.to_base64()),
+            attr("amount", amount),
             attr("receiver", contract_info.creator),
         ],
         data: None,
@@ -306,27 +439,6 @@ pub fn try_update_info(
     })
 }
 
-// when user sell NFT to
-// pub fn try_receive_nft(
-//     deps: DepsMut,
-//     info: MessageInfo,
-//     env: Env,
-//     rcv_msg: Cw721ReceiveMsg,
-// ) -> Result<HandleResponse, ContractError> {
-//     if let Some(msg) = rcv_msg.msg.clone() {
-//         if let Ok(ask_msg) = from_binary::<AskNftMsg>(&msg) {
-//             return handle_ask_auction(deps, info, env, ask_msg, rcv_msg);
-//         }
-//         if let Ok(sell_msg) = from_binary::<SellNft>(&msg) {
-//             return handle_sell_nft(deps, info, sell_msg, rcv_msg);
-//         }
-//         if let Ok(gift_msg) = from_binary::<GiftNft>(&msg) {
-//             return handle_transfer_nft(info, gift_msg, rcv_msg);
-//         }
-//     }
-//     Err(ContractError::NoData {})
-// }
-
 pub fn try_migrate(
     deps: DepsMut,
     info: MessageInfo,
@@ -492,18 +604,18 @@ pub fn verify_nft(
     // verify ownership of token id
     verify_owner(deps, contract_addr, token_id, sender)?;
 
-    // verify if the nft contract address is whitelisted. If not => reject
-    let is_approved: IsApprovedForAllResponse = deps.querier.query_wasm_smart(
-        get_storage_addr(deps, HumanAddr::from(governance), WHITELIST_STORAGE)?,
-        &ProxyQueryMsg::Msg(MarketWhiteListdQueryMsg::IsApprovedForAll {
-            nft_addr: contract_addr.to_string(),
-        }),
-    )?;
+    verify_whitelist(deps, governance, contract_addr)?;
 
-    if !is_approved.approved {
-        return Err(ContractError::NotWhilteList {});
-    }
+    verify_not_on_sale(deps, governance, contract_addr, token_id)
+}
 
+/// rejects a token that is in an offering or an auction of the market
+pub fn verify_not_on_sale(
+    deps: Deps,
+    governance: &str,
+    contract_addr: &str,
+    token_id: &str,
+) -> Result<(), ContractError> {
     // check if offering exists
     let offering_result: Result<QueryOfferingsResult, ContractError> = deps
         .querier
@@ -538,6 +650,25 @@ pub fn verify_nft(
     Ok(())
 }
 
+// verify if the nft contract address is whitelisted. If not => reject
+pub fn verify_whitelist(
+    deps: Deps,
+    governance: &str,
+    contract_addr: &str,
+) -> Result<(), ContractError> {
+    let is_approved: IsApprovedForAllResponse = deps.querier.query_wasm_smart(
+        get_storage_addr(deps, HumanAddr::from(governance), WHITELIST_STORAGE)?,
+        &ProxyQueryMsg::Msg(MarketWhiteListdQueryMsg::IsApprovedForAll {
+            nft_addr: contract_addr.to_string(),
+        }),
+    )?;
+
+    if !is_approved.approved {
+        return Err(ContractError::NotWhilteList {});
+    }
+    Ok(())
+}
+
 pub fn verify_native_funds(native_funds: &[Coin], denom: &str, price: &Uint128) -> StdResult<()> {
     // native case, and no extra data has been provided => use default denom, which is orai
     // if native_funds.is_none() {
@@ -605,6 +736,39 @@ pub fn verify_funds(
     };
 }
 
+/// a cw20 asset is only paid with its own token, sent by the cw20 contract
+pub fn verify_token_addr(
+    asset_info: &AssetInfo,
+    token_addr: Option<&HumanAddr>,
+) -> Result<(), ContractError> {
+    if let AssetInfo::Token { contract_addr } = asset_info {
+        if token_addr != Some(contract_addr) {
+            return Err(ContractError::InvalidPaymentAsset {});
+        }
+    }
+    Ok(())
+}
+
+/// the price of the asset sent by the buyer, among the prices accepted by the seller
+pub fn get_paid_price(
+    prices: &[AssetPrice],
+    funds: &Funds,
+    token_addr: Option<&HumanAddr>,
+) -> Option<AssetPrice> {
+    prices
+        .iter()
+        .find(|price| match (&price.asset_info, funds) {
+            (AssetInfo::NativeToken { denom }, Funds::Native { fund }) => {
+                fund.iter().any(|coin| coin.denom.eq(denom))
+            }
+            (AssetInfo::Token { contract_addr }, Funds::Cw20 { .. }) => {
+                token_addr.map(|addr| addr.eq(contract_addr)) == Some(true)
+            }
+            _ => false,
+        })
+        .cloned()
+}
+
 pub fn get_asset_info(token_id: &str, default_denom: &str) -> StdResult<(AssetInfo, String)> {
     let TokenInfo { token_id: id, data } = parse_token_id(token_id);
     Ok(match data {
@@ -622,8 +786,33 @@ pub fn query_contract_info(deps: Deps) -> StdResult<ContractInfo> {
     CONTRACT_INFO.load(deps.storage)
 }
 
-pub fn query_market_fees(deps: Deps) -> StdResult<Uint128> {
-    MARKET_FEES.load(deps.storage)
+pub fn query_market_fees(deps: Deps, asset_info: Option<AssetInfo>) -> StdResult<Uint128> {
+    let asset_info = match asset_info {
+        Some(asset_info) => asset_info,
+        None => AssetInfo::NativeToken {
+            denom: CONTRACT_INFO.load(deps.storage)?.denom,
+        },
+    };
+    Ok(MARKET_FEES
+        .may_load(deps.storage, &to_vec(&asset_info)?)?
+        .unwrap_or_default())
+}
+
+// empty if the offering only accepts its payment asset
+pub fn query_offering_prices(
+    deps: Deps,
+    governance: &str,
+    contract_addr: HumanAddr,
+    token_id: &str,
+) -> StdResult<Vec<AssetPrice>> {
+    deps.querier.query_wasm_smart(
+        get_storage_addr(deps, governance.into(), PAYMENT_STORAGE)?,
+        &ProxyQueryMsg::Msg(PaymentQueryMsg::GetOfferingPrices {
+            contract_addr,
+            token_id: token_id.into(),
+            sender: None,
+        }),
+    )
 }
 
 pub fn query_offering_payment_asset_info(
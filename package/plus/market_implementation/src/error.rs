@@ -25,6 +25,9 @@ pub enum ContractError {
     #[error("Funds amount is empty")]
     InvalidSentFundAmount {},
 
+    #[error("The sent asset is not accepted by the seller")]
+    InvalidPaymentAsset {},
+
     #[error("The auction asker address is invalid")]
     InvalidSellerAddr {},
 
@@ -89,6 +92,12 @@ pub enum ContractError {
 
     #[error("Token already been sold")]
     TokenOnSale {},
+
+    #[error("Offer is not found")]
+    OfferNotFound {},
+
+    #[error("Offer has expired")]
+    OfferExpired {},
 }
 
 impl Into<String> for ContractError {
@@ -2,6 +2,7 @@ pub mod ai_royalty;
 pub mod auction;
 pub mod contract;
 pub mod msg;
+pub mod offer;
 pub mod offering;
 pub mod state;
 
@@ -1,11 +1,13 @@
-use cosmwasm_std::{Coin, Empty, HumanAddr, Uint128};
+use cosmwasm_std::{Empty, HumanAddr, Uint128};
 use cw20::Cw20ReceiveMsg;
-use market::{StorageHandleMsg, StorageQueryMsg};
+use cw721::Cw721ReceiveMsg;
+use market::{AssetInfo, StorageHandleMsg, StorageQueryMsg};
 use market_ai_royalty::{AiRoyaltyQueryMsg, Royalty, RoyaltyMsg};
-use market_auction::{AuctionHandleMsg, AuctionQueryMsg};
+use market_auction::{AuctionHandleMsg, AuctionQueryMsg, AuctionType};
 use market_first_lv_royalty::FirstLvRoyaltyQueryMsg;
-use market_payment::{PaymentHandleMsg, PaymentQueryMsg};
-use market_royalty::{MintMsg, OfferingHandleMsg, OfferingQueryMsg};
+use market_offer::OfferQueryMsg;
+use market_payment::{AssetPrice, PaymentHandleMsg, PaymentQueryMsg};
+use market_royalty::{BundleItem, MintMsg, OfferingHandleMsg, OfferingQueryMsg};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -30,6 +32,8 @@ pub struct MigrateMsg {}
 #[serde(rename_all = "snake_case")]
 pub enum HandleMsg {
     Receive(Cw20ReceiveMsg),
+    // the owner accepts an offer by sending the nft with an AcceptOfferMsg
+    ReceiveNft(Cw721ReceiveMsg),
     // this require bidder to pay fee for asker
     CancelBid {
         auction_id: u64,
@@ -53,16 +57,33 @@ pub enum HandleMsg {
         buyout_price: Option<Uint128>,
         step_price: Option<u64>,
         royalty: Option<u64>,
+        // english auction if None
+        auction_type: Option<AuctionType>,
+        // in seconds, a bid inside the window before end_timestamp extends the auction by the duration
+        extension_window: Option<u64>,
+        extension_duration: Option<u64>,
+        max_end_timestamp: Option<Uint128>,
     },
     SellNft {
         contract_addr: HumanAddr,
         token_id: String,
         off_price: Uint128,
         royalty: Option<u64>,
+        // other assets accepted besides the one of the token id, each with its own price
+        prices: Option<Vec<AssetPrice>>,
     },
-    // withdraw funds from auction marketplace to the owner wallet
+    // sell several nfts together for one price, they are kept by the market until sold or withdrawn
+    SellBundle {
+        items: Vec<BundleItem>,
+        off_price: Uint128,
+        royalty: Option<u64>,
+        // other assets accepted besides the one of the first token id, each with its own price
+        prices: Option<Vec<AssetPrice>>,
+    },
+    // withdraw the fees collected in an asset from auction marketplace to the owner wallet
     WithdrawFunds {
-        funds: Coin,
+        asset_info: AssetInfo,
+        amount: Uint128,
     },
     UpdateInfo(UpdateContractMsg),
     EmergencyCancelAuction {
@@ -91,6 +112,16 @@ pub enum HandleMsg {
         operator: HumanAddr,
     },
     TransferNftDirectly(GiftNft),
+    // lock the sent funds in an offer, the token_id is None for a collection offer
+    MakeOffer {
+        contract_addr: HumanAddr,
+        token_id: Option<String>,
+        end_timestamp: Uint128,
+    },
+    // the buyer takes the funds back, expired or not
+    CancelOffer {
+        offer_id: u64,
+    },
     // UpdateOfferingRoyalties {
     //     royalty: Vec<OfferingRoyalty>,
     // },
@@ -108,6 +139,10 @@ pub struct AskNftMsg {
     pub buyout_price: Option<Uint128>,
     pub step_price: Option<u64>,
     pub royalty: Option<u64>,
+    pub auction_type: Option<AuctionType>,
+    pub extension_window: Option<u64>,
+    pub extension_duration: Option<u64>,
+    pub max_end_timestamp: Option<Uint128>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -115,6 +150,16 @@ pub struct AskNftMsg {
 pub struct SellNft {
     pub off_price: Uint128,
     pub royalty: Option<u64>,
+    // other assets accepted besides the one of the token id, each with its own price
+    pub prices: Option<Vec<AssetPrice>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AcceptOfferMsg {
+    pub offer_id: u64,
+    // royalty of the seller on the next sale, like when selling the nft
+    pub royalty: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -143,11 +188,13 @@ pub struct UpdateContractMsg {
 pub enum QueryMsg {
     // Auction info must be queried from auction contract
     GetContractInfo {},
-    GetMarketFees {},
+    // fees collected in the asset, in the market denom by default
+    GetMarketFees { asset_info: Option<AssetInfo> },
     Auction(AuctionQueryMsg),
     Offering(OfferingQueryMsg),
     AiRoyalty(AiRoyaltyQueryMsg),
     FirstLvRoyalty(FirstLvRoyaltyQueryMsg),
+    Offer(OfferQueryMsg),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
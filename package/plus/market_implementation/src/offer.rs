@@ -0,0 +1,248 @@
+use crate::contract::{get_handle_msg, get_storage_addr, verify_not_on_sale, verify_whitelist};
+use crate::error::ContractError;
+use crate::msg::ProxyQueryMsg;
+use crate::offering::{settle_payment, OFFERING_STORAGE};
+use crate::state::{ContractInfo, CONTRACT_INFO};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Binary, Coin, Deps, DepsMut, Env, HandleResponse, HumanAddr,
+    MessageInfo, StdResult, Uint128, WasmMsg,
+};
+use cw721::Cw721HandleMsg;
+use market::{query_proxy, AssetInfo};
+use market_ai_royalty::{parse_transfer_msg, sanitize_royalty};
+use market_offer::{Offer, OfferHandleMsg, OfferQueryMsg};
+use market_payment::AssetPrice;
+use market_royalty::{OfferingQueryMsg, OfferingRoyalty};
+
+pub const OFFER_STORAGE: &str = "offer";
+
+/// the asset and amount of the native funds locked in an offer, only one coin can be sent
+pub fn parse_native_offer_funds(
+    sent_funds: &[Coin],
+) -> Result<(AssetInfo, Uint128), ContractError> {
+    match sent_funds {
+        [fund] => Ok((
+            AssetInfo::NativeToken {
+                denom: fund.denom.clone(),
+            },
+            fund.amount,
+        )),
+        _ => Err(ContractError::InvalidSentFundAmount {}),
+    }
+}
+
+/// funds are the asset and the amount locked by the buyer
+pub fn try_make_offer(
+    deps: DepsMut,
+    sender: HumanAddr,
+    env: Env,
+    contract_addr: HumanAddr,
+    token_id: Option<String>,
+    end_timestamp: Uint128,
+    funds: (AssetInfo, Uint128),
+) -> Result<HandleResponse, ContractError> {
+    let (asset_info, amount) = funds;
+    let ContractInfo { governance, .. } = CONTRACT_INFO.load(deps.storage)?;
+
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    if end_timestamp.u128() <= env.block.time as u128 {
+        return Err(ContractError::InvalidArgument {
+            arg: "end_timestamp".to_string(),
+        });
+    }
+    verify_whitelist(deps.as_ref(), &governance, &contract_addr)?;
+
+    let offer = Offer {
+        id: None,
+        contract_addr: deps.api.canonical_address(&contract_addr)?,
+        token_id: token_id.clone(),
+        buyer: deps.api.canonical_address(&sender)?,
+        asset_info,
+        amount,
+        end_timestamp,
+    };
+
+    Ok(HandleResponse {
+        messages: vec![get_handle_msg(
+            governance.as_str(),
+            OFFER_STORAGE,
+            OfferHandleMsg::UpdateOffer { offer },
+        )?],
+        attributes: vec![
+            attr("action", "make_offer"),
+            attr("buyer", sender),
+            attr("contract_addr", contract_addr),
+            attr("token_id", token_id.unwrap_or_default()),
+            attr("amount", amount),
+            attr("end_timestamp", end_timestamp),
+        ],
+        data: None,
+    })
+}
+
+pub fn try_cancel_offer(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    offer_id: u64,
+) -> Result<HandleResponse, ContractError> {
+    let ContractInfo { governance, .. } = CONTRACT_INFO.load(deps.storage)?;
+    let offer = get_offer(deps.as_ref(), offer_id)?;
+    let buyer = deps.api.human_address(&offer.buyer)?;
+    if buyer.ne(&info.sender) {
+        return Err(ContractError::Unauthorized {
+            sender: info.sender.to_string(),
+        });
+    }
+
+    Ok(HandleResponse {
+        messages: vec![
+            // refund the locked funds to the buyer
+            parse_transfer_msg(
+                offer.asset_info,
+                offer.amount,
+                env.contract.address.as_str(),
+                buyer.clone(),
+            )?,
+            get_handle_msg(
+                governance.as_str(),
+                OFFER_STORAGE,
+                OfferHandleMsg::RemoveOffer { id: offer_id },
+            )?,
+        ],
+        attributes: vec![
+            attr("action", "cancel_offer"),
+            attr("buyer", buyer),
+            attr("offer_id", offer_id),
+            attr("amount", offer.amount),
+        ],
+        data: None,
+    })
+}
+
+/// the nft has been sent to the market by the seller, it goes to the buyer and the locked funds are split like when buying an offering
+pub fn try_accept_offer(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    seller: HumanAddr,
+    token_id: String,
+    offer_id: u64,
+    royalty: Option<u64>,
+) -> Result<HandleResponse, ContractError> {
+    let ContractInfo {
+        governance,
+        max_royalty,
+        ..
+    } = CONTRACT_INFO.load(deps.storage)?;
+    let offer = get_offer(deps.as_ref(), offer_id)?;
+
+    // the sender of the nft is the nft contract
+    let contract_addr = deps.api.human_address(&offer.contract_addr)?;
+    if contract_addr.ne(&info.sender) {
+        return Err(ContractError::InvalidContractAddr {});
+    }
+    if let Some(offer_token_id) = offer.token_id.as_ref() {
+        if offer_token_id.ne(&token_id) {
+            return Err(ContractError::InvalidArgument {
+                arg: "token_id".to_string(),
+            });
+        }
+    }
+    if offer.is_expired(env.block.time) {
+        return Err(ContractError::OfferExpired {});
+    }
+    // the token cannot be sold twice
+    verify_not_on_sale(deps.as_ref(), &governance, &contract_addr, &token_id)?;
+    let royalty = Some(sanitize_royalty(
+        royalty.unwrap_or(0),
+        max_royalty,
+        "royalty",
+    )?);
+    let buyer = deps.api.human_address(&offer.buyer)?;
+
+    // the nft may have never been on sale
+    let mut offering_royalty: OfferingRoyalty = deps
+        .querier
+        .query_wasm_smart(
+            get_storage_addr(deps.as_ref(), governance.clone(), OFFERING_STORAGE)?,
+            &ProxyQueryMsg::Offering(OfferingQueryMsg::GetOfferingRoyaltyByContractTokenId {
+                contract: contract_addr.clone(),
+                token_id: token_id.clone(),
+            }) as &ProxyQueryMsg,
+        )
+        .unwrap_or(OfferingRoyalty {
+            token_id: token_id.clone(),
+            contract_addr: contract_addr.clone(),
+            previous_owner: None,
+            current_owner: seller.clone(),
+            prev_royalty: None,
+            cur_royalty: royalty,
+        });
+    offering_royalty.current_owner = seller.clone();
+    offering_royalty.cur_royalty = royalty;
+
+    let mut rsp = HandleResponse::default();
+    rsp.attributes.extend(vec![attr("action", "accept_offer")]);
+
+    let mut cosmos_msgs = settle_payment(
+        deps,
+        &env,
+        AssetPrice {
+            asset_info: offer.asset_info,
+            price: offer.amount,
+        },
+        seller.clone(),
+        offering_royalty,
+        Some(buyer.clone()),
+        &mut rsp,
+    )?;
+
+    // transfer the nft to the buyer
+    cosmos_msgs.push(
+        WasmMsg::Execute {
+            contract_addr,
+            msg: to_binary(&Cw721HandleMsg::TransferNft {
+                recipient: buyer.clone(),
+                token_id: token_id.clone(),
+            })?,
+            send: vec![],
+        }
+        .into(),
+    );
+
+    cosmos_msgs.push(get_handle_msg(
+        governance.as_str(),
+        OFFER_STORAGE,
+        OfferHandleMsg::RemoveOffer { id: offer_id },
+    )?);
+
+    rsp.messages.extend(cosmos_msgs);
+    rsp.attributes.extend(vec![
+        attr("buyer", buyer),
+        attr("seller", seller),
+        attr("token_id", token_id),
+        attr("offer_id", offer_id),
+        attr("total_price", offer.amount),
+        attr("royalty", true),
+    ]);
+
+    Ok(rsp)
+}
+
+pub fn query_offer(deps: Deps, msg: OfferQueryMsg) -> StdResult<Binary> {
+    let contract_info = CONTRACT_INFO.load(deps.storage)?;
+    query_proxy(
+        deps,
+        get_storage_addr(deps, contract_info.governance, OFFER_STORAGE)?,
+        to_binary(&ProxyQueryMsg::Msg(msg))?,
+    )
+}
+
+fn get_offer(deps: Deps, offer_id: u64) -> Result<Offer, ContractError> {
+    query_offer(deps, OfferQueryMsg::GetOfferRaw { offer_id })
+        .and_then(|offer| from_binary(&offer))
+        .map_err(|_| ContractError::OfferNotFound {})
+}
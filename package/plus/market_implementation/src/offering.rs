@@ -1,22 +1,25 @@
 use crate::ai_royalty::{add_msg_royalty, get_royalties};
 use crate::contract::{
-    get_asset_info, get_handle_msg, get_storage_addr, query_offering_payment_asset_info,
-    verify_funds, verify_nft, verify_owner, PAYMENT_STORAGE,
+    add_market_fees, get_asset_info, get_handle_msg, get_paid_price, get_storage_addr,
+    query_offering_payment_asset_info, query_offering_prices, verify_funds, verify_nft,
+    verify_owner, verify_token_addr, PAYMENT_STORAGE,
 };
 use crate::error::ContractError;
-use crate::msg::{ProxyHandleMsg, ProxyQueryMsg};
-use crate::state::{ContractInfo, CONTRACT_INFO, MARKET_FEES};
+use crate::msg::{ProxyHandleMsg, ProxyQueryMsg, SellNft};
+use crate::state::{ContractInfo, CONTRACT_INFO};
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env, HandleResponse,
-    MessageInfo, StdResult, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Api, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env,
+    HandleResponse, MessageInfo, StdResult, Uint128, WasmMsg,
 };
 use cosmwasm_std::{Coin, HumanAddr};
 use cw721::Cw721HandleMsg;
 use market::{query_proxy, AssetInfo, Funds, StorageHandleMsg};
 use market_ai_royalty::{parse_transfer_msg, pay_royalties, sanitize_royalty, Royalty, RoyaltyMsg};
-use market_payment::{Payment, PaymentHandleMsg};
-use market_royalty::{MintMsg, Offering, OfferingHandleMsg, OfferingQueryMsg, OfferingRoyalty};
-use std::ops::{Add, Mul, Sub};
+use market_payment::{AssetPrice, Payment, PaymentHandleMsg, PaymentPrices};
+use market_royalty::{
+    BundleItem, MintMsg, Offering, OfferingHandleMsg, OfferingQueryMsg, OfferingRoyalty,
+};
+use std::ops::{Mul, Sub};
 
 pub const OFFERING_STORAGE: &str = "offering_v1.1";
 pub const OFFERING_STORAGE_TEMP: &str = "offering_temp";
@@ -58,19 +61,17 @@ pub fn try_handle_mint(
 }
 
 pub fn try_buy(
-    deps: DepsMut,
+    mut deps: DepsMut,
     sender: HumanAddr,
     env: Env,
     offering_id: u64,
     // token_funds: Option<Uint128>,
     // native_funds: Option<Vec<Coin>>,
     funds: Funds,
+    // the cw20 contract when paying with cw20
+    token_addr: Option<HumanAddr>,
 ) -> Result<HandleResponse, ContractError> {
-    let ContractInfo {
-        governance,
-        decimal_point,
-        ..
-    } = CONTRACT_INFO.load(deps.storage)?;
+    let ContractInfo { governance, .. } = CONTRACT_INFO.load(deps.storage)?;
 
     // get royalties
     let mut rsp = HandleResponse::default();
@@ -79,122 +80,101 @@ pub fn try_buy(
     // check if offering exists, when return StdError => it will show EOF while parsing a JSON value.
     let off: Offering = get_offering(deps.as_ref(), offering_id)?;
     let seller_addr = deps.api.human_address(&off.seller)?;
-    let contract_addr = deps.api.human_address(&off.contract_addr)?;
+    let items = get_offering_items(deps.api, &off)?;
     let token_id = off.token_id;
 
     // collect payment type
-    let asset_info: AssetInfo = query_offering_payment_asset_info(
+    let contract_addr = deps.api.human_address(&off.contract_addr)?;
+    let prices = query_offering_prices(
         deps.as_ref(),
         governance.as_str(),
-        deps.api.human_address(&off.contract_addr)?,
+        contract_addr.clone(),
         token_id.as_str(),
     )?;
+    // the buyer chooses by sending one of the assets accepted by the seller
+    let AssetPrice { asset_info, price } = if prices.is_empty() {
+        let asset_info = query_offering_payment_asset_info(
+            deps.as_ref(),
+            governance.as_str(),
+            contract_addr,
+            token_id.as_str(),
+        )?;
+        verify_token_addr(&asset_info, token_addr.as_ref())?;
+        AssetPrice {
+            asset_info,
+            price: off.price,
+        }
+    } else {
+        get_paid_price(&prices, &funds, token_addr.as_ref())
+            .ok_or(ContractError::InvalidPaymentAsset {})?
+    };
 
     let mut cosmos_msgs = vec![];
     // check for enough coins, if has price then payout to all participants
-    if !off.price.is_zero() {
-        let contract_info = CONTRACT_INFO.load(deps.storage)?;
-
-        let mut seller_amount = off.price;
-
-        // pay for the owner of this minter contract if there is fee set in marketplace
-        let fee_amount = off.price.mul(Decimal::permille(contract_info.fee));
-        MARKET_FEES.update(deps.storage, |current_fees| -> StdResult<_> {
-            Ok(current_fees.add(fee_amount))
-        })?;
-
+    if !price.is_zero() {
         // we collect asset info to check transfer method later
         verify_funds(
             &funds,
             // native_funds.as_deref(),
             // token_funds,
             asset_info.clone(),
-            &seller_amount,
+            &price,
         )?;
 
-        // Rust will automatically floor down the value to 0 if amount is too small => error
-        seller_amount = seller_amount.sub(fee_amount)?;
-
-        let remaining_for_royalties = seller_amount;
-
-        // corner case for 721 which has previous owner
-        let mut offering_royalty_result: OfferingRoyalty = deps
-            .querier
-            .query_wasm_smart(
-                get_storage_addr(deps.as_ref(), governance.clone(), OFFERING_STORAGE)?,
-                &ProxyQueryMsg::Offering(OfferingQueryMsg::GetOfferingRoyaltyByContractTokenId {
-                    contract: deps.api.human_address(&off.contract_addr)?,
-                    token_id: token_id.clone(),
-                }) as &ProxyQueryMsg,
-            )
-            .map_err(|err| ContractError::Std(err))?;
-
-        // pay for creator, ai provider and others
-        if let Ok(mut royalties) = get_royalties(deps.as_ref(), contract_addr.as_str(), &token_id) {
-            // payout for the previous owner
-            if offering_royalty_result.previous_owner.is_some()
-                && offering_royalty_result.prev_royalty.is_some()
-            {
-                royalties.push(Royalty {
-                    contract_addr: offering_royalty_result.contract_addr.clone(),
-                    token_id: offering_royalty_result.token_id.clone(),
-                    creator: offering_royalty_result.previous_owner.unwrap(),
-                    royalty: offering_royalty_result.prev_royalty.unwrap(),
-                    creator_type: "previous_owner".into(),
-                })
-            }
-
-            pay_royalties(
-                &royalties,
-                &remaining_for_royalties,
-                decimal_point,
-                &mut seller_amount,
-                &mut cosmos_msgs,
-                &mut rsp,
-                env.contract.address.as_str(),
-                &to_binary(&asset_info)?.to_base64(),
-                asset_info.clone(),
-            )?;
-        }
-
-        // update offering royalty result, current royalty info now turns to prev
-        offering_royalty_result.prev_royalty = offering_royalty_result.cur_royalty;
-        offering_royalty_result.previous_owner =
-            Some(offering_royalty_result.current_owner.clone());
-        cosmos_msgs.push(get_offering_handle_msg(
-            governance.clone(),
-            OFFERING_STORAGE,
-            OfferingHandleMsg::UpdateOfferingRoyalty {
-                offering: offering_royalty_result.clone(),
-            },
-        )?);
-
-        // pay the left to the seller
-        if !seller_amount.is_zero() {
-            cosmos_msgs.push(parse_transfer_msg(
-                asset_info,
-                seller_amount,
-                env.contract.address.as_str(),
+        // a bundle splits its price evenly between the items, the last one takes the rest
+        let share = price.multiply_ratio(1u128, items.len() as u128);
+        let mut remaining = price;
+        for (index, item) in items.iter().enumerate() {
+            let price = if index + 1 == items.len() {
+                remaining
+            } else {
+                share
+            };
+            remaining = remaining.sub(price)?;
+
+            // corner case for 721 which has previous owner
+            let offering_royalty_result: OfferingRoyalty = deps
+                .querier
+                .query_wasm_smart(
+                    get_storage_addr(deps.as_ref(), governance.clone(), OFFERING_STORAGE)?,
+                    &ProxyQueryMsg::Offering(
+                        OfferingQueryMsg::GetOfferingRoyaltyByContractTokenId {
+                            contract: item.contract_addr.clone(),
+                            token_id: item.token_id.clone(),
+                        },
+                    ) as &ProxyQueryMsg,
+                )
+                .map_err(ContractError::Std)?;
+
+            cosmos_msgs.extend(settle_payment(
+                deps.branch(),
+                &env,
+                AssetPrice {
+                    asset_info: asset_info.clone(),
+                    price,
+                },
                 seller_addr.clone(),
+                offering_royalty_result,
+                None,
+                &mut rsp,
             )?);
         }
     }
 
-    // create transfer cw721 msg
-    let transfer_cw721_msg = Cw721HandleMsg::TransferNft {
-        recipient: sender.clone(),
-        token_id: token_id.clone(),
-    };
-
-    // if everything is fine transfer NFT token to buyer
-    cosmos_msgs.push(
-        WasmMsg::Execute {
-            contract_addr: deps.api.human_address(&off.contract_addr)?,
-            msg: to_binary(&transfer_cw721_msg)?,
-            send: vec![],
-        }
-        .into(),
-    );
+    // if everything is fine transfer NFT tokens to buyer
+    for item in items {
+        cosmos_msgs.push(
+            WasmMsg::Execute {
+                contract_addr: item.contract_addr,
+                msg: to_binary(&Cw721HandleMsg::TransferNft {
+                    recipient: sender.clone(),
+                    token_id: item.token_id,
+                })?,
+                send: vec![],
+            }
+            .into(),
+        );
+    }
 
     // remove offering in the offering storage
     cosmos_msgs.push(get_offering_handle_msg(
@@ -209,7 +189,7 @@ pub fn try_buy(
         attr("seller", seller_addr),
         attr("token_id", token_id.clone()),
         attr("offering_id", offering_id),
-        attr("total_price", off.price),
+        attr("total_price", price),
         attr("royalty", true),
     ]);
 
@@ -239,6 +219,96 @@ pub fn try_buy(
     Ok(rsp)
 }
 
+/// pays the market fee, the royalties and the seller out of the price, then the current royalty info of the token turns to prev. The buyer becomes the current owner if given
+pub fn settle_payment(
+    deps: DepsMut,
+    env: &Env,
+    paid: AssetPrice,
+    seller_addr: HumanAddr,
+    mut offering_royalty: OfferingRoyalty,
+    buyer: Option<HumanAddr>,
+    rsp: &mut HandleResponse,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let AssetPrice { asset_info, price } = paid;
+    let ContractInfo {
+        governance,
+        decimal_point,
+        fee,
+        ..
+    } = CONTRACT_INFO.load(deps.storage)?;
+
+    let mut cosmos_msgs = vec![];
+    let mut seller_amount = price;
+
+    // pay for the owner of this minter contract if there is fee set in marketplace
+    let fee_amount = price.mul(Decimal::permille(fee));
+    add_market_fees(deps.storage, &asset_info, fee_amount)?;
+
+    // Rust will automatically floor down the value to 0 if amount is too small => error
+    seller_amount = seller_amount.sub(fee_amount)?;
+
+    let remaining_for_royalties = seller_amount;
+
+    // pay for creator, ai provider and others
+    if let Ok(mut royalties) = get_royalties(
+        deps.as_ref(),
+        offering_royalty.contract_addr.as_str(),
+        &offering_royalty.token_id,
+    ) {
+        // payout for the previous owner
+        if let (Some(previous_owner), Some(prev_royalty)) = (
+            offering_royalty.previous_owner.as_ref(),
+            offering_royalty.prev_royalty,
+        ) {
+            royalties.push(Royalty {
+                contract_addr: offering_royalty.contract_addr.clone(),
+                token_id: offering_royalty.token_id.clone(),
+                creator: previous_owner.clone(),
+                royalty: prev_royalty,
+                creator_type: "previous_owner".into(),
+            })
+        }
+
+        pay_royalties(
+            &royalties,
+            &remaining_for_royalties,
+            decimal_point,
+            &mut seller_amount,
+            &mut cosmos_msgs,
+            rsp,
+            env.contract.address.as_str(),
+            &to_binary(&asset_info)?.to_base64(),
+            asset_info.clone(),
+        )?;
+    }
+
+    // update offering royalty result, current royalty info now turns to prev
+    offering_royalty.prev_royalty = offering_royalty.cur_royalty;
+    offering_royalty.previous_owner = Some(offering_royalty.current_owner.clone());
+    if let Some(buyer) = buyer {
+        offering_royalty.current_owner = buyer;
+    }
+    cosmos_msgs.push(get_offering_handle_msg(
+        governance,
+        OFFERING_STORAGE,
+        OfferingHandleMsg::UpdateOfferingRoyalty {
+            offering: offering_royalty,
+        },
+    )?);
+
+    // pay the left to the seller
+    if !seller_amount.is_zero() {
+        cosmos_msgs.push(parse_transfer_msg(
+            asset_info,
+            seller_amount,
+            env.contract.address.as_str(),
+            seller_addr,
+        )?);
+    }
+
+    Ok(cosmos_msgs)
+}
+
 pub fn try_withdraw(
     deps: DepsMut,
     info: MessageInfo,
@@ -264,26 +334,28 @@ pub fn try_withdraw(
     let mut cosmos_msg: Vec<CosmosMsg> = vec![];
 
     // check if token_id is currently sold by the requesting address
-    // transfer token back to original owner if market owns the nft
-    if verify_owner(
-        deps.as_ref(),
-        &deps.api.human_address(&off.contract_addr)?,
-        &off.token_id,
-        &env.contract.address,
-    )
-    .is_ok()
-    {
-        let transfer_cw721_msg = Cw721HandleMsg::TransferNft {
-            recipient: deps.api.human_address(&off.seller)?,
-            token_id: off.token_id.clone(),
-        };
-
-        let exec_cw721_transfer = WasmMsg::Execute {
-            contract_addr: deps.api.human_address(&off.contract_addr)?,
-            msg: to_binary(&transfer_cw721_msg)?,
-            send: vec![],
-        };
-        cosmos_msg.push(exec_cw721_transfer.into())
+    // transfer token back to original owner if market owns the nft, every item of a bundle
+    for item in get_offering_items(deps.api, &off)? {
+        if verify_owner(
+            deps.as_ref(),
+            &item.contract_addr,
+            &item.token_id,
+            &env.contract.address,
+        )
+        .is_ok()
+        {
+            let transfer_cw721_msg = Cw721HandleMsg::TransferNft {
+                recipient: deps.api.human_address(&off.seller)?,
+                token_id: item.token_id,
+            };
+
+            let exec_cw721_transfer = WasmMsg::Execute {
+                contract_addr: item.contract_addr,
+                msg: to_binary(&transfer_cw721_msg)?,
+                send: vec![],
+            };
+            cosmos_msg.push(exec_cw721_transfer.into())
+        }
     }
 
     // remove offering
@@ -305,15 +377,197 @@ pub fn try_withdraw(
     })
 }
 
+/// the nfts of a bundle are kept by the market until the bundle is bought or withdrawn, so none of them can be sold alone
+pub fn try_handle_sell_bundle(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    items: Vec<BundleItem>,
+    sell: SellNft,
+) -> Result<HandleResponse, ContractError> {
+    let SellNft {
+        off_price,
+        royalty,
+        prices,
+    } = sell;
+    let ContractInfo {
+        governance,
+        max_royalty,
+        denom,
+        ..
+    } = CONTRACT_INFO.load(deps.storage)?;
+
+    // the first token id carries the payment asset like when selling a single nft
+    let first_item = items.first().ok_or(ContractError::InvalidArgument {
+        arg: "items".to_string(),
+    })?;
+    let (asset_info, token_id) = get_asset_info(&first_item.token_id, &denom)?;
+    let payment_prices = get_payment_prices(&asset_info, off_price, prices)?;
+    let contract_addr = first_item.contract_addr.clone();
+    let mut bundle: Vec<BundleItem> = vec![BundleItem {
+        contract_addr: contract_addr.clone(),
+        token_id: token_id.clone(),
+    }];
+    for item in items.iter().skip(1) {
+        if bundle.contains(item) {
+            return Err(ContractError::InvalidArgument {
+                arg: "items".to_string(),
+            });
+        }
+        bundle.push(item.clone());
+    }
+    if bundle.len() < 2 {
+        return Err(ContractError::InvalidArgument {
+            arg: "items".to_string(),
+        });
+    }
+
+    let royalty = Some(sanitize_royalty(
+        royalty.unwrap_or(0),
+        max_royalty,
+        "royalty",
+    )?);
+
+    let mut cosmos_msgs = vec![];
+    for item in bundle.iter() {
+        verify_nft(
+            deps.as_ref(),
+            &governance,
+            &item.contract_addr,
+            &item.token_id,
+            &info.sender,
+        )?;
+
+        let mut offering_royalty_result: OfferingRoyalty = deps
+            .querier
+            .query_wasm_smart(
+                get_storage_addr(deps.as_ref(), governance.clone(), OFFERING_STORAGE)?,
+                &ProxyQueryMsg::Offering(OfferingQueryMsg::GetOfferingRoyaltyByContractTokenId {
+                    contract: item.contract_addr.clone(),
+                    token_id: item.token_id.clone(),
+                }) as &ProxyQueryMsg,
+            )
+            .unwrap_or(OfferingRoyalty {
+                token_id: item.token_id.clone(),
+                contract_addr: item.contract_addr.clone(),
+                previous_owner: None,
+                current_owner: info.sender.clone(),
+                prev_royalty: None,
+                cur_royalty: royalty,
+            });
+        offering_royalty_result.current_owner = info.sender.clone();
+        offering_royalty_result.cur_royalty = royalty;
+
+        cosmos_msgs.push(get_offering_handle_msg(
+            governance.clone(),
+            OFFERING_STORAGE,
+            OfferingHandleMsg::UpdateOfferingRoyalty {
+                offering: offering_royalty_result,
+            },
+        )?);
+
+        // the market needs to be approved to move the nft
+        cosmos_msgs.push(
+            WasmMsg::Execute {
+                contract_addr: item.contract_addr.clone(),
+                msg: to_binary(&Cw721HandleMsg::TransferNft {
+                    recipient: env.contract.address.clone(),
+                    token_id: item.token_id.clone(),
+                })?,
+                send: vec![],
+            }
+            .into(),
+        );
+    }
+
+    let offering = Offering {
+        id: None,
+        token_id: token_id.clone(),
+        contract_addr: deps.api.canonical_address(&contract_addr)?,
+        seller: deps.api.canonical_address(&info.sender)?,
+        price: off_price,
+        bundle: Some(bundle.clone()),
+    };
+
+    cosmos_msgs.push(get_offering_handle_msg(
+        governance.clone(),
+        OFFERING_STORAGE,
+        OfferingHandleMsg::UpdateOffering { offering },
+    )?);
+
+    cosmos_msgs.push(get_handle_msg(
+        governance.as_str(),
+        PAYMENT_STORAGE,
+        PaymentHandleMsg::UpdateOfferingPayment(Payment {
+            contract_addr: contract_addr.clone(),
+            token_id: token_id.clone(),
+            sender: None,
+            asset_info,
+        }),
+    )?);
+    if payment_prices.len() > 1 {
+        cosmos_msgs.push(get_handle_msg(
+            governance.as_str(),
+            PAYMENT_STORAGE,
+            PaymentHandleMsg::UpdateOfferingPrices(PaymentPrices {
+                contract_addr,
+                token_id: token_id.clone(),
+                sender: None,
+                prices: payment_prices,
+            }),
+        )?);
+    }
+
+    Ok(HandleResponse {
+        messages: cosmos_msgs,
+        attributes: vec![
+            attr("action", "sell_bundle"),
+            attr("seller", info.sender),
+            attr("price", off_price),
+            attr("token_id", token_id),
+            attr("items", bundle.len()),
+        ],
+        data: None,
+    })
+}
+
+/// the prices of an offering, the asset of the token id comes first. Each asset is accepted once
+fn get_payment_prices(
+    asset_info: &AssetInfo,
+    off_price: Uint128,
+    prices: Option<Vec<AssetPrice>>,
+) -> Result<Vec<AssetPrice>, ContractError> {
+    let mut payment_prices = vec![AssetPrice {
+        asset_info: asset_info.clone(),
+        price: off_price,
+    }];
+    for price in prices.unwrap_or_default() {
+        if payment_prices
+            .iter()
+            .any(|payment_price| payment_price.asset_info.eq(&price.asset_info))
+        {
+            return Err(ContractError::InvalidArgument {
+                arg: "prices".to_string(),
+            });
+        }
+        payment_prices.push(price);
+    }
+    Ok(payment_prices)
+}
+
 pub fn try_handle_sell_nft(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
     contract_addr: HumanAddr,
     initial_token_id: String,
-    off_price: Uint128,
-    royalty: Option<u64>,
+    sell: SellNft,
 ) -> Result<HandleResponse, ContractError> {
+    let SellNft {
+        off_price,
+        royalty,
+        prices,
+    } = sell;
     let ContractInfo {
         governance,
         max_royalty,
@@ -322,6 +576,7 @@ pub fn try_handle_sell_nft(
     } = CONTRACT_INFO.load(deps.storage)?;
 
     let (asset_info, token_id) = get_asset_info(&initial_token_id, &denom)?;
+    let payment_prices = get_payment_prices(&asset_info, off_price, prices)?;
 
     verify_nft(
         deps.as_ref(),
@@ -362,6 +617,7 @@ pub fn try_handle_sell_nft(
         contract_addr: deps.api.canonical_address(&contract_addr)?,
         seller: deps.api.canonical_address(&info.sender)?,
         price: off_price,
+        bundle: None,
     };
 
     let mut cosmos_msgs = vec![];
@@ -379,12 +635,24 @@ pub fn try_handle_sell_nft(
         governance.as_str(),
         PAYMENT_STORAGE,
         PaymentHandleMsg::UpdateOfferingPayment(Payment {
-            contract_addr,
+            contract_addr: contract_addr.clone(),
             token_id: token_id.clone(),
             sender: None, // for 721, contract & token id combined is already unique
             asset_info: asset_info.clone(),
         }),
     )?);
+    if payment_prices.len() > 1 {
+        cosmos_msgs.push(get_handle_msg(
+            governance.as_str(),
+            PAYMENT_STORAGE,
+            PaymentHandleMsg::UpdateOfferingPrices(PaymentPrices {
+                contract_addr,
+                token_id: token_id.clone(),
+                sender: None,
+                prices: payment_prices,
+            }),
+        )?);
+    }
 
     // update offering royalty result
     cosmos_msgs.push(get_offering_handle_msg(
@@ -451,6 +719,17 @@ fn get_offering(deps: Deps, offering_id: u64) -> Result<Offering, ContractError>
     Ok(offering)
 }
 
+/// every nft sold by the offering, the offering nft alone if it is not a bundle
+pub fn get_offering_items(api: &dyn Api, off: &Offering) -> StdResult<Vec<BundleItem>> {
+    match off.bundle.as_ref() {
+        Some(bundle) => Ok(bundle.clone()),
+        None => Ok(vec![BundleItem {
+            contract_addr: api.human_address(&off.contract_addr)?,
+            token_id: off.token_id.clone(),
+        }]),
+    }
+}
+
 pub fn get_offering_handle_msg(
     addr: HumanAddr,
     name: &str,
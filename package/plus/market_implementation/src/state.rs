@@ -2,7 +2,7 @@ use cosmwasm_std::{HumanAddr, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cw_storage_plus::Item;
+use cw_storage_plus::{Item, Map};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct ContractInfo {
@@ -21,4 +21,7 @@ pub struct ContractInfo {
 }
 
 pub const CONTRACT_INFO: Item<ContractInfo> = Item::new("contract_info");
-pub const MARKET_FEES: Item<Uint128> = Item::new("market_fees");
+/// total of the fees before they were kept by asset, moved to the market denom by the migration
+pub const LEGACY_MARKET_FEES: Item<Uint128> = Item::new("market_fees");
+/// fees collected by the market and not withdrawn yet, keyed by the serialized asset info
+pub const MARKET_FEES: Map<&[u8], Uint128> = Map::new("market_asset_fees");
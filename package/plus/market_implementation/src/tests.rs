@@ -1,8 +1,10 @@
 use crate::auction::DEFAULT_AUCTION_BLOCK;
-use crate::contract::{handle, init, query, verify_owner, MAX_DECIMAL_POINT, MAX_ROYALTY_PERCENT};
+use crate::contract::{
+    handle, init, migrate, query, verify_owner, MAX_DECIMAL_POINT, MAX_ROYALTY_PERCENT,
+};
 use crate::error::ContractError;
 use crate::msg::*;
-use crate::state::ContractInfo;
+use crate::state::{ContractInfo, LEGACY_MARKET_FEES};
 use cosmwasm_std::testing::{mock_info, MockApi, MockStorage};
 use cosmwasm_std::{
     coin, coins, from_binary, from_slice, to_binary, Binary, ContractResult, CosmosMsg, Decimal,
@@ -10,15 +12,20 @@ use cosmwasm_std::{
     SystemError, SystemResult, Uint128, WasmMsg, WasmQuery, StdError,
 };
 use cw20::{Cw20CoinHuman, Cw20ReceiveMsg, MinterResponse};
-use cw721::{ApprovedForAllResponse, OwnerOfResponse};
-use market::parse_token_id;
+use cw721::{ApprovedForAllResponse, Cw721ReceiveMsg, OwnerOfResponse};
+use market::{parse_token_id, AssetInfo};
 use market_ai_royalty::{AiRoyaltyQueryMsg, Royalty, RoyaltyMsg};
 use market_auction::mock::{mock_dependencies, mock_env, MockQuerier};
-use market_auction::{AuctionQueryMsg, AuctionsResponse, PagingOptions};
+use market_auction::{
+    Auction, AuctionQueryMsg, AuctionType, AuctionsResponse, Decay, PagingOptions,
+    QueryAuctionsResult,
+};
 use market_royalty::{
-    Cw20HookMsg, ExtraData, MintIntermediate, MintMsg, MintStruct, OfferingQueryMsg,
+    BundleItem, Cw20HookMsg, ExtraData, MintIntermediate, MintMsg, MintStruct, OfferingQueryMsg,
     OfferingRoyalty, OfferingsResponse, QueryOfferingsResult,
 };
+use market_offer::{OfferQueryMsg, OffersResponse, QueryOffersResult};
+use market_payment::{AssetPrice, PaymentQueryMsg};
 use market_whitelist::MarketWhiteListHandleMsg;
 use std::mem::transmute;
 use std::ops::{Add, Mul};
@@ -37,6 +44,7 @@ pub const OW20_MINTER: &str = "ow20_minter";
 pub const FIRST_LV_ROYALTY_ADDR: &str = "first_lv_royalty_addr";
 pub const PAYMENT_STORAGE_ADDR: &str = "payment_storage_addr";
 pub const WHITELIST_ADDR: &str = "whitelist_addr";
+pub const OFFER_ADDR: &str = "offer_addr";
 pub const CONTRACT_NAME: &str = "Auction Marketplace";
 pub const DENOM: &str = "orai";
 pub const AUCTION_STORAGE: &str = "auction";
@@ -45,11 +53,14 @@ pub const AI_ROYALTY_STORAGE: &str = "ai_royalty";
 pub const WHITELIST_STORAGE: &str = "whitelist_storage";
 pub const FIRST_LV_ROYALTY_STORAGE: &str = "first_lv_royalty";
 pub const PAYMENT_STORAGE: &str = "market_721_payment_storage";
+pub const OFFER_STORAGE: &str = "offer";
 pub const DECIMAL: u64 = MAX_DECIMAL_POINT / 100;
 
 pub const PROVIDER_NFT: &str = "providerNFT";
 pub const PROVIDER_NFT_NATIVE: &str = "eyJ0b2tlbl9pbmZvIjp7InRva2VuX2lkIjoicHJvdmlkZXJORlQiLCAiZGF0YSI6ImV5SmhjM05sZEY5cGJtWnZJanA3SW01aGRHbDJaVjkwYjJ0bGJpSTZleUprWlc1dmJTSTZJbTl5WVdraWZYMTkifX0="; // {"token_info":{"token_id":"providerNFT", "data":"eyJhc3NldF9pbmZvIjp7Im5hdGl2ZV90b2tlbiI6eyJkZW5vbSI6Im9yYWkifX19"}}
 pub const PROVIDER_NFT_CW20: &str = "eyJ0b2tlbl9pbmZvIjp7InRva2VuX2lkIjoicHJvdmlkZXJORlQiLCAiZGF0YSI6ImV5SmhjM05sZEY5cGJtWnZJanA3SW5SdmEyVnVJanA3SW1OdmJuUnlZV04wWDJGa1pISWlPaUpQVnpJd0luMTlmUT09In19"; // {"token_info":{"token_id":"providerNFT", "data":"eyJhc3NldF9pbmZvIjp7InRva2VuIjp7ImNvbnRyYWN0X2FkZHIiOiJPVzIwIn19fQ=="}}
+// the cw20 contract of PROVIDER_NFT_CW20
+pub const PROVIDER_NFT_CW20_TOKEN: &str = "OW20";
 pub const BIDDER: &str = "bidder";
 pub const PROVIDER: &str = "provider";
 pub const SELLABLE_NFT: &str = "SellableNFT";
@@ -81,6 +92,7 @@ pub struct DepsManager {
     first_lv_royalty: OwnedDeps<MockStorage, MockApi, MockQuerier>,
     payment_storage: OwnedDeps<MockStorage, MockApi, MockQuerier>,
     whitelist: OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    offer: OwnedDeps<MockStorage, MockApi, MockQuerier>,
     // main deps
     deps: OwnedDeps<MockStorage, MockApi, MockQuerier>,
 }
@@ -127,6 +139,7 @@ impl DepsManager {
                         PAYMENT_STORAGE.to_string(),
                         HumanAddr::from(PAYMENT_STORAGE_ADDR),
                     ),
+                    (OFFER_STORAGE.to_string(), HumanAddr::from(OFFER_ADDR)),
                 ],
                 implementations: vec![HumanAddr::from(MARKET_ADDR)],
             },
@@ -238,6 +251,17 @@ impl DepsManager {
         )
         .unwrap();
 
+        let mut offer = mock_dependencies(HumanAddr::from(OFFER_ADDR), &[], Self::query_wasm);
+        let _res = market_offer_storage::contract::init(
+            offer.as_mut(),
+            mock_env(OFFER_ADDR),
+            info.clone(),
+            market_offer_storage::msg::InitMsg {
+                governance: HumanAddr::from(HUB_ADDR),
+            },
+        )
+        .unwrap();
+
         let mut ow20 = mock_dependencies(HumanAddr::from(OW20), &[], Self::query_wasm);
         let _res = ow20::contract::init(
             ow20.as_mut(),
@@ -313,6 +337,7 @@ impl DepsManager {
             ow721,
             whitelist,
             payment_storage,
+            offer,
             ow20,
         }
     }
@@ -381,6 +406,13 @@ impl DepsManager {
                         from_slice(msg).unwrap(),
                     )
                     .ok(),
+                    OFFER_ADDR => market_offer_storage::contract::handle(
+                        self.offer.as_mut(),
+                        mock_env(HUB_ADDR),
+                        mock_info(HUB_ADDR, &[]),
+                        from_slice(msg).unwrap(),
+                    )
+                    .ok(),
                     OW20 => ow20::contract::handle(
                         self.ow20.as_mut(),
                         mock_env(OW20),
@@ -486,6 +518,12 @@ impl DepsManager {
                             from_slice(msg).unwrap(),
                         )
                         .unwrap_or_default(),
+                        OFFER_ADDR => market_offer_storage::contract::query(
+                            manager.offer.as_ref(),
+                            mock_env(OFFER_ADDR),
+                            from_slice(msg).unwrap(),
+                        )
+                        .unwrap_or_default(),
                         _ => Binary::default(),
                     };
 
@@ -563,6 +601,10 @@ fn sell_auction_happy_path() {
             end_timestamp: None,
             step_price: None,
             royalty: Some(40 * DECIMAL),
+            auction_type: None,
+            extension_window: None,
+            extension_duration: None,
+            max_end_timestamp: None,
         };
 
         //manager.handle_wasm(res, ret)
@@ -637,6 +679,10 @@ fn sell_auction_cw20_happy_path() {
             end_timestamp: None,
             step_price: None,
             royalty: Some(40 * DECIMAL),
+            auction_type: None,
+            extension_window: None,
+            extension_duration: None,
+            max_end_timestamp: None,
         };
 
         //manager.handle_wasm(res, ret)
@@ -715,6 +761,10 @@ fn test_royalty_auction_happy_path() {
             end_timestamp: Some(Uint128::from(contract_env.block.time + 100)),
             step_price: Some(10),
             royalty: Some(40 * DECIMAL),
+            auction_type: None,
+            extension_window: None,
+            extension_duration: None,
+            max_end_timestamp: None,
         };
 
         //manager.handle_wasm(res, ret)
@@ -731,7 +781,7 @@ fn test_royalty_auction_happy_path() {
             .unwrap();
 
         // now claim winner after expired
-        let current_market_fee: Uint128 = from_binary(&manager.query(QueryMsg::GetMarketFees {  }).unwrap()).unwrap();
+        let current_market_fee: Uint128 = from_binary(&manager.query(QueryMsg::GetMarketFees { asset_info: None }).unwrap()).unwrap();
         let claim_info = mock_info("anyone", &coins(0, DENOM));
         let claim_msg = HandleMsg::ClaimWinner { auction_id: 1 };
         let mut claim_contract_env = contract_env.clone();
@@ -745,7 +795,7 @@ fn test_royalty_auction_happy_path() {
             .find(|attr| attr.key.eq("token_id"))
             .unwrap();
 
-        let after_claim_market_fee: Uint128 = from_binary(&manager.query(QueryMsg::GetMarketFees {  }).unwrap()).unwrap();
+        let after_claim_market_fee: Uint128 = from_binary(&manager.query(QueryMsg::GetMarketFees { asset_info: None }).unwrap()).unwrap();
         // fee 2% of 200 = 4
         assert_eq!(after_claim_market_fee, current_market_fee + market_fee * Uint128::from(200u128));
         assert_eq!(attr.value, PROVIDER_NFT);
@@ -774,6 +824,10 @@ fn test_royalty_auction_happy_path() {
             end_timestamp: Some(Uint128::from(contract_env.block.time + 100)),
             step_price: Some(10),
             royalty: Some(40 * DECIMAL),
+            auction_type: None,
+            extension_window: None,
+            extension_duration: None,
+            max_end_timestamp: None,
         };
 
         let _result = manager
@@ -912,6 +966,10 @@ fn test_royalty_auction_cw20_happy_path() {
             end_timestamp: Some(Uint128::from(contract_env.block.time + 100)),
             step_price: Some(10),
             royalty: Some(40 * DECIMAL),
+            auction_type: None,
+            extension_window: None,
+            extension_duration: None,
+            max_end_timestamp: None,
         };
 
         //manager.handle_wasm(res, ret)
@@ -927,7 +985,7 @@ fn test_royalty_auction_cw20_happy_path() {
         let mut bid_contract_env = contract_env.clone();
         bid_contract_env.block.time = contract_env.block.time + 15;
         let _res = manager
-            .handle_with_env(bid_contract_env, mock_info(BIDDER, &vec![]), bid_msg)
+            .handle_with_env(bid_contract_env, mock_info(PROVIDER_NFT_CW20_TOKEN, &[]), bid_msg)
             .unwrap();
 
         // now claim winner after expired
@@ -970,6 +1028,10 @@ fn test_royalty_auction_cw20_happy_path() {
             end_timestamp: Some(Uint128::from(contract_env.block.time + 100)),
             step_price: Some(10),
             royalty: Some(40 * DECIMAL),
+            auction_type: None,
+            extension_window: None,
+            extension_duration: None,
+            max_end_timestamp: None,
         };
 
         let _result = manager
@@ -985,7 +1047,7 @@ fn test_royalty_auction_cw20_happy_path() {
         let mut bid_contract_env = contract_env.clone();
         bid_contract_env.block.time = contract_env.block.time + 15;
         let _res = manager
-            .handle_with_env(bid_contract_env, mock_info("bidder1", &vec![]), bid_msg)
+            .handle_with_env(bid_contract_env, mock_info(PROVIDER_NFT_CW20_TOKEN, &[]), bid_msg)
             .unwrap();
 
         let result: AuctionsResponse = from_binary(
@@ -1114,6 +1176,10 @@ fn cancel_auction_happy_path() {
             end_timestamp: None,
             step_price: None,
             royalty: None,
+            auction_type: None,
+            extension_window: None,
+            extension_duration: None,
+            max_end_timestamp: None,
         };
 
         let _result = manager.handle(mock_info(PROVIDER, &vec![]), sell_msg.clone());
@@ -1201,6 +1267,10 @@ fn cancel_auction_unhappy_path() {
             end_timestamp: None,
             step_price: None,
             royalty: None,
+            auction_type: None,
+            extension_window: None,
+            extension_duration: None,
+            max_end_timestamp: None,
         };
 
         //manager.handle_wasm(res, ret)
@@ -1281,6 +1351,10 @@ fn cancel_auction_verify_owner() {
             end_timestamp: None,
             step_price: None,
             royalty: None,
+            auction_type: None,
+            extension_window: None,
+            extension_duration: None,
+            max_end_timestamp: None,
         };
 
         let _result = manager.handle(mock_info(PROVIDER, &vec![]), sell_msg.clone());
@@ -1393,6 +1467,10 @@ fn cancel_bid_happy_path() {
             end_timestamp: None,
             step_price: None,
             royalty: None,
+            auction_type: None,
+            extension_window: None,
+            extension_duration: None,
+            max_end_timestamp: None,
         };
 
         //manager.handle_wasm(res, ret)
@@ -1481,6 +1559,10 @@ fn cancel_bid_cw20_happy_path() {
             end_timestamp: None,
             step_price: None,
             royalty: None,
+            auction_type: None,
+            extension_window: None,
+            extension_duration: None,
+            max_end_timestamp: None,
         };
 
         //manager.handle_wasm(res, ret)
@@ -1506,7 +1588,7 @@ fn cancel_bid_cw20_happy_path() {
             msg: Some(to_binary(&Cw20HookMsg::BidNft { auction_id: 1 }).unwrap()),
         });
         let _res = manager
-            .handle_with_env(mock_env(MARKET_ADDR), mock_info(BIDDER, &vec![]), bid_msg)
+            .handle_with_env(mock_env(MARKET_ADDR), mock_info(PROVIDER_NFT_CW20_TOKEN, &[]), bid_msg)
             .unwrap();
 
         let cancel_bid_msg = HandleMsg::CancelBid { auction_id: 1 };
@@ -1576,6 +1658,10 @@ fn cancel_bid_unhappy_path() {
             end_timestamp: None,
             step_price: None,
             royalty: None,
+            auction_type: None,
+            extension_window: None,
+            extension_duration: None,
+            max_end_timestamp: None,
         };
 
         //manager.handle_wasm(res, ret)
@@ -1663,6 +1749,10 @@ fn claim_winner_return_back_to_owner() {
             end_timestamp: Some(Uint128::from(contract_env.block.time + 100)),
             step_price: None,
             royalty: None,
+            auction_type: None,
+            extension_window: None,
+            extension_duration: None,
+            max_end_timestamp: None,
         };
 
         //manager.handle_wasm(res, ret)
@@ -1760,6 +1850,10 @@ fn claim_winner_return_back_to_owner() {
             end_timestamp: None,
             step_price: None,
             royalty: None,
+            auction_type: None,
+            extension_window: None,
+            extension_duration: None,
+            max_end_timestamp: None,
         };
 
         //manager.handle_wasm(res, ret)
@@ -1833,6 +1927,10 @@ fn claim_winner_verify_owner() {
             end_timestamp: Some(Uint128::from(contract_env.block.time + 100)),
             step_price: None,
             royalty: None,
+            auction_type: None,
+            extension_window: None,
+            extension_duration: None,
+            max_end_timestamp: None,
         };
 
         manager
@@ -1891,6 +1989,401 @@ fn claim_winner_verify_owner() {
 
 // // test royalty
 
+#[test]
+fn dutch_auction_happy_path() {
+    unsafe {
+        let manager = DepsManager::get_new();
+        handle_whitelist(manager);
+        let contract_env = mock_env(MARKET_ADDR);
+
+        let creator_info = mock_info("creator", &vec![coin(50, DENOM)]);
+        let mint_msg = HandleMsg::MintNft(MintMsg {
+            contract_addr: HumanAddr::from(OW721),
+            creator: HumanAddr::from(PROVIDER),
+            mint: MintIntermediate {
+                mint: MintStruct {
+                    token_id: String::from(PROVIDER_NFT),
+                    owner: HumanAddr::from(PROVIDER),
+                    name: String::from("asbv"),
+                    description: None,
+                    image: String::from("baxv"),
+                },
+            },
+            creator_type: String::from("sacx"),
+            royalty: None,
+        });
+        manager.handle(creator_info, mint_msg).unwrap();
+
+        let _result = oraichain_nft::contract::handle(
+            manager.ow721.as_mut(),
+            mock_env(OW721),
+            mock_info(PROVIDER, &vec![]),
+            oraichain_nft::msg::HandleMsg::ApproveAll {
+                operator: HumanAddr::from(MARKET_ADDR),
+                expires: None,
+            },
+        );
+
+        let sell_msg = |floor_price: u64, decay: Decay| HandleMsg::AskNft {
+            contract_addr: HumanAddr::from(OW721),
+            token_id: String::from(PROVIDER_NFT_NATIVE),
+            price: Uint128::from(1000u64),
+            cancel_fee: None,
+            start: None,
+            end: None,
+            buyout_price: None,
+            start_timestamp: Some(Uint128::from(contract_env.block.time)),
+            end_timestamp: Some(Uint128::from(contract_env.block.time + 100)),
+            step_price: None,
+            royalty: None,
+            auction_type: Some(AuctionType::Dutch {
+                floor_price: Uint128::from(floor_price),
+                decay,
+            }),
+            extension_window: None,
+            extension_duration: None,
+            max_end_timestamp: None,
+        };
+
+        // the floor price cannot be above the starting price
+        assert!(matches!(
+            manager.handle(mock_info(PROVIDER, &vec![]), sell_msg(1001, Decay::Linear {})),
+            Err(ContractError::InvalidArgument { .. })
+        ));
+        manager
+            .handle(mock_info(PROVIDER, &vec![]), sell_msg(200, Decay::Linear {}))
+            .unwrap();
+
+        // halfway through, the price went down linearly to 600
+        let mut buy_env = contract_env.clone();
+        buy_env.block.time = contract_env.block.time + 50;
+        assert!(matches!(
+            manager.handle_with_env(
+                buy_env.clone(),
+                mock_info(BIDDER, &coins(599, DENOM)),
+                HandleMsg::BidNft { auction_id: 1 },
+            ),
+            Err(ContractError::Std(_))
+        ));
+        let results = manager
+            .handle_with_env(
+                buy_env,
+                mock_info(BIDDER, &coins(700, DENOM)),
+                HandleMsg::BidNft { auction_id: 1 },
+            )
+            .unwrap();
+
+        let market_res = results.last().unwrap();
+        let total_price = market_res
+            .attributes
+            .iter()
+            .find(|attr| attr.key.eq("total_price"))
+            .unwrap();
+        assert_eq!(total_price.value, "600");
+        // what was sent above the current price is refunded
+        assert!(market_res.messages.iter().any(|msg| match msg {
+            CosmosMsg::Bank(cosmwasm_std::BankMsg::Send {
+                to_address, amount, ..
+            }) => to_address.eq(&HumanAddr::from(BIDDER)) && amount[0].amount.eq(&Uint128(100)),
+            _ => false,
+        }));
+
+        // the auction is settled at once
+        let value: AuctionsResponse = from_binary(
+            &manager
+                .query(QueryMsg::Auction(AuctionQueryMsg::GetAuctions {
+                    options: PagingOptions {
+                        offset: None,
+                        limit: None,
+                        order: None,
+                    },
+                }))
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(0, value.items.len());
+        let result: OwnerOfResponse = from_binary(
+            &oraichain_nft::contract::query(
+                manager.ow721.as_ref(),
+                mock_env(OW721),
+                oraichain_nft::msg::QueryMsg::OwnerOf {
+                    token_id: String::from(PROVIDER_NFT),
+                    include_expired: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(result.owner, HumanAddr::from(BIDDER));
+    }
+}
+
+#[test]
+fn dutch_auction_cw20_foreign_token() {
+    unsafe {
+        let manager = DepsManager::get_new();
+        handle_whitelist(manager);
+        let contract_env = mock_env(MARKET_ADDR);
+
+        let creator_info = mock_info("creator", &[coin(50, DENOM)]);
+        let mint_msg = HandleMsg::MintNft(MintMsg {
+            contract_addr: HumanAddr::from(OW721),
+            creator: HumanAddr::from(PROVIDER),
+            mint: MintIntermediate {
+                mint: MintStruct {
+                    token_id: String::from(PROVIDER_NFT),
+                    owner: HumanAddr::from(PROVIDER),
+                    name: String::from("asbv"),
+                    description: None,
+                    image: String::from("baxv"),
+                },
+            },
+            creator_type: String::from("sacx"),
+            royalty: None,
+        });
+        manager.handle(creator_info, mint_msg).unwrap();
+
+        let _result = oraichain_nft::contract::handle(
+            manager.ow721.as_mut(),
+            mock_env(OW721),
+            mock_info(PROVIDER, &[]),
+            oraichain_nft::msg::HandleMsg::ApproveAll {
+                operator: HumanAddr::from(MARKET_ADDR),
+                expires: None,
+            },
+        );
+
+        let sell_msg = HandleMsg::AskNft {
+            contract_addr: HumanAddr::from(OW721),
+            token_id: String::from(PROVIDER_NFT_CW20),
+            price: Uint128::from(1000u64),
+            cancel_fee: None,
+            start: None,
+            end: None,
+            buyout_price: None,
+            start_timestamp: Some(Uint128::from(contract_env.block.time)),
+            end_timestamp: Some(Uint128::from(contract_env.block.time + 100)),
+            step_price: None,
+            royalty: None,
+            auction_type: Some(AuctionType::Dutch {
+                floor_price: Uint128::from(200u64),
+                decay: Decay::Linear {},
+            }),
+            extension_window: None,
+            extension_duration: None,
+            max_end_timestamp: None,
+        };
+        manager.handle(mock_info(PROVIDER, &[]), sell_msg).unwrap();
+
+        let buy_msg = HandleMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(BIDDER),
+            amount: Uint128::from(1000u64),
+            msg: Some(to_binary(&Cw20HookMsg::BidNft { auction_id: 1 }).unwrap()),
+        });
+        // the tokens of another cw20 contract or native coins do not pay for the auction
+        assert!(matches!(
+            manager.handle(mock_info("foreign_token", &[]), buy_msg.clone()),
+            Err(ContractError::InvalidPaymentAsset {})
+        ));
+        assert!(matches!(
+            manager.handle(
+                mock_info(BIDDER, &coins(1000, DENOM)),
+                HandleMsg::BidNft { auction_id: 1 },
+            ),
+            Err(ContractError::InvalidPaymentAsset {})
+        ));
+
+        let results = manager
+            .handle(mock_info(PROVIDER_NFT_CW20_TOKEN, &[]), buy_msg)
+            .unwrap();
+        let total_price = results
+            .last()
+            .unwrap()
+            .attributes
+            .iter()
+            .find(|attr| attr.key.eq("total_price"))
+            .unwrap();
+        assert_eq!(total_price.value, "1000");
+        let result: OwnerOfResponse = from_binary(
+            &oraichain_nft::contract::query(
+                manager.ow721.as_ref(),
+                mock_env(OW721),
+                oraichain_nft::msg::QueryMsg::OwnerOf {
+                    token_id: String::from(PROVIDER_NFT),
+                    include_expired: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(result.owner, HumanAddr::from(BIDDER));
+    }
+}
+
+#[test]
+fn dutch_auction_stepwise_price() {
+    let auction = Auction {
+        id: None,
+        token_id: String::from(PROVIDER_NFT),
+        contract_addr: Default::default(),
+        asker: Default::default(),
+        bidder: None,
+        start: 0,
+        end: 0,
+        price: Uint128::from(1000u64),
+        orig_price: Uint128::from(1000u64),
+        buyout_price: None,
+        cancel_fee: None,
+        start_timestamp: Uint128::from(100u64),
+        end_timestamp: Uint128::from(200u64),
+        step_price: 0,
+        auction_type: Some(AuctionType::Dutch {
+            floor_price: Uint128::from(200u64),
+            decay: Decay::Stepwise { interval: 30 },
+        }),
+        extension: None,
+    };
+    assert_eq!(auction.current_price(50), Uint128::from(1000u64));
+    assert_eq!(auction.current_price(129), Uint128::from(1000u64));
+    // one step of 30 seconds out of 100
+    assert_eq!(auction.current_price(150), Uint128::from(760u64));
+    assert_eq!(auction.current_price(190), Uint128::from(280u64));
+    assert_eq!(auction.current_price(250), Uint128::from(200u64));
+}
+
+#[test]
+fn auction_extension_happy_path() {
+    unsafe {
+        let manager = DepsManager::get_new();
+        handle_whitelist(manager);
+        let contract_env = mock_env(MARKET_ADDR);
+
+        let creator_info = mock_info("creator", &vec![coin(50, DENOM)]);
+        let mint_msg = HandleMsg::MintNft(MintMsg {
+            contract_addr: HumanAddr::from(OW721),
+            creator: HumanAddr::from(PROVIDER),
+            mint: MintIntermediate {
+                mint: MintStruct {
+                    token_id: String::from(PROVIDER_NFT),
+                    owner: HumanAddr::from(PROVIDER),
+                    name: String::from("asbv"),
+                    description: None,
+                    image: String::from("baxv"),
+                },
+            },
+            creator_type: String::from("sacx"),
+            royalty: None,
+        });
+        manager.handle(creator_info, mint_msg).unwrap();
+
+        let _result = oraichain_nft::contract::handle(
+            manager.ow721.as_mut(),
+            mock_env(OW721),
+            mock_info(PROVIDER, &vec![]),
+            oraichain_nft::msg::HandleMsg::ApproveAll {
+                operator: HumanAddr::from(MARKET_ADDR),
+                expires: None,
+            },
+        );
+
+        let sell_msg = |extension_window: Option<u64>,
+                        max_end_timestamp: u64,
+                        auction_type: Option<AuctionType>| HandleMsg::AskNft {
+            contract_addr: HumanAddr::from(OW721),
+            token_id: String::from(PROVIDER_NFT_NATIVE),
+            price: Uint128::from(10u64),
+            cancel_fee: None,
+            start: None,
+            end: None,
+            buyout_price: None,
+            start_timestamp: Some(Uint128::from(contract_env.block.time)),
+            end_timestamp: Some(Uint128::from(contract_env.block.time + 100)),
+            step_price: Some(10),
+            royalty: None,
+            auction_type,
+            extension_window,
+            extension_duration: Some(30),
+            max_end_timestamp: Some(Uint128::from(contract_env.block.time + max_end_timestamp)),
+        };
+
+        // the window goes with the duration, and the hard cap cannot be before the end
+        assert!(matches!(
+            manager.handle(mock_info(PROVIDER, &vec![]), sell_msg(None, 150, None)),
+            Err(ContractError::InvalidArgument { .. })
+        ));
+        assert!(matches!(
+            manager.handle(mock_info(PROVIDER, &vec![]), sell_msg(Some(10), 50, None)),
+            Err(ContractError::InvalidArgument { .. })
+        ));
+        // a dutch auction goes to its first buyer and cannot be extended
+        assert!(matches!(
+            manager.handle(
+                mock_info(PROVIDER, &[]),
+                sell_msg(
+                    Some(10),
+                    150,
+                    Some(AuctionType::Dutch {
+                        floor_price: Uint128::from(5u64),
+                        decay: Decay::Linear {},
+                    })
+                )
+            ),
+            Err(ContractError::InvalidArgument { .. })
+        ));
+        manager
+            .handle(mock_info(PROVIDER, &vec![]), sell_msg(Some(10), 150, None))
+            .unwrap();
+
+        let bid = |manager: &mut DepsManager, bidder: &str, amount: u128, time: u64| {
+            let mut bid_env = contract_env.clone();
+            bid_env.block.time = contract_env.block.time + time;
+            manager.handle_with_env(
+                bid_env,
+                mock_info(bidder, &coins(amount, DENOM)),
+                HandleMsg::BidNft { auction_id: 1 },
+            )
+        };
+        let query_auction = |manager: &DepsManager| -> QueryAuctionsResult {
+            from_binary(
+                &manager
+                    .query(QueryMsg::Auction(AuctionQueryMsg::GetAuction { auction_id: 1 }))
+                    .unwrap(),
+            )
+            .unwrap()
+        };
+
+        // outside the window, the end does not move
+        bid(manager, BIDDER, 20, 50).unwrap();
+        let auction = query_auction(manager);
+        assert_eq!(auction.end_timestamp, Uint128::from(contract_env.block.time + 100));
+        assert_eq!(auction.end, contract_env.block.height + DEFAULT_AUCTION_BLOCK);
+        assert_eq!(auction.extension.unwrap().extended, 0);
+
+        // inside the window, the auction goes on for the duration after the bid
+        bid(manager, "bidder1", 30, 95).unwrap();
+        let auction = query_auction(manager);
+        assert_eq!(auction.end_timestamp, Uint128::from(contract_env.block.time + 125));
+        // the end block moves by the same share of the auction
+        assert_eq!(
+            auction.end,
+            contract_env.block.height + DEFAULT_AUCTION_BLOCK * 125 / 100
+        );
+        assert_eq!(auction.extension.unwrap().extended, 1);
+
+        // up to the hard cap
+        bid(manager, BIDDER, 40, 120).unwrap();
+        bid(manager, "bidder1", 50, 145).unwrap();
+        let auction = query_auction(manager);
+        assert_eq!(auction.end_timestamp, Uint128::from(contract_env.block.time + 150));
+        assert_eq!(auction.extension.unwrap().extended, 2);
+
+        assert!(matches!(
+            bid(manager, BIDDER, 60, 151),
+            Err(ContractError::AuctionHasEnded {})
+        ));
+    }
+}
+
 #[test]
 fn test_royalties() {
     unsafe {
@@ -1934,6 +2427,7 @@ fn test_royalties() {
             token_id: String::from(SELLABLE_NFT_NATIVE),
             off_price: Uint128(50),
             royalty: Some(10 * DECIMAL),
+            prices: None,
         };
         manager.handle(info_sell.clone(), msg).unwrap();
 
@@ -1969,6 +2463,7 @@ fn test_royalties() {
             token_id: String::from(SELLABLE_NFT_NATIVE),
             off_price: Uint128(50),
             royalty: Some(10 * DECIMAL),
+            prices: None,
         };
         manager.handle(mock_info("buyer", &vec![]), msg).unwrap();
 
@@ -2004,6 +2499,7 @@ fn test_royalties() {
             token_id: String::from(SELLABLE_NFT_NATIVE),
             off_price: Uint128(50),
             royalty: Some(10 * DECIMAL),
+            prices: None,
         };
         manager.handle(mock_info("buyer1", &vec![]), msg).unwrap();
 
@@ -2176,6 +2672,7 @@ fn test_royalties_ow20() {
             token_id: String::from(SELLABLE_NFT_CW20),
             off_price: Uint128(50),
             royalty: Some(10 * DECIMAL),
+            prices: None,
         };
         manager.handle(info_sell.clone(), msg).unwrap();
 
@@ -2196,8 +2693,13 @@ fn test_royalties_ow20() {
             amount: Uint128::from(50u64),
             msg: Some(to_binary(&Cw20HookMsg::BuyNft { offering_id: 1 }).unwrap()),
         });
+        // a cw20 other than the token of the offering must be rejected
+        assert!(matches!(
+            manager.handle(mock_info("fake_token", &[]), buy_msg.clone()),
+            Err(ContractError::InvalidPaymentAsset {})
+        ));
         let _res = manager
-            .handle(mock_info("buyer", &vec![]), buy_msg)
+            .handle(mock_info(PROVIDER_NFT_CW20_TOKEN, &[]), buy_msg)
             .unwrap();
 
         let _result = oraichain_nft::contract::handle(
@@ -2216,6 +2718,7 @@ fn test_royalties_ow20() {
             token_id: String::from(SELLABLE_NFT_CW20),
             off_price: Uint128(50),
             royalty: Some(10 * DECIMAL),
+            prices: None,
         };
         manager.handle(mock_info("buyer", &vec![]), msg).unwrap();
 
@@ -2238,7 +2741,7 @@ fn test_royalties_ow20() {
             msg: Some(to_binary(&Cw20HookMsg::BuyNft { offering_id: 2 }).unwrap()),
         });
         let _res = manager
-            .handle(mock_info("buyer1", &vec![]), buy_msg)
+            .handle(mock_info(PROVIDER_NFT_CW20_TOKEN, &[]), buy_msg)
             .unwrap();
 
         let _result = oraichain_nft::contract::handle(
@@ -2256,6 +2759,7 @@ fn test_royalties_ow20() {
             token_id: String::from(SELLABLE_NFT_CW20),
             off_price: Uint128(50),
             royalty: Some(10 * DECIMAL),
+            prices: None,
         };
         manager.handle(mock_info("buyer1", &vec![]), msg).unwrap();
 
@@ -2266,7 +2770,7 @@ fn test_royalties_ow20() {
             .unwrap();
         let offering: QueryOfferingsResult = from_binary(&offering_bin).unwrap();
         // other buyer again
-        let info_buy = mock_info("buyer2", &coins(9000000, DENOM));
+        let info_buy = mock_info(PROVIDER_NFT_CW20_TOKEN, &[]);
 
         let buy_msg = HandleMsg::Receive(Cw20ReceiveMsg {
             sender: HumanAddr::from("buyer2"),
@@ -2447,6 +2951,7 @@ fn test_buy_market_fee_calculate() {
             token_id: String::from(SELLABLE_NFT_NATIVE),
             off_price: Uint128(100),
             royalty: Some(10 * DECIMAL),
+            prices: None,
         };
         manager.handle(info_sell.clone(), msg).unwrap();
 
@@ -2473,26 +2978,58 @@ fn test_buy_market_fee_calculate() {
         );
 
         // Buy nft and check market fee storage
-        let current_market_fee: Uint128 = from_binary(&manager.query(QueryMsg::GetMarketFees {  }).unwrap()).unwrap();
+        let current_market_fee: Uint128 = from_binary(&manager.query(QueryMsg::GetMarketFees { asset_info: None }).unwrap()).unwrap();
 
         let buy_msg = HandleMsg::BuyNft { offering_id: 1 };
         let info_buy = mock_info("buyer", &coins(100, DENOM));
         let buy_result = manager.handle(info_buy, buy_msg).unwrap();
 
-        let after_buy_market_fee: Uint128 = from_binary(&manager.query(QueryMsg::GetMarketFees {  }).unwrap()).unwrap();
+        let after_buy_market_fee: Uint128 = from_binary(&manager.query(QueryMsg::GetMarketFees { asset_info: None }).unwrap()).unwrap();
         // 2% market fee of 100 = 2
         assert_eq!(after_buy_market_fee, current_market_fee + market_fee * Uint128::from(100u128));
     }
 }
 
 #[test]
-fn withdraw_offering() {
+fn migrate_market_fees() {
     unsafe {
         let manager = DepsManager::get_new();
-        handle_whitelist(manager);
-        // withdraw offering
-        let withdraw_info = mock_info("seller", &coins(2, DENOM));
-        // no offering to withdraw case
+        LEGACY_MARKET_FEES
+            .save(manager.deps.as_mut().storage, &Uint128::from(30u64))
+            .unwrap();
+        migrate(
+            manager.deps.as_mut(),
+            mock_env(MARKET_ADDR),
+            mock_info(CREATOR, &[]),
+            MigrateMsg {},
+        )
+        .unwrap();
+
+        // the fees collected before are in the market denom
+        let market_fee: Uint128 = from_binary(
+            &manager
+                .query(QueryMsg::GetMarketFees { asset_info: None })
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(market_fee, Uint128::from(30u64));
+        assert_eq!(
+            LEGACY_MARKET_FEES
+                .may_load(manager.deps.as_ref().storage)
+                .unwrap(),
+            None
+        );
+    }
+}
+
+#[test]
+fn withdraw_offering() {
+    unsafe {
+        let manager = DepsManager::get_new();
+        handle_whitelist(manager);
+        // withdraw offering
+        let withdraw_info = mock_info("seller", &coins(2, DENOM));
+        // no offering to withdraw case
         let withdraw_no_offering = HandleMsg::WithdrawNft { offering_id: 1 };
 
         assert!(matches!(
@@ -2535,6 +3072,7 @@ fn withdraw_offering() {
             token_id: String::from(SELLABLE_NFT_NATIVE),
             off_price: Uint128::from(10u64),
             royalty: None,
+            prices: None,
         };
 
         let _res = manager.handle(mock_info(PROVIDER, &vec![]), msg).unwrap();
@@ -2621,6 +3159,7 @@ fn withdraw_verify_owner() {
             token_id: String::from(SELLABLE_NFT_NATIVE),
             off_price: Uint128::from(10u64),
             royalty: None,
+            prices: None,
         };
 
         let _res = manager.handle(mock_info(PROVIDER, &vec![]), msg).unwrap();
@@ -2720,6 +3259,7 @@ fn admin_withdraw_offering() {
             token_id: String::from(SELLABLE_NFT_NATIVE),
             off_price: Uint128::from(10u64),
             royalty: None,
+            prices: None,
         };
 
         let _res = manager.handle(mock_info(PROVIDER, &vec![]), msg).unwrap();
@@ -2800,6 +3340,7 @@ fn test_sell_nft_unhappy() {
             token_id: String::from(SELLABLE_NFT_NATIVE),
             off_price: Uint128::from(10u64),
             royalty: None,
+            prices: None,
         };
 
         let _res = manager
@@ -2863,6 +3404,7 @@ fn test_buy_nft_unhappy() {
             token_id: String::from(SELLABLE_NFT_NATIVE),
             off_price: Uint128::from(11u64),
             royalty: None,
+            prices: None,
         };
 
         let _res = manager
@@ -3088,3 +3630,694 @@ fn update_approve_all() {
         )
     }
 }
+
+#[test]
+fn offer_happy_path() {
+    unsafe {
+        let manager = DepsManager::get_new();
+        handle_whitelist(manager);
+        let contract_env = mock_env(MARKET_ADDR);
+
+        let creator_info = mock_info("creator", &vec![coin(50, DENOM)]);
+        let mint_msg = HandleMsg::MintNft(MintMsg {
+            contract_addr: HumanAddr::from(OW721),
+            creator: HumanAddr::from(PROVIDER),
+            mint: MintIntermediate {
+                mint: MintStruct {
+                    token_id: String::from(PROVIDER_NFT),
+                    owner: HumanAddr::from(PROVIDER),
+                    name: String::from("asbv"),
+                    description: None,
+                    image: String::from("baxv"),
+                },
+            },
+            creator_type: String::from("sacx"),
+            royalty: None,
+        });
+        manager.handle(creator_info, mint_msg).unwrap();
+
+        let make_offer = |token_id: Option<&str>, end_timestamp: u64| HandleMsg::MakeOffer {
+            contract_addr: HumanAddr::from(OW721),
+            token_id: token_id.map(String::from),
+            end_timestamp: Uint128::from(end_timestamp),
+        };
+        let query_offer_ids = |manager: &DepsManager, token_id: Option<&str>| -> Vec<u64> {
+            let value: OffersResponse = from_binary(
+                &manager
+                    .query(QueryMsg::Offer(OfferQueryMsg::GetOffersByContractTokenId {
+                        contract: HumanAddr::from(OW721),
+                        token_id: token_id.map(String::from),
+                        options: market_offer::PagingOptions {
+                            offset: None,
+                            limit: None,
+                            order: Some(Order::Ascending as u8),
+                        },
+                    }))
+                    .unwrap(),
+            )
+            .unwrap();
+            value.items.iter().map(|offer| offer.id).collect()
+        };
+
+        // the offer must end in the future
+        assert!(matches!(
+            manager.handle(
+                mock_info(BIDDER, &coins(1000, DENOM)),
+                make_offer(None, contract_env.block.time),
+            ),
+            Err(ContractError::InvalidArgument { .. })
+        ));
+        // an offer for another token, then an offer for the whole collection
+        manager
+            .handle(
+                mock_info("bidder1", &coins(500, DENOM)),
+                make_offer(Some("otherNFT"), contract_env.block.time + 100),
+            )
+            .unwrap();
+        manager
+            .handle(
+                mock_info(BIDDER, &coins(1000, DENOM)),
+                make_offer(None, contract_env.block.time + 100),
+            )
+            .unwrap();
+        assert_eq!(query_offer_ids(manager, Some("otherNFT")), vec![1]);
+        assert_eq!(query_offer_ids(manager, None), vec![2]);
+
+        // the funds of the offers cannot be withdrawn as fees
+        let withdraw_msg = |amount: u64| HandleMsg::WithdrawFunds {
+            asset_info: AssetInfo::NativeToken {
+                denom: String::from(DENOM),
+            },
+            amount: Uint128::from(amount),
+        };
+        assert!(matches!(
+            manager.handle(mock_info(CREATOR, &[]), withdraw_msg(1)),
+            Err(ContractError::InsufficientFunds {})
+        ));
+
+        // an offer cannot be accepted while the nft is on sale
+        oraichain_nft::contract::handle(
+            manager.ow721.as_mut(),
+            mock_env(OW721),
+            mock_info(PROVIDER, &[]),
+            oraichain_nft::msg::HandleMsg::ApproveAll {
+                operator: HumanAddr::from(MARKET_ADDR),
+                expires: None,
+            },
+        )
+        .unwrap();
+        manager
+            .handle(
+                mock_info(PROVIDER, &[]),
+                HandleMsg::SellNft {
+                    contract_addr: HumanAddr::from(OW721),
+                    token_id: String::from(PROVIDER_NFT_NATIVE),
+                    off_price: Uint128(50),
+                    royalty: None,
+                    prices: None,
+                },
+            )
+            .unwrap();
+        let on_sale_accept_msg = HandleMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: HumanAddr::from(PROVIDER),
+            token_id: String::from(PROVIDER_NFT),
+            msg: Some(
+                to_binary(&AcceptOfferMsg {
+                    offer_id: 2,
+                    royalty: None,
+                })
+                .unwrap(),
+            ),
+        });
+        assert!(matches!(
+            manager.handle(mock_info(OW721, &[]), on_sale_accept_msg),
+            Err(ContractError::TokenOnSale {})
+        ));
+        manager
+            .handle(
+                mock_info(PROVIDER, &[]),
+                HandleMsg::WithdrawNft { offering_id: 1 },
+            )
+            .unwrap();
+
+        // the owner sends the nft to the market to accept an offer
+        oraichain_nft::contract::handle(
+            manager.ow721.as_mut(),
+            mock_env(OW721),
+            mock_info(PROVIDER, &vec![]),
+            oraichain_nft::msg::HandleMsg::TransferNft {
+                recipient: HumanAddr::from(MARKET_ADDR),
+                token_id: String::from(PROVIDER_NFT),
+            },
+        )
+        .unwrap();
+        let accept_msg = |offer_id: u64| {
+            HandleMsg::ReceiveNft(Cw721ReceiveMsg {
+                sender: HumanAddr::from(PROVIDER),
+                token_id: String::from(PROVIDER_NFT),
+                msg: Some(
+                    to_binary(&AcceptOfferMsg {
+                        offer_id,
+                        royalty: None,
+                    })
+                    .unwrap(),
+                ),
+            })
+        };
+
+        // the offer is for another token
+        assert!(matches!(
+            manager.handle(mock_info(OW721, &vec![]), accept_msg(1)),
+            Err(ContractError::InvalidArgument { .. })
+        ));
+        // only the nft contract can send the nft
+        assert!(matches!(
+            manager.handle(mock_info(PROVIDER, &vec![]), accept_msg(2)),
+            Err(ContractError::InvalidContractAddr {})
+        ));
+        let mut expired_env = contract_env.clone();
+        expired_env.block.time = contract_env.block.time + 100;
+        assert!(matches!(
+            manager.handle_with_env(expired_env, mock_info(OW721, &vec![]), accept_msg(2)),
+            Err(ContractError::OfferExpired {})
+        ));
+
+        let results = manager
+            .handle(mock_info(OW721, &vec![]), accept_msg(2))
+            .unwrap();
+
+        // the seller gets the price minus the market fee
+        let market_res = results.last().unwrap();
+        let seller_amount: u128 = market_res
+            .messages
+            .iter()
+            .map(|msg| match msg {
+                CosmosMsg::Bank(cosmwasm_std::BankMsg::Send {
+                    to_address, amount, ..
+                }) if to_address.eq(&HumanAddr::from(PROVIDER)) => amount[0].amount.u128(),
+                _ => 0,
+            })
+            .sum();
+        assert_eq!(seller_amount, 980);
+        let result: OwnerOfResponse = from_binary(
+            &oraichain_nft::contract::query(
+                manager.ow721.as_ref(),
+                mock_env(OW721),
+                oraichain_nft::msg::QueryMsg::OwnerOf {
+                    token_id: String::from(PROVIDER_NFT),
+                    include_expired: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(result.owner, HumanAddr::from(BIDDER));
+        assert_eq!(query_offer_ids(manager, None), Vec::<u64>::new());
+
+        // the seller gets the royalty of the previous owner on the next sale
+        let offering_royalty: OfferingRoyalty = from_binary(
+            &manager
+                .query(QueryMsg::Offering(
+                    OfferingQueryMsg::GetOfferingRoyaltyByContractTokenId {
+                        contract: HumanAddr::from(OW721),
+                        token_id: String::from(PROVIDER_NFT),
+                    },
+                ))
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            offering_royalty.previous_owner,
+            Some(HumanAddr::from(PROVIDER))
+        );
+        assert_eq!(offering_royalty.current_owner, HumanAddr::from(BIDDER));
+
+        // only the buyer can take the funds back
+        assert!(matches!(
+            manager.handle(
+                mock_info(BIDDER, &vec![]),
+                HandleMsg::CancelOffer { offer_id: 1 }
+            ),
+            Err(ContractError::Unauthorized { .. })
+        ));
+        let results = manager
+            .handle(
+                mock_info("bidder1", &vec![]),
+                HandleMsg::CancelOffer { offer_id: 1 },
+            )
+            .unwrap();
+        assert!(results.last().unwrap().messages.iter().any(|msg| match msg {
+            CosmosMsg::Bank(cosmwasm_std::BankMsg::Send {
+                to_address, amount, ..
+            }) => to_address.eq(&HumanAddr::from("bidder1")) && amount[0].amount.eq(&Uint128(500)),
+            _ => false,
+        }));
+        assert_eq!(query_offer_ids(manager, Some("otherNFT")), Vec::<u64>::new());
+
+        // only the fee of the accepted offer can be withdrawn
+        assert!(matches!(
+            manager.handle(mock_info(CREATOR, &[]), withdraw_msg(21)),
+            Err(ContractError::InsufficientFunds {})
+        ));
+        manager
+            .handle(mock_info(CREATOR, &[]), withdraw_msg(20))
+            .unwrap();
+    }
+}
+
+#[test]
+fn cw20_offer() {
+    unsafe {
+        let manager = DepsManager::get_new();
+        handle_whitelist(manager);
+        let contract_env = mock_env(MARKET_ADDR);
+
+        let offer_msg = HandleMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(BIDDER),
+            amount: Uint128::from(300u64),
+            msg: Some(
+                to_binary(&Cw20HookMsg::MakeOffer {
+                    contract_addr: HumanAddr::from(OW721),
+                    token_id: Some(String::from(PROVIDER_NFT)),
+                    end_timestamp: Uint128::from(contract_env.block.time + 100),
+                })
+                .unwrap(),
+            ),
+        });
+        manager
+            .handle(mock_info(OW20, &vec![]), offer_msg)
+            .unwrap();
+
+        let offer: QueryOffersResult = from_binary(
+            &manager
+                .query(QueryMsg::Offer(OfferQueryMsg::GetOffer { offer_id: 1 }))
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(offer.buyer, HumanAddr::from(BIDDER));
+        assert_eq!(
+            offer.asset_info,
+            AssetInfo::Token {
+                contract_addr: HumanAddr::from(OW20)
+            }
+        );
+
+        // the refund is a cw20 transfer
+        let results = manager
+            .handle(
+                mock_info(BIDDER, &vec![]),
+                HandleMsg::CancelOffer { offer_id: 1 },
+            )
+            .unwrap();
+        assert!(results.last().unwrap().messages.iter().any(|msg| match msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. }) =>
+                contract_addr.eq(&HumanAddr::from(OW20)),
+            _ => false,
+        }));
+    }
+}
+
+#[test]
+fn bundle_happy_path() {
+    unsafe {
+        let manager = DepsManager::get_new();
+        handle_whitelist(manager);
+
+        for token_id in &[PROVIDER_NFT, SELLABLE_NFT] {
+            let mint_msg = HandleMsg::MintNft(MintMsg {
+                contract_addr: HumanAddr::from(OW721),
+                creator: HumanAddr::from(PROVIDER),
+                mint: MintIntermediate {
+                    mint: MintStruct {
+                        token_id: String::from(*token_id),
+                        owner: HumanAddr::from(PROVIDER),
+                        name: String::from("asbv"),
+                        description: None,
+                        image: String::from("baxv"),
+                    },
+                },
+                creator_type: String::from("sacx"),
+                royalty: None,
+            });
+            manager
+                .handle(mock_info("creator", &vec![coin(50, DENOM)]), mint_msg)
+                .unwrap();
+        }
+        oraichain_nft::contract::handle(
+            manager.ow721.as_mut(),
+            mock_env(OW721),
+            mock_info(PROVIDER, &vec![]),
+            oraichain_nft::msg::HandleMsg::ApproveAll {
+                operator: HumanAddr::from(MARKET_ADDR),
+                expires: None,
+            },
+        )
+        .unwrap();
+
+        let sell_bundle = |token_ids: &[&str], prices: Option<Vec<AssetPrice>>| HandleMsg::SellBundle {
+            items: token_ids
+                .iter()
+                .map(|token_id| BundleItem {
+                    contract_addr: HumanAddr::from(OW721),
+                    token_id: String::from(*token_id),
+                })
+                .collect(),
+            off_price: Uint128::from(1000u64),
+            royalty: None,
+            prices,
+        };
+        let query_owner = |manager: &DepsManager, token_id: &str| -> HumanAddr {
+            let result: OwnerOfResponse = from_binary(
+                &oraichain_nft::contract::query(
+                    manager.ow721.as_ref(),
+                    mock_env(OW721),
+                    oraichain_nft::msg::QueryMsg::OwnerOf {
+                        token_id: String::from(token_id),
+                        include_expired: None,
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            result.owner
+        };
+
+        // a bundle has at least two different nfts
+        assert!(matches!(
+            manager.handle(mock_info(PROVIDER, &vec![]), sell_bundle(&[PROVIDER_NFT], None)),
+            Err(ContractError::InvalidArgument { .. })
+        ));
+        assert!(matches!(
+            manager.handle(
+                mock_info(PROVIDER, &vec![]),
+                sell_bundle(&[PROVIDER_NFT, PROVIDER_NFT], None)
+            ),
+            Err(ContractError::InvalidArgument { .. })
+        ));
+
+        // the market keeps every nft of the bundle, so none of them can be sold alone
+        manager
+            .handle(
+                mock_info(PROVIDER, &vec![]),
+                sell_bundle(&[PROVIDER_NFT, SELLABLE_NFT], None),
+            )
+            .unwrap();
+        assert_eq!(
+            query_owner(manager, SELLABLE_NFT),
+            HumanAddr::from(MARKET_ADDR)
+        );
+        assert!(manager
+            .handle(
+                mock_info(PROVIDER, &vec![]),
+                HandleMsg::SellNft {
+                    contract_addr: HumanAddr::from(OW721),
+                    token_id: String::from(SELLABLE_NFT),
+                    off_price: Uint128::from(10u64),
+                    royalty: None,
+                    prices: None,
+                },
+            )
+            .is_err());
+
+        // withdrawing gives back every nft
+        manager
+            .handle(
+                mock_info(PROVIDER, &vec![]),
+                HandleMsg::WithdrawNft { offering_id: 1 },
+            )
+            .unwrap();
+        assert_eq!(query_owner(manager, PROVIDER_NFT), HumanAddr::from(PROVIDER));
+        assert_eq!(query_owner(manager, SELLABLE_NFT), HumanAddr::from(PROVIDER));
+
+        // a bundle accepts other assets like a single nft
+        let ow20_price = AssetPrice {
+            asset_info: AssetInfo::Token {
+                contract_addr: HumanAddr::from(OW20),
+            },
+            price: Uint128::from(5000u64),
+        };
+        manager
+            .handle(
+                mock_info(PROVIDER, &[]),
+                sell_bundle(&[PROVIDER_NFT, SELLABLE_NFT], Some(vec![ow20_price.clone()])),
+            )
+            .unwrap();
+        let prices: Vec<AssetPrice> = from_binary(
+            &market_payment_storage::contract::query(
+                manager.payment_storage.as_ref(),
+                mock_env(PAYMENT_STORAGE_ADDR),
+                market_payment_storage::msg::QueryMsg::Msg(PaymentQueryMsg::GetOfferingPrices {
+                    contract_addr: HumanAddr::from(OW721),
+                    token_id: String::from(PROVIDER_NFT),
+                    sender: None,
+                }),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(prices.len(), 2);
+        assert_eq!(prices[1], ow20_price);
+        assert!(matches!(
+            manager.handle(
+                mock_info("fake_token", &[]),
+                HandleMsg::Receive(Cw20ReceiveMsg {
+                    sender: HumanAddr::from(BIDDER),
+                    amount: Uint128::from(5000u64),
+                    msg: Some(to_binary(&Cw20HookMsg::BuyNft { offering_id: 2 }).unwrap()),
+                }),
+            ),
+            Err(ContractError::InvalidPaymentAsset {})
+        ));
+        assert!(manager
+            .handle(
+                mock_info(BIDDER, &coins(500, DENOM)),
+                HandleMsg::BuyNft { offering_id: 2 }
+            )
+            .is_err());
+        let results = manager
+            .handle(
+                mock_info(BIDDER, &coins(1000, DENOM)),
+                HandleMsg::BuyNft { offering_id: 2 },
+            )
+            .unwrap();
+
+        // the seller gets the price minus the market fee of every nft
+        let seller_amount: u128 = results
+            .last()
+            .unwrap()
+            .messages
+            .iter()
+            .map(|msg| match msg {
+                CosmosMsg::Bank(cosmwasm_std::BankMsg::Send {
+                    to_address, amount, ..
+                }) if to_address.eq(&HumanAddr::from(PROVIDER)) => amount[0].amount.u128(),
+                _ => 0,
+            })
+            .sum();
+        assert_eq!(seller_amount, 980);
+        assert_eq!(query_owner(manager, PROVIDER_NFT), HumanAddr::from(BIDDER));
+        assert_eq!(query_owner(manager, SELLABLE_NFT), HumanAddr::from(BIDDER));
+
+        let offering_royalty: OfferingRoyalty = from_binary(
+            &manager
+                .query(QueryMsg::Offering(
+                    OfferingQueryMsg::GetOfferingRoyaltyByContractTokenId {
+                        contract: HumanAddr::from(OW721),
+                        token_id: String::from(SELLABLE_NFT),
+                    },
+                ))
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            offering_royalty.previous_owner,
+            Some(HumanAddr::from(PROVIDER))
+        );
+    }
+}
+
+#[test]
+fn multi_asset_offering() {
+    unsafe {
+        let manager = DepsManager::get_new();
+        handle_whitelist(manager);
+
+        let mint_msg = HandleMsg::MintNft(MintMsg {
+            contract_addr: HumanAddr::from(OW721),
+            creator: HumanAddr::from(PROVIDER),
+            mint: MintIntermediate {
+                mint: MintStruct {
+                    token_id: String::from(PROVIDER_NFT),
+                    owner: HumanAddr::from(PROVIDER),
+                    name: String::from("asbv"),
+                    description: None,
+                    image: String::from("baxv"),
+                },
+            },
+            creator_type: String::from("sacx"),
+            royalty: None,
+        });
+        manager
+            .handle(mock_info("creator", &vec![coin(50, DENOM)]), mint_msg)
+            .unwrap();
+        oraichain_nft::contract::handle(
+            manager.ow721.as_mut(),
+            mock_env(OW721),
+            mock_info(PROVIDER, &vec![]),
+            oraichain_nft::msg::HandleMsg::ApproveAll {
+                operator: HumanAddr::from(MARKET_ADDR),
+                expires: None,
+            },
+        )
+        .unwrap();
+
+        let sell_msg = |prices: Vec<AssetPrice>| HandleMsg::SellNft {
+            contract_addr: HumanAddr::from(OW721),
+            token_id: String::from(PROVIDER_NFT),
+            off_price: Uint128::from(1000u64),
+            royalty: None,
+            prices: Some(prices),
+        };
+        let ow20_price = AssetPrice {
+            asset_info: AssetInfo::Token {
+                contract_addr: HumanAddr::from(OW20),
+            },
+            price: Uint128::from(5000u64),
+        };
+
+        // the asset of the token id is already accepted
+        assert!(matches!(
+            manager.handle(
+                mock_info(PROVIDER, &vec![]),
+                sell_msg(vec![AssetPrice {
+                    asset_info: AssetInfo::NativeToken {
+                        denom: String::from(DENOM),
+                    },
+                    price: Uint128::from(10u64),
+                }]),
+            ),
+            Err(ContractError::InvalidArgument { .. })
+        ));
+        manager
+            .handle(mock_info(PROVIDER, &vec![]), sell_msg(vec![ow20_price.clone()]))
+            .unwrap();
+        let prices: Vec<AssetPrice> = from_binary(
+            &market_payment_storage::contract::query(
+                manager.payment_storage.as_ref(),
+                mock_env(PAYMENT_STORAGE_ADDR),
+                market_payment_storage::msg::QueryMsg::Msg(PaymentQueryMsg::GetOfferingPrices {
+                    contract_addr: HumanAddr::from(OW721),
+                    token_id: String::from(PROVIDER_NFT),
+                    sender: None,
+                }),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(prices.len(), 2);
+        assert_eq!(prices[1], ow20_price);
+
+        let cw20_buy = |amount: u64| {
+            HandleMsg::Receive(Cw20ReceiveMsg {
+                sender: HumanAddr::from(BIDDER),
+                amount: Uint128::from(amount),
+                msg: Some(to_binary(&Cw20HookMsg::BuyNft { offering_id: 1 }).unwrap()),
+            })
+        };
+        // only the accepted assets can pay, each at its own price
+        assert!(matches!(
+            manager.handle(
+                mock_info(BIDDER, &coins(1000, "atom")),
+                HandleMsg::BuyNft { offering_id: 1 }
+            ),
+            Err(ContractError::InvalidPaymentAsset {})
+        ));
+        assert!(matches!(
+            manager.handle(mock_info("fake_token", &vec![]), cw20_buy(5000)),
+            Err(ContractError::InvalidPaymentAsset {})
+        ));
+        assert!(manager
+            .handle(mock_info(OW20, &vec![]), cw20_buy(1000))
+            .is_err());
+
+        let results = manager
+            .handle(mock_info(OW20, &vec![]), cw20_buy(5000))
+            .unwrap();
+
+        // the fee and the royalties are paid in the cw20 token
+        let seller_amount: u128 = results
+            .last()
+            .unwrap()
+            .messages
+            .iter()
+            .map(|msg| match msg {
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr, msg, ..
+                }) if contract_addr.eq(&HumanAddr::from(OW20)) => match from_binary(msg) {
+                    Ok(cw20::Cw20HandleMsg::Transfer { recipient, amount })
+                        if recipient.eq(&HumanAddr::from(PROVIDER)) =>
+                    {
+                        amount.u128()
+                    }
+                    _ => 0,
+                },
+                _ => 0,
+            })
+            .sum();
+        assert_eq!(seller_amount, 4900);
+
+        // the fee is kept and withdrawn in the cw20 token
+        let query_fees = |manager: &DepsManager, asset_info: Option<AssetInfo>| -> Uint128 {
+            from_binary(
+                &manager
+                    .query(QueryMsg::GetMarketFees { asset_info })
+                    .unwrap(),
+            )
+            .unwrap()
+        };
+        assert_eq!(query_fees(manager, None), Uint128::zero());
+        assert_eq!(
+            query_fees(manager, Some(ow20_price.asset_info.clone())),
+            Uint128::from(100u64)
+        );
+        let withdraw_msg = |amount: u64| HandleMsg::WithdrawFunds {
+            asset_info: ow20_price.asset_info.clone(),
+            amount: Uint128::from(amount),
+        };
+        assert!(matches!(
+            manager.handle(mock_info(CREATOR, &[]), withdraw_msg(101)),
+            Err(ContractError::InsufficientFunds {})
+        ));
+        let results = manager
+            .handle(mock_info(CREATOR, &[]), withdraw_msg(100))
+            .unwrap();
+        assert!(results.last().unwrap().messages.iter().any(|msg| match msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) if contract_addr.eq(&HumanAddr::from(OW20)) => matches!(
+                from_binary(msg),
+                Ok(cw20::Cw20HandleMsg::Transfer { recipient, amount })
+                    if recipient.eq(&HumanAddr::from(CREATOR)) && amount.eq(&Uint128::from(100u64))
+            ),
+            _ => false,
+        }));
+        assert_eq!(
+            query_fees(manager, Some(ow20_price.asset_info.clone())),
+            Uint128::zero()
+        );
+
+        let result: OwnerOfResponse = from_binary(
+            &oraichain_nft::contract::query(
+                manager.ow721.as_ref(),
+                mock_env(OW721),
+                oraichain_nft::msg::QueryMsg::OwnerOf {
+                    token_id: String::from(PROVIDER_NFT),
+                    include_expired: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(result.owner, HumanAddr::from(BIDDER));
+    }
+}
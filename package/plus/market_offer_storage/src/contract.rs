@@ -0,0 +1,288 @@
+use crate::error::ContractError;
+use crate::msg::{HandleMsg, InitMsg, QueryMsg, UpdateContractMsg};
+use crate::state::{get_contract_token_id, increment_offers, offers, ContractInfo, CONTRACT_INFO};
+use cosmwasm_std::{
+    attr, to_binary, Binary, Deps, DepsMut, Env, HandleResponse, InitResponse, MessageInfo, Order,
+    StdResult,
+};
+use cosmwasm_std::{Api, HumanAddr, KV};
+use cw_storage_plus::Bound;
+use market_offer::{
+    Offer, OfferHandleMsg, OfferQueryMsg, OffersResponse, PagingOptions, QueryOffersResult,
+};
+use std::convert::TryInto;
+
+// settings for pagination
+const MAX_LIMIT: u8 = 100;
+const DEFAULT_LIMIT: u8 = 20;
+
+// Note, you can use StdResult in some functions where you do not
+// make use of the custom errors
+pub fn init(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InitMsg,
+) -> Result<InitResponse, ContractError> {
+    // first time deploy, it will not know about the implementation
+    let info = ContractInfo {
+        governance: msg.governance,
+        creator: info.sender,
+    };
+    CONTRACT_INFO.save(deps.storage, &info)?;
+    Ok(InitResponse::default())
+}
+
+// And declare a custom Error variant for the ones where you will want to make use of it
+pub fn handle(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: HandleMsg,
+) -> Result<HandleResponse, ContractError> {
+    match msg {
+        HandleMsg::Msg(offer_handle) => match offer_handle {
+            OfferHandleMsg::UpdateOffer { offer } => try_update_offer(deps, info, env, offer),
+            OfferHandleMsg::RemoveOffer { id } => try_remove_offer(deps, info, env, id),
+        },
+        HandleMsg::UpdateInfo(msg) => try_update_info(deps, info, env, msg),
+    }
+}
+
+pub fn try_update_offer(
+    deps: DepsMut,
+    info: MessageInfo,
+    _env: Env,
+    mut offer: Offer,
+) -> Result<HandleResponse, ContractError> {
+    // must check the sender is implementation contract
+    let contract_info = CONTRACT_INFO.load(deps.storage)?;
+
+    if contract_info.governance.ne(&info.sender) {
+        return Err(ContractError::Unauthorized {
+            sender: info.sender.to_string(),
+        });
+    }
+
+    // if no id then create new one as insert
+    let id = match offer.id {
+        None => {
+            let new_id = increment_offers(deps.storage)?;
+            offer.id = Some(new_id);
+            new_id
+        }
+        Some(old_id) => old_id,
+    };
+
+    offers().save(deps.storage, &id.to_be_bytes(), &offer)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![attr("action", "update_offer"), attr("offer_id", id)],
+        data: None,
+    })
+}
+
+pub fn try_remove_offer(
+    deps: DepsMut,
+    info: MessageInfo,
+    _env: Env,
+    id: u64,
+) -> Result<HandleResponse, ContractError> {
+    let contract_info = CONTRACT_INFO.load(deps.storage)?;
+    if contract_info.governance.ne(&info.sender) {
+        return Err(ContractError::Unauthorized {
+            sender: info.sender.to_string(),
+        });
+    }
+
+    offers().remove(deps.storage, &id.to_be_bytes())?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![attr("action", "remove_offer"), attr("offer_id", id)],
+        data: None,
+    })
+}
+
+pub fn try_update_info(
+    deps: DepsMut,
+    info: MessageInfo,
+    _env: Env,
+    msg: UpdateContractMsg,
+) -> Result<HandleResponse, ContractError> {
+    let new_contract_info = CONTRACT_INFO.update(deps.storage, |mut contract_info| {
+        // Unauthorized
+        if !info.sender.eq(&contract_info.creator) {
+            return Err(ContractError::Unauthorized {
+                sender: info.sender.to_string(),
+            });
+        }
+        if let Some(governance) = msg.governance {
+            contract_info.governance = governance;
+        }
+        if let Some(creator) = msg.creator {
+            contract_info.creator = creator;
+        }
+        Ok(contract_info)
+    })?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![attr("action", "update_info")],
+        data: to_binary(&new_contract_info).ok(),
+    })
+}
+
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Msg(offer_query) => match offer_query {
+            OfferQueryMsg::GetOffers { options } => to_binary(&query_offers(deps, &options)?),
+            OfferQueryMsg::GetOffersByBuyer { buyer, options } => {
+                to_binary(&query_offers_by_buyer(deps, buyer, &options)?)
+            }
+            OfferQueryMsg::GetOffersByContract { contract, options } => {
+                to_binary(&query_offers_by_contract(deps, contract, &options)?)
+            }
+            OfferQueryMsg::GetOffersByContractTokenId {
+                contract,
+                token_id,
+                options,
+            } => to_binary(&query_offers_by_contract_tokenid(
+                deps, contract, token_id, &options,
+            )?),
+            OfferQueryMsg::GetOfferRaw { offer_id } => to_binary(&query_offer_raw(deps, offer_id)?),
+            OfferQueryMsg::GetOffer { offer_id } => to_binary(&query_offer(deps, offer_id)?),
+        },
+        QueryMsg::GetContractInfo {} => to_binary(&query_contract_info(deps)?),
+    }
+}
+
+// ============================== Query Handlers ==============================
+
+fn _get_range_params(options: &PagingOptions) -> (usize, Option<Bound>, Option<Bound>, Order) {
+    let limit = options.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let mut order_enum = Order::Descending;
+    let mut min: Option<Bound> = None;
+    let mut max: Option<Bound> = None;
+    if let Some(num) = options.order {
+        if num == 1 {
+            order_enum = Order::Ascending;
+        }
+    }
+
+    // if there is offset, assign to min or max
+    let offset_value = options
+        .offset
+        .map(|offset| Bound::Exclusive(offset.to_be_bytes().to_vec()));
+    match order_enum {
+        Order::Ascending => min = offset_value,
+        Order::Descending => max = offset_value,
+    }
+
+    (limit, min, max, order_enum)
+}
+
+pub fn query_offers(deps: Deps, options: &PagingOptions) -> StdResult<OffersResponse> {
+    let (limit, min, max, order_enum) = _get_range_params(options);
+
+    let res: StdResult<Vec<QueryOffersResult>> = offers()
+        .range(deps.storage, min, max, order_enum)
+        .take(limit)
+        .map(|kv_item| parse_offer(deps.api, kv_item))
+        .collect();
+
+    Ok(OffersResponse { items: res? })
+}
+
+pub fn query_offers_by_buyer(
+    deps: Deps,
+    buyer: HumanAddr,
+    options: &PagingOptions,
+) -> StdResult<OffersResponse> {
+    let (limit, min, max, order_enum) = _get_range_params(options);
+    let buyer_raw = deps.api.canonical_address(&buyer)?;
+    let res: StdResult<Vec<QueryOffersResult>> = offers()
+        .idx
+        .buyer
+        .items(deps.storage, &buyer_raw, min, max, order_enum)
+        .take(limit)
+        .map(|kv_item| parse_offer(deps.api, kv_item))
+        .collect();
+
+    Ok(OffersResponse { items: res? })
+}
+
+pub fn query_offers_by_contract(
+    deps: Deps,
+    contract: HumanAddr,
+    options: &PagingOptions,
+) -> StdResult<OffersResponse> {
+    let (limit, min, max, order_enum) = _get_range_params(options);
+    let contract_raw = deps.api.canonical_address(&contract)?;
+    let res: StdResult<Vec<QueryOffersResult>> = offers()
+        .idx
+        .contract
+        .items(deps.storage, &contract_raw, min, max, order_enum)
+        .take(limit)
+        .map(|kv_item| parse_offer(deps.api, kv_item))
+        .collect();
+
+    Ok(OffersResponse { items: res? })
+}
+
+pub fn query_offers_by_contract_tokenid(
+    deps: Deps,
+    contract: HumanAddr,
+    token_id: Option<String>,
+    options: &PagingOptions,
+) -> StdResult<OffersResponse> {
+    let (limit, min, max, order_enum) = _get_range_params(options);
+    let contract_raw = deps.api.canonical_address(&contract)?;
+    let res: StdResult<Vec<QueryOffersResult>> = offers()
+        .idx
+        .contract_token_id
+        .items(
+            deps.storage,
+            &get_contract_token_id(&contract_raw, token_id.as_deref()),
+            min,
+            max,
+            order_enum,
+        )
+        .take(limit)
+        .map(|kv_item| parse_offer(deps.api, kv_item))
+        .collect();
+
+    Ok(OffersResponse { items: res? })
+}
+
+pub fn query_offer_raw(deps: Deps, offer_id: u64) -> StdResult<Offer> {
+    offers().load(deps.storage, &offer_id.to_be_bytes())
+}
+
+pub fn query_offer(deps: Deps, offer_id: u64) -> StdResult<QueryOffersResult> {
+    let offer = offers().load(deps.storage, &offer_id.to_be_bytes())?;
+    let kv_item: KV<Offer> = (offer_id.to_be_bytes().to_vec(), offer);
+    parse_offer(deps.api, Ok(kv_item))
+}
+
+pub fn query_contract_info(deps: Deps) -> StdResult<ContractInfo> {
+    CONTRACT_INFO.load(deps.storage)
+}
+
+fn parse_offer(api: &dyn Api, item: StdResult<KV<Offer>>) -> StdResult<QueryOffersResult> {
+    item.and_then(|(k, offer)| {
+        // will panic if length is greater than 8, but we can make sure it is u64
+        // try_into will box vector to fixed array
+        let id: u64 = u64::from_be_bytes(k.try_into().unwrap());
+        Ok(QueryOffersResult {
+            id,
+            contract_addr: api.human_address(&offer.contract_addr)?,
+            token_id: offer.token_id,
+            buyer: api.human_address(&offer.buyer)?,
+            asset_info: offer.asset_info,
+            amount: offer.amount,
+            end_timestamp: offer.end_timestamp,
+        })
+    })
+}
@@ -0,0 +1,31 @@
+use cosmwasm_std::HumanAddr;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use market_offer::{OfferHandleMsg, OfferQueryMsg};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InitMsg {
+    pub governance: HumanAddr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HandleMsg {
+    Msg(OfferHandleMsg),
+    // other implementation
+    UpdateInfo(UpdateContractMsg),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UpdateContractMsg {
+    pub governance: Option<HumanAddr>,
+    pub creator: Option<HumanAddr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Msg(OfferQueryMsg),
+    GetContractInfo {},
+}
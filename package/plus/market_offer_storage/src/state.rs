@@ -0,0 +1,65 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{CanonicalAddr, HumanAddr, StdResult, Storage};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, MultiIndex};
+use market_offer::Offer;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ContractInfo {
+    /// the contract that has permission to update the implementation
+    pub governance: HumanAddr,
+    pub creator: HumanAddr,
+}
+
+pub const OFFERS_COUNT: Item<u64> = Item::new("num_offers");
+pub const CONTRACT_INFO: Item<ContractInfo> = Item::new("contract_info");
+
+pub fn num_offers(storage: &dyn Storage) -> StdResult<u64> {
+    Ok(OFFERS_COUNT.may_load(storage)?.unwrap_or_default())
+}
+
+pub fn increment_offers(storage: &mut dyn Storage) -> StdResult<u64> {
+    let val = num_offers(storage)? + 1;
+    OFFERS_COUNT.save(storage, &val)?;
+    Ok(val)
+}
+
+// a token may have many offers, so the contract token id index is not unique
+pub struct OfferIndexes<'a> {
+    pub buyer: MultiIndex<'a, Offer>,
+    pub contract: MultiIndex<'a, Offer>,
+    pub contract_token_id: MultiIndex<'a, Offer>,
+}
+
+impl<'a> IndexList<Offer> for OfferIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Offer>> + '_> {
+        let v: Vec<&dyn Index<Offer>> = vec![&self.buyer, &self.contract, &self.contract_token_id];
+        Box::new(v.into_iter())
+    }
+}
+
+// contract nft + token id => index key, collection offers only have the contract nft
+pub fn get_contract_token_id(contract: &CanonicalAddr, token_id: Option<&str>) -> Vec<u8> {
+    let mut vec = contract.as_slice().to_vec();
+    if let Some(token_id) = token_id {
+        // separator so that no token id can be mistaken for the collection
+        vec.push(b'/');
+        vec.extend(token_id.as_bytes());
+    }
+    vec
+}
+
+// this IndexedMap instance has a lifetime
+pub fn offers<'a>() -> IndexedMap<'a, &'a [u8], Offer, OfferIndexes<'a>> {
+    let indexes = OfferIndexes {
+        buyer: MultiIndex::new(|o| o.buyer.to_vec(), "offers", "offers__buyer"),
+        contract: MultiIndex::new(|o| o.contract_addr.to_vec(), "offers", "offers__contract"),
+        contract_token_id: MultiIndex::new(
+            |o| get_contract_token_id(&o.contract_addr, o.token_id.as_deref()),
+            "offers",
+            "offers__contract_token_id",
+        ),
+    };
+    IndexedMap::new("offers", indexes)
+}
@@ -0,0 +1,170 @@
+use crate::contract::*;
+
+use crate::msg::*;
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+};
+use cosmwasm_std::Api;
+use cosmwasm_std::{coins, from_binary, Env, HumanAddr, Order, OwnedDeps, Uint128};
+use market_offer::{
+    AssetInfo, Offer, OfferHandleMsg, OfferQueryMsg, OffersResponse, PagingOptions,
+    QueryOffersResult,
+};
+
+const CREATOR: &str = "owner";
+const DENOM: &str = "orai";
+
+fn setup_contract() -> (OwnedDeps<MockStorage, MockApi, MockQuerier>, Env) {
+    let mut deps = mock_dependencies(&coins(100000, DENOM));
+    deps.api.canonical_length = 54;
+    let msg = InitMsg {
+        governance: HumanAddr::from(CREATOR),
+    };
+    let info = mock_info(CREATOR, &[]);
+    let contract_env = mock_env();
+    let res = init(deps.as_mut(), contract_env.clone(), info, msg).unwrap();
+    assert_eq!(0, res.messages.len());
+    (deps, contract_env)
+}
+
+fn query_ids(deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, msg: OfferQueryMsg) -> Vec<u64> {
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Msg(msg)).unwrap();
+    let value: OffersResponse = from_binary(&res).unwrap();
+    value.items.iter().map(|f| f.id).collect()
+}
+
+#[test]
+fn query_offers_by_token() {
+    let (mut deps, contract_env) = setup_contract();
+
+    let info = mock_info(CREATOR, &[]);
+    let contract_addr = deps
+        .api
+        .canonical_address(&HumanAddr::from("contract_addr"))
+        .unwrap();
+    let buyer = deps
+        .api
+        .canonical_address(&HumanAddr::from("buyer"))
+        .unwrap();
+
+    // odd offers are for token 1, even offers for the whole collection
+    for i in 1..=10 {
+        let offer = Offer {
+            id: None,
+            contract_addr: contract_addr.clone(),
+            token_id: if i % 2 == 1 {
+                Some(String::from("1"))
+            } else {
+                None
+            },
+            buyer: buyer.clone(),
+            asset_info: AssetInfo::NativeToken {
+                denom: DENOM.into(),
+            },
+            amount: Uint128(i),
+            end_timestamp: Uint128::from(contract_env.block.time + 100),
+        };
+        let msg = HandleMsg::Msg(OfferHandleMsg::UpdateOffer { offer });
+        handle(deps.as_mut(), contract_env.clone(), info.clone(), msg).unwrap();
+    }
+
+    let options = PagingOptions {
+        limit: Some(3),
+        offset: None,
+        order: Some(Order::Ascending as u8),
+    };
+    let ids = query_ids(
+        &deps,
+        OfferQueryMsg::GetOffersByContractTokenId {
+            contract: "contract_addr".into(),
+            token_id: Some(String::from("1")),
+            options: options.clone(),
+        },
+    );
+    assert_eq!(ids, vec![1, 3, 5]);
+
+    let ids = query_ids(
+        &deps,
+        OfferQueryMsg::GetOffersByContractTokenId {
+            contract: "contract_addr".into(),
+            token_id: None,
+            options: PagingOptions {
+                offset: Some(4),
+                ..options.clone()
+            },
+        },
+    );
+    assert_eq!(ids, vec![6, 8, 10]);
+
+    // newest first by default
+    let ids = query_ids(
+        &deps,
+        OfferQueryMsg::GetOffersByBuyer {
+            buyer: "buyer".into(),
+            options: PagingOptions {
+                limit: Some(3),
+                offset: Some(9),
+                order: None,
+            },
+        },
+    );
+    assert_eq!(ids, vec![8, 7, 6]);
+
+    // remove an offer
+    handle(
+        deps.as_mut(),
+        contract_env.clone(),
+        info.clone(),
+        HandleMsg::Msg(OfferHandleMsg::RemoveOffer { id: 1 }),
+    )
+    .unwrap();
+    let ids = query_ids(
+        &deps,
+        OfferQueryMsg::GetOffersByContract {
+            contract: "contract_addr".into(),
+            options: PagingOptions {
+                limit: Some(100),
+                ..options
+            },
+        },
+    );
+    assert_eq!(ids, vec![2, 3, 4, 5, 6, 7, 8, 9, 10]);
+
+    let res = query(
+        deps.as_ref(),
+        contract_env,
+        QueryMsg::Msg(OfferQueryMsg::GetOffer { offer_id: 2 }),
+    )
+    .unwrap();
+    let value: QueryOffersResult = from_binary(&res).unwrap();
+    assert_eq!(value.token_id, None);
+    assert_eq!(value.buyer, HumanAddr::from("buyer"));
+    assert_eq!(value.amount, Uint128(2));
+}
+
+#[test]
+fn update_offer_unauthorized() {
+    let (mut deps, contract_env) = setup_contract();
+    let contract_addr = deps
+        .api
+        .canonical_address(&HumanAddr::from("contract_addr"))
+        .unwrap();
+    let offer = Offer {
+        id: None,
+        contract_addr: contract_addr.clone(),
+        token_id: None,
+        buyer: contract_addr,
+        asset_info: AssetInfo::NativeToken {
+            denom: DENOM.into(),
+        },
+        amount: Uint128(1),
+        end_timestamp: Uint128::from(contract_env.block.time + 100),
+    };
+    assert!(handle(
+        deps.as_mut(),
+        contract_env,
+        mock_info("hacker", &[]),
+        HandleMsg::Msg(OfferHandleMsg::UpdateOffer { offer }),
+    )
+    .is_err());
+}
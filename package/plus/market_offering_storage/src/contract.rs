@@ -407,6 +407,7 @@ pub fn query_offering(deps: Deps, offering_id: u64) -> StdResult<QueryOfferingsR
         price: offering.price,
         contract_addr: deps.api.human_address(&offering.contract_addr)?,
         seller: deps.api.human_address(&offering.seller)?,
+        bundle: offering.bundle,
     })
 }
 
@@ -433,6 +434,7 @@ pub fn query_offering_by_contract_tokenid(
             price: offering_result.price,
             contract_addr: deps.api.human_address(&offering_result.contract_addr)?,
             seller: deps.api.human_address(&offering_result.seller)?,
+            bundle: offering_result.bundle,
         };
         Ok(offering_resposne)
     } else {
@@ -540,6 +542,7 @@ fn parse_offering<'a>(
             price: offering.price,
             contract_addr: api.human_address(&offering.contract_addr)?,
             seller: api.human_address(&offering.seller)?,
+            bundle: offering.bundle,
         })
     })
 }
@@ -66,6 +66,7 @@ fn sort_offering() {
                 .canonical_address(&HumanAddr::from("seller"))
                 .unwrap(),
             price: Uint128::from(1u64),
+            bundle: None,
         };
         offerings.push(offering);
     }
@@ -217,6 +218,7 @@ fn withdraw_offering() {
                 .canonical_address(&HumanAddr::from("seller"))
                 .unwrap(),
             price: Uint128::from(1u64),
+            bundle: None,
         };
         offerings.push(offering);
     }
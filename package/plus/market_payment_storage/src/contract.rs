@@ -1,7 +1,8 @@
 use crate::error::ContractError;
 use crate::msg::{HandleMsg, InitMsg, MigrateMsg, QueryMsg, UpdateContractMsg};
 use crate::state::{
-    parse_payment_key, ContractInfo, PaymentKey, AUCTION_PAYMENTS, CONTRACT_INFO, OFFERING_PAYMENTS,
+    parse_payment_key, ContractInfo, PaymentKey, AUCTION_PAYMENTS, CONTRACT_INFO,
+    OFFERING_PAYMENTS, OFFERING_PRICES,
 };
 
 use cosmwasm_std::{
@@ -10,7 +11,8 @@ use cosmwasm_std::{
 };
 use cw_storage_plus::Bound;
 use market_payment::{
-    AssetInfo, Payment, PaymentHandleMsg, PaymentMsg, PaymentQueryMsg, PaymentResponse,
+    AssetInfo, AssetPrice, Payment, PaymentHandleMsg, PaymentMsg, PaymentPrices, PaymentQueryMsg,
+    PaymentResponse,
 };
 
 // settings for pagination
@@ -50,6 +52,9 @@ pub fn handle(
             PaymentHandleMsg::UpdateOfferingPayment(payment) => {
                 try_update_offering_payment(deps, info, env, payment)
             }
+            PaymentHandleMsg::UpdateOfferingPrices(prices) => {
+                try_update_offering_prices(deps, info, env, prices)
+            }
             PaymentHandleMsg::RemoveAuctionPayment {
                 contract_addr,
                 token_id,
@@ -102,6 +107,16 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
                 token_id,
                 sender,
             )?),
+            PaymentQueryMsg::GetOfferingPrices {
+                contract_addr,
+                token_id,
+                sender,
+            } => to_binary(&query_offering_prices(
+                deps,
+                contract_addr,
+                token_id,
+                sender,
+            )?),
             PaymentQueryMsg::GetOfferingPayments {
                 offset,
                 limit,
@@ -128,15 +143,14 @@ pub fn try_update_offering_payment(
         });
     };
 
-    OFFERING_PAYMENTS.save(
-        deps.storage,
-        &parse_payment_key(
-            payment.contract_addr.as_str(),
-            payment.token_id.as_str(),
-            payment.sender,
-        )?,
-        &payment.asset_info,
+    let key = parse_payment_key(
+        payment.contract_addr.as_str(),
+        payment.token_id.as_str(),
+        payment.sender,
     )?;
+    OFFERING_PAYMENTS.save(deps.storage, &key, &payment.asset_info)?;
+    // a single payment replaces the prices of the previous offering
+    OFFERING_PRICES.remove(deps.storage, &key);
     let asset_info_bin = to_binary(&payment.asset_info)?;
 
     return Ok(HandleResponse {
@@ -149,6 +163,49 @@ pub fn try_update_offering_payment(
     });
 }
 
+pub fn try_update_offering_prices(
+    deps: DepsMut,
+    info: MessageInfo,
+    _env: Env,
+    payment_prices: PaymentPrices,
+) -> Result<HandleResponse, ContractError> {
+    // must check the sender is implementation contract
+    let contract_info = CONTRACT_INFO.load(deps.storage)?;
+
+    if contract_info.governance.ne(&info.sender) {
+        return Err(ContractError::Unauthorized {
+            sender: info.sender.to_string(),
+        });
+    };
+
+    let first_price = match payment_prices.prices.first() {
+        Some(price) => price.clone(),
+        None => {
+            return Err(ContractError::InvalidArgument {
+                arg: "prices".to_string(),
+            })
+        }
+    };
+    let key = parse_payment_key(
+        payment_prices.contract_addr.as_str(),
+        payment_prices.token_id.as_str(),
+        payment_prices.sender,
+    )?;
+    // the first asset stays the payment of the offering for the single asset queries
+    OFFERING_PAYMENTS.save(deps.storage, &key, &first_price.asset_info)?;
+    OFFERING_PRICES.save(deps.storage, &key, &payment_prices.prices)?;
+    let prices_bin = to_binary(&payment_prices.prices)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![
+            attr("action", "update_offering_prices"),
+            attr("prices", prices_bin),
+        ],
+        data: None,
+    })
+}
+
 pub fn try_update_auction_payment(
     deps: DepsMut,
     info: MessageInfo,
@@ -201,10 +258,9 @@ pub fn try_remove_offering_payment(
     }
 
     // remove offering
-    OFFERING_PAYMENTS.remove(
-        deps.storage,
-        &parse_payment_key(contract_addr.as_str(), token_id.as_str(), sender)?,
-    );
+    let key = parse_payment_key(contract_addr.as_str(), token_id.as_str(), sender)?;
+    OFFERING_PAYMENTS.remove(deps.storage, &key);
+    OFFERING_PRICES.remove(deps.storage, &key);
 
     return Ok(HandleResponse {
         messages: vec![],
@@ -321,6 +377,20 @@ pub fn query_offering_payment(
         })) // if we cannot find the type of payment => default is ORAI
 }
 
+pub fn query_offering_prices(
+    deps: Deps,
+    contract_addr: HumanAddr,
+    token_id: String,
+    sender: Option<HumanAddr>,
+) -> StdResult<Vec<AssetPrice>> {
+    Ok(OFFERING_PRICES
+        .may_load(
+            deps.storage,
+            &parse_payment_key(contract_addr.as_str(), token_id.as_str(), sender)?,
+        )?
+        .unwrap_or_default())
+}
+
 fn _get_range_params(
     limit: Option<u8>,
     offset: Option<Binary>,
@@ -1,4 +1,4 @@
-use market_payment::AssetInfo;
+use market_payment::{AssetInfo, AssetPrice};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -36,3 +36,5 @@ pub const CONTRACT_INFO: Item<ContractInfo> = Item::new("payment_storage_info");
 pub const OFFERING_PAYMENTS: Map<&[u8], AssetInfo> = Map::new("offering_payments_v1.1");
 
 pub const AUCTION_PAYMENTS: Map<&[u8], AssetInfo> = Map::new("auction_payments_v1.1");
+
+pub const OFFERING_PRICES: Map<&[u8], Vec<AssetPrice>> = Map::new("offering_prices");
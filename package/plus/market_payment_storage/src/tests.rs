@@ -11,8 +11,10 @@ use cosmwasm_std::to_binary;
 use cosmwasm_std::Decimal;
 use cosmwasm_std::{coin, coins, from_binary, HumanAddr, OwnedDeps, Uint128};
 use market_payment::AssetInfo;
+use market_payment::AssetPrice;
 use market_payment::Payment;
 use market_payment::PaymentHandleMsg;
+use market_payment::PaymentPrices;
 use market_payment::PaymentQueryMsg;
 use market_payment::PaymentResponse;
 
@@ -94,6 +96,93 @@ fn remove_offering_payment() {
     println!("new asset info: {:?}", asset_info)
 }
 
+#[test]
+fn update_offering_prices() {
+    let mut deps = setup_contract();
+    let info = mock_info("market_hub", &vec![]);
+    let query_prices = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>| -> Vec<AssetPrice> {
+        from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Msg(PaymentQueryMsg::GetOfferingPrices {
+                    contract_addr: HumanAddr::from("abc"),
+                    token_id: "foobar".into(),
+                    sender: None,
+                }),
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    let prices = vec![
+        AssetPrice {
+            asset_info: AssetInfo::Token {
+                contract_addr: HumanAddr::from("airi"),
+            },
+            price: Uint128::from(100u64),
+        },
+        AssetPrice {
+            asset_info: AssetInfo::NativeToken {
+                denom: DENOM.into(),
+            },
+            price: Uint128::from(10u64),
+        },
+    ];
+    let update_prices = |prices: Vec<AssetPrice>| {
+        HandleMsg::Msg(PaymentHandleMsg::UpdateOfferingPrices(PaymentPrices {
+            contract_addr: HumanAddr::from("abc"),
+            token_id: "foobar".into(),
+            sender: None,
+            prices,
+        }))
+    };
+
+    assert!(handle(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        update_prices(vec![])
+    )
+    .is_err());
+    handle(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        update_prices(prices.clone()),
+    )
+    .unwrap();
+    assert_eq!(query_prices(&deps), prices);
+
+    // the first asset is the payment of the offering
+    let asset_info: AssetInfo = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Msg(PaymentQueryMsg::GetOfferingPayment {
+                contract_addr: HumanAddr::from("abc"),
+                token_id: "foobar".into(),
+                sender: None,
+            }),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(asset_info, prices[0].asset_info);
+
+    // a single payment replaces the prices
+    let msg = HandleMsg::Msg(PaymentHandleMsg::UpdateOfferingPayment(Payment {
+        contract_addr: HumanAddr::from("abc"),
+        token_id: "foobar".into(),
+        asset_info: AssetInfo::NativeToken {
+            denom: DENOM.into(),
+        },
+        sender: None,
+    }));
+    handle(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(query_prices(&deps), vec![]);
+}
+
 #[test]
 fn check_query_offering_1155_payments() {
     let mut deps = setup_contract();